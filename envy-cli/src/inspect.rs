@@ -0,0 +1,206 @@
+//! Human readable summaries of an asset, for the `tree`, `list` and `validate` commands
+
+use envy::{
+    asset::{
+        container::{ChunkTag, Container, MetadataRecord},
+        AvailableResources, Severity,
+    },
+    LayoutTemplate, NodeImplTemplate, NodeTemplate, NodeVisibility, ShapeKind,
+};
+
+use crate::LoadedAsset;
+
+/// Label used for the root template, which has no name
+const ROOT_LABEL: &str = "<root>";
+
+/// The root template followed by every named template, sorted by name
+///
+/// `LayoutRoot` always holds an empty template named `""`, which is skipped.
+pub fn templates(asset: &LoadedAsset) -> Vec<(Option<&str>, &LayoutTemplate)> {
+    let mut templates = asset
+        .root
+        .iter_templates()
+        .into_iter()
+        .filter(|(name, _)| !name.is_empty())
+        .map(|(name, template)| (Some(name), template))
+        .collect::<Vec<_>>();
+    templates.sort_by_key(|(name, _)| *name);
+    templates.insert(0, (None, asset.root.root_template()));
+    templates
+}
+
+fn describe_node(node: &NodeTemplate) -> String {
+    let mut description = node.name.clone();
+
+    match &node.implementation {
+        NodeImplTemplate::Empty => {}
+        NodeImplTemplate::Image(image) => {
+            description += &format!(" [image '{}'", image.texture_name);
+            if let Some(mask) = image.mask_texture_name.as_ref() {
+                description += &format!(", mask '{mask}'");
+            }
+            description += "]";
+        }
+        NodeImplTemplate::Text(text) => {
+            description += &format!(" [text '{}' {:?}]", text.font_name, text.text);
+        }
+        NodeImplTemplate::Sublayout(sublayout) => {
+            description += &format!(" [sublayout '{}']", sublayout.sublayout_name);
+        }
+        NodeImplTemplate::Shape(shape) => {
+            description += match &shape.kind {
+                ShapeKind::Rectangle { .. } => " [rectangle]",
+                ShapeKind::Ellipse => " [ellipse]",
+                ShapeKind::Polygon { .. } => " [polygon]",
+                ShapeKind::Polyline { .. } => " [polyline]",
+            };
+        }
+    }
+
+    let transform = &node.transform;
+    description += &format!(
+        " at ({}, {}) size ({}, {})",
+        transform.position.x, transform.position.y, transform.size.x, transform.size.y
+    );
+
+    if transform.angle != 0.0 {
+        description += &format!(" angle {}", transform.angle);
+    }
+
+    if transform.scale != glam::Vec2::ONE {
+        description += &format!(" scale ({}, {})", transform.scale.x, transform.scale.y);
+    }
+
+    if node.visibility == NodeVisibility::Hidden {
+        description += " hidden";
+    }
+
+    description
+}
+
+fn print_node(node: &NodeTemplate, depth: usize) {
+    println!("{:indent$}{}", "", describe_node(node), indent = depth * 2);
+    node.children
+        .iter()
+        .for_each(|child| print_node(child, depth + 1));
+}
+
+/// Prints the nodes of every template, or only those of `template_name`
+pub fn tree(asset: &LoadedAsset, template_name: Option<&str>) -> Result<(), String> {
+    let templates = templates(asset)
+        .into_iter()
+        .filter(|(name, _)| template_name.is_none() || *name == template_name)
+        .collect::<Vec<_>>();
+
+    if templates.is_empty() {
+        return Err(format!("there is no template named '{}'", template_name.unwrap_or_default()));
+    }
+
+    for (index, (name, template)) in templates.into_iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+
+        let [width, height] = template.canvas_size;
+        println!("{} ({width}x{height})", name.unwrap_or(ROOT_LABEL));
+        template
+            .root_nodes
+            .iter()
+            .for_each(|node| print_node(node, 1));
+    }
+
+    Ok(())
+}
+
+/// Prints the version of the asset and its templates, animations, resources and chunks
+pub fn list(asset: &LoadedAsset) {
+    match envy::asset::legacy_version(&asset.bytes) {
+        Some((major, minor, patch)) => println!("format: legacy {major}.{minor}.{patch}"),
+        None => {
+            let major = u16::from_le_bytes([asset.bytes[4], asset.bytes[5]]);
+            let minor = u16::from_le_bytes([asset.bytes[6], asset.bytes[7]]);
+            println!("format: container {major}.{minor}");
+        }
+    }
+
+    let container = Container::parse(&asset.bytes).ok();
+    let generator = container
+        .as_ref()
+        .and_then(|container| container.chunk(ChunkTag::METADATA).ok().flatten())
+        .and_then(|mut records| records.read::<MetadataRecord>().ok());
+    if let Some(metadata) = generator {
+        println!("generator: {}", metadata.generator);
+    }
+
+    println!("\ntemplates:");
+    let templates = templates(asset);
+    for (name, template) in templates.iter() {
+        let mut node_count = 0;
+        template.walk_tree(|_| node_count += 1);
+        let [width, height] = template.canvas_size;
+        let base = template
+            .inheritance
+            .as_ref()
+            .map(|inheritance| format!(", derives from '{}'", inheritance.base))
+            .unwrap_or_default();
+        println!(
+            "  {:<24} {width}x{height}, nodes: {node_count}, animations: {}{}{base}",
+            name.unwrap_or(ROOT_LABEL),
+            template.animations.len(),
+            if template.state_machine.is_some() { ", state machine" } else { "" },
+        );
+    }
+
+    println!("\nanimations:");
+    for (name, template) in templates.iter() {
+        for (animation_name, animation) in template.animations.iter() {
+            println!(
+                "  {:<24} {} frames, node animations: {}{}",
+                format!("{}/{animation_name}", name.unwrap_or(ROOT_LABEL)),
+                animation.total_duration,
+                animation.node_animations.len(),
+                if animation.is_baked() { ", baked" } else { "" },
+            );
+        }
+    }
+
+    println!("\nresources:");
+    for (kind, name, bytes) in asset.resources.iter() {
+        println!("  {kind:<5} {name:<24} {} bytes", bytes.len());
+    }
+    for reference in asset.resources.unresolved.iter() {
+        println!("  {:<5} {:<24} referenced", reference.kind, reference.name);
+    }
+
+    if let Some(container) = container {
+        println!("\nchunks:");
+        for (tag, flags, payload) in container.chunks() {
+            let compression = match flags.compression() {
+                Some(compression) => format!("{compression:?}").to_lowercase(),
+                None => "unsupported".to_string(),
+            };
+            println!("  {tag} {:>10} bytes, compression {compression}", payload.len());
+        }
+    }
+}
+
+/// Prints every problem with the asset, returning whether it passed
+pub fn validate(asset: &LoadedAsset, deny_warnings: bool) -> bool {
+    let available = AvailableResources::new()
+        .with_images(asset.resources.images.keys().map(String::as_str))
+        .with_fonts(asset.resources.fonts.keys().map(String::as_str));
+    let diagnostics = envy::asset::validate_with_resources(&asset.root, &available);
+
+    diagnostics
+        .iter()
+        .for_each(|diagnostic| println!("{diagnostic}"));
+
+    let errors = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity() == Severity::Error)
+        .count();
+    let warnings = diagnostics.len() - errors;
+    eprintln!("{errors} errors, {warnings} warnings");
+
+    errors == 0 && (!deny_warnings || warnings == 0)
+}
@@ -0,0 +1,296 @@
+//! Inspects, converts and validates `.envy` assets without a GPU
+//!
+//! Assets are loaded into an in-memory [`MemoryAssetProvider`] with [`RecordingBackend`] as the layout's backend, so
+//! nothing is ever decoded or rendered. Run `envy-cli help` for the list of commands.
+//!
+//! The exit code is `0` on success, `1` when `validate` finds a problem, `diff` finds a difference or `merge` finds a
+//! conflict, and `2` when the arguments are wrong or a file could not be read or written.
+
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use envy::{
+    asset::{container::Compression, MemoryAssetProvider, SerializeOptions},
+    testing::RecordingBackend,
+    LayoutRoot,
+};
+
+mod inspect;
+mod merge;
+mod text;
+
+const USAGE: &str = "\
+usage: envy-cli <command> [arguments]
+
+commands:
+  tree <asset> [template]       print the nodes of every template, or of a single one
+  list <asset>                  list the templates, animations, resources and chunks of an asset
+  extract <asset> <directory>   write every image and font of an asset to a directory
+  to-text <asset> <output>      convert an asset to JSON, writing its images and fonts next to it
+      --resources <directory>   where to write the images and fonts instead of `resources`
+  from-text <input> <asset>     convert JSON written by `to-text` back to an asset
+  upgrade <asset> <output>      rewrite an asset from any older version of envy in the current format
+  validate <asset>              report problems with an asset, failing if there are any errors
+      --deny-warnings           also fail if there are any warnings
+  diff <old> <new>              print the structural differences between two assets
+  merge <base> <ours> <theirs> [path]
+                                merge the changes of two assets to the same base over <ours>, writing conflicts to
+                                `<path>.conflicts`, can be used as a git merge driver with `merge %O %A %B %P`
+
+`from-text`, `upgrade` and `merge` accept `--compression <none|zstd|lz4>`, methods other than `none` need envy-cli to be built
+with the feature of the same name.";
+
+/// Exit code of `validate`, `diff` and `merge` when they find a problem, a difference or a conflict
+const EXIT_INVALID: u8 = 1;
+
+/// Exit code for bad arguments and failed reads and writes
+const EXIT_FAILURE: u8 = 2;
+
+/// An asset that has been read into memory
+pub struct LoadedAsset {
+    pub bytes: Vec<u8>,
+    pub root: LayoutRoot<RecordingBackend>,
+    pub resources: MemoryAssetProvider,
+}
+
+/// Reads an asset, returning an error instead of panicking when it is malformed
+pub fn load_asset(path: &Path) -> Result<LoadedAsset, String> {
+    let bytes = read(path)?;
+    let mut resources = MemoryAssetProvider::new();
+    let root = envy::asset::try_deserialize(&mut resources, &bytes)
+        .map_err(|error| format!("failed to load {}: {error}", path.display()))?;
+
+    Ok(LoadedAsset {
+        bytes,
+        root,
+        resources,
+    })
+}
+
+pub fn read(path: &Path) -> Result<Vec<u8>, String> {
+    std::fs::read(path).map_err(|error| format!("failed to read {}: {error}", path.display()))
+}
+
+pub fn write(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .map_err(|error| format!("failed to create {}: {error}", parent.display()))?;
+    }
+
+    std::fs::write(path, bytes).map_err(|error| format!("failed to write {}: {error}", path.display()))
+}
+
+/// Why a command failed
+enum Error {
+    /// The arguments are wrong, the usage is printed after the message
+    Usage(String),
+    Failed(String),
+}
+
+impl From<String> for Error {
+    fn from(value: String) -> Self {
+        Self::Failed(value)
+    }
+}
+
+/// Arguments of a command, split into positional arguments and `--flag [value]` options
+struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, Option<String>)>,
+}
+
+impl Args {
+    /// Options that are followed by a value
+    const VALUE_OPTIONS: &[&str] = &["--resources", "--compression"];
+
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self, Error> {
+        let mut positional = vec![];
+        let mut options = vec![];
+        let mut args = args;
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                positional.push(arg);
+                continue;
+            }
+
+            let (name, value) = match arg.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None if Self::VALUE_OPTIONS.contains(&arg.as_str()) => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| Error::Usage(format!("{arg} needs a value")))?;
+                    (arg, Some(value))
+                }
+                None => (arg, None),
+            };
+
+            options.push((name, value));
+        }
+
+        Ok(Self {
+            positional,
+            options,
+        })
+    }
+
+    /// Exactly `N` positional arguments
+    fn positional<const N: usize>(&self) -> Result<[&str; N], Error> {
+        if self.positional.len() != N {
+            return Err(Error::Usage("wrong number of arguments".to_string()));
+        }
+
+        Ok(std::array::from_fn(|index| self.positional[index].as_str()))
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.options.iter().any(|(option, _)| option == name)
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(option, _)| option == name)
+            .and_then(|(_, value)| value.as_deref())
+    }
+
+    /// Fails on any option that isn't one of `known`
+    fn expect_options(&self, known: &[&str]) -> Result<(), Error> {
+        match self.options.iter().find(|(option, _)| !known.contains(&option.as_str())) {
+            Some((option, _)) => Err(Error::Usage(format!("unknown option {option}"))),
+            None => Ok(()),
+        }
+    }
+
+    fn serialize_options(&self) -> Result<SerializeOptions, Error> {
+        let compression = match self.value("--compression") {
+            None | Some("none") => Compression::None,
+            #[cfg(feature = "zstd")]
+            Some("zstd") => Compression::Zstd,
+            #[cfg(feature = "lz4")]
+            Some("lz4") => Compression::Lz4,
+            Some(other) => return Err(Error::Usage(format!("unsupported compression '{other}'"))),
+        };
+
+        // Images are usually already compressed by their own format, see `SerializeOptions::image_compression`
+        Ok(SerializeOptions {
+            compression,
+            ..Default::default()
+        })
+    }
+}
+
+/// Runs a command, returning whether it succeeded
+fn run(command: &str, args: &Args) -> Result<bool, Error> {
+    match command {
+        "tree" => {
+            args.expect_options(&[])?;
+            let (asset, template) = match args.positional.as_slice() {
+                [asset] => (asset, None),
+                [asset, template] => (asset, Some(template.as_str())),
+                _ => return Err(Error::Usage("wrong number of arguments".to_string())),
+            };
+            inspect::tree(&load_asset(Path::new(asset))?, template)?;
+        }
+        "list" => {
+            args.expect_options(&[])?;
+            let [asset] = args.positional()?;
+            inspect::list(&load_asset(Path::new(asset))?);
+        }
+        "extract" => {
+            args.expect_options(&[])?;
+            let [asset, directory] = args.positional()?;
+            let asset = load_asset(Path::new(asset))?;
+            for (kind, name, bytes) in asset.resources.iter() {
+                let path = Path::new(directory).join(text::resource_file_name(kind, name, bytes));
+                write(&path, bytes)?;
+                println!("{}", path.display());
+            }
+        }
+        "to-text" => {
+            args.expect_options(&["--resources"])?;
+            let [asset, output] = args.positional()?;
+            let output = PathBuf::from(output);
+            let resource_dir = args
+                .value("--resources")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| output.with_file_name("resources"));
+            text::to_text(&load_asset(Path::new(asset))?, &output, &resource_dir)?;
+        }
+        "from-text" => {
+            args.expect_options(&["--compression"])?;
+            let [input, output] = args.positional()?;
+            let options = args.serialize_options()?;
+            let (root, resources) = text::from_text(Path::new(input))?;
+            write(Path::new(output), &envy::asset::serialize_with_options(&root, &resources, options))?;
+        }
+        "upgrade" => {
+            args.expect_options(&["--compression"])?;
+            let [input, output] = args.positional()?;
+            let options = args.serialize_options()?;
+            let asset = load_asset(Path::new(input))?;
+            if let Some(reference) = asset.resources.unresolved.first() {
+                return Err(Error::Failed(format!(
+                    "{} '{}' is referenced instead of embedded, pack {input} before upgrading it",
+                    reference.kind, reference.name
+                )));
+            }
+
+            write(
+                Path::new(output),
+                &envy::asset::serialize_with_options(&asset.root, &asset.resources, options),
+            )?;
+        }
+        "validate" => {
+            args.expect_options(&["--deny-warnings"])?;
+            let [asset] = args.positional()?;
+            return Ok(inspect::validate(
+                &load_asset(Path::new(asset))?,
+                args.flag("--deny-warnings"),
+            ));
+        }
+        "diff" => {
+            args.expect_options(&[])?;
+            let [old, new] = args.positional()?;
+            return Ok(merge::diff(Path::new(old), Path::new(new))?);
+        }
+        "merge" => {
+            args.expect_options(&["--compression"])?;
+            let (paths, path) = match args.positional.as_slice() {
+                [base, ours, theirs] => ([base, ours, theirs], None),
+                [base, ours, theirs, path] => ([base, ours, theirs], Some(Path::new(path))),
+                _ => return Err(Error::Usage("wrong number of arguments".to_string())),
+            };
+            let [base, ours, theirs] = paths.map(Path::new);
+            return Ok(merge::merge(base, ours, theirs, path, args.serialize_options()?)?);
+        }
+        "help" => println!("{USAGE}"),
+        other => return Err(Error::Usage(format!("unknown command '{other}'"))),
+    }
+
+    Ok(true)
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let Some(command) = args.next() else {
+        eprintln!("{USAGE}");
+        return ExitCode::from(EXIT_FAILURE);
+    };
+
+    match Args::parse(args).and_then(|args| run(&command, &args)) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(EXIT_INVALID),
+        Err(Error::Usage(error)) => {
+            eprintln!("error: {error}\n\n{USAGE}");
+            ExitCode::from(EXIT_FAILURE)
+        }
+        Err(Error::Failed(error)) => {
+            eprintln!("error: {error}");
+            ExitCode::from(EXIT_FAILURE)
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! Structural diffs and three-way merges of assets, for the `diff` and `merge` commands
+//!
+//! `merge` can be used as a git merge driver, so that branches that touch different nodes of the same layout merge
+//! cleanly:
+//!
+//! ```text
+//! # .git/config or ~/.gitconfig
+//! [merge "envy"]
+//!     name = envy layout merge
+//!     driver = envy-cli merge %O %A %B %P
+//!
+//! # .gitattributes
+//! *.envy merge=envy
+//! ```
+//!
+//! The merged asset is written over `ours`, which is where git expects it. When there are conflicts the version from
+//! `ours` is kept for each of them, the command fails so that git marks the file as conflicted, and every conflict is
+//! written to a report next to the asset, with the value from each side between conflict markers.
+
+use std::path::Path;
+
+use envy::{
+    asset::{
+        diff::{AssetContents, Conflict, ConflictKind, NodeProperty},
+        SerializeOptions,
+    },
+    testing::RecordingBackend,
+    NodeAnimation, NodeTemplate,
+};
+use serde_json::{json, Value};
+
+/// Field of [`NodeAnimation`] for every channel name reported by the merge
+const CHANNEL_FIELDS: &[(&str, &str)] = &[
+    ("angle", "angle_channel"),
+    ("position", "position_channel"),
+    ("motion path", "motion_path"),
+    ("size", "size_channel"),
+    ("scale", "scale_channel"),
+    ("color", "color_channel"),
+    ("visibility", "visibility_channel"),
+    ("UV offset", "uv_offset_channel"),
+    ("UV scale", "uv_scale_channel"),
+    ("fill amount", "fill_amount_channel"),
+    ("texture", "texture_channel"),
+    ("font size", "font_size_channel"),
+    ("line height", "line_height_channel"),
+    ("outline thickness", "outline_thickness_channel"),
+    ("outline color", "outline_color_channel"),
+    ("visible characters", "visible_characters_channel"),
+    ("text", "text_channel"),
+    ("sublayout animation", "sublayout_animation_channel"),
+    ("gradient stop colors", "gradient_stop_color_channels"),
+    ("baked samples", "baked"),
+];
+
+fn load(path: &Path) -> Result<AssetContents, String> {
+    let bytes = crate::read(path)?;
+    AssetContents::try_from_bytes(&bytes).map_err(|error| format!("failed to load {}: {error}", path.display()))
+}
+
+/// Prints every difference between two assets, returning whether they are the same
+pub fn diff(old: &Path, new: &Path) -> Result<bool, String> {
+    let changes = envy::asset::diff::diff(&load(old)?, &load(new)?);
+    changes.iter().for_each(|change| println!("{change}"));
+    Ok(changes.is_empty())
+}
+
+fn to_json(value: &impl serde::Serialize) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+fn property_value(node: &NodeTemplate, property: NodeProperty) -> Value {
+    match property {
+        NodeProperty::Angle => to_json(&node.transform.angle),
+        NodeProperty::Position => to_json(&node.transform.position),
+        NodeProperty::Size => to_json(&node.transform.size),
+        NodeProperty::Scale => to_json(&node.transform.scale),
+        NodeProperty::Anchor => to_json(&node.transform.anchor),
+        NodeProperty::Color => to_json(&node.color),
+        NodeProperty::Visibility => to_json(&node.visibility),
+        NodeProperty::Gradient => to_json(&node.gradient),
+        NodeProperty::Implementation => to_json(&node.implementation),
+    }
+}
+
+/// The value `conflict` is about in one side of the merge, `None` if that side doesn't have it
+fn conflict_value(contents: &AssetContents, conflict: &Conflict) -> Option<Value> {
+    if let ConflictKind::Resource { kind, name } = &conflict.kind {
+        let bytes = contents.resources.get(*kind, name)?;
+        return Some(json!(format!("{} bytes", bytes.len())));
+    }
+
+    let template = contents.template(conflict.template.as_deref())?;
+    let animation = |name: &str| {
+        template
+            .animations
+            .iter()
+            .find(|(animation, _)| animation == name)
+            .map(|(_, animation)| animation)
+    };
+    let node_animation = |name: &str, node: &str| -> Option<&NodeAnimation> {
+        animation(name)?
+            .node_animations
+            .iter()
+            .find(|animation| animation.node_path == node)
+    };
+
+    let value = match &conflict.kind {
+        ConflictKind::Template => to_json(template),
+        ConflictKind::CanvasSize => to_json(&template.canvas_size),
+        ConflictKind::StateMachine => to_json(&template.state_machine),
+        ConflictKind::Inheritance => to_json(&template.inheritance),
+        ConflictKind::Node { node } => to_json(template.get_node_by_path(node)?),
+        ConflictKind::NodeProperty { node, property } => property_value(template.get_node_by_path(node)?, *property),
+        ConflictKind::ChildOrder { parent } => {
+            let children = if parent.is_empty() {
+                &template.root_nodes
+            } else {
+                &template.get_node_by_path(parent)?.children
+            };
+            json!(children.iter().map(|child| child.name.as_str()).collect::<Vec<_>>())
+        }
+        ConflictKind::Animation { animation: name } => to_json(animation(name)?),
+        ConflictKind::AnimationDuration { animation: name } => json!(animation(name)?.total_duration),
+        ConflictKind::NodeAnimation { animation, node } => to_json(node_animation(animation, node)?),
+        ConflictKind::Channel {
+            animation,
+            node,
+            channel,
+        } => {
+            let (_, field) = CHANNEL_FIELDS.iter().find(|(name, _)| name == channel)?;
+            to_json(node_animation(animation, node)?).get(*field)?.clone()
+        }
+        ConflictKind::Resource { .. } => unreachable!(),
+    };
+
+    Some(value)
+}
+
+/// One conflict of the report, with the value from each side between conflict markers
+fn report_entry(conflict: &Conflict, [base, ours, theirs]: [&AssetContents; 3]) -> String {
+    let side = |contents: &AssetContents| match conflict_value(contents, conflict) {
+        Some(value) => serde_json::to_string_pretty(&value).unwrap_or_default(),
+        None => "(missing)".to_string(),
+    };
+
+    format!(
+        "{conflict}\n<<<<<<< ours\n{}\n||||||| base\n{}\n=======\n{}\n>>>>>>> theirs\n",
+        side(ours),
+        side(base),
+        side(theirs)
+    )
+}
+
+/// Merges the changes `ours` and `theirs` made to `base`, writing the result over `ours`
+///
+/// Conflicts are written to `<path>.conflicts`, where `path` is the path of the asset in the repository, or `ours` when
+/// it isn't given. Returns whether the merge was clean, an outdated report is removed when it is.
+pub fn merge(
+    base: &Path,
+    ours: &Path,
+    theirs: &Path,
+    path: Option<&Path>,
+    options: SerializeOptions,
+) -> Result<bool, String> {
+    let contents = [load(base)?, load(ours)?, load(theirs)?];
+    let [base_contents, ours_contents, theirs_contents] = &contents;
+    let result = envy::asset::diff::merge(base_contents, ours_contents, theirs_contents);
+
+    let root = result
+        .contents
+        .try_to_root::<RecordingBackend>()
+        .map_err(|error| format!("failed to write the merged asset: {error}"))?;
+    let bytes = envy::asset::serialize_with_options(&root, &result.contents.resources, options);
+    crate::write(ours, &bytes)?;
+
+    let mut report_path = path.unwrap_or(ours).as_os_str().to_owned();
+    report_path.push(".conflicts");
+    let report_path = Path::new(&report_path);
+
+    if result.conflicts.is_empty() {
+        if report_path.exists() {
+            std::fs::remove_file(report_path)
+                .map_err(|error| format!("failed to remove {}: {error}", report_path.display()))?;
+        }
+        return Ok(true);
+    }
+
+    let sides = [base_contents, ours_contents, theirs_contents];
+    let report = result
+        .conflicts
+        .iter()
+        .map(|conflict| report_entry(conflict, sides))
+        .collect::<Vec<_>>()
+        .join("\n");
+    crate::write(report_path, report.as_bytes())?;
+
+    result
+        .conflicts
+        .iter()
+        .for_each(|conflict| eprintln!("conflict: {conflict}"));
+    eprintln!(
+        "{} conflicts, kept ours for each of them, see {}",
+        result.conflicts.len(),
+        report_path.display()
+    );
+
+    Ok(false)
+}
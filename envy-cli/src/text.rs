@@ -0,0 +1,113 @@
+//! JSON representation of an asset, for the `to-text` and `from-text` commands
+//!
+//! Templates are written with their serde representation, while images and fonts are written to their own files and
+//! listed by path, relative to the JSON file:
+//!
+//! ```json
+//! {
+//!   "root": { "canvas_size": [1920, 1080], "root_nodes": [...], "animations": [...], "state_machine": null },
+//!   "templates": { "button": { ... } },
+//!   "images": { "background": "resources/background.png" },
+//!   "fonts": { "title": "resources/title.ttf" }
+//! }
+//! ```
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+use envy::{
+    asset::{MemoryAssetProvider, ResourceKind},
+    testing::RecordingBackend,
+    LayoutRoot, LayoutTemplate,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::LoadedAsset;
+
+#[derive(Serialize, Deserialize)]
+struct TextAsset {
+    root: LayoutTemplate,
+    #[serde(default)]
+    templates: BTreeMap<String, LayoutTemplate>,
+    #[serde(default)]
+    images: BTreeMap<String, PathBuf>,
+    #[serde(default)]
+    fonts: BTreeMap<String, PathBuf>,
+}
+
+/// Name of the file a resource is extracted to
+///
+/// Path separators in the name are replaced, and names without an extension get the usual one for their contents so
+/// that the files can be opened by other tools (and found again by the `pack` example).
+pub fn resource_file_name(kind: ResourceKind, name: &str, bytes: &[u8]) -> String {
+    let name = name.replace(['/', '\\'], "_");
+    if Path::new(&name).extension().is_some() {
+        return name;
+    }
+
+    let extension = match kind {
+        ResourceKind::Image if bytes.starts_with(b"\x89PNG") => "png",
+        ResourceKind::Image if bytes.starts_with(&[0xff, 0xd8, 0xff]) => "jpg",
+        ResourceKind::Image => "bin",
+        ResourceKind::Font if bytes.starts_with(b"OTTO") => "otf",
+        ResourceKind::Font => "ttf",
+    };
+
+    format!("{name}.{extension}")
+}
+
+/// Writes `asset` as JSON to `output`, and its images and fonts to `resource_dir`
+pub fn to_text(asset: &LoadedAsset, output: &Path, resource_dir: &Path) -> Result<(), String> {
+    let base = output.parent().unwrap_or(Path::new(""));
+    let mut text = TextAsset {
+        root: asset.root.root_template().clone(),
+        templates: crate::inspect::templates(asset)
+            .into_iter()
+            .filter_map(|(name, template)| Some((name?.to_string(), template.clone())))
+            .collect(),
+        images: BTreeMap::new(),
+        fonts: BTreeMap::new(),
+    };
+
+    for (kind, name, bytes) in asset.resources.iter() {
+        let path = resource_dir.join(resource_file_name(kind, name, bytes));
+        crate::write(&path, bytes)?;
+
+        let path = match path.strip_prefix(base) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => std::path::absolute(&path)
+                .map_err(|error| format!("failed to resolve {}: {error}", path.display()))?,
+        };
+
+        let _ = match kind {
+            ResourceKind::Image => text.images.insert(name.to_string(), path),
+            ResourceKind::Font => text.fonts.insert(name.to_string(), path),
+        };
+    }
+
+    let json = serde_json::to_string_pretty(&text).map_err(|error| format!("failed to write JSON: {error}"))?;
+    crate::write(output, json.as_bytes())
+}
+
+/// Reads JSON written by [`to_text`], along with the images and fonts it lists
+pub fn from_text(input: &Path) -> Result<(LayoutRoot<RecordingBackend>, MemoryAssetProvider), String> {
+    let json = crate::read(input)?;
+    let text: TextAsset = serde_json::from_slice(&json)
+        .map_err(|error| format!("failed to parse {}: {error}", input.display()))?;
+
+    let base = input.parent().unwrap_or(Path::new(""));
+    let mut resources = MemoryAssetProvider::new();
+    for (kind, files) in [(ResourceKind::Image, text.images), (ResourceKind::Font, text.fonts)] {
+        for (name, path) in files {
+            resources.insert(kind, name, crate::read(&base.join(path))?);
+        }
+    }
+
+    let templates = text.templates.into_iter().collect::<HashMap<_, _>>();
+    envy::asset::check_templates(&text.root, &templates)
+        .map_err(|error| format!("failed to load {}: {error}", input.display()))?;
+    let root = LayoutRoot::from_root_template(text.root, templates);
+    Ok((root, resources))
+}
@@ -0,0 +1,209 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use envy::{
+    asset::{deserialize, serialize, MemoryAssetProvider, ResourceKind},
+    testing::RecordingBackend,
+    Animation, AnimationChannel, AnimationTransform, ImageNodeTemplate, LayoutRoot, LayoutTemplate, NodeAnimation,
+    NodeImplTemplate, NodeTemplate, NodeTransform, SublayoutNodeTemplate, TextAlignment, TextNodeTemplate,
+    TransformStep,
+};
+
+const RED: &[u8] = &[255, 0, 0, 255];
+const FONT: &[u8] = b"not really a font";
+
+/// An empty directory for the files of `test`
+fn directory(test: &str) -> PathBuf {
+    let directory = Path::new(env!("CARGO_TARGET_TMPDIR")).join(test);
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+/// Runs envy-cli, returning its exit code
+fn envy_cli(args: &[&Path]) -> i32 {
+    let output = Command::new(env!("CARGO_BIN_EXE_envy-cli")).args(args).output().unwrap();
+    output.status.code().unwrap()
+}
+
+fn node(name: &str, implementation: NodeImplTemplate) -> NodeTemplate {
+    NodeTemplate {
+        name: name.to_string(),
+        transform: NodeTransform::from_size(glam::Vec2::splat(10.0)),
+        color: [255; 4],
+        visibility: Default::default(),
+        gradient: None,
+        children: vec![],
+        implementation,
+    }
+}
+
+fn image(name: &str, texture: &str) -> NodeTemplate {
+    node(
+        name,
+        NodeImplTemplate::Image(ImageNodeTemplate {
+            texture_name: texture.to_string(),
+            mask_texture_name: None,
+            image_scaling_mode_x: Default::default(),
+            image_scaling_mode_y: Default::default(),
+            uv_offset: glam::Vec2::ZERO,
+            uv_scale: glam::Vec2::ONE,
+            fill_method: Default::default(),
+            fill_origin: Default::default(),
+            fill_amount: 1.0,
+        }),
+    )
+}
+
+/// Moves `path` over 10 frames, in an animation that lasts `total_duration` frames
+fn slide(path: &str, total_duration: usize) -> Animation {
+    let position = AnimationChannel::from_transforms(glam::Vec2::ZERO, vec![AnimationTransform {
+        end: glam::Vec2::new(10.0, 0.0),
+        duration: 10,
+        first_step: TransformStep::Linear,
+        additional_steps: vec![],
+        end_tangents: Default::default(),
+    }]);
+
+    let animation = NodeAnimation {
+        node_path: path.to_string(),
+        angle_channel: None,
+        position_channel: Some(position),
+        motion_path: None,
+        size_channel: None,
+        scale_channel: None,
+        color_channel: None,
+        visibility_channel: None,
+        uv_offset_channel: None,
+        uv_scale_channel: None,
+        fill_amount_channel: None,
+        texture_channel: None,
+        font_size_channel: None,
+        line_height_channel: None,
+        outline_thickness_channel: None,
+        outline_color_channel: None,
+        visible_characters_channel: None,
+        text_channel: None,
+        sublayout_animation_channel: None,
+        gradient_stop_color_channels: vec![],
+        baked: None,
+    };
+
+    Animation {
+        node_animations: vec![animation],
+        total_duration,
+    }
+}
+
+/// Writes an asset with the `root_nodes` and `animations` of its root template to `path`, embedding [`RED`] as `red`
+fn write_asset(path: &Path, root_nodes: Vec<NodeTemplate>, animations: Vec<(String, Animation)>) {
+    let template = LayoutTemplate {
+        canvas_size: [100, 100],
+        root_nodes,
+        animations,
+        ..Default::default()
+    };
+
+    let mut resources = MemoryAssetProvider::new();
+    resources.insert(ResourceKind::Image, "red", RED.to_vec());
+    let root = LayoutRoot::<RecordingBackend>::from_root_template(template, []);
+    std::fs::write(path, serialize(&root, &resources)).unwrap();
+}
+
+#[test]
+fn validate_exit_codes() {
+    let directory = directory("validate_exit_codes");
+    let validate = |path: &Path, deny_warnings: bool| {
+        let mut args = vec![Path::new("validate"), path];
+        if deny_warnings {
+            args.push(Path::new("--deny-warnings"));
+        }
+        envy_cli(&args)
+    };
+
+    let valid = directory.join("valid.envy");
+    write_asset(&valid, vec![image("a", "red")], vec![]);
+    assert_eq!(validate(&valid, false), 0);
+    assert_eq!(validate(&valid, true), 0);
+
+    // The animation ends before its channel does
+    let warning = directory.join("warning.envy");
+    write_asset(&warning, vec![image("a", "red")], vec![("slide".to_string(), slide("a", 5))]);
+    assert_eq!(validate(&warning, false), 0);
+    assert_eq!(validate(&warning, true), 1);
+
+    let error = directory.join("error.envy");
+    write_asset(&error, vec![image("a", "red"), image("a", "red")], vec![]);
+    assert_eq!(validate(&error, false), 1);
+
+    assert_eq!(validate(&directory.join("missing.envy"), false), 2);
+    assert_eq!(envy_cli(&[Path::new("validate")]), 2);
+    assert_eq!(envy_cli(&[Path::new("validate"), &valid, Path::new("--unknown")]), 2);
+}
+
+#[test]
+fn text_round_trip() {
+    let directory = directory("text_round_trip");
+
+    let mut button = LayoutTemplate {
+        canvas_size: [10, 10],
+        root_nodes: vec![node(
+            "label",
+            NodeImplTemplate::Text(TextNodeTemplate {
+                font_name: "title".to_string(),
+                text: "Start".to_string(),
+                font_size: 16.0,
+                line_height: 20.0,
+                outline_thickness: 0.0,
+                outline_color: [0, 0, 0, 255],
+                alignment: TextAlignment::default(),
+            }),
+        )],
+        ..Default::default()
+    };
+    button.add_animation("slide", slide("label", 10));
+
+    let root_template = LayoutTemplate {
+        canvas_size: [100, 100],
+        root_nodes: vec![
+            image("background", "red"),
+            node(
+                "button",
+                NodeImplTemplate::Sublayout(SublayoutNodeTemplate {
+                    sublayout_name: "button".to_string(),
+                }),
+            ),
+        ],
+        ..Default::default()
+    };
+
+    let mut resources = MemoryAssetProvider::new();
+    resources.insert(ResourceKind::Image, "red", RED.to_vec());
+    resources.insert(ResourceKind::Font, "title", FONT.to_vec());
+    let root = LayoutRoot::<RecordingBackend>::from_root_template(root_template, [("button".to_string(), button)]);
+
+    let asset = directory.join("asset.envy");
+    let text = directory.join("text/asset.json");
+    let converted = directory.join("converted.envy");
+    std::fs::write(&asset, serialize(&root, &resources)).unwrap();
+
+    assert_eq!(envy_cli(&[Path::new("to-text"), &asset, &text]), 0);
+    assert!(directory.join("text/resources/red.bin").exists());
+    assert_eq!(envy_cli(&[Path::new("from-text"), &text, &converted]), 0);
+
+    let mut loaded = MemoryAssetProvider::new();
+    let converted: LayoutRoot<RecordingBackend> = deserialize(&mut loaded, &std::fs::read(&converted).unwrap());
+    assert_eq!(loaded, resources);
+
+    let templates = |root: &LayoutRoot<RecordingBackend>| {
+        root.iter_templates()
+            .into_iter()
+            .map(|(name, template)| (name.to_string(), template.clone()))
+            .collect::<BTreeMap<_, _>>()
+    };
+    assert!(converted.root_template() == root.root_template());
+    assert!(templates(&converted) == templates(&root));
+}
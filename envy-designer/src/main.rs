@@ -224,6 +224,9 @@ impl Application {
 
         let file_data = std::fs::read(path).unwrap();
         let mut root = envy::asset::deserialize(&mut backend, &file_data);
+
+        // The designer lists and previews every resource, so there is nothing to gain from loading them lazily
+        backend.load_deferred_resources();
         root.setup(&mut backend);
 
         let templates = root
@@ -317,7 +320,7 @@ impl eframe::App for Application {
                 } else if ui.button("Save").clicked() {
                     if let Some(path) = self.file_path.as_ref() {
                         let root = self.root.lock();
-                        let bytes = envy::asset::serialize(&root, &self.backend.lock());
+                        let bytes = envy::asset::serialize(&root, &*self.backend.lock());
                         std::fs::write(path, &bytes).unwrap();
                     } else {
                         let file = rfd::FileDialog::new()
@@ -328,7 +331,7 @@ impl eframe::App for Application {
                         if let Some(file) = file {
                             self.file_path = Some(file.clone());
                             let root = self.root.lock();
-                            let bytes = envy::asset::serialize(&root, &self.backend.lock());
+                            let bytes = envy::asset::serialize(&root, &*self.backend.lock());
                             std::fs::write(&file, &bytes).unwrap();
                         }
                     }
@@ -342,7 +345,7 @@ impl eframe::App for Application {
                     if let Some(file) = file {
                         self.file_path = Some(file.clone());
                         let root = self.root.lock();
-                        let bytes = envy::asset::serialize(&root, &self.backend.lock());
+                        let bytes = envy::asset::serialize(&root, &*self.backend.lock());
                         std::fs::write(&file, &bytes).unwrap();
                     }
                     ui.close();
@@ -1,3 +1,4 @@
 pub mod animator;
+pub mod curve_editor;
 pub mod layout_renderer;
 pub mod resource_viewer;
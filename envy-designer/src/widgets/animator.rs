@@ -1,11 +1,15 @@
-use std::{sync::Arc, time::Instant};
+use std::{collections::HashMap, sync::Arc, time::Instant};
 
 use camino::{Utf8Path, Utf8PathBuf};
 use egui_ltreeview::Action;
-use envy::{Animation, AnimationChannel, LayoutRoot, LayoutTree, NodeAnimation, NodeImplTemplate, NodeTemplate};
+use envy::{
+    Animation, AnimationChannel, ChannelMode, DiscreteChannel, Interpolatable, LayoutRoot, LayoutTree, MotionPath, NodeAnimation,
+    NodeImplTemplate, NodeTemplate, NodeVisibility, SUBLAYOUT_PATH_SEPARATOR,
+};
 use envy_wgpu::WgpuBackend;
 use parking_lot::Mutex;
 
+use crate::widgets::curve_editor::{CurveTarget, curve_toggle};
 use crate::widgets::layout_renderer::{
     LayoutPaintCallback, PaintingReference, pipeline::CopyTexturePipeline,
 };
@@ -33,6 +37,9 @@ pub struct AnimatorWidget {
     animation: String,
     editing_node: Option<Utf8PathBuf>,
     current_keyframe: usize,
+    curve_target: Option<CurveTarget>,
+    dragging_path_point: Option<usize>,
+    quantize_bake: bool,
     playback_start: Option<Instant>,
 }
 
@@ -57,6 +64,9 @@ impl AnimatorWidget {
             animation: String::new(),
             editing_node: None,
             current_keyframe: 0usize,
+            curve_target: None,
+            dragging_path_point: None,
+            quantize_bake: false,
             playback_start: None,
         }
     }
@@ -110,6 +120,13 @@ impl AnimatorWidget {
                     });
             });
 
+            // Nodes inside of sublayouts can be animated too, so the tree view needs every template
+            let sublayouts = root
+                .iter_templates()
+                .into_iter()
+                .map(|(name, template)| (name.to_string(), template.root_nodes.clone()))
+                .collect::<HashMap<_, _>>();
+
             let template = match &self.layout_reference {
                 LayoutReference::Root => root.root_template_mut(),
                 LayoutReference::Sublayout(reference) => root.template_mut(reference).unwrap(),
@@ -182,6 +199,20 @@ impl AnimatorWidget {
                 ui.add(egui::DragValue::new(&mut animation.total_duration));
             });
 
+            ui.horizontal(|ui| {
+                if animation.is_baked() {
+                    ui.label("Baked").on_hover_text("Edits aren't previewed until the baked data is cleared");
+                    if ui.button("Clear Baked Data").clicked() {
+                        animation.clear_baked();
+                    }
+                } else {
+                    ui.checkbox(&mut self.quantize_bake, "Quantize");
+                    if ui.button("Bake").clicked() {
+                        animation.bake(self.quantize_bake);
+                    }
+                }
+            });
+
             if ui.button("Play Animation").clicked() {
                 self.playback_start = Some(Instant::now());
             }
@@ -204,9 +235,19 @@ impl AnimatorWidget {
                             node: &NodeTemplate,
                             parent: &Utf8Path,
                             animation: &Animation,
+                            sublayouts: &HashMap<String, Vec<NodeTemplate>>,
                             builder: &mut egui_ltreeview::TreeViewBuilder<'_, Utf8PathBuf>,
                         ) {
-                            let path = parent.join(&node.name);
+                            let path = if parent.as_str().ends_with(SUBLAYOUT_PATH_SEPARATOR) {
+                                Utf8PathBuf::from(format!("{parent}{}", node.name))
+                            } else {
+                                parent.join(&node.name)
+                            };
+
+                            let sublayout_nodes = match &node.implementation {
+                                NodeImplTemplate::Sublayout(sublayout) => sublayouts.get(&sublayout.sublayout_name),
+                                _ => None,
+                            };
 
                             let is_animated = animation
                                 .node_animations
@@ -218,7 +259,7 @@ impl AnimatorWidget {
                                 egui::RichText::new(&node.name).color(disabled_color).into()
                             };
 
-                            if node.children.is_empty() {
+                            if node.children.is_empty() && sublayout_nodes.is_none_or(Vec::is_empty) {
                                 builder.leaf(path, label);
                             } else {
                                 builder.dir(path.clone(), label);
@@ -229,9 +270,23 @@ impl AnimatorWidget {
                                         child,
                                         &path,
                                         animation,
+                                        sublayouts,
                                         builder,
                                     );
                                 });
+
+                                let sublayout_path = Utf8PathBuf::from(format!("{path}{SUBLAYOUT_PATH_SEPARATOR}"));
+                                for child in sublayout_nodes.into_iter().flatten() {
+                                    recursive(
+                                        enabled_color,
+                                        disabled_color,
+                                        child,
+                                        &sublayout_path,
+                                        animation,
+                                        sublayouts,
+                                        builder,
+                                    );
+                                }
                                 builder.close_dir();
                             }
                         }
@@ -244,6 +299,7 @@ impl AnimatorWidget {
                                 root,
                                 Utf8Path::new(""),
                                 animation,
+                                &sublayouts,
                                 builder,
                             );
                         }
@@ -256,6 +312,7 @@ impl AnimatorWidget {
                     };
 
                     self.editing_node = Some(nodes[0].clone());
+                    self.curve_target = None;
                 }
             });
         });
@@ -265,16 +322,29 @@ impl AnimatorWidget {
         };
 
         let template = match &self.layout_reference {
-            LayoutReference::Root => root.root_template_mut(),
-            LayoutReference::Sublayout(reference) => root.template_mut(reference).unwrap(),
+            LayoutReference::Root => root.root_template(),
+            LayoutReference::Sublayout(reference) => root.template(reference).unwrap(),
         };
 
         // This could technically fail due to tab render order if we rename the node in a different
         // tab while this one is visible
-        let Some(default_node) = template.get_node_by_path(node).map(|node| node.clone()) else {
+        let Some(default_node) = root.get_template_node_by_path(template, node).cloned() else {
             return;
         };
 
+        let sublayout_animations = match &default_node.implementation {
+            NodeImplTemplate::Sublayout(sublayout) => root
+                .template(&sublayout.sublayout_name)
+                .map(|template| template.animations.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>())
+                .unwrap_or_default(),
+            _ => vec![],
+        };
+
+        let template = match &self.layout_reference {
+            LayoutReference::Root => root.root_template_mut(),
+            LayoutReference::Sublayout(reference) => root.template_mut(reference).unwrap(),
+        };
+
         let animation = &mut template
             .animations
             .iter_mut()
@@ -293,11 +363,24 @@ impl AnimatorWidget {
                 node_path: node.to_string(),
                 angle_channel: None,
                 position_channel: None,
+                motion_path: None,
                 scale_channel: None,
                 size_channel: None,
                 color_channel: None,
                 uv_offset_channel: None,
                 uv_scale_channel: None,
+                fill_amount_channel: None,
+                font_size_channel: None,
+                line_height_channel: None,
+                outline_thickness_channel: None,
+                outline_color_channel: None,
+                visible_characters_channel: None,
+                visibility_channel: None,
+                texture_channel: None,
+                text_channel: None,
+                sublayout_animation_channel: None,
+                gradient_stop_color_channels: vec![],
+                baked: None,
             });
             animation.node_animations.last_mut().unwrap()
         };
@@ -320,10 +403,7 @@ impl AnimatorWidget {
                         .changed()
                     {
                         if has_angle_channel {
-                            node_animation.angle_channel = Some(AnimationChannel {
-                                start: default_node.transform.angle,
-                                transforms: vec![],
-                            });
+                            node_animation.angle_channel = Some(AnimationChannel::new(default_node.transform.angle));
                         } else {
                             node_animation.angle_channel = None;
                         }
@@ -335,25 +415,33 @@ impl AnimatorWidget {
                         .changed()
                     {
                         if has_position_channel {
-                            node_animation.position_channel = Some(AnimationChannel {
-                                start: default_node.transform.position,
-                                transforms: vec![],
-                            });
+                            node_animation.position_channel = Some(AnimationChannel::new(default_node.transform.position));
                         } else {
                             node_animation.position_channel = None;
                         }
                     }
 
+                    let mut has_motion_path = node_animation.motion_path.is_some();
+                    if ui.checkbox(&mut has_motion_path, "Follow Motion Path?").changed() {
+                        if has_motion_path {
+                            let start = default_node.transform.position;
+                            node_animation.motion_path = Some(MotionPath::new(
+                                start,
+                                start + glam::Vec2::new(200.0, 0.0),
+                                animation.total_duration,
+                            ));
+                        } else {
+                            node_animation.motion_path = None;
+                        }
+                    }
+
                     let mut has_size_channel = node_animation.size_channel.is_some();
                     if ui
                         .checkbox(&mut has_size_channel, "Animate Size Channel?")
                         .changed()
                     {
                         if has_size_channel {
-                            node_animation.size_channel = Some(AnimationChannel {
-                                start: default_node.transform.size,
-                                transforms: vec![],
-                            });
+                            node_animation.size_channel = Some(AnimationChannel::new(default_node.transform.size));
                         } else {
                             node_animation.size_channel = None;
                         }
@@ -365,10 +453,7 @@ impl AnimatorWidget {
                         .changed()
                     {
                         if has_scale_channel {
-                            node_animation.scale_channel = Some(AnimationChannel {
-                                start: default_node.transform.scale,
-                                transforms: vec![],
-                            });
+                            node_animation.scale_channel = Some(AnimationChannel::new(default_node.transform.scale));
                         } else {
                             node_animation.scale_channel = None;
                         }
@@ -380,15 +465,27 @@ impl AnimatorWidget {
                         .changed()
                     {
                         if has_color_channel {
-                            node_animation.color_channel = Some(AnimationChannel {
-                                start: default_node.color,
-                                transforms: vec![],
-                            });
+                            node_animation.color_channel = Some(AnimationChannel::new(default_node.color));
                         } else {
                             node_animation.color_channel = None;
                         }
                     }
 
+                    let mut has_visibility_channel = node_animation.visibility_channel.is_some();
+                    if ui
+                        .checkbox(&mut has_visibility_channel, "Animate Visibility Channel?")
+                        .changed()
+                    {
+                        if has_visibility_channel {
+                            node_animation.visibility_channel = Some(DiscreteChannel {
+                                start: default_node.visibility,
+                                keyframes: vec![],
+                            });
+                        } else {
+                            node_animation.visibility_channel = None;
+                        }
+                    }
+
                     if let NodeImplTemplate::Image(default_image) = &default_node.implementation {
                         let mut has_uv_offset_channel = node_animation.uv_offset_channel.is_some();
                         if ui
@@ -396,10 +493,7 @@ impl AnimatorWidget {
                             .changed()
                         {
                             if has_uv_offset_channel {
-                                node_animation.uv_offset_channel = Some(AnimationChannel {
-                                    start: default_image.uv_offset,
-                                    transforms: vec![],
-                                });
+                                node_animation.uv_offset_channel = Some(AnimationChannel::new(default_image.uv_offset));
                             } else {
                                 node_animation.uv_offset_channel = None;
                             }
@@ -411,20 +505,163 @@ impl AnimatorWidget {
                             .changed()
                         {
                             if has_uv_scale_channel {
-                                node_animation.uv_scale_channel = Some(AnimationChannel {
-                                    start: default_image.uv_scale,
-                                    transforms: vec![],
-                                });
+                                node_animation.uv_scale_channel = Some(AnimationChannel::new(default_image.uv_scale));
                             } else {
                                 node_animation.uv_scale_channel = None;
                             }
                         }
+
+                        let mut has_fill_amount_channel = node_animation.fill_amount_channel.is_some();
+                        if ui
+                            .checkbox(&mut has_fill_amount_channel, "Animate Fill Amount Channel?")
+                            .changed()
+                        {
+                            if has_fill_amount_channel {
+                                node_animation.fill_amount_channel = Some(AnimationChannel::new(default_image.fill_amount));
+                            } else {
+                                node_animation.fill_amount_channel = None;
+                            }
+                        }
+
+                        let mut has_texture_channel = node_animation.texture_channel.is_some();
+                        if ui
+                            .checkbox(&mut has_texture_channel, "Animate Texture Channel?")
+                            .changed()
+                        {
+                            if has_texture_channel {
+                                node_animation.texture_channel = Some(DiscreteChannel {
+                                    start: default_image.texture_name.clone(),
+                                    keyframes: vec![],
+                                });
+                            } else {
+                                node_animation.texture_channel = None;
+                            }
+                        }
+                    }
+
+                    if let NodeImplTemplate::Text(default_text) = &default_node.implementation {
+                        let mut has_font_size_channel = node_animation.font_size_channel.is_some();
+                        if ui
+                            .checkbox(&mut has_font_size_channel, "Animate Font Size Channel?")
+                            .changed()
+                        {
+                            if has_font_size_channel {
+                                node_animation.font_size_channel = Some(AnimationChannel::new(default_text.font_size));
+                            } else {
+                                node_animation.font_size_channel = None;
+                            }
+                        }
+
+                        let mut has_line_height_channel = node_animation.line_height_channel.is_some();
+                        if ui
+                            .checkbox(&mut has_line_height_channel, "Animate Line Height Channel?")
+                            .changed()
+                        {
+                            if has_line_height_channel {
+                                node_animation.line_height_channel = Some(AnimationChannel::new(default_text.line_height));
+                            } else {
+                                node_animation.line_height_channel = None;
+                            }
+                        }
+
+                        let mut has_outline_thickness_channel = node_animation.outline_thickness_channel.is_some();
+                        if ui
+                            .checkbox(&mut has_outline_thickness_channel, "Animate Outline Thickness Channel?")
+                            .changed()
+                        {
+                            if has_outline_thickness_channel {
+                                node_animation.outline_thickness_channel = Some(AnimationChannel::new(default_text.outline_thickness));
+                            } else {
+                                node_animation.outline_thickness_channel = None;
+                            }
+                        }
+
+                        let mut has_outline_color_channel = node_animation.outline_color_channel.is_some();
+                        if ui
+                            .checkbox(&mut has_outline_color_channel, "Animate Outline Color Channel?")
+                            .changed()
+                        {
+                            if has_outline_color_channel {
+                                node_animation.outline_color_channel = Some(AnimationChannel::new(default_text.outline_color));
+                            } else {
+                                node_animation.outline_color_channel = None;
+                            }
+                        }
+
+                        let mut has_visible_characters_channel = node_animation.visible_characters_channel.is_some();
+                        if ui
+                            .checkbox(&mut has_visible_characters_channel, "Animate Visible Characters Channel?")
+                            .changed()
+                        {
+                            if has_visible_characters_channel {
+                                node_animation.visible_characters_channel = Some(AnimationChannel::new(default_text.text.chars().count() as f32));
+                            } else {
+                                node_animation.visible_characters_channel = None;
+                            }
+                        }
+
+                        let mut has_text_channel = node_animation.text_channel.is_some();
+                        if ui
+                            .checkbox(&mut has_text_channel, "Animate Text Channel?")
+                            .changed()
+                        {
+                            if has_text_channel {
+                                node_animation.text_channel = Some(DiscreteChannel {
+                                    start: default_text.text.clone(),
+                                    keyframes: vec![],
+                                });
+                            } else {
+                                node_animation.text_channel = None;
+                            }
+                        }
+                    }
+
+                    if matches!(&default_node.implementation, NodeImplTemplate::Sublayout(_)) {
+                        let mut has_sublayout_animation_channel = node_animation.sublayout_animation_channel.is_some();
+                        if ui
+                            .checkbox(&mut has_sublayout_animation_channel, "Animate Sublayout Animation Channel?")
+                            .changed()
+                        {
+                            if has_sublayout_animation_channel {
+                                node_animation.sublayout_animation_channel = Some(DiscreteChannel {
+                                    start: String::new(),
+                                    keyframes: vec![],
+                                });
+                            } else {
+                                node_animation.sublayout_animation_channel = None;
+                            }
+                        }
+                    }
+
+                    if let Some(gradient) = default_node.gradient.as_ref() {
+                        node_animation
+                            .gradient_stop_color_channels
+                            .resize_with(gradient.stops.len(), || None);
+                        for (idx, (stop, channel)) in gradient
+                            .stops
+                            .iter()
+                            .zip(node_animation.gradient_stop_color_channels.iter_mut())
+                            .enumerate()
+                        {
+                            let mut has_stop_channel = channel.is_some();
+                            if ui
+                                .checkbox(&mut has_stop_channel, format!("Animate Gradient Stop {idx}?"))
+                                .changed()
+                            {
+                                if has_stop_channel {
+                                    *channel = Some(AnimationChannel::new(stop.color));
+                                } else {
+                                    *channel = None;
+                                }
+                            }
+                        }
                     }
                 });
 
                 if let Some(channel) = node_animation.angle_channel.as_mut() {
                     ui.horizontal(|ui| {
                         ui.label("Angle");
+                        channel_mode_combo(ui, "Angle", channel);
 
                         let keyframe = channel.keyframe_mut(self.current_keyframe);
                         let mut has_keyframe = keyframe.is_some();
@@ -443,19 +680,14 @@ impl AnimatorWidget {
                             ui.add_enabled(false, egui::DragValue::new(&mut value));
                         }
 
-                        if ui.button("<").clicked() {
-                            self.current_keyframe =
-                                channel.get_prev_keyframe_idx(self.current_keyframe);
-                        } else if ui.button(">").clicked() {
-                            self.current_keyframe =
-                                channel.get_next_keyframe_idx(self.current_keyframe);
-                        }
+                        curve_toggle(ui, &mut self.curve_target, CurveTarget::Angle);
                     });
                 }
 
                 if let Some(channel) = node_animation.position_channel.as_mut() {
                     ui.horizontal(|ui| {
                         ui.label("Position");
+                        channel_mode_combo(ui, "Position", channel);
 
                         let keyframe = channel.keyframe_mut(self.current_keyframe);
                         let mut has_keyframe = keyframe.is_some();
@@ -480,12 +712,52 @@ impl AnimatorWidget {
                             });
                         }
 
-                        if ui.button("<").clicked() {
-                            self.current_keyframe =
-                                channel.get_prev_keyframe_idx(self.current_keyframe);
-                        } else if ui.button(">").clicked() {
-                            self.current_keyframe =
-                                channel.get_next_keyframe_idx(self.current_keyframe);
+                        curve_toggle(ui, &mut self.curve_target, CurveTarget::Position);
+                    });
+                }
+
+                if let Some(path) = node_animation.motion_path.as_mut() {
+                    ui.horizontal(|ui| {
+                        ui.label("Motion Path");
+                        egui::ComboBox::from_id_salt("motion-path-mode")
+                            .selected_text(format!("{:?}", path.mode))
+                            .width(80.0)
+                            .show_ui(ui, |ui| {
+                                for mode in [ChannelMode::Absolute, ChannelMode::Offset, ChannelMode::Multiply] {
+                                    ui.selectable_value(&mut path.mode, mode, format!("{mode:?}"));
+                                }
+                            });
+
+                        let channel = &mut path.progress_channel;
+                        let keyframe = channel.keyframe_mut(self.current_keyframe);
+                        let mut has_keyframe = keyframe.is_some();
+                        if ui.checkbox(&mut has_keyframe, "").changed() {
+                            if has_keyframe {
+                                channel.insert_keyframe(self.current_keyframe);
+                            } else {
+                                channel.remove_keyframe(self.current_keyframe);
+                            }
+                        }
+
+                        if let Some(value) = channel.keyframe_mut(self.current_keyframe) {
+                            ui.add(egui::DragValue::new(value).range(0.0..=1.0).speed(0.01));
+                        } else {
+                            let mut value = channel.value_for_frame(self.current_keyframe);
+                            ui.add_enabled(false, egui::DragValue::new(&mut value));
+                        }
+
+                        curve_toggle(ui, &mut self.curve_target, CurveTarget::PathProgress);
+
+                        ui.checkbox(&mut path.orient_to_path, "Orient to Path");
+                        if ui.button("Add Point").clicked() {
+                            let last = path.points().last().copied().unwrap_or_default();
+                            let previous =
+                                path.points().iter().rev().nth(1).copied().unwrap_or(last - glam::Vec2::X * 200.0);
+                            path.push_point(last + (last - previous));
+                        }
+
+                        if ui.add_enabled(path.points().len() > 2, egui::Button::new("Remove Point")).clicked() {
+                            path.pop_point();
                         }
                     });
                 }
@@ -493,6 +765,7 @@ impl AnimatorWidget {
                 if let Some(channel) = node_animation.size_channel.as_mut() {
                     ui.horizontal(|ui| {
                         ui.label("Size");
+                        channel_mode_combo(ui, "Size", channel);
 
                         let keyframe = channel.keyframe_mut(self.current_keyframe);
                         let mut has_keyframe = keyframe.is_some();
@@ -517,19 +790,14 @@ impl AnimatorWidget {
                             });
                         }
 
-                        if ui.button("<").clicked() {
-                            self.current_keyframe =
-                                channel.get_prev_keyframe_idx(self.current_keyframe);
-                        } else if ui.button(">").clicked() {
-                            self.current_keyframe =
-                                channel.get_next_keyframe_idx(self.current_keyframe);
-                        }
+                        curve_toggle(ui, &mut self.curve_target, CurveTarget::Size);
                     });
                 }
 
                 if let Some(channel) = node_animation.scale_channel.as_mut() {
                     ui.horizontal(|ui| {
                         ui.label("Scale");
+                        channel_mode_combo(ui, "Scale", channel);
 
                         let keyframe = channel.keyframe_mut(self.current_keyframe);
                         let mut has_keyframe = keyframe.is_some();
@@ -554,19 +822,14 @@ impl AnimatorWidget {
                             });
                         }
 
-                        if ui.button("<").clicked() {
-                            self.current_keyframe =
-                                channel.get_prev_keyframe_idx(self.current_keyframe);
-                        } else if ui.button(">").clicked() {
-                            self.current_keyframe =
-                                channel.get_next_keyframe_idx(self.current_keyframe);
-                        }
+                        curve_toggle(ui, &mut self.curve_target, CurveTarget::Scale);
                     });
                 }
 
                 if let Some(channel) = node_animation.color_channel.as_mut() {
                     ui.horizontal(|ui| {
                         ui.label("Color");
+                        channel_mode_combo(ui, "Color", channel);
 
                         let keyframe = channel.keyframe_mut(self.current_keyframe);
                         let mut has_keyframe = keyframe.is_some();
@@ -585,13 +848,72 @@ impl AnimatorWidget {
                             ui.color_edit_button_srgba_unmultiplied(&mut value);
                         }
 
-                        if ui.button("<").clicked() {
-                            self.current_keyframe =
-                                channel.get_prev_keyframe_idx(self.current_keyframe);
-                        } else if ui.button(">").clicked() {
-                            self.current_keyframe =
-                                channel.get_next_keyframe_idx(self.current_keyframe);
+                        curve_toggle(ui, &mut self.curve_target, CurveTarget::Color);
+                    });
+                }
+
+                if let Some(channel) = node_animation.visibility_channel.as_mut() {
+                    ui.horizontal(|ui| {
+                        ui.label("Visibility");
+
+                        let keyframe = channel.keyframe_mut(self.current_keyframe);
+                        let mut has_keyframe = keyframe.is_some();
+                        if ui.checkbox(&mut has_keyframe, "").changed() {
+                            if has_keyframe {
+                                channel.insert_keyframe(self.current_keyframe);
+                            } else {
+                                channel.remove_keyframe(self.current_keyframe);
+                            }
+                        }
+
+                        if let Some(value) = channel.keyframe_mut(self.current_keyframe) {
+                            egui::ComboBox::new("visibility-keyframe", "")
+                                .selected_text(format!("{value:?}"))
+                                .show_ui(ui, |ui| {
+                                    for visibility in [NodeVisibility::Hidden, NodeVisibility::Inherited, NodeVisibility::Visible] {
+                                        ui.selectable_value(value, visibility, format!("{visibility:?}"));
+                                    }
+                                });
+                        } else {
+                            let value = *channel.value_for_frame(self.current_keyframe);
+                            ui.add_enabled_ui(false, |ui| {
+                                egui::ComboBox::new("visibility-keyframe", "")
+                                    .selected_text(format!("{value:?}"))
+                                    .show_ui(ui, |_| {});
+                            });
+                        }
+
+                        curve_toggle(ui, &mut self.curve_target, CurveTarget::Visibility);
+                    });
+                }
+
+                for (idx, channel) in node_animation.gradient_stop_color_channels.iter_mut().enumerate() {
+                    let Some(channel) = channel.as_mut() else {
+                        continue;
+                    };
+
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Gradient Stop {idx}"));
+                        channel_mode_combo(ui, format!("Gradient Stop {idx}"), channel);
+
+                        let keyframe = channel.keyframe_mut(self.current_keyframe);
+                        let mut has_keyframe = keyframe.is_some();
+                        if ui.checkbox(&mut has_keyframe, "").changed() {
+                            if has_keyframe {
+                                channel.insert_keyframe(self.current_keyframe);
+                            } else {
+                                channel.remove_keyframe(self.current_keyframe);
+                            }
+                        }
+
+                        if let Some(value) = channel.keyframe_mut(self.current_keyframe) {
+                            ui.color_edit_button_srgba_unmultiplied(value);
+                        } else {
+                            let mut value = channel.value_for_frame(self.current_keyframe);
+                            ui.color_edit_button_srgba_unmultiplied(&mut value);
                         }
+
+                        curve_toggle(ui, &mut self.curve_target, CurveTarget::GradientStop(idx));
                     });
                 }
 
@@ -599,6 +921,7 @@ impl AnimatorWidget {
                     if let Some(channel) = node_animation.uv_offset_channel.as_mut() {
                         ui.horizontal(|ui| {
                             ui.label("UV Offset");
+                            channel_mode_combo(ui, "UV Offset", channel);
 
                             let keyframe = channel.keyframe_mut(self.current_keyframe);
                             let mut has_keyframe = keyframe.is_some();
@@ -623,19 +946,14 @@ impl AnimatorWidget {
                                 });
                             }
 
-                            if ui.button("<").clicked() {
-                                self.current_keyframe =
-                                    channel.get_prev_keyframe_idx(self.current_keyframe);
-                            } else if ui.button(">").clicked() {
-                                self.current_keyframe =
-                                    channel.get_next_keyframe_idx(self.current_keyframe);
-                            }
+                            curve_toggle(ui, &mut self.curve_target, CurveTarget::UvOffset);
                         });
                     }
 
                     if let Some(channel) = node_animation.uv_scale_channel.as_mut() {
                         ui.horizontal(|ui| {
                             ui.label("UV Scale");
+                            channel_mode_combo(ui, "UV Scale", channel);
 
                             let keyframe = channel.keyframe_mut(self.current_keyframe);
                             let mut has_keyframe = keyframe.is_some();
@@ -660,16 +978,279 @@ impl AnimatorWidget {
                                 });
                             }
 
-                            if ui.button("<").clicked() {
-                                self.current_keyframe =
-                                    channel.get_prev_keyframe_idx(self.current_keyframe);
-                            } else if ui.button(">").clicked() {
-                                self.current_keyframe =
-                                    channel.get_next_keyframe_idx(self.current_keyframe);
+                            curve_toggle(ui, &mut self.curve_target, CurveTarget::UvScale);
+                        });
+                    }
+
+                    if let Some(channel) = node_animation.fill_amount_channel.as_mut() {
+                        ui.horizontal(|ui| {
+                            ui.label("Fill Amount");
+                            channel_mode_combo(ui, "Fill Amount", channel);
+
+                            let keyframe = channel.keyframe_mut(self.current_keyframe);
+                            let mut has_keyframe = keyframe.is_some();
+                            if ui.checkbox(&mut has_keyframe, "").changed() {
+                                if has_keyframe {
+                                    channel.insert_keyframe(self.current_keyframe);
+                                } else {
+                                    channel.remove_keyframe(self.current_keyframe);
+                                }
+                            }
+
+                            if let Some(value) = channel.keyframe_mut(self.current_keyframe) {
+                                ui.add(egui::DragValue::new(value).range(0.0..=1.0).speed(0.01));
+                            } else {
+                                let mut value = channel.value_for_frame(self.current_keyframe);
+                                ui.add_enabled(false, egui::DragValue::new(&mut value));
+                            }
+
+                            curve_toggle(ui, &mut self.curve_target, CurveTarget::FillAmount);
+                        });
+                    }
+
+                    if let Some(channel) = node_animation.font_size_channel.as_mut() {
+                        ui.horizontal(|ui| {
+                            ui.label("Font Size");
+                            channel_mode_combo(ui, "Font Size", channel);
+
+                            let keyframe = channel.keyframe_mut(self.current_keyframe);
+                            let mut has_keyframe = keyframe.is_some();
+                            if ui.checkbox(&mut has_keyframe, "").changed() {
+                                if has_keyframe {
+                                    channel.insert_keyframe(self.current_keyframe);
+                                } else {
+                                    channel.remove_keyframe(self.current_keyframe);
+                                }
+                            }
+
+                            if let Some(value) = channel.keyframe_mut(self.current_keyframe) {
+                                ui.add(egui::DragValue::new(value).range(1.0..=f32::INFINITY).speed(1.0));
+                            } else {
+                                let mut value = channel.value_for_frame(self.current_keyframe);
+                                ui.add_enabled(false, egui::DragValue::new(&mut value));
+                            }
+
+                            curve_toggle(ui, &mut self.curve_target, CurveTarget::FontSize);
+                        });
+                    }
+
+                    if let Some(channel) = node_animation.line_height_channel.as_mut() {
+                        ui.horizontal(|ui| {
+                            ui.label("Line Height");
+                            channel_mode_combo(ui, "Line Height", channel);
+
+                            let keyframe = channel.keyframe_mut(self.current_keyframe);
+                            let mut has_keyframe = keyframe.is_some();
+                            if ui.checkbox(&mut has_keyframe, "").changed() {
+                                if has_keyframe {
+                                    channel.insert_keyframe(self.current_keyframe);
+                                } else {
+                                    channel.remove_keyframe(self.current_keyframe);
+                                }
+                            }
+
+                            if let Some(value) = channel.keyframe_mut(self.current_keyframe) {
+                                ui.add(egui::DragValue::new(value).range(1.0..=f32::INFINITY).speed(1.0));
+                            } else {
+                                let mut value = channel.value_for_frame(self.current_keyframe);
+                                ui.add_enabled(false, egui::DragValue::new(&mut value));
+                            }
+
+                            curve_toggle(ui, &mut self.curve_target, CurveTarget::LineHeight);
+                        });
+                    }
+
+                    if let Some(channel) = node_animation.outline_thickness_channel.as_mut() {
+                        ui.horizontal(|ui| {
+                            ui.label("Outline Thickness");
+                            channel_mode_combo(ui, "Outline Thickness", channel);
+
+                            let keyframe = channel.keyframe_mut(self.current_keyframe);
+                            let mut has_keyframe = keyframe.is_some();
+                            if ui.checkbox(&mut has_keyframe, "").changed() {
+                                if has_keyframe {
+                                    channel.insert_keyframe(self.current_keyframe);
+                                } else {
+                                    channel.remove_keyframe(self.current_keyframe);
+                                }
+                            }
+
+                            if let Some(value) = channel.keyframe_mut(self.current_keyframe) {
+                                ui.add(egui::DragValue::new(value).range(0.0..=f32::INFINITY).speed(0.1));
+                            } else {
+                                let mut value = channel.value_for_frame(self.current_keyframe);
+                                ui.add_enabled(false, egui::DragValue::new(&mut value));
+                            }
+
+                            curve_toggle(ui, &mut self.curve_target, CurveTarget::OutlineThickness);
+                        });
+                    }
+
+                    if let Some(channel) = node_animation.outline_color_channel.as_mut() {
+                        ui.horizontal(|ui| {
+                            ui.label("Outline Color");
+                            channel_mode_combo(ui, "Outline Color", channel);
+
+                            let keyframe = channel.keyframe_mut(self.current_keyframe);
+                            let mut has_keyframe = keyframe.is_some();
+                            if ui.checkbox(&mut has_keyframe, "").changed() {
+                                if has_keyframe {
+                                    channel.insert_keyframe(self.current_keyframe);
+                                } else {
+                                    channel.remove_keyframe(self.current_keyframe);
+                                }
+                            }
+
+                            if let Some(value) = channel.keyframe_mut(self.current_keyframe) {
+                                ui.color_edit_button_srgba_unmultiplied(value);
+                            } else {
+                                let mut value = channel.value_for_frame(self.current_keyframe);
+                                ui.color_edit_button_srgba_unmultiplied(&mut value);
+                            }
+
+                            curve_toggle(ui, &mut self.curve_target, CurveTarget::OutlineColor);
+                        });
+                    }
+
+                    if let Some(channel) = node_animation.visible_characters_channel.as_mut() {
+                        ui.horizontal(|ui| {
+                            ui.label("Visible Characters");
+                            channel_mode_combo(ui, "Visible Characters", channel);
+
+                            let keyframe = channel.keyframe_mut(self.current_keyframe);
+                            let mut has_keyframe = keyframe.is_some();
+                            if ui.checkbox(&mut has_keyframe, "").changed() {
+                                if has_keyframe {
+                                    channel.insert_keyframe(self.current_keyframe);
+                                } else {
+                                    channel.remove_keyframe(self.current_keyframe);
+                                }
+                            }
+
+                            if let Some(value) = channel.keyframe_mut(self.current_keyframe) {
+                                ui.add(egui::DragValue::new(value).range(0.0..=f32::INFINITY).speed(1.0));
+                            } else {
+                                let mut value = channel.value_for_frame(self.current_keyframe);
+                                ui.add_enabled(false, egui::DragValue::new(&mut value));
+                            }
+
+                            curve_toggle(
+                                ui,
+                                &mut self.curve_target,
+                                CurveTarget::VisibleCharacters,
+                            );
+                        });
+                    }
+
+                    if let Some(channel) = node_animation.texture_channel.as_mut() {
+                        ui.horizontal(|ui| {
+                            ui.label("Texture");
+
+                            let keyframe = channel.keyframe_mut(self.current_keyframe);
+                            let mut has_keyframe = keyframe.is_some();
+                            if ui.checkbox(&mut has_keyframe, "").changed() {
+                                if has_keyframe {
+                                    channel.insert_keyframe(self.current_keyframe);
+                                } else {
+                                    channel.remove_keyframe(self.current_keyframe);
+                                }
+                            }
+
+                            if let Some(value) = channel.keyframe_mut(self.current_keyframe) {
+                                egui::ComboBox::new("texture-keyframe", "")
+                                    .selected_text(value.as_str())
+                                    .show_ui(ui, |ui| {
+                                        let backend = self.backend.lock();
+                                        for name in backend.iter_texture_names() {
+                                            if ui.selectable_label(value == name, name).clicked() {
+                                                *value = name.to_string();
+                                            }
+                                        }
+                                    });
+                            } else {
+                                let value = channel.value_for_frame(self.current_keyframe);
+                                ui.add_enabled_ui(false, |ui| {
+                                    egui::ComboBox::new("texture-keyframe", "")
+                                        .selected_text(value.as_str())
+                                        .show_ui(ui, |_| {});
+                                });
+                            }
+
+                            curve_toggle(ui, &mut self.curve_target, CurveTarget::Texture);
+                        });
+                    }
+
+                    if let Some(channel) = node_animation.text_channel.as_mut() {
+                        ui.horizontal(|ui| {
+                            ui.label("Text");
+
+                            let keyframe = channel.keyframe_mut(self.current_keyframe);
+                            let mut has_keyframe = keyframe.is_some();
+                            if ui.checkbox(&mut has_keyframe, "").changed() {
+                                if has_keyframe {
+                                    channel.insert_keyframe(self.current_keyframe);
+                                } else {
+                                    channel.remove_keyframe(self.current_keyframe);
+                                }
                             }
+
+                            if let Some(value) = channel.keyframe_mut(self.current_keyframe) {
+                                ui.text_edit_singleline(value);
+                            } else {
+                                let mut value = channel.value_for_frame(self.current_keyframe).clone();
+                                ui.add_enabled(false, egui::TextEdit::singleline(&mut value));
+                            }
+
+                            curve_toggle(ui, &mut self.curve_target, CurveTarget::Text);
                         });
                     }
                 }
+
+                if let Some(channel) = node_animation.sublayout_animation_channel.as_mut() {
+                    ui.horizontal(|ui| {
+                        ui.label("Sublayout Animation");
+
+                        let keyframe = channel.keyframe_mut(self.current_keyframe);
+                        let mut has_keyframe = keyframe.is_some();
+                        if ui.checkbox(&mut has_keyframe, "").changed() {
+                            if has_keyframe {
+                                channel.insert_keyframe(self.current_keyframe);
+                            } else {
+                                channel.remove_keyframe(self.current_keyframe);
+                            }
+                        }
+
+                        if let Some(value) = channel.keyframe_mut(self.current_keyframe) {
+                            egui::ComboBox::new("sublayout-animation-keyframe", "")
+                                .selected_text(value.as_str())
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(value, String::new(), "None");
+                                    for name in sublayout_animations.iter() {
+                                        ui.selectable_value(value, name.clone(), name);
+                                    }
+                                });
+                        } else {
+                            let value = channel.value_for_frame(self.current_keyframe);
+                            ui.add_enabled_ui(false, |ui| {
+                                egui::ComboBox::new("sublayout-animation-keyframe", "")
+                                    .selected_text(value.as_str())
+                                    .show_ui(ui, |_| {});
+                            });
+                        }
+
+                        curve_toggle(ui, &mut self.curve_target, CurveTarget::SublayoutAnimation);
+                    });
+                }
+
+                if let Some(target) = self.curve_target {
+                    ui.separator();
+                    target.show(
+                        ui,
+                        node_animation,
+                        &mut self.current_keyframe,
+                        animation.total_duration,
+                    );
+                }
             });
         });
 
@@ -700,20 +1281,128 @@ impl AnimatorWidget {
         tree.sync_to_template(template, &root, &mut self.backend.lock());
         tree.sync_to_animation_keyframe(&self.animation, preview_frame);
 
+        // The motion path of the node being animated is drawn over the preview so its points can be dragged around
+        let motion_path = self.editing_node.as_ref().and_then(|node| {
+            let animation = &template.animations.iter().find(|(name, _)| *name == self.animation)?.1;
+            let path = animation
+                .node_animations
+                .iter()
+                .find(|anim| anim.node_path.as_str() == node.as_str())?
+                .motion_path
+                .clone()?;
+            let rest = root.get_template_node_by_path(template, node)?.transform.position;
+            let position_affine = *tree.get_node_by_path(node)?.position_affine();
+            Some((path, rest, position_affine))
+        });
+
+        let mut moved_point = None;
         egui::Frame::canvas(ui.style()).show(ui, |ui| {
-            let (rect, _response) =
+            let (rect, response) =
                 ui.allocate_exact_size(ui.available_size(), egui::Sense::drag());
 
-            ui.painter()
-                .with_clip_rect(rect)
-                .add(egui_wgpu::Callback::new_paint_callback(
-                    rect,
-                    LayoutPaintCallback {
-                        reference: PaintingReference::Tree(self.tree.clone()),
-                        pipeline: self.pipeline.clone(),
-                        backend: self.backend.clone(),
-                    },
-                ));
+            let painter = ui.painter().with_clip_rect(rect);
+            painter.add(egui_wgpu::Callback::new_paint_callback(
+                rect,
+                LayoutPaintCallback {
+                    reference: PaintingReference::Tree(self.tree.clone()),
+                    pipeline: self.pipeline.clone(),
+                    backend: self.backend.clone(),
+                },
+            ));
+
+            let Some((path, rest, position_affine)) = motion_path else {
+                self.dragging_path_point = None;
+                return;
+            };
+
+            // Matches the viewport the layout is rendered into
+            let scale = (rect.width() / 1920.0).min(rect.height() / 1080.0);
+            let origin = rect.center() - egui::vec2(1920.0, 1080.0) * scale / 2.0;
+            let to_screen = |point: glam::Vec2| {
+                let point = position_affine.transform_point2(glam::Vec2::resolve(path.mode, rest, glam::Vec2::ZERO, point));
+                origin + egui::vec2(point.x, point.y) * scale
+            };
+
+            let sample_count = (path.points().len().saturating_sub(1) * 16).max(1);
+            let samples = (0..=sample_count)
+                .filter_map(|idx| path.point_at(idx as f32 / sample_count as f32))
+                .map(|(point, _)| to_screen(point))
+                .collect::<Vec<_>>();
+            painter.add(egui::Shape::line(samples, egui::Stroke::new(2.0, egui::Color32::YELLOW)));
+
+            let (progress, _) = path.progress_channel.evaluate(preview_frame as f32);
+            if let Some((point, _)) = path.point_at(progress) {
+                painter.circle_stroke(to_screen(point), 6.0, egui::Stroke::new(2.0, egui::Color32::WHITE));
+            }
+
+            let handles = path.points().iter().map(|point| to_screen(*point)).collect::<Vec<_>>();
+            if response.drag_started() {
+                self.dragging_path_point = response.interact_pointer_pos().and_then(|pos| {
+                    handles.iter().position(|handle| handle.distance(pos) <= 8.0)
+                });
+            }
+
+            if let (Some(idx), Some(pos)) = (self.dragging_path_point, response.interact_pointer_pos())
+                && response.dragged()
+            {
+                let layout = (pos - origin) / scale;
+                let local = position_affine.inverse().transform_point2(glam::Vec2::new(layout.x, layout.y));
+                let point = match path.mode {
+                    ChannelMode::Absolute => local,
+                    ChannelMode::Offset => local - rest,
+                    ChannelMode::Multiply => glam::Vec2::select(rest.cmpne(glam::Vec2::ZERO), local / rest, glam::Vec2::ONE),
+                };
+                moved_point = Some((idx, point));
+            }
+
+            if response.drag_stopped() {
+                self.dragging_path_point = None;
+            }
+
+            for (idx, handle) in handles.into_iter().enumerate() {
+                let color = if self.dragging_path_point == Some(idx) {
+                    egui::Color32::WHITE
+                } else {
+                    egui::Color32::YELLOW
+                };
+                painter.circle_filled(handle, 5.0, color);
+            }
         });
+
+        drop(tree);
+        if let Some((idx, point)) = moved_point {
+            let template = match &self.layout_reference {
+                LayoutReference::Root => root.root_template_mut(),
+                LayoutReference::Sublayout(reference) => root.template_mut(reference).unwrap(),
+            };
+
+            let path = self.editing_node.as_ref().and_then(|node| {
+                template
+                    .animations
+                    .iter_mut()
+                    .find(|(name, _)| *name == self.animation)?
+                    .1
+                    .node_animations
+                    .iter_mut()
+                    .find(|anim| anim.node_path.as_str() == node.as_str())?
+                    .motion_path
+                    .as_mut()
+            });
+
+            if let Some(path) = path {
+                path.set_point(idx, point);
+            }
+        }
     }
 }
+
+fn channel_mode_combo<T>(ui: &mut egui::Ui, id: impl std::hash::Hash, channel: &mut AnimationChannel<T>) {
+    egui::ComboBox::from_id_salt(("channel-mode", id))
+        .selected_text(format!("{:?}", channel.mode))
+        .width(80.0)
+        .show_ui(ui, |ui| {
+            for mode in [ChannelMode::Absolute, ChannelMode::Offset, ChannelMode::Multiply] {
+                ui.selectable_value(&mut channel.mode, mode, format!("{mode:?}"));
+            }
+        });
+}
@@ -0,0 +1,651 @@
+use egui::{Color32, Pos2, Rect, Sense, Shape, Stroke};
+use envy::{AnimationChannel, DiscreteChannel, Interpolatable, NodeAnimation, TangentMode};
+
+const CURVE_HEIGHT: f32 = 180.0;
+const TIMELINE_HEIGHT: f32 = 36.0;
+const KEY_RADIUS: f32 = 4.0;
+const HIT_RADIUS: f32 = 8.0;
+
+/// Number of samples drawn per frame, so that overshooting curves look smooth
+const SAMPLES_PER_FRAME: usize = 4;
+
+/// The channel of a [`NodeAnimation`] shown in the curve editor
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CurveTarget {
+    Angle,
+    Position,
+    PathProgress,
+    Size,
+    Scale,
+    Color,
+    Visibility,
+    GradientStop(usize),
+    UvOffset,
+    UvScale,
+    FillAmount,
+    Texture,
+    FontSize,
+    LineHeight,
+    OutlineThickness,
+    OutlineColor,
+    VisibleCharacters,
+    Text,
+    SublayoutAnimation,
+}
+
+impl CurveTarget {
+    /// Shows the curve of this channel, or does nothing if the node animation doesn't have it
+    pub fn show(
+        self,
+        ui: &mut egui::Ui,
+        node_animation: &mut NodeAnimation,
+        current_keyframe: &mut usize,
+        duration: usize,
+    ) {
+        let id = ("curve-editor", self);
+        match self {
+            Self::Angle => curve(
+                ui,
+                id,
+                node_animation.angle_channel.as_mut(),
+                current_keyframe,
+                duration,
+            ),
+            Self::Position => curve(
+                ui,
+                id,
+                node_animation.position_channel.as_mut(),
+                current_keyframe,
+                duration,
+            ),
+            Self::PathProgress => curve(
+                ui,
+                id,
+                node_animation.motion_path.as_mut().map(|path| &mut path.progress_channel),
+                current_keyframe,
+                duration,
+            ),
+            Self::Size => curve(
+                ui,
+                id,
+                node_animation.size_channel.as_mut(),
+                current_keyframe,
+                duration,
+            ),
+            Self::Scale => curve(
+                ui,
+                id,
+                node_animation.scale_channel.as_mut(),
+                current_keyframe,
+                duration,
+            ),
+            Self::Color => curve(
+                ui,
+                id,
+                node_animation.color_channel.as_mut(),
+                current_keyframe,
+                duration,
+            ),
+            Self::GradientStop(idx) => curve(
+                ui,
+                id,
+                node_animation
+                    .gradient_stop_color_channels
+                    .get_mut(idx)
+                    .and_then(Option::as_mut),
+                current_keyframe,
+                duration,
+            ),
+            Self::UvOffset => curve(
+                ui,
+                id,
+                node_animation.uv_offset_channel.as_mut(),
+                current_keyframe,
+                duration,
+            ),
+            Self::UvScale => curve(
+                ui,
+                id,
+                node_animation.uv_scale_channel.as_mut(),
+                current_keyframe,
+                duration,
+            ),
+            Self::FillAmount => curve(
+                ui,
+                id,
+                node_animation.fill_amount_channel.as_mut(),
+                current_keyframe,
+                duration,
+            ),
+            Self::FontSize => curve(
+                ui,
+                id,
+                node_animation.font_size_channel.as_mut(),
+                current_keyframe,
+                duration,
+            ),
+            Self::LineHeight => curve(
+                ui,
+                id,
+                node_animation.line_height_channel.as_mut(),
+                current_keyframe,
+                duration,
+            ),
+            Self::OutlineThickness => curve(
+                ui,
+                id,
+                node_animation.outline_thickness_channel.as_mut(),
+                current_keyframe,
+                duration,
+            ),
+            Self::OutlineColor => curve(
+                ui,
+                id,
+                node_animation.outline_color_channel.as_mut(),
+                current_keyframe,
+                duration,
+            ),
+            Self::VisibleCharacters => curve(
+                ui,
+                id,
+                node_animation.visible_characters_channel.as_mut(),
+                current_keyframe,
+                duration,
+            ),
+            Self::Visibility => timeline(
+                ui,
+                node_animation.visibility_channel.as_ref(),
+                current_keyframe,
+                duration,
+                |value| format!("{value:?}"),
+            ),
+            Self::Texture => timeline(
+                ui,
+                node_animation.texture_channel.as_ref(),
+                current_keyframe,
+                duration,
+                String::clone,
+            ),
+            Self::Text => timeline(
+                ui,
+                node_animation.text_channel.as_ref(),
+                current_keyframe,
+                duration,
+                String::clone,
+            ),
+            Self::SublayoutAnimation => timeline(
+                ui,
+                node_animation.sublayout_animation_channel.as_ref(),
+                current_keyframe,
+                duration,
+                String::clone,
+            ),
+        }
+    }
+}
+
+/// Selects `target` in the curve editor, replacing the keyframe steppers of each channel row
+pub fn curve_toggle(ui: &mut egui::Ui, selected: &mut Option<CurveTarget>, target: CurveTarget) {
+    if ui
+        .selectable_label(*selected == Some(target), "Curve")
+        .clicked()
+    {
+        *selected = Some(target);
+    }
+}
+
+/// A value that can be drawn as one curve per component
+pub trait CurveValue: Interpolatable {
+    const COMPONENT_COLORS: &'static [Color32];
+
+    fn component(&self, idx: usize) -> f32;
+    fn set_component(&mut self, idx: usize, value: f32);
+}
+
+impl CurveValue for f32 {
+    const COMPONENT_COLORS: &'static [Color32] = &[Color32::LIGHT_BLUE];
+
+    fn component(&self, _: usize) -> f32 {
+        *self
+    }
+
+    fn set_component(&mut self, _: usize, value: f32) {
+        *self = value;
+    }
+}
+
+impl CurveValue for glam::Vec2 {
+    const COMPONENT_COLORS: &'static [Color32] = &[Color32::LIGHT_RED, Color32::LIGHT_GREEN];
+
+    fn component(&self, idx: usize) -> f32 {
+        self[idx]
+    }
+
+    fn set_component(&mut self, idx: usize, value: f32) {
+        self[idx] = value;
+    }
+}
+
+impl CurveValue for [u8; 4] {
+    const COMPONENT_COLORS: &'static [Color32] = &[
+        Color32::LIGHT_RED,
+        Color32::LIGHT_GREEN,
+        Color32::LIGHT_BLUE,
+        Color32::LIGHT_GRAY,
+    ];
+
+    fn component(&self, idx: usize) -> f32 {
+        self[idx] as f32
+    }
+
+    fn set_component(&mut self, idx: usize, value: f32) {
+        self[idx] = value.round().clamp(0.0, 255.0) as u8;
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum DragHandle {
+    Key,
+    In,
+    Out,
+}
+
+/// What is being dragged, along with the value range at the start of the drag so the view doesn't rescale under the cursor
+#[derive(Debug, Copy, Clone)]
+struct CurveDrag {
+    keyframe: usize,
+    component: usize,
+    handle: DragHandle,
+    min: f32,
+    max: f32,
+}
+
+struct CurveView {
+    rect: Rect,
+    duration: f32,
+    min: f32,
+    max: f32,
+}
+
+impl CurveView {
+    fn pos(&self, frame: f32, value: f32) -> Pos2 {
+        Pos2::new(
+            egui::lerp(self.rect.x_range(), frame / self.duration),
+            egui::lerp(
+                self.rect.bottom()..=self.rect.top(),
+                (value - self.min) / (self.max - self.min),
+            ),
+        )
+    }
+
+    fn frame_at(&self, x: f32) -> usize {
+        (egui::remap_clamp(x, self.rect.x_range(), 0.0..=self.duration)).round() as usize
+    }
+
+    fn value_at(&self, y: f32) -> f32 {
+        egui::remap(y, self.rect.bottom()..=self.rect.top(), self.min..=self.max)
+    }
+}
+
+/// A keyframe of a channel as seen by the curve editor
+struct Key<T> {
+    frame: usize,
+    value: T,
+    /// Frames and change in value of the segments before and after the keyframe
+    before: Option<(usize, T)>,
+    after: Option<(usize, T)>,
+    in_slope: f32,
+    out_slope: f32,
+}
+
+impl<T: CurveValue> Key<T> {
+    /// Bezier control points equivalent to the Hermite slopes of the keyframe
+    fn handle(&self, handle: DragHandle, component: usize) -> Option<(f32, f32)> {
+        let frame = self.frame as f32;
+        let value = self.value.component(component);
+        match handle {
+            DragHandle::Key => Some((frame, value)),
+            DragHandle::In => self.before.map(|(duration, prev)| {
+                let delta = value - prev.component(component);
+                (
+                    frame - duration as f32 / 3.0,
+                    value - self.in_slope * delta / 3.0,
+                )
+            }),
+            DragHandle::Out => self.after.map(|(duration, next)| {
+                let delta = next.component(component) - value;
+                (
+                    frame + duration as f32 / 3.0,
+                    value + self.out_slope * delta / 3.0,
+                )
+            }),
+        }
+    }
+}
+
+fn collect_keys<T: CurveValue>(channel: &AnimationChannel<T>) -> Vec<Key<T>> {
+    let frames = channel.keyframe_indices().collect::<Vec<_>>();
+    frames
+        .iter()
+        .enumerate()
+        .map(|(idx, frame)| {
+            let value = channel.value_for_frame(*frame);
+            let (in_slope, out_slope) = channel.key_slopes(idx);
+            Key {
+                frame: *frame,
+                value,
+                before: idx
+                    .checked_sub(1)
+                    .map(|prev| (frame - frames[prev], channel.value_for_frame(frames[prev]))),
+                after: frames
+                    .get(idx + 1)
+                    .map(|next| (next - frame, channel.value_for_frame(*next))),
+                in_slope,
+                out_slope,
+            }
+        })
+        .collect()
+}
+
+fn curve<T: CurveValue>(
+    ui: &mut egui::Ui,
+    id_salt: impl std::hash::Hash,
+    channel: Option<&mut AnimationChannel<T>>,
+    current_keyframe: &mut usize,
+    duration: usize,
+) {
+    let Some(channel) = channel else {
+        return;
+    };
+
+    let id = ui.make_persistent_id(id_salt);
+    let duration = duration.max(channel.duration()).max(1);
+    let keys = collect_keys(channel);
+    let components = T::COMPONENT_COLORS.len();
+
+    let samples = (0..=duration * SAMPLES_PER_FRAME)
+        .map(|step| {
+            let frame = step as f32 / SAMPLES_PER_FRAME as f32;
+            (frame, channel.evaluate(frame).0)
+        })
+        .collect::<Vec<_>>();
+
+    let mut drag = ui.data(|data| data.get_temp::<CurveDrag>(id));
+    let (min, max) = match drag {
+        Some(drag) => (drag.min, drag.max),
+        None => {
+            let (min, max) = samples
+                .iter()
+                .flat_map(|(_, value)| (0..components).map(|component| value.component(component)))
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), value| {
+                    (min.min(value), max.max(value))
+                });
+            let padding = ((max - min) * 0.1).max(1.0);
+            (min - padding, max + padding)
+        }
+    };
+
+    let (response, painter) = ui.allocate_painter(
+        egui::vec2(ui.available_width(), CURVE_HEIGHT),
+        Sense::click_and_drag(),
+    );
+    let view = CurveView {
+        rect: response.rect.shrink(HIT_RADIUS),
+        duration: duration as f32,
+        min,
+        max,
+    };
+
+    let selected = keys.iter().position(|key| key.frame == *current_keyframe);
+    let hit_test = |pos: Pos2| {
+        let handles = selected
+            .into_iter()
+            .flat_map(|idx| [(idx, DragHandle::In), (idx, DragHandle::Out)])
+            .chain((0..keys.len()).map(|idx| (idx, DragHandle::Key)));
+
+        handles
+            .flat_map(|(idx, handle)| {
+                (0..components).map(move |component| (idx, handle, component))
+            })
+            .find(|(idx, handle, component)| {
+                keys[*idx]
+                    .handle(*handle, *component)
+                    .is_some_and(|(frame, value)| {
+                        view.pos(frame, value).distance(pos) <= HIT_RADIUS
+                    })
+            })
+    };
+
+    if response.drag_started()
+        && let Some((idx, handle, component)) = response.interact_pointer_pos().and_then(hit_test)
+    {
+        *current_keyframe = keys[idx].frame;
+        drag = Some(CurveDrag {
+            keyframe: keys[idx].frame,
+            component,
+            handle,
+            min,
+            max,
+        });
+        ui.data_mut(|data| data.insert_temp(id, drag.unwrap()));
+    }
+
+    if let (Some(drag), Some(pos)) = (drag, response.interact_pointer_pos())
+        && response.dragged()
+    {
+        let value = view.value_at(pos.y);
+        let key = keys.iter().find(|key| key.frame == drag.keyframe);
+        match (drag.handle, key) {
+            (DragHandle::Key, _) => {
+                if let Some(keyframe) = channel.keyframe_mut(drag.keyframe) {
+                    keyframe.set_component(drag.component, value);
+                }
+            }
+            (handle, Some(key)) => {
+                let own = key.value.component(drag.component);
+                let slope = match handle {
+                    DragHandle::In => key.before.and_then(|(_, prev)| {
+                        let delta = own - prev.component(drag.component);
+                        (delta.abs() > f32::EPSILON).then(|| (own - value) * 3.0 / delta)
+                    }),
+                    _ => key.after.and_then(|(_, next)| {
+                        let delta = next.component(drag.component) - own;
+                        (delta.abs() > f32::EPSILON).then(|| (value - own) * 3.0 / delta)
+                    }),
+                };
+
+                if let (Some(slope), Some(tangents)) = (slope, channel.tangents_mut(drag.keyframe))
+                {
+                    if tangents.mode != TangentMode::Broken {
+                        tangents.in_slope = key.in_slope;
+                        tangents.out_slope = key.out_slope;
+                        tangents.mode = TangentMode::Broken;
+                    }
+
+                    if handle == DragHandle::In {
+                        tangents.in_slope = slope;
+                    } else {
+                        tangents.out_slope = slope;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if response.drag_stopped() {
+        ui.data_mut(|data| data.remove::<CurveDrag>(id));
+    }
+
+    if response.clicked()
+        && let Some(pos) = response.interact_pointer_pos()
+    {
+        *current_keyframe = match hit_test(pos) {
+            Some((idx, _, _)) => keys[idx].frame,
+            None => view.frame_at(pos.x),
+        };
+    }
+
+    let visuals = ui.visuals();
+    painter.rect_filled(response.rect, 2.0, visuals.extreme_bg_color);
+    painter.vline(
+        view.pos(*current_keyframe as f32, 0.0).x,
+        view.rect.y_range(),
+        Stroke::new(1.0, visuals.selection.bg_fill),
+    );
+
+    for (component, color) in T::COMPONENT_COLORS.iter().enumerate() {
+        let points = samples
+            .iter()
+            .map(|(frame, value)| view.pos(*frame, value.component(component)))
+            .collect();
+        painter.add(Shape::line(points, Stroke::new(1.5, *color)));
+
+        for key in keys.iter() {
+            let (frame, value) = key.handle(DragHandle::Key, component).unwrap();
+            let pos = view.pos(frame, value);
+            if key.frame == *current_keyframe {
+                for handle in [DragHandle::In, DragHandle::Out] {
+                    if let Some((frame, value)) = key.handle(handle, component) {
+                        let handle_pos = view.pos(frame, value);
+                        painter.line_segment(
+                            [pos, handle_pos],
+                            Stroke::new(1.0, visuals.weak_text_color()),
+                        );
+                        painter.circle_filled(
+                            handle_pos,
+                            KEY_RADIUS * 0.75,
+                            visuals.weak_text_color(),
+                        );
+                    }
+                }
+
+                painter.circle(
+                    pos,
+                    KEY_RADIUS + 1.0,
+                    *color,
+                    Stroke::new(1.5, visuals.strong_text_color()),
+                );
+            } else {
+                painter.circle_filled(pos, KEY_RADIUS, *color);
+            }
+        }
+    }
+
+    let slopes = selected.map(|idx| (keys[idx].in_slope, keys[idx].out_slope));
+    ui.horizontal(|ui| {
+        let (Some(tangents), Some((in_slope, out_slope))) =
+            (channel.tangents_mut(*current_keyframe), slopes)
+        else {
+            ui.label("Select a keyframe to edit its tangents");
+            return;
+        };
+
+        ui.label("Tangents");
+        let mode = tangents.mode;
+        egui::ComboBox::from_id_salt((id, "tangent-mode"))
+            .selected_text(format!("{:?}", tangents.mode))
+            .show_ui(ui, |ui| {
+                for mode in [
+                    TangentMode::Linear,
+                    TangentMode::Flat,
+                    TangentMode::Auto,
+                    TangentMode::Broken,
+                ] {
+                    ui.selectable_value(&mut tangents.mode, mode, format!("{mode:?}"));
+                }
+            });
+
+        // Switching to broken tangents keeps the current shape of the curve
+        if tangents.mode == TangentMode::Broken && mode != TangentMode::Broken {
+            tangents.in_slope = in_slope;
+            tangents.out_slope = out_slope;
+        }
+
+        if tangents.mode == TangentMode::Broken {
+            ui.add(
+                egui::DragValue::new(&mut tangents.in_slope)
+                    .speed(0.05)
+                    .prefix("In: "),
+            );
+            ui.add(
+                egui::DragValue::new(&mut tangents.out_slope)
+                    .speed(0.05)
+                    .prefix("Out: "),
+            );
+        }
+    });
+}
+
+fn timeline<T>(
+    ui: &mut egui::Ui,
+    channel: Option<&DiscreteChannel<T>>,
+    current_keyframe: &mut usize,
+    duration: usize,
+    label: impl Fn(&T) -> String,
+) {
+    let Some(channel) = channel else {
+        return;
+    };
+
+    let duration = channel
+        .keyframe_starts()
+        .map(|(frame, _)| frame)
+        .fold(duration, usize::max)
+        .max(1);
+    let (response, painter) = ui.allocate_painter(
+        egui::vec2(ui.available_width(), TIMELINE_HEIGHT),
+        Sense::click(),
+    );
+    let view = CurveView {
+        rect: response.rect.shrink(HIT_RADIUS),
+        duration: duration as f32,
+        min: 0.0,
+        max: 1.0,
+    };
+
+    if response.clicked()
+        && let Some(pos) = response.interact_pointer_pos()
+    {
+        *current_keyframe = channel
+            .keyframe_starts()
+            .map(|(frame, _)| frame)
+            .find(|frame| (view.pos(*frame as f32, 0.5).x - pos.x).abs() <= HIT_RADIUS)
+            .unwrap_or_else(|| view.frame_at(pos.x));
+    }
+
+    let visuals = ui.visuals();
+    painter.rect_filled(response.rect, 2.0, visuals.extreme_bg_color);
+    painter.vline(
+        view.pos(*current_keyframe as f32, 0.0).x,
+        view.rect.y_range(),
+        Stroke::new(1.0, visuals.selection.bg_fill),
+    );
+
+    for (frame, value) in channel.keyframe_starts() {
+        let pos = view.pos(frame as f32, 0.5);
+        let color = if frame == *current_keyframe {
+            visuals.strong_text_color()
+        } else {
+            visuals.text_color()
+        };
+
+        painter.add(Shape::convex_polygon(
+            vec![
+                pos + egui::vec2(0.0, -KEY_RADIUS),
+                pos + egui::vec2(KEY_RADIUS, 0.0),
+                pos + egui::vec2(0.0, KEY_RADIUS),
+                pos + egui::vec2(-KEY_RADIUS, 0.0),
+            ],
+            color,
+            Stroke::NONE,
+        ));
+        painter.text(
+            pos + egui::vec2(KEY_RADIUS * 2.0, 0.0),
+            egui::Align2::LEFT_CENTER,
+            label(value),
+            egui::FontId::proportional(11.0),
+            color,
+        );
+    }
+}
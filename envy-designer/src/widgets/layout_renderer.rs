@@ -5,7 +5,7 @@ use egui::epaint::ViewportInPixels;
 use egui_ltreeview::DirPosition;
 use egui_wgpu::CallbackTrait;
 use envy::{
-    ImageNodeTemplate, ImageScalingMode, LayoutRoot, LayoutTemplate, LayoutTree, MoveNodePosition, NodeImplTemplate, NodeTemplate, NodeTransform, NodeVisibility, SublayoutNodeTemplate, TextAlignment, TextNodeTemplate
+    ImageFillMethod, ImageFillOrigin, ImageNodeTemplate, ImageScalingMode, LayoutRoot, LayoutTemplate, LayoutTree, MoveNodePosition, NodeImplTemplate, NodeTemplate, NodeTransform, NodeVisibility, Gradient, GradientKind, GradientStop, ShapeKind, ShapeNodeTemplate, SublayoutNodeTemplate, TextAlignment, TextNodeTemplate
 };
 use envy_wgpu::WgpuBackend;
 use parking_lot::Mutex;
@@ -230,6 +230,7 @@ impl LayoutRenderer {
                     color: [255; 4],
                     children: vec![],
                     visibility: NodeVisibility::default(),
+                    gradient: None,
                     implementation: NodeImplTemplate::Empty,
                 };
                 if node.as_str().is_empty() {
@@ -422,15 +423,102 @@ impl LayoutRenderer {
                     ui.end_row();
                 });
 
+                ui.horizontal(|ui| {
+                    let mut has_gradient = node.gradient.is_some();
+                    if ui.checkbox(&mut has_gradient, "Gradient").changed() {
+                        node.gradient = has_gradient.then(Gradient::default);
+                        changed |= true;
+                    }
+
+                    let Some(gradient) = node.gradient.as_mut() else {
+                        return;
+                    };
+
+                    let mut is_radial = matches!(gradient.kind, GradientKind::Radial { .. });
+                    egui::ComboBox::new("gradient-kind", "")
+                        .selected_text(if is_radial { "Radial" } else { "Linear" })
+                        .show_ui(ui, |ui| {
+                            changed |= ui.selectable_value(&mut is_radial, false, "Linear").changed();
+                            changed |= ui.selectable_value(&mut is_radial, true, "Radial").changed();
+                        });
+
+                    match (is_radial, &gradient.kind) {
+                        (true, GradientKind::Linear { .. }) => {
+                            gradient.kind = GradientKind::Radial {
+                                center: glam::Vec2::splat(0.5),
+                                radius: 0.5,
+                            };
+                        }
+                        (false, GradientKind::Radial { .. }) => gradient.kind = GradientKind::default(),
+                        _ => {}
+                    }
+                });
+
+                if let Some(gradient) = node.gradient.as_mut() {
+                    egui::Grid::new("gradient-grid").show(ui, |ui| {
+                        match &mut gradient.kind {
+                            GradientKind::Linear { start, end } => {
+                                ui.label("Start");
+                                changed |= ui.add(egui::DragValue::new(&mut start.x).speed(0.01)).changed();
+                                changed |= ui.add(egui::DragValue::new(&mut start.y).speed(0.01)).changed();
+                                ui.end_row();
+                                ui.label("End");
+                                changed |= ui.add(egui::DragValue::new(&mut end.x).speed(0.01)).changed();
+                                changed |= ui.add(egui::DragValue::new(&mut end.y).speed(0.01)).changed();
+                                ui.end_row();
+                            }
+                            GradientKind::Radial { center, radius } => {
+                                ui.label("Center");
+                                changed |= ui.add(egui::DragValue::new(&mut center.x).speed(0.01)).changed();
+                                changed |= ui.add(egui::DragValue::new(&mut center.y).speed(0.01)).changed();
+                                ui.end_row();
+                                ui.label("Radius");
+                                changed |= ui.add(egui::DragValue::new(radius).range(0.0..=f32::INFINITY).speed(0.01)).changed();
+                                ui.end_row();
+                            }
+                        }
+
+                        let mut remove = None;
+                        for (idx, stop) in gradient.stops.iter_mut().enumerate() {
+                            ui.label(format!("Stop {idx}"));
+                            changed |= ui.add(egui::DragValue::new(&mut stop.offset).range(0.0..=1.0).speed(0.01)).changed();
+                            changed |= ui.color_edit_button_srgba_unmultiplied(&mut stop.color).changed();
+                            if ui.button("Remove").clicked() {
+                                remove = Some(idx);
+                            }
+                            ui.end_row();
+                        }
+
+                        if let Some(idx) = remove {
+                            gradient.stops.remove(idx);
+                            changed |= true;
+                        }
+
+                        if gradient.stops.len() < envy::MAX_GRADIENT_STOPS && ui.button("Add Stop").clicked() {
+                            gradient.stops.push(GradientStop {
+                                offset: 1.0,
+                                color: gradient.stops.last().map(|stop| stop.color).unwrap_or([255; 4]),
+                            });
+                            changed |= true;
+                        }
+                        ui.end_row();
+                    });
+
+                    if changed {
+                        gradient.stops.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+                    }
+                }
+
                 ui.horizontal(|ui| {
                     ui.label("Node Type");
-                    let supported = ["Empty", "Image", "Text", "Sublayout"];
+                    let supported = ["Empty", "Image", "Text", "Sublayout", "Shape"];
 
                     let mut current_idx = match &node.implementation {
                         NodeImplTemplate::Empty => 0,
                         NodeImplTemplate::Image(_) => 1,
                         NodeImplTemplate::Text(_) => 2,
                         NodeImplTemplate::Sublayout(_) => 3,
+                        NodeImplTemplate::Shape(_) => 4,
                     };
 
                     let old_idx = current_idx;
@@ -438,7 +526,7 @@ impl LayoutRenderer {
                     egui::ComboBox::new("node-picker", "").show_index(
                         ui,
                         &mut current_idx,
-                        4,
+                        supported.len(),
                         |x| supported[x],
                     );
 
@@ -453,6 +541,9 @@ impl LayoutRenderer {
                                     image_scaling_mode_y: Default::default(),
                                     uv_offset: glam::Vec2::ZERO,
                                     uv_scale: glam::Vec2::ONE,
+                                    fill_method: Default::default(),
+                                    fill_origin: Default::default(),
+                                    fill_amount: 1.0,
                                 })
                             }
                             2 => {
@@ -472,6 +563,14 @@ impl LayoutRenderer {
                                         sublayout_name: "".to_string(),
                                     })
                             }
+                            4 => {
+                                node.implementation = NodeImplTemplate::Shape(ShapeNodeTemplate {
+                                    kind: ShapeKind::default(),
+                                    fill_color: Some([255; 4]),
+                                    stroke_color: [255; 4],
+                                    stroke_thickness: 0.0,
+                                })
+                            }
                             _ => unimplemented!(),
                         }
                         changed |= true;
@@ -591,6 +690,44 @@ impl LayoutRenderer {
                                         changed |= ui.add(egui::DragValue::new(&mut image.uv_scale.y).speed(0.1)).changed();
                                     });
                             });
+
+                        egui::Grid::new("fill")
+                            .show(ui, |ui| {
+                                ui.label("Fill Method");
+                                egui::ComboBox::new("fill-method", "")
+                                    .selected_text(format!("{:?}", image.fill_method))
+                                    .show_ui(ui, |ui| {
+                                        for method in [
+                                            ImageFillMethod::Horizontal,
+                                            ImageFillMethod::Vertical,
+                                            ImageFillMethod::RadialClockwise,
+                                            ImageFillMethod::RadialCounterClockwise,
+                                        ] {
+                                            changed |= ui
+                                                .selectable_value(&mut image.fill_method, method, format!("{method:?}"))
+                                                .changed();
+                                        }
+                                    });
+                                ui.end_row();
+                                ui.label("Fill Origin");
+                                egui::ComboBox::new("fill-origin", "")
+                                    .selected_text(format!("{:?}", image.fill_origin))
+                                    .show_ui(ui, |ui| {
+                                        for origin in [
+                                            ImageFillOrigin::Left,
+                                            ImageFillOrigin::Top,
+                                            ImageFillOrigin::Right,
+                                            ImageFillOrigin::Bottom,
+                                        ] {
+                                            changed |= ui
+                                                .selectable_value(&mut image.fill_origin, origin, format!("{origin:?}"))
+                                                .changed();
+                                        }
+                                    });
+                                ui.end_row();
+                                ui.label("Fill Amount");
+                                changed |= ui.add(egui::Slider::new(&mut image.fill_amount, 0.0..=1.0)).changed();
+                            });
                     }
                     NodeImplTemplate::Text(text) => {
                         ui.horizontal(|ui| {
@@ -700,6 +837,109 @@ impl LayoutRenderer {
                                 });
                         });
                     }
+                    NodeImplTemplate::Shape(shape) => {
+                        ui.horizontal(|ui| {
+                            ui.label("Shape");
+                            let kinds = ["Rectangle", "Ellipse", "Polygon", "Polyline"];
+                            let mut current_kind = match &shape.kind {
+                                ShapeKind::Rectangle { .. } => 0,
+                                ShapeKind::Ellipse => 1,
+                                ShapeKind::Polygon { .. } => 2,
+                                ShapeKind::Polyline { .. } => 3,
+                            };
+                            let old_kind = current_kind;
+                            egui::ComboBox::new("shape-kind", "").show_index(
+                                ui,
+                                &mut current_kind,
+                                kinds.len(),
+                                |x| kinds[x],
+                            );
+
+                            if old_kind != current_kind {
+                                // Keep the points around when swapping between polygons and polylines
+                                let points = match &mut shape.kind {
+                                    ShapeKind::Polygon { points } | ShapeKind::Polyline { points } => {
+                                        std::mem::take(points)
+                                    }
+                                    _ => vec![
+                                        glam::Vec2::new(0.5, 0.0),
+                                        glam::Vec2::new(1.0, 1.0),
+                                        glam::Vec2::new(0.0, 1.0),
+                                    ],
+                                };
+
+                                shape.kind = match current_kind {
+                                    0 => ShapeKind::default(),
+                                    1 => ShapeKind::Ellipse,
+                                    2 => ShapeKind::Polygon { points },
+                                    3 => ShapeKind::Polyline { points },
+                                    _ => unimplemented!(),
+                                };
+                                changed |= true;
+                            }
+                        });
+
+                        egui::Grid::new("shape-grid").show(ui, |ui| {
+                            match &mut shape.kind {
+                                ShapeKind::Rectangle { corner_radii } => {
+                                    for (label, radius) in ["Top Left Radius", "Top Right Radius", "Bottom Right Radius", "Bottom Left Radius"]
+                                        .into_iter()
+                                        .zip(corner_radii.iter_mut())
+                                    {
+                                        ui.label(label);
+                                        changed |= ui.add(egui::DragValue::new(radius).range(0.0..=f32::INFINITY).speed(1.0)).changed();
+                                        ui.end_row();
+                                    }
+                                }
+                                ShapeKind::Ellipse => {}
+                                ShapeKind::Polygon { points } | ShapeKind::Polyline { points } => {
+                                    let mut remove = None;
+                                    for (idx, point) in points.iter_mut().enumerate() {
+                                        ui.label(format!("Point {idx}"));
+                                        changed |= ui.add(egui::DragValue::new(&mut point.x).speed(0.01)).changed();
+                                        changed |= ui.add(egui::DragValue::new(&mut point.y).speed(0.01)).changed();
+                                        if ui.button("Remove").clicked() {
+                                            remove = Some(idx);
+                                        }
+                                        ui.end_row();
+                                    }
+
+                                    if let Some(idx) = remove {
+                                        points.remove(idx);
+                                        changed |= true;
+                                    }
+
+                                    if ui.button("Add Point").clicked() {
+                                        points.push(points.last().copied().unwrap_or(glam::Vec2::splat(0.5)));
+                                        changed |= true;
+                                    }
+                                    ui.end_row();
+                                }
+                            }
+
+                            if !matches!(shape.kind, ShapeKind::Polyline { .. }) {
+                                ui.label("Fill");
+                                let mut is_filled = shape.fill_color.is_some();
+                                if ui.checkbox(&mut is_filled, "").changed() {
+                                    shape.fill_color = is_filled.then_some([255; 4]);
+                                    changed |= true;
+                                }
+                                if let Some(fill_color) = shape.fill_color.as_mut() {
+                                    changed |= ui.color_edit_button_srgba_unmultiplied(fill_color).changed();
+                                }
+                                ui.end_row();
+                            }
+
+                            ui.label("Stroke Thickness");
+                            changed |= ui.add(egui::DragValue::new(&mut shape.stroke_thickness).range(0.0..=f32::INFINITY).speed(1.0).clamp_existing_to_range(true)).changed();
+                            ui.end_row();
+                            ui.label("Stroke Color");
+                            changed |= ui
+                                .color_edit_button_srgba_unmultiplied(&mut shape.stroke_color)
+                                .changed();
+                            ui.end_row();
+                        });
+                    }
                 }
             });
 
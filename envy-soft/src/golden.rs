@@ -0,0 +1,203 @@
+//! Comparison of rendered images against golden images stored as PNGs
+//!
+//! [`assert_matches_golden`] is meant to be called from tests after rendering a layout into a
+//! [`Canvas`](crate::Canvas). When the comparison fails, the rendered image and a diff image are written next to the
+//! golden image as `<name>.actual.png` and `<name>.diff.png`, so they can be inspected or uploaded as CI artifacts.
+//!
+//! Golden images are never created or overwritten implicitly. Set the [`UPDATE_ENV_VAR`] environment variable to write
+//! the rendered images as the new golden images instead of comparing against them.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use image::RgbaImage;
+
+/// Environment variable that, when set to anything other than `0`, updates golden images instead of comparing
+pub const UPDATE_ENV_VAR: &str = "ENVY_UPDATE_GOLDEN";
+
+/// How far a rendered image is allowed to stray from its golden image
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Tolerance {
+    /// Largest difference allowed in any channel of a pixel before the pixel counts as mismatched
+    pub channel: u8,
+
+    /// Fraction of pixels, in the range `[0.0, 1.0]`, that are allowed to be mismatched
+    pub mismatched_pixels: f32,
+}
+
+impl Tolerance {
+    pub const EXACT: Self = Self {
+        channel: 0,
+        mismatched_pixels: 0.0,
+    };
+}
+
+impl Default for Tolerance {
+    /// Allows for small rounding differences and the odd pixel along the edges of triangles
+    fn default() -> Self {
+        Self {
+            channel: 2,
+            mismatched_pixels: 0.001,
+        }
+    }
+}
+
+/// The result of comparing two images
+#[derive(Debug, Clone)]
+pub struct ImageDiff {
+    /// Number of pixels that differ by more than the channel tolerance
+    pub mismatched_pixels: usize,
+
+    /// Largest difference found in any channel of any pixel
+    pub max_channel_difference: u8,
+
+    /// Mismatched pixels are drawn in red over a faded copy of the expected image
+    pub image: RgbaImage,
+}
+
+/// Why a rendered image failed to match its golden image
+#[derive(Debug)]
+pub enum GoldenError {
+    /// The golden image does not exist yet
+    Missing(PathBuf),
+
+    /// The golden image could not be loaded
+    Load(PathBuf, image::ImageError),
+
+    /// The rendered image is a different size than the golden image
+    SizeMismatch {
+        expected: (u32, u32),
+        actual: (u32, u32),
+    },
+
+    /// Too many pixels differ from the golden image
+    Mismatch {
+        diff: ImageDiff,
+        total_pixels: usize,
+    },
+}
+
+impl fmt::Display for GoldenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing(path) => write!(
+                f,
+                "golden image {} does not exist, run with {UPDATE_ENV_VAR}=1 to create it",
+                path.display()
+            ),
+            Self::Load(path, error) => write!(f, "failed to load golden image {}: {error}", path.display()),
+            Self::SizeMismatch { expected, actual } => write!(
+                f,
+                "expected a {}x{} image, rendered a {}x{} image",
+                expected.0, expected.1, actual.0, actual.1
+            ),
+            Self::Mismatch { diff, total_pixels } => write!(
+                f,
+                "{} of {total_pixels} pixels differ from the golden image (largest channel difference is {})",
+                diff.mismatched_pixels, diff.max_channel_difference
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GoldenError {}
+
+/// Compares every pixel of `actual` against `expected`
+///
+/// Returns `None` when the images are not the same size.
+pub fn diff_images(expected: &RgbaImage, actual: &RgbaImage, channel_tolerance: u8) -> Option<ImageDiff> {
+    if expected.dimensions() != actual.dimensions() {
+        return None;
+    }
+
+    let mut mismatched_pixels = 0;
+    let mut max_channel_difference = 0;
+    let mut image = RgbaImage::new(expected.width(), expected.height());
+
+    for ((expected, actual), out) in expected.pixels().zip(actual.pixels()).zip(image.pixels_mut()) {
+        let difference = (0..4)
+            .map(|channel| expected.0[channel].abs_diff(actual.0[channel]))
+            .max()
+            .unwrap_or_default();
+
+        max_channel_difference = max_channel_difference.max(difference);
+
+        out.0 = if difference > channel_tolerance {
+            mismatched_pixels += 1;
+            [0xFF, 0x00, 0x00, 0xFF]
+        } else {
+            let [r, g, b, a] = expected.0;
+            [r / 4, g / 4, b / 4, a / 4]
+        };
+    }
+
+    Some(ImageDiff {
+        mismatched_pixels,
+        max_channel_difference,
+        image,
+    })
+}
+
+/// Compares `actual` against the golden image at `path`, or replaces the golden image if [`UPDATE_ENV_VAR`] is set
+///
+/// On failure, `<name>.actual.png` and (if the sizes match) `<name>.diff.png` are written next to the golden image.
+pub fn check_golden(actual: &RgbaImage, path: impl AsRef<Path>, tolerance: Tolerance) -> Result<(), GoldenError> {
+    let path = path.as_ref();
+
+    if std::env::var_os(UPDATE_ENV_VAR).is_some_and(|value| value != "0") {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).unwrap();
+        }
+        actual.save(path).unwrap();
+        return Ok(());
+    }
+
+    let result = if path.exists() {
+        compare(actual, path, tolerance)
+    } else {
+        Err(GoldenError::Missing(path.to_path_buf()))
+    };
+
+    if result.is_err() {
+        actual.save(sibling_path(path, "actual")).unwrap();
+    }
+
+    result
+}
+
+fn compare(actual: &RgbaImage, path: &Path, tolerance: Tolerance) -> Result<(), GoldenError> {
+    let expected = image::open(path)
+        .map_err(|error| GoldenError::Load(path.to_path_buf(), error))?
+        .to_rgba8();
+
+    let Some(diff) = diff_images(&expected, actual, tolerance.channel) else {
+        return Err(GoldenError::SizeMismatch {
+            expected: expected.dimensions(),
+            actual: actual.dimensions(),
+        });
+    };
+
+    let total_pixels = expected.width() as usize * expected.height() as usize;
+    if diff.mismatched_pixels as f32 > total_pixels as f32 * tolerance.mismatched_pixels {
+        diff.image.save(sibling_path(path, "diff")).unwrap();
+        return Err(GoldenError::Mismatch { diff, total_pixels });
+    }
+
+    Ok(())
+}
+
+/// Panicking version of [`check_golden`], for use in tests
+#[track_caller]
+pub fn assert_matches_golden(actual: &RgbaImage, path: impl AsRef<Path>, tolerance: Tolerance) {
+    if let Err(error) = check_golden(actual, path, tolerance) {
+        panic!("{error}");
+    }
+}
+
+/// `dir/name.png` -> `dir/name.<suffix>.png`
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{stem}.{suffix}.png"))
+}
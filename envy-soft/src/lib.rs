@@ -0,0 +1,985 @@
+//! A CPU software rasterizer implementing [`EnvyBackend`]
+//!
+//! [`SoftBackend`] renders layouts into a [`Canvas`] without a GPU. Textures are drawn with the same vertex layout,
+//! tiling and fills as `envy-wgpu`, and glyphs and shapes are tessellated with the same `lyon` and `cosmic-text` paths,
+//! so the output closely follows what the GPU backend produces. It is primarily intended for golden image tests, see
+//! the [`golden`] module.
+//!
+//! ```
+//! use envy::{LayoutTree, NodeItem, NodeTransform, ShapeKind, ShapeNode};
+//! use envy_soft::{Canvas, SoftBackend};
+//!
+//! let mut backend = SoftBackend::new();
+//! let mut tree = LayoutTree::new().with_child(NodeItem::new(
+//!     "box",
+//!     NodeTransform::from_size(glam::Vec2::new(960.0, 540.0)),
+//!     [255, 0, 0, 255],
+//!     ShapeNode::new(ShapeKind::default()),
+//! ));
+//!
+//! tree.setup(&mut backend);
+//! tree.update();
+//! tree.propagate();
+//! tree.prepare(&mut backend);
+//!
+//! // The 1920x1080 layout space is stretched to fit the canvas
+//! let mut canvas = Canvas::new(192, 108);
+//! tree.render(&backend, &mut canvas);
+//!
+//! let image = canvas.to_image();
+//! assert_eq!(image.get_pixel(10, 10).0, [255, 0, 0, 255]);
+//! assert_eq!(image.get_pixel(150, 100).0, [0, 0, 0, 0]);
+//! ```
+
+use std::{borrow::Cow, sync::Arc};
+
+use bitvec::vec::BitVec;
+use cosmic_text::{
+    Align, CacheKey, Command, Family, FontSystem, LineIter, Metrics, SwashCache, fontdb::{FaceInfo, Source}
+};
+use envy::{
+    DrawTextureArgs, DrawUniform, EnvyBackend, ImageFillMethod, ImageFillOrigin, ImageScalingMode, PreparedGlyph, ShapeKind, ShapeTessellationArgs, TextAlignment, TextLayoutArgs, TextureFillArgs, TextureRequestArgs
+};
+use glam::{Vec2, Vec3, Vec4};
+use image::RgbaImage;
+use indexmap::IndexMap;
+use lyon::{
+    geom::{Angle, Box2D},
+    math::{point, vector},
+    path::{builder::BorderRadii, FillRule, Polygon, Winding},
+    tessellation::{
+        BuffersBuilder, FillOptions, FillTessellator, FillVertex, StrokeOptions, StrokeTessellator, StrokeVertex, VertexBuffers
+    },
+};
+
+#[cfg(feature = "asset")]
+use envy::asset::ResourceKind;
+use raster::{AddressMode, RasterVertex};
+
+pub mod golden;
+mod raster;
+
+pub use raster::Canvas;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SoftTextureHandle(usize);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SoftUniformHandle(usize);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SoftFontHandle(usize);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SoftGlyphHandle(usize);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SoftShapeHandle(usize);
+
+#[derive(Copy, Clone)]
+struct TextureVertex {
+    pos: Vec3,
+    tex: Vec2,
+}
+
+#[rustfmt::skip]
+impl TextureVertex {
+    const TOP_LEFT:     Self = Self { pos: Vec3::new(-0.5, -0.5, 0.0), tex: Vec2::ZERO };
+    const TOP_RIGHT:    Self = Self { pos: Vec3::new( 0.5, -0.5, 0.0), tex: Vec2::X };
+    const BOTTOM_LEFT:  Self = Self { pos: Vec3::new(-0.5,  0.5, 0.0), tex: Vec2::Y };
+    const BOTTOM_RIGHT: Self = Self { pos: Vec3::new( 0.5,  0.5, 0.0), tex: Vec2::ONE };
+
+    /// Creates a vertex at `pos` inside of the unit quad, interpolating the texture coordinates
+    /// from the corners of `quad` (top left, top right, bottom left, bottom right)
+    fn interpolated(quad: &[Vec2; 4], pos: Vec2) -> Self {
+        let t = pos + 0.5;
+        let top = quad[0].lerp(quad[1], t.x);
+        let bottom = quad[2].lerp(quad[3], t.x);
+        Self {
+            pos: pos.extend(0.0),
+            tex: top.lerp(bottom, t.y),
+        }
+    }
+}
+
+/// Triangle list of positions, with indices into the vertices
+#[derive(Default)]
+struct Mesh {
+    vertices: Vec<Vec2>,
+    indices: Vec<u32>,
+}
+
+impl Mesh {
+    fn from_buffers(buffers: VertexBuffers<Vec2, u32>) -> Self {
+        Self {
+            vertices: buffers.vertices,
+            indices: buffers.indices,
+        }
+    }
+
+    fn draw(&self, canvas: &mut Canvas, view_proj: glam::Mat4, uniform: &DrawUniform) {
+        for triangle in self.indices.chunks_exact(3) {
+            let vertices = [0, 1, 2].map(|corner| {
+                let vertex = self.vertices[triangle[corner] as usize];
+                raster::project(
+                    canvas,
+                    view_proj,
+                    uniform,
+                    vertex.extend(0.0),
+                    raster::gradient_uv(&uniform.gradient, vertex),
+                )
+            });
+
+            raster::fill_triangle(canvas, vertices, |gradient_uv| {
+                uniform.color * raster::gradient_color(&uniform.gradient, gradient_uv)
+            });
+        }
+    }
+}
+
+struct ReservedTexture {
+    image: Arc<RgbaImage>,
+    address_x: AddressMode,
+    address_y: AddressMode,
+    quad: [Vec2; 4],
+    fill: TextureFillArgs,
+    vertices: Vec<TextureVertex>,
+}
+
+struct TextureBackend {
+    cpu_image_cache: IndexMap<Cow<'static, str>, Vec<u8>>,
+    image_cache: IndexMap<Cow<'static, str>, Arc<RgbaImage>>,
+    textures: Vec<ReservedTexture>,
+    texture_slots: BitVec,
+    default_mask_texture: RgbaImage,
+}
+
+impl TextureBackend {
+    #[rustfmt::skip]
+    const STRETCH_VERTICES: &[TextureVertex; 6] = &[
+        TextureVertex::TOP_LEFT, TextureVertex::BOTTOM_LEFT, TextureVertex::TOP_RIGHT,
+        TextureVertex::TOP_RIGHT, TextureVertex::BOTTOM_LEFT, TextureVertex::BOTTOM_RIGHT
+    ];
+
+    const FULL_FILL: TextureFillArgs = TextureFillArgs {
+        method: ImageFillMethod::Horizontal,
+        origin: ImageFillOrigin::Left,
+        amount: 1.0,
+    };
+
+    fn new() -> Self {
+        Self {
+            cpu_image_cache: IndexMap::new(),
+            image_cache: IndexMap::new(),
+            textures: vec![],
+            texture_slots: BitVec::new(),
+            default_mask_texture: RgbaImage::from_pixel(1, 1, image::Rgba([0xFF; 4])),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.cpu_image_cache.clear();
+        self.image_cache.clear();
+        self.textures.clear();
+        self.texture_slots.clear();
+    }
+
+    /// Rewrites the vertices of the texture with the current quad and fill
+    fn write_vertices(&mut self, handle: SoftTextureHandle) {
+        let texture = &mut self.textures[handle.0];
+        let quad = &texture.quad;
+        let fill = texture.fill;
+        let amount = fill.amount.clamp(0.0, 1.0);
+
+        let mut vertices = vec![];
+
+        let push_rect = |min: Vec2, max: Vec2, vertices: &mut Vec<TextureVertex>| {
+            let top_left = TextureVertex::interpolated(quad, min);
+            let top_right = TextureVertex::interpolated(quad, Vec2::new(max.x, min.y));
+            let bottom_left = TextureVertex::interpolated(quad, Vec2::new(min.x, max.y));
+            let bottom_right = TextureVertex::interpolated(quad, max);
+            vertices.extend([top_left, bottom_left, top_right, top_right, bottom_left, bottom_right]);
+        };
+
+        let min = Vec2::splat(-0.5);
+        let max = Vec2::splat(0.5);
+
+        match (fill.method, fill.origin) {
+            _ if amount <= 0.0 => {}
+            (ImageFillMethod::Horizontal, ImageFillOrigin::Left | ImageFillOrigin::Top) => {
+                push_rect(min, Vec2::new(amount - 0.5, 0.5), &mut vertices)
+            }
+            (ImageFillMethod::Horizontal, ImageFillOrigin::Right | ImageFillOrigin::Bottom) => {
+                push_rect(Vec2::new(0.5 - amount, -0.5), max, &mut vertices)
+            }
+            (ImageFillMethod::Vertical, ImageFillOrigin::Top | ImageFillOrigin::Left) => {
+                push_rect(min, Vec2::new(0.5, amount - 0.5), &mut vertices)
+            }
+            (ImageFillMethod::Vertical, ImageFillOrigin::Bottom | ImageFillOrigin::Right) => {
+                push_rect(Vec2::new(-0.5, 0.5 - amount), max, &mut vertices)
+            }
+            (ImageFillMethod::RadialClockwise | ImageFillMethod::RadialCounterClockwise, origin) => {
+                let start = match origin {
+                    ImageFillOrigin::Left => Vec2::NEG_X,
+                    ImageFillOrigin::Top => Vec2::NEG_Y,
+                    ImageFillOrigin::Right => Vec2::X,
+                    ImageFillOrigin::Bottom => Vec2::Y,
+                };
+
+                // Positions are y-down, so a positive angle sweeps clockwise on screen
+                let direction = if fill.method == ImageFillMethod::RadialClockwise { 1.0 } else { -1.0 };
+                let point_at = |angle: f32| {
+                    let dir = Vec2::from_angle(angle * direction).rotate(start);
+                    dir / dir.abs().max_element() * 0.5
+                };
+
+                let sweep = amount * std::f32::consts::TAU;
+                let mut perimeter = vec![point_at(0.0)];
+                for corner in 0..4 {
+                    let angle = std::f32::consts::FRAC_PI_4 + corner as f32 * std::f32::consts::FRAC_PI_2;
+                    if angle >= sweep {
+                        break;
+                    }
+                    perimeter.push(point_at(angle));
+                }
+                perimeter.push(point_at(sweep));
+
+                let center = TextureVertex::interpolated(quad, Vec2::ZERO);
+                for edge in perimeter.windows(2) {
+                    vertices.extend([
+                        center,
+                        TextureVertex::interpolated(quad, edge[0]),
+                        TextureVertex::interpolated(quad, edge[1]),
+                    ]);
+                }
+            }
+        }
+
+        texture.vertices = vertices;
+    }
+}
+
+struct GlyphMeshes {
+    fill: Mesh,
+    outline: Option<Mesh>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct OutlineCacheKey {
+    inner: CacheKey,
+    outline: u32
+}
+
+struct SoftFontBackend {
+    system: FontSystem,
+    swash: SwashCache,
+    glyphs: IndexMap<OutlineCacheKey, GlyphMeshes>,
+    loaded_fonts: IndexMap<String, FaceInfo>,
+}
+
+impl SoftFontBackend {
+    fn new() -> Self {
+        Self {
+            system: FontSystem::new_with_locale_and_db(
+                "".to_string(),
+                cosmic_text::fontdb::Database::new(),
+            ),
+            swash: SwashCache::new(),
+            glyphs: IndexMap::new(),
+            loaded_fonts: IndexMap::new(),
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    fn add_font(&mut self, name: impl Into<String>, font_data: Vec<u8>) -> FaceInfo {
+        let ids = self
+            .system
+            .db_mut()
+            .load_font_source(Source::Binary(Arc::new(font_data)));
+        let face = self.system.db().face(ids[0]).unwrap();
+        self.loaded_fonts.insert(name.into(), face.clone());
+        face.clone()
+    }
+
+    fn prepare_glyph(&mut self, key: OutlineCacheKey, width: f32, height: f32, outline: f32) -> SoftGlyphHandle {
+        if let Some((idx, _, _)) = self.glyphs.get_full(&key) {
+            return SoftGlyphHandle(idx);
+        }
+
+        let commands = self
+            .swash
+            .get_outline_commands(&mut self.system, key.inner)
+            .unwrap();
+
+        let mut builder = lyon::path::Path::builder().with_svg();
+
+        let mut is_open = false;
+
+        let center_x = width / 2.0;
+        let center_y = height / 2.0;
+        let norm_point = |x: f32, y: f32| point(x - center_x, y - center_y);
+
+        for command in commands.iter() {
+            match command {
+                Command::MoveTo(p) => {
+                    if is_open {
+                        builder.close();
+                    }
+                    is_open = true;
+
+                    builder.move_to(norm_point(p.x, -p.y));
+                }
+                Command::Close => {
+                    if is_open {
+                        builder.close();
+                    }
+                    is_open = false;
+                }
+                Command::LineTo(p) => {
+                    is_open = true;
+                    builder.line_to(norm_point(p.x, -p.y));
+                }
+                Command::QuadTo(ctrl, p) => {
+                    is_open = true;
+                    builder.quadratic_bezier_to(norm_point(ctrl.x, -ctrl.y), norm_point(p.x, -p.y));
+                }
+                Command::CurveTo(ctrl_a, ctrl_b, p) => {
+                    is_open = true;
+                    builder.cubic_bezier_to(
+                        norm_point(ctrl_a.x, -ctrl_a.y),
+                        norm_point(ctrl_b.x, -ctrl_b.y),
+                        norm_point(p.x, -p.y),
+                    );
+                }
+            }
+        }
+
+        let path = builder.build();
+
+        let mut fill = VertexBuffers::new();
+        FillTessellator::new()
+            .tessellate_path(
+                &path,
+                &FillOptions::tolerance(0.02).with_fill_rule(FillRule::NonZero),
+                &mut BuffersBuilder::new(&mut fill, |vertex: FillVertex| {
+                    Vec2::from(vertex.position().to_array())
+                }),
+            )
+            .unwrap();
+
+        let outline = (outline > 0.0).then(|| {
+            let mut stroke = VertexBuffers::new();
+            StrokeTessellator::new()
+                .tessellate_path(
+                    &path,
+                    &StrokeOptions::tolerance(0.02),
+                    &mut BuffersBuilder::new(&mut stroke, |vertex: StrokeVertex| {
+                        Vec2::from((vertex.position() + vertex.normal() * outline).to_array())
+                    }),
+                )
+                .unwrap();
+            Mesh::from_buffers(stroke)
+        });
+
+        let index = self.glyphs.len();
+        self.glyphs.insert(key, GlyphMeshes {
+            fill: Mesh::from_buffers(fill),
+            outline,
+        });
+
+        SoftGlyphHandle(index)
+    }
+
+    fn layout(
+        &mut self,
+        mut new_uniform: impl FnMut() -> SoftUniformHandle,
+        args: TextLayoutArgs<'_, SoftBackend>,
+    ) -> Vec<PreparedGlyph<SoftBackend>> {
+        let face = &self.loaded_fonts[args.handle.0];
+
+        let metrics = Metrics::new(args.font_size, args.line_height);
+        let mut buffer = cosmic_text::Buffer::new(&mut self.system, metrics);
+        let mut buffer = buffer.borrow_with(&mut self.system);
+        buffer.set_size(Some(args.buffer_size.x), Some(args.buffer_size.y));
+        let attrs = cosmic_text::Attrs {
+            family: Family::Name(&face.families[0].0),
+            stretch: face.stretch,
+            style: face.style,
+            weight: face.weight,
+            ..cosmic_text::Attrs::new()
+        };
+        buffer.set_rich_text(
+            [(args.text, attrs.clone())],
+            &attrs,
+            cosmic_text::Shaping::Basic,
+            Some(match args.alignment {
+                TextAlignment::Left => Align::Left,
+                TextAlignment::Center => Align::Center,
+                TextAlignment::Right => Align::Right,
+                TextAlignment::Justify => Align::Justified,
+                TextAlignment::End => Align::End
+            }),
+        );
+
+        let line_starts = LineIter::new(args.text).map(|(range, _)| range.start).collect::<Vec<_>>();
+
+        let mut glyphs = vec![];
+
+        for run in buffer.layout_runs() {
+            for glyph in run.glyphs.iter() {
+                let byte_index = line_starts.get(run.line_i).copied().unwrap_or_default() + glyph.start;
+                let char_index = args.text.get(..byte_index).map(|text| text.chars().count()).unwrap_or_default();
+                glyphs.push((
+                    OutlineCacheKey {
+                        inner: CacheKey::new(
+                                glyph.font_id,
+                                glyph.glyph_id,
+                                glyph.font_size,
+                                (0.0, 0.0),
+                                glyph.cache_key_flags,
+                            )
+                            .0,
+                        outline: args.outline_thickness.to_bits()
+                    },
+                    glyph.w,
+                    run.line_height,
+                    glyph.x + glyph.x_offset * glyph.font_size,
+                    glyph.y + glyph.y_offset * glyph.font_size + run.line_y,
+                    char_index,
+                ));
+            }
+        }
+
+        let mut prepared_glyphs = vec![];
+        for (key, w, h, x, y, char_index) in glyphs {
+            let handle = self.prepare_glyph(key, w, h, args.outline_thickness);
+            prepared_glyphs.push(PreparedGlyph {
+                glyph_handle: handle,
+                uniform_handle: new_uniform(),
+                outline_uniform_handle: (args.outline_thickness > 0.0).then(&mut new_uniform),
+                offset_in_buffer: Vec2::new(x, y),
+                size: Vec2::new(w, h),
+                char_index,
+            });
+        }
+
+        prepared_glyphs
+    }
+}
+
+#[derive(Default)]
+struct ReservedShape {
+    fill: Mesh,
+    stroke: Mesh,
+}
+
+struct SoftShapeBackend {
+    shapes: Vec<ReservedShape>,
+    shape_slots: BitVec,
+}
+
+impl SoftShapeBackend {
+    fn new() -> Self {
+        Self {
+            shapes: vec![],
+            shape_slots: BitVec::new(),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.shapes.clear();
+        self.shape_slots.clear();
+    }
+
+    fn build_path(kind: &ShapeKind, size: Vec2) -> Option<lyon::path::Path> {
+        let half = size / 2.0;
+        let norm_point = |p: Vec2| point(p.x * size.x - half.x, p.y * size.y - half.y);
+
+        let mut builder = lyon::path::Path::builder();
+        match kind {
+            ShapeKind::Rectangle { corner_radii } => {
+                let max_radius = half.x.min(half.y);
+                let [top_left, top_right, bottom_right, bottom_left] =
+                    corner_radii.map(|radius| radius.clamp(0.0, max_radius));
+                builder.add_rounded_rectangle(
+                    &Box2D::new(point(-half.x, -half.y), point(half.x, half.y)),
+                    &BorderRadii {
+                        top_left,
+                        top_right,
+                        bottom_left,
+                        bottom_right,
+                    },
+                    Winding::Positive,
+                );
+            }
+            ShapeKind::Ellipse => {
+                builder.add_ellipse(
+                    point(0.0, 0.0),
+                    vector(half.x, half.y),
+                    Angle::zero(),
+                    Winding::Positive,
+                );
+            }
+            ShapeKind::Polygon { points } | ShapeKind::Polyline { points } => {
+                if points.len() < 2 {
+                    return None;
+                }
+
+                let points = points.iter().copied().map(norm_point).collect::<Vec<_>>();
+                builder.add_polygon(Polygon {
+                    points: &points,
+                    closed: matches!(kind, ShapeKind::Polygon { .. }),
+                });
+            }
+        }
+
+        Some(builder.build())
+    }
+
+    fn prepare(&mut self, args: ShapeTessellationArgs<'_>) -> Option<SoftShapeHandle> {
+        let path = Self::build_path(args.kind, args.size)?;
+
+        let mut shape = ReservedShape::default();
+
+        if args.fill {
+            let mut fill = VertexBuffers::new();
+            FillTessellator::new()
+                .tessellate_path(
+                    &path,
+                    &FillOptions::tolerance(0.1).with_fill_rule(FillRule::NonZero),
+                    &mut BuffersBuilder::new(&mut fill, |vertex: FillVertex| {
+                        Vec2::from(vertex.position().to_array())
+                    }),
+                )
+                .ok()?;
+            shape.fill = Mesh::from_buffers(fill);
+        }
+
+        if args.stroke_thickness > 0.0 {
+            let mut stroke = VertexBuffers::new();
+            StrokeTessellator::new()
+                .tessellate_path(
+                    &path,
+                    &StrokeOptions::tolerance(0.1).with_line_width(args.stroke_thickness),
+                    &mut BuffersBuilder::new(&mut stroke, |vertex: StrokeVertex| {
+                        Vec2::from(vertex.position().to_array())
+                    }),
+                )
+                .ok()?;
+            shape.stroke = Mesh::from_buffers(stroke);
+        }
+
+        let index = match self.shape_slots.first_zero() {
+            Some(index) => {
+                self.shape_slots.set(index, true);
+                self.shapes[index] = shape;
+                index
+            }
+            None => {
+                self.shape_slots.push(true);
+                self.shapes.push(shape);
+                self.shapes.len() - 1
+            }
+        };
+
+        Some(SoftShapeHandle(index))
+    }
+}
+
+/// An [`EnvyBackend`] that rasterizes layouts on the CPU
+///
+/// Resources are loaded the same way as with `envy-wgpu`. Draw calls are made into a [`Canvas`], which is used as the
+/// render pass of this backend.
+pub struct SoftBackend {
+    view_proj: glam::Mat4,
+    uniforms: Vec<DrawUniform>,
+    uniform_slots: BitVec,
+    textures: TextureBackend,
+    fonts: SoftFontBackend,
+    shapes: SoftShapeBackend,
+    #[cfg(feature = "asset")]
+    deferred: envy::asset::DeferredResources,
+}
+
+impl Default for SoftBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SoftBackend {
+    pub fn new() -> Self {
+        Self {
+            view_proj: glam::Mat4::orthographic_rh(0.0, 1920.0, 1080.0, 0.0, 0.0, 1.0),
+            uniforms: vec![],
+            uniform_slots: BitVec::new(),
+            textures: TextureBackend::new(),
+            fonts: SoftFontBackend::new(),
+            shapes: SoftShapeBackend::new(),
+            #[cfg(feature = "asset")]
+            deferred: envy::asset::DeferredResources::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.uniforms.clear();
+        self.uniform_slots.clear();
+        self.textures.reset();
+        self.fonts.reset();
+        self.shapes.reset();
+        #[cfg(feature = "asset")]
+        self.deferred.clear();
+    }
+
+    /// Loads every image and font from an asset that has not been requested by a node yet
+    #[cfg(feature = "asset")]
+    pub fn load_deferred_resources(&mut self) {
+        let resources = self.deferred.drain().collect::<Vec<_>>();
+        for resource in resources {
+            let Some(bytes) = resource.load() else {
+                continue;
+            };
+
+            match resource.kind() {
+                ResourceKind::Image => self.add_texture(resource.name().to_string(), &bytes),
+                ResourceKind::Font => {
+                    let _ = self.add_font(resource.name(), bytes);
+                }
+            }
+        }
+    }
+
+    /// Decodes `image_bytes` and adds it as a texture, replacing the image of any texture with the same name
+    ///
+    /// Like in `envy-wgpu`, images with the same bytes as a texture that was already added share its decoded image.
+    pub fn add_texture(&mut self, name: impl Into<Cow<'static, str>>, image_bytes: &[u8]) {
+        let name = name.into();
+        let shared = self
+            .textures
+            .cpu_image_cache
+            .iter()
+            .filter(|(other, bytes)| **other != name && bytes.as_slice() == image_bytes)
+            .find_map(|(other, _)| self.textures.image_cache.get(other).cloned());
+
+        let image = shared
+            .unwrap_or_else(|| Arc::new(image::load_from_memory(image_bytes).unwrap().to_rgba8()));
+        self.textures.cpu_image_cache.insert(name.clone(), image_bytes.to_vec());
+        self.insert_image(name, image);
+    }
+
+    /// Adds an already decoded image as a texture, replacing the image of any texture with the same name
+    pub fn add_image(&mut self, name: impl Into<Cow<'static, str>>, image: RgbaImage) {
+        let mut out = std::io::Cursor::new(vec![]);
+        image.write_to(&mut out, image::ImageFormat::Png).unwrap();
+
+        let name = name.into();
+        self.textures.cpu_image_cache.insert(name.clone(), out.into_inner());
+        self.insert_image(name, Arc::new(image));
+    }
+
+    fn insert_image(&mut self, name: Cow<'static, str>, image: Arc<RgbaImage>) {
+        if let Some(prev) = self.textures.image_cache.insert(name, image.clone()) {
+            self.textures
+                .textures
+                .iter_mut()
+                .filter(|reserved| Arc::ptr_eq(&reserved.image, &prev))
+                .for_each(|reserved| reserved.image = image.clone());
+        }
+    }
+
+    pub fn add_font(&mut self, name: impl Into<String>, font: Vec<u8>) -> FaceInfo {
+        self.fonts.add_font(name, font)
+    }
+
+    pub fn get_texture(&self, name: impl AsRef<str>) -> Option<&RgbaImage> {
+        self.textures.image_cache.get(name.as_ref()).map(|image| &**image)
+    }
+
+    pub fn get_font_face_info(&self, name: impl AsRef<str>) -> Option<&FaceInfo> {
+        self.fonts.loaded_fonts.get(name.as_ref())
+    }
+
+    pub fn remove_font(&mut self, name: &str) {
+        let _ = self.fonts.loaded_fonts.shift_remove(name);
+        #[cfg(feature = "asset")]
+        self.deferred.remove(ResourceKind::Font, name);
+    }
+
+    pub fn remove_texture(&mut self, name: &str) {
+        let _ = self.textures.image_cache.shift_remove(name);
+        let _ = self.textures.cpu_image_cache.shift_remove(name);
+        #[cfg(feature = "asset")]
+        self.deferred.remove(ResourceKind::Image, name);
+    }
+
+    pub fn iter_texture_names(&self) -> impl Iterator<Item = &str> {
+        self.textures.image_cache.keys().map(|key| key.as_ref())
+    }
+
+    pub fn iter_font_names(&self) -> impl Iterator<Item = &str> {
+        self.fonts.loaded_fonts.keys().map(|key| key.as_ref())
+    }
+
+    fn new_uniform(uniforms: &mut Vec<DrawUniform>, uniform_slots: &mut BitVec) -> SoftUniformHandle {
+        if let Some(first_available) = uniform_slots.first_zero() {
+            uniform_slots.set(first_available, true);
+            SoftUniformHandle(first_available)
+        } else {
+            uniform_slots.push(true);
+            uniforms.push(DrawUniform::new(glam::Mat4::IDENTITY, Vec4::ONE));
+            SoftUniformHandle(uniforms.len() - 1)
+        }
+    }
+}
+
+impl EnvyBackend for SoftBackend {
+    type TextureHandle = SoftTextureHandle;
+    type UniformHandle = SoftUniformHandle;
+    type FontHandle = SoftFontHandle;
+    type GlyphHandle = SoftGlyphHandle;
+    type ShapeHandle = SoftShapeHandle;
+
+    type RenderPass<'a> = Canvas;
+
+    fn request_texture_by_name(&mut self, name: impl AsRef<str>, args: TextureRequestArgs) -> Option<Self::TextureHandle> {
+        #[cfg(feature = "asset")]
+        if let Some(bytes) = self.deferred.take(ResourceKind::Image, name.as_ref()) {
+            self.add_texture(name.as_ref().to_string(), &bytes);
+        }
+
+        let image = self.textures.image_cache.get(name.as_ref())?.clone();
+        let address_mode = |mode: ImageScalingMode| match mode {
+            ImageScalingMode::Stretch => AddressMode::ClampToEdge,
+            ImageScalingMode::Tiling => AddressMode::Repeat,
+        };
+
+        let texture = ReservedTexture {
+            image,
+            address_x: address_mode(args.scaling_x),
+            address_y: address_mode(args.scaling_y),
+            quad: [Vec2::ZERO, Vec2::X, Vec2::Y, Vec2::ONE],
+            fill: TextureBackend::FULL_FILL,
+            vertices: vec![],
+        };
+
+        let handle = if let Some(first_available) = self.textures.texture_slots.first_zero() {
+            self.textures.texture_slots.set(first_available, true);
+            self.textures.textures[first_available] = texture;
+            SoftTextureHandle(first_available)
+        } else {
+            let handle = SoftTextureHandle(self.textures.textures.len());
+            self.textures.texture_slots.push(true);
+            self.textures.textures.push(texture);
+            handle
+        };
+
+        self.textures.write_vertices(handle);
+
+        Some(handle)
+    }
+
+    fn request_font_by_name(&mut self, name: impl AsRef<str>) -> Option<Self::FontHandle> {
+        #[cfg(feature = "asset")]
+        if let Some(bytes) = self.deferred.take(ResourceKind::Font, name.as_ref()) {
+            let _ = self.add_font(name.as_ref(), bytes);
+        }
+
+        self.fonts
+            .loaded_fonts
+            .get_index_of(name.as_ref())
+            .map(SoftFontHandle)
+    }
+
+    fn request_new_uniform(&mut self) -> Option<Self::UniformHandle> {
+        Some(Self::new_uniform(&mut self.uniforms, &mut self.uniform_slots))
+    }
+
+    fn update_uniform(&mut self, uniform: Self::UniformHandle, data: DrawUniform) {
+        self.uniforms[uniform.0] = data;
+    }
+
+    fn update_texture_scaling(&mut self, handle: Self::TextureHandle, uv_offset: Vec2, uv_scale: Vec2, size: Vec2) {
+        let texture = &self.textures.textures[handle.0];
+        let texture_size = Vec2::new(texture.image.width() as f32, texture.image.height() as f32);
+
+        let mut vertices = *TextureBackend::STRETCH_VERTICES;
+
+        if texture.address_x == AddressMode::Repeat {
+            vertices.iter_mut().for_each(|vertex| vertex.tex.x *= size.x / texture_size.x);
+        }
+
+        if texture.address_y == AddressMode::Repeat {
+            vertices.iter_mut().for_each(|vertex| vertex.tex.y *= size.y / texture_size.y);
+        }
+
+        let uv_scale = Vec2::new(
+            if uv_scale.x == 0.0 { 0.0 } else { uv_scale.x.recip() },
+            if uv_scale.y == 0.0 { 0.0 } else { uv_scale.y.recip() }
+        );
+
+        vertices.iter_mut().for_each(|vert| {
+            vert.tex = vert.tex * uv_scale + uv_offset / texture_size;
+        });
+
+        self.textures.textures[handle.0].quad = [vertices[0].tex, vertices[2].tex, vertices[1].tex, vertices[5].tex];
+        self.textures.write_vertices(handle);
+    }
+
+    fn update_texture_fill(&mut self, handle: Self::TextureHandle, args: TextureFillArgs) {
+        self.textures.textures[handle.0].fill = args;
+        self.textures.write_vertices(handle);
+    }
+
+    fn layout_text(&mut self, args: TextLayoutArgs<'_, Self>) -> Vec<PreparedGlyph<Self>> {
+        let Self { uniforms, uniform_slots, fonts, .. } = self;
+        fonts.layout(|| Self::new_uniform(uniforms, uniform_slots), args)
+    }
+
+    fn prepare_shape(&mut self, args: ShapeTessellationArgs<'_>) -> Option<Self::ShapeHandle> {
+        self.shapes.prepare(args)
+    }
+
+    fn draw_texture(
+        &self,
+        uniform: Self::UniformHandle,
+        texture: Self::TextureHandle,
+        pass: &mut Self::RenderPass<'_>,
+    ) {
+        self.draw_texture_ext(
+            uniform,
+            DrawTextureArgs {
+                texture,
+                mask_texture: None,
+            },
+            pass,
+        );
+    }
+
+    fn draw_texture_ext(
+        &self,
+        uniform: Self::UniformHandle,
+        args: DrawTextureArgs<Self>,
+        pass: &mut Self::RenderPass<'_>,
+    ) {
+        let uniform = &self.uniforms[uniform.0];
+        let texture = &self.textures.textures[args.texture.0];
+        let (mask, mask_x, mask_y) = match args.mask_texture {
+            Some(mask) => {
+                let mask = &self.textures.textures[mask.0];
+                (&*mask.image, mask.address_x, mask.address_y)
+            }
+            None => (&self.textures.default_mask_texture, AddressMode::ClampToEdge, AddressMode::ClampToEdge),
+        };
+
+        for triangle in texture.vertices.chunks_exact(3) {
+            let vertices: [RasterVertex<(Vec2, Vec2)>; 3] = [0, 1, 2].map(|corner| {
+                let vertex = triangle[corner];
+                raster::project(
+                    pass,
+                    self.view_proj,
+                    uniform,
+                    vertex.pos,
+                    (vertex.tex, raster::gradient_uv(&uniform.gradient, vertex.pos.truncate())),
+                )
+            });
+
+            raster::fill_triangle(pass, vertices, |(tex, gradient_uv)| {
+                let mask_color = raster::sample(mask, tex, mask_x, mask_y);
+                let mask_color = (mask_color.truncate() * mask_color.w).extend(mask_color.w);
+                raster::sample(&texture.image, tex, texture.address_x, texture.address_y)
+                    * mask_color
+                    * uniform.color
+                    * raster::gradient_color(&uniform.gradient, gradient_uv)
+            });
+        }
+    }
+
+    fn draw_glyph(
+        &self,
+        uniform: Self::UniformHandle,
+        outline_uniform: Option<Self::UniformHandle>,
+        glyph: Self::GlyphHandle,
+        pass: &mut Self::RenderPass<'_>,
+    ) {
+        let meshes = self.fonts.glyphs.get_index(glyph.0).unwrap().1;
+        meshes.fill.draw(pass, self.view_proj, &self.uniforms[uniform.0]);
+        if let (Some(outline_uniform), Some(outline)) = (outline_uniform, meshes.outline.as_ref()) {
+            outline.draw(pass, self.view_proj, &self.uniforms[outline_uniform.0]);
+        }
+    }
+
+    fn draw_shape(
+        &self,
+        fill_uniform: Option<Self::UniformHandle>,
+        stroke_uniform: Option<Self::UniformHandle>,
+        handle: Self::ShapeHandle,
+        pass: &mut Self::RenderPass<'_>,
+    ) {
+        let shape = &self.shapes.shapes[handle.0];
+        for (uniform, mesh) in [(fill_uniform, &shape.fill), (stroke_uniform, &shape.stroke)] {
+            if let Some(uniform) = uniform {
+                mesh.draw(pass, self.view_proj, &self.uniforms[uniform.0]);
+            }
+        }
+    }
+
+    fn release_font(&mut self, _handle: Self::FontHandle) {}
+
+    fn release_shape(&mut self, handle: Self::ShapeHandle) {
+        self.shapes.shape_slots.set(handle.0, false);
+    }
+
+    fn release_texture(&mut self, handle: Self::TextureHandle) {
+        self.textures.texture_slots.set(handle.0, false);
+    }
+
+    fn release_uniform(&mut self, handle: Self::UniformHandle) {
+        self.uniform_slots.set(handle.0, false);
+    }
+}
+
+#[cfg(feature = "asset")]
+impl envy::asset::EnvyAssetProvider for SoftBackend {
+    fn fetch_font_bytes_by_name<'a>(&'a self, name: &str) -> Cow<'a, [u8]> {
+        if let Some(bytes) = self.deferred.bytes(ResourceKind::Font, name) {
+            return Cow::Owned(bytes);
+        }
+
+        self.fonts
+            .loaded_fonts
+            .get(name)
+            .map(|face| match &face.source {
+                Source::Binary(binary) => Cow::Borrowed((**binary).as_ref()),
+                _ => unimplemented!(),
+            })
+            .unwrap()
+    }
+
+    fn fetch_image_bytes_by_name<'a>(&'a self, name: &str) -> Cow<'a, [u8]> {
+        if let Some(bytes) = self.deferred.bytes(ResourceKind::Image, name) {
+            return Cow::Owned(bytes);
+        }
+
+        Cow::Borrowed(self.textures.cpu_image_cache.get(name).unwrap().as_slice())
+    }
+
+    fn load_font_bytes_with_name(&mut self, name: String, bytes: Vec<u8>) {
+        // The deferred bytes are out of date and would be picked over these ones
+        self.deferred.remove(ResourceKind::Font, &name);
+        let _ = self.add_font(name, bytes);
+    }
+
+    fn load_image_bytes_with_name(&mut self, name: String, bytes: Vec<u8>) {
+        self.deferred.remove(ResourceKind::Image, &name);
+        self.add_texture(name, &bytes);
+    }
+
+    /// Images and fonts are only decoded once a node requests them, or [`SoftBackend::load_deferred_resources`] is
+    /// called
+    fn defer_resource(&mut self, resource: envy::asset::LazyResource) {
+        self.deferred.insert(resource);
+    }
+}
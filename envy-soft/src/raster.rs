@@ -0,0 +1,243 @@
+use envy::{DrawUniform, GradientUniform};
+use glam::{Vec2, Vec4};
+use image::RgbaImage;
+
+/// An RGBA render target that [`SoftBackend`](crate::SoftBackend) draws into
+///
+/// Layouts are always drawn in the 1920x1080 layout space, which is stretched to fit the size of the canvas. Colors
+/// are blended the same way as the `envy-wgpu` pipelines, with straight (not premultiplied) alpha.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<Vec4>,
+}
+
+impl Canvas {
+    /// Creates a fully transparent canvas
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![Vec4::ZERO; width as usize * height as usize],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Fills the entire canvas with `color`
+    pub fn clear(&mut self, color: [u8; 4]) {
+        self.pixels.fill(Vec4::from_array(color.map(|component| component as f32 / 255.0)));
+    }
+
+    /// Converts the canvas into an 8-bit image, rounding each channel to the nearest value
+    pub fn to_image(&self) -> RgbaImage {
+        let bytes = self
+            .pixels
+            .iter()
+            .flat_map(|pixel| pixel.to_array().map(|component| (component.clamp(0.0, 1.0) * 255.0).round() as u8))
+            .collect();
+
+        RgbaImage::from_raw(self.width, self.height, bytes).unwrap()
+    }
+
+    /// Maps a position in normalized device coordinates onto the canvas, in pixels
+    fn to_pixel(&self, ndc: Vec2) -> Vec2 {
+        Vec2::new(
+            (ndc.x + 1.0) * 0.5 * self.width as f32,
+            (1.0 - ndc.y) * 0.5 * self.height as f32,
+        )
+    }
+
+    /// Blends `color` over the pixel at `(x, y)` using `src * src_alpha + dst * (1 - src_alpha)`
+    fn blend(&mut self, x: u32, y: u32, color: Vec4) {
+        let pixel = &mut self.pixels[(x + y * self.width) as usize];
+        let alpha = color.w.clamp(0.0, 1.0);
+        let rgb = color.truncate() * alpha + pixel.truncate() * (1.0 - alpha);
+        *pixel = rgb.extend(alpha + pixel.w * (1.0 - alpha));
+    }
+}
+
+/// Edge function of the line `a -> b`, positive when `p` is to the right of the line in screen space (y down)
+fn edge(a: Vec2, b: Vec2, p: Vec2) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+/// Whether a pixel center lying exactly on the edge `a -> b` belongs to the triangle
+///
+/// Only top and left edges own their pixels, so triangles sharing an edge never blend the same pixel twice
+fn owns_edge(a: Vec2, b: Vec2) -> bool {
+    let delta = b - a;
+    (delta.y == 0.0 && delta.x > 0.0) || delta.y < 0.0
+}
+
+/// A vertex after it has been transformed onto the canvas
+#[derive(Copy, Clone)]
+pub(crate) struct RasterVertex<T> {
+    pub position: Vec2,
+    pub varying: T,
+}
+
+/// Values interpolated across the face of a triangle
+pub(crate) trait Varying: Copy {
+    fn weighted(values: [Self; 3], weights: [f32; 3]) -> Self;
+}
+
+impl Varying for Vec2 {
+    fn weighted(values: [Self; 3], weights: [f32; 3]) -> Self {
+        values[0] * weights[0] + values[1] * weights[1] + values[2] * weights[2]
+    }
+}
+
+impl<A: Varying, B: Varying> Varying for (A, B) {
+    fn weighted(values: [Self; 3], weights: [f32; 3]) -> Self {
+        (
+            A::weighted(values.map(|value| value.0), weights),
+            B::weighted(values.map(|value| value.1), weights),
+        )
+    }
+}
+
+/// Projects a vertex of a mesh onto the canvas with the same transforms as the vertex shaders of `envy-wgpu`
+pub(crate) fn project<T>(
+    canvas: &Canvas,
+    view_proj: glam::Mat4,
+    uniform: &DrawUniform,
+    vertex: glam::Vec3,
+    varying: T,
+) -> RasterVertex<T> {
+    let clip = view_proj * uniform.model_matrix * vertex.extend(1.0);
+    RasterVertex {
+        position: canvas.to_pixel(clip.truncate().truncate() / clip.w),
+        varying,
+    }
+}
+
+/// Rasterizes a triangle, sampling every pixel whose center is covered by it
+///
+/// `shade` is called with the interpolated varyings of the pixel and returns the color to blend onto the canvas
+pub(crate) fn fill_triangle<T: Varying>(
+    canvas: &mut Canvas,
+    mut vertices: [RasterVertex<T>; 3],
+    mut shade: impl FnMut(T) -> Vec4,
+) {
+    let mut area = edge(vertices[0].position, vertices[1].position, vertices[2].position);
+    if area == 0.0 || !area.is_finite() {
+        return;
+    }
+
+    if area < 0.0 {
+        vertices.swap(1, 2);
+        area = -area;
+    }
+
+    let [a, b, c] = vertices.map(|vertex| vertex.position);
+    let min = a.min(b).min(c).floor().max(Vec2::ZERO);
+    let max = a
+        .max(b)
+        .max(c)
+        .ceil()
+        .min(Vec2::new(canvas.width as f32, canvas.height as f32));
+
+    if min.x >= max.x || min.y >= max.y {
+        return;
+    }
+
+    let edges = [(b, c), (c, a), (a, b)];
+    let owned = edges.map(|(start, end)| owns_edge(start, end));
+
+    for y in min.y as u32..max.y as u32 {
+        for x in min.x as u32..max.x as u32 {
+            let center = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+            let weights = edges.map(|(start, end)| edge(start, end, center));
+            let covered = weights
+                .iter()
+                .zip(owned.iter())
+                .all(|(weight, owned)| *weight > 0.0 || (*weight == 0.0 && *owned));
+
+            if !covered {
+                continue;
+            }
+
+            let varying = T::weighted(vertices.map(|vertex| vertex.varying), weights.map(|weight| weight / area));
+            let color = shade(varying);
+            canvas.blend(x, y, color);
+        }
+    }
+}
+
+/// Position of a vertex in the coordinate space of the gradient, matching `gradient_uv` in the shaders
+pub(crate) fn gradient_uv(gradient: &GradientUniform, vertex: Vec2) -> Vec2 {
+    glam::Mat2::from_cols_array(&gradient.uv_matrix.to_array()) * vertex + gradient.uv_translation
+}
+
+/// Color of the gradient at `uv`, matching `gradient_color` in the shaders
+pub(crate) fn gradient_color(gradient: &GradientUniform, uv: Vec2) -> Vec4 {
+    if gradient.kind == GradientUniform::KIND_NONE || gradient.stop_count == 0 {
+        return Vec4::ONE;
+    }
+
+    let t = if gradient.kind == GradientUniform::KIND_LINEAR {
+        let start = gradient.points.truncate().truncate();
+        let direction = Vec2::new(gradient.points.z, gradient.points.w) - start;
+        (uv - start).dot(direction) / direction.dot(direction).max(0.000001)
+    } else {
+        (uv - gradient.points.truncate().truncate()).length() / gradient.points.z.max(0.000001)
+    }
+    .clamp(0.0, 1.0);
+
+    let unpack = |color: u32| Vec4::from_array(color.to_le_bytes().map(|component| component as f32 / 255.0));
+
+    let mut color = unpack(gradient.colors[0]);
+    for idx in 1..gradient.stop_count as usize {
+        let start = gradient.offsets[idx - 1];
+        let end = gradient.offsets[idx];
+        let progress = ((t - start) / (end - start).max(0.000001)).clamp(0.0, 1.0);
+        color = color.lerp(unpack(gradient.colors[idx]), progress);
+    }
+
+    color
+}
+
+/// How texture coordinates outside of `[0.0, 1.0]` are sampled
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum AddressMode {
+    ClampToEdge,
+    Repeat,
+}
+
+impl AddressMode {
+    fn texel(self, coord: i64, size: u32) -> u32 {
+        match self {
+            Self::ClampToEdge => coord.clamp(0, size as i64 - 1) as u32,
+            Self::Repeat => coord.rem_euclid(size as i64) as u32,
+        }
+    }
+}
+
+/// Samples `image` with bilinear filtering, like the linear samplers created by `envy-wgpu`
+pub(crate) fn sample(image: &RgbaImage, uv: Vec2, address_x: AddressMode, address_y: AddressMode) -> Vec4 {
+    if image.width() == 0 || image.height() == 0 {
+        return Vec4::ZERO;
+    }
+
+    let texel = uv * Vec2::new(image.width() as f32, image.height() as f32) - 0.5;
+    let base = texel.floor();
+    let fract = texel - base;
+
+    let fetch = |dx: i64, dy: i64| {
+        let x = address_x.texel(base.x as i64 + dx, image.width());
+        let y = address_y.texel(base.y as i64 + dy, image.height());
+        Vec4::from_array(image.get_pixel(x, y).0.map(|component| component as f32 / 255.0))
+    };
+
+    let top = fetch(0, 0).lerp(fetch(1, 0), fract.x);
+    let bottom = fetch(0, 1).lerp(fetch(1, 1), fract.x);
+    top.lerp(bottom, fract.y)
+}
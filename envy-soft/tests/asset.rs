@@ -0,0 +1,76 @@
+#![cfg(feature = "asset")]
+
+use envy::{
+    asset::{
+        container::{AliasRecord, ChunkTag, Container},
+        deserialize, serialize, ResourceKind,
+    },
+    ImageNodeTemplate, LayoutRoot, LayoutTemplate, NodeImplTemplate, NodeTemplate, NodeTransform,
+};
+use envy_soft::SoftBackend;
+use image::{Rgba, RgbaImage};
+
+fn png(image: &RgbaImage) -> Vec<u8> {
+    let mut out = std::io::Cursor::new(vec![]);
+    image.write_to(&mut out, image::ImageFormat::Png).unwrap();
+    out.into_inner()
+}
+
+fn image(name: &str, texture: &str) -> NodeTemplate {
+    NodeTemplate {
+        name: name.to_string(),
+        transform: NodeTransform::from_size(glam::Vec2::splat(16.0)),
+        color: [0xFF; 4],
+        visibility: Default::default(),
+        gradient: None,
+        children: vec![],
+        implementation: NodeImplTemplate::Image(ImageNodeTemplate {
+            texture_name: texture.to_string(),
+            mask_texture_name: None,
+            image_scaling_mode_x: Default::default(),
+            image_scaling_mode_y: Default::default(),
+            uv_offset: glam::Vec2::ZERO,
+            uv_scale: glam::Vec2::ONE,
+            fill_method: Default::default(),
+            fill_origin: Default::default(),
+            fill_amount: 1.0,
+        }),
+    }
+}
+
+#[test]
+fn identical_images_are_stored_and_loaded_once() {
+    let red = png(&RgbaImage::from_pixel(4, 4, Rgba([0xFF, 0x00, 0x00, 0xFF])));
+    let blue = png(&RgbaImage::from_pixel(4, 4, Rgba([0x00, 0x00, 0xFF, 0xFF])));
+
+    let mut backend = SoftBackend::new();
+    backend.add_texture("red", &red);
+    backend.add_texture("also_red", &red);
+    backend.add_texture("blue", &blue);
+
+    let template = LayoutTemplate {
+        canvas_size: [64, 64],
+        root_nodes: vec![image("a", "red"), image("b", "also_red"), image("c", "blue")],
+        ..Default::default()
+    };
+    let root = LayoutRoot::<SoftBackend>::from_root_template(template, []);
+    let bytes = serialize(&root, &backend);
+
+    let container = Container::parse(&bytes).unwrap();
+    let mut records = container.chunk(ChunkTag::ALIASES).unwrap().unwrap();
+    let alias: AliasRecord = records.read().unwrap();
+    assert!(records.is_empty());
+    assert_eq!(alias.kind, ResourceKind::Image);
+    assert_eq!((alias.name.as_str(), alias.target.as_str()), ("also_red", "red"));
+
+    let mut loaded = SoftBackend::new();
+    let mut root: LayoutRoot<SoftBackend> = deserialize(&mut loaded, &bytes);
+    root.setup(&mut loaded);
+
+    let first = loaded.get_texture("red").unwrap();
+    assert!(std::ptr::eq(first, loaded.get_texture("also_red").unwrap()));
+    assert!(!std::ptr::eq(first, loaded.get_texture("blue").unwrap()));
+    assert_eq!(first.get_pixel(0, 0), &Rgba([0xFF, 0x00, 0x00, 0xFF]));
+
+    root.release(&mut loaded);
+}
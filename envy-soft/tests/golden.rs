@@ -0,0 +1,201 @@
+use envy::{
+    Gradient, GradientKind, GradientStop, ImageFillMethod, ImageFillOrigin, ImageNode, ImageScalingMode, LayoutTree,
+    NodeItem, NodeTransform, ShapeKind, ShapeNode, TextAlignment, TextNode,
+};
+use envy_soft::{
+    golden::{assert_matches_golden, Tolerance},
+    Canvas, SoftBackend,
+};
+use glam::Vec2;
+use image::{Rgba, RgbaImage};
+
+fn golden_path(name: &str) -> std::path::PathBuf {
+    std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{name}.png"))
+}
+
+fn render(backend: &mut SoftBackend, tree: &mut LayoutTree<SoftBackend>) -> RgbaImage {
+    tree.setup(backend);
+    tree.update();
+    tree.propagate();
+    tree.prepare(backend);
+
+    let mut canvas = Canvas::new(480, 270);
+    canvas.clear([0x20, 0x20, 0x20, 0xFF]);
+    tree.render(backend, &mut canvas);
+
+    tree.release(backend);
+    canvas.to_image()
+}
+
+/// Tuffy, a public domain font that is bundled with the tests so that text renders the same everywhere
+fn test_font() -> Vec<u8> {
+    std::fs::read(std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fonts/Tuffy.ttf")).unwrap()
+}
+
+fn checkerboard() -> RgbaImage {
+    RgbaImage::from_fn(64, 64, |x, y| {
+        if (x / 16) % 2 == (y / 16) % 2 {
+            Rgba([0xFF, 0xFF, 0xFF, 0xFF])
+        } else {
+            Rgba([0x30, 0x60, 0xC0, 0xFF])
+        }
+    })
+}
+
+fn circle_mask() -> RgbaImage {
+    RgbaImage::from_fn(32, 32, |x, y| {
+        let distance = (Vec2::new(x as f32, y as f32) + 0.5 - 16.0).length();
+        Rgba([0xFF, 0xFF, 0xFF, if distance < 14.0 { 0xFF } else { 0x00 }])
+    })
+}
+
+fn image(name: &str, configure: impl FnOnce(&mut ImageNode<SoftBackend>)) -> ImageNode<SoftBackend> {
+    let mut node = ImageNode::new(name);
+    configure(&mut node);
+    node
+}
+
+#[test]
+fn textures() {
+    let mut backend = SoftBackend::new();
+    backend.add_image("checkerboard", checkerboard());
+    backend.add_image("circle", circle_mask());
+
+    let mut tree = LayoutTree::new()
+        .with_child(NodeItem::new(
+            "stretched",
+            NodeTransform::from_xy(40.0, 40.0).with_size_xy(320.0, 320.0),
+            [0xFF; 4],
+            ImageNode::new("checkerboard"),
+        ))
+        .with_child(NodeItem::new(
+            "tinted_tiled",
+            NodeTransform::from_xy(400.0, 40.0).with_size_xy(480.0, 320.0),
+            [0xFF, 0x80, 0x80, 0xC0],
+            image("checkerboard", |node| {
+                node.set_scaling_x(ImageScalingMode::Tiling);
+                node.set_scaling_y(ImageScalingMode::Tiling);
+            }),
+        ))
+        .with_child(NodeItem::new(
+            "masked",
+            NodeTransform::from_xy(920.0, 40.0).with_size_xy(320.0, 320.0),
+            [0xFF; 4],
+            image("checkerboard", |node| node.set_mask_texture_name(Some("circle".to_string()))),
+        ))
+        .with_child(NodeItem::new(
+            "radial_fill",
+            NodeTransform::from_xy(1280.0, 40.0).with_size_xy(320.0, 320.0),
+            [0xFF; 4],
+            image("checkerboard", |node| {
+                node.set_fill_method(ImageFillMethod::RadialClockwise);
+                node.set_fill_origin(ImageFillOrigin::Top);
+                node.set_fill_amount(0.6);
+            }),
+        ))
+        .with_child(NodeItem::new(
+            "rotated",
+            NodeTransform::from_xy(200.0, 600.0).with_size_xy(240.0, 240.0).with_angle(30.0),
+            [0x80, 0xFF, 0x80, 0xFF],
+            ImageNode::new("checkerboard"),
+        ));
+
+    let image = render(&mut backend, &mut tree);
+    assert_matches_golden(&image, golden_path("textures"), Tolerance::default());
+}
+
+#[test]
+fn shapes() {
+    let mut backend = SoftBackend::new();
+
+    let mut stroked = ShapeNode::new(ShapeKind::Ellipse);
+    stroked.set_stroke_color([0xFF, 0xC0, 0x00, 0xFF]);
+    stroked.set_stroke_thickness(16.0);
+
+    let mut outline = ShapeNode::new(ShapeKind::Polyline {
+        points: vec![Vec2::new(0.0, 1.0), Vec2::new(0.25, 0.0), Vec2::new(0.75, 1.0), Vec2::new(1.0, 0.0)],
+    });
+    outline.set_fill_color(None);
+    outline.set_stroke_thickness(12.0);
+
+    let mut gradient = NodeItem::new(
+        "gradient",
+        NodeTransform::from_xy(1000.0, 80.0).with_size_xy(640.0, 360.0),
+        [0xFF; 4],
+        ShapeNode::new(ShapeKind::Rectangle {
+            corner_radii: [48.0, 0.0, 96.0, 0.0],
+        }),
+    );
+    gradient.set_gradient(Some(Gradient {
+        kind: GradientKind::Linear {
+            start: Vec2::new(0.0, 0.5),
+            end: Vec2::new(1.0, 0.5),
+        },
+        stops: vec![
+            GradientStop {
+                offset: 0.0,
+                color: [0xFF, 0x00, 0x40, 0xFF],
+            },
+            GradientStop {
+                offset: 1.0,
+                color: [0x00, 0x40, 0xFF, 0x80],
+            },
+        ],
+    }));
+
+    let mut tree = LayoutTree::new()
+        .with_child(NodeItem::new(
+            "stroked",
+            NodeTransform::from_xy(80.0, 80.0).with_size_xy(480.0, 360.0),
+            [0x40, 0x80, 0xFF, 0xFF],
+            stroked,
+        ))
+        .with_child(NodeItem::new(
+            "triangle",
+            NodeTransform::from_xy(640.0, 80.0).with_size_xy(300.0, 360.0),
+            [0x80, 0xFF, 0x40, 0xFF],
+            ShapeNode::new(ShapeKind::Polygon {
+                points: vec![Vec2::new(0.5, 0.0), Vec2::new(1.0, 1.0), Vec2::new(0.0, 1.0)],
+            }),
+        ))
+        .with_child(NodeItem::new(
+            "outline",
+            NodeTransform::from_xy(80.0, 560.0).with_size_xy(960.0, 400.0),
+            [0xFF; 4],
+            outline,
+        ))
+        .with_child(gradient);
+
+    let image = render(&mut backend, &mut tree);
+    assert_matches_golden(&image, golden_path("shapes"), Tolerance::default());
+}
+
+#[test]
+fn text() {
+    let mut backend = SoftBackend::new();
+    backend.add_font("tuffy", test_font());
+
+    let mut outlined = TextNode::new("tuffy", 200.0, 200.0, "Outlined");
+    outlined.set_outline_thickness(4.0);
+    outlined.set_outline_color([0x00, 0x00, 0x00, 0xFF]);
+
+    let mut right = TextNode::new("tuffy", 96.0, 112.0, "Right aligned\nover two lines");
+    right.set_alignment(TextAlignment::Right);
+
+    let mut tree = LayoutTree::new()
+        .with_child(NodeItem::new(
+            "outlined",
+            NodeTransform::from_xy(80.0, 80.0).with_size_xy(1200.0, 200.0),
+            [0xFF, 0xC0, 0x40, 0xFF],
+            outlined,
+        ))
+        .with_child(NodeItem::new(
+            "right",
+            NodeTransform::from_xy(640.0, 480.0).with_size_xy(1200.0, 240.0),
+            [0x80, 0xC0, 0xFF, 0xFF],
+            right,
+        ));
+
+    let image = render(&mut backend, &mut tree);
+    assert_matches_golden(&image, golden_path("text"), Tolerance::default());
+}
@@ -1,22 +1,23 @@
 use std::{
-    borrow::Cow, cmp::Ordering, num::NonZeroU64, ops::{Index, IndexMut, Range}, sync::Arc
+    borrow::Cow, cmp::Ordering, collections::HashMap, num::NonZeroU64, ops::{Index, IndexMut, Range}, sync::Arc
 };
 
 use bitvec::vec::BitVec;
 use bytemuck::{Pod, Zeroable};
 use camino::Utf8Path;
 use cosmic_text::{
-    Align, CacheKey, Command, Family, FontSystem, Metrics, SwashCache, fontdb::{FaceInfo, Source}
+    Align, CacheKey, Command, Family, FontSystem, LineIter, Metrics, SwashCache, fontdb::{FaceInfo, Source}
 };
 use envy::{
-    DrawTextureArgs, DrawUniform, EnvyBackend, ImageScalingMode, PreparedGlyph, TextAlignment, TextLayoutArgs, TextureRequestArgs, ViewUniform, asset::EnvyAssetProvider
+    DrawTextureArgs, DrawUniform, EnvyBackend, ImageFillMethod, ImageFillOrigin, ImageScalingMode, PreparedGlyph, ShapeKind, ShapeTessellationArgs, TextAlignment, TextLayoutArgs, TextureFillArgs, TextureRequestArgs, ViewUniform, asset::{DeferredResources, EnvyAssetProvider, LazyResource, ResourceKind}
 };
 use glam::{Vec3, Vec4};
 use image::{codecs::png::PngEncoder, ImageEncoder};
 use indexmap::IndexMap;
 use lyon::{
-    math::point,
-    path::FillRule,
+    geom::{Angle, Box2D},
+    math::{point, vector},
+    path::{builder::BorderRadii, FillRule, Polygon, Winding},
     tessellation::{
         FillGeometryBuilder, FillOptions, FillTessellator, FillVertex, GeometryBuilder, GeometryBuilderError, StrokeGeometryBuilder, StrokeOptions, StrokeTessellator, VertexId
     },
@@ -45,6 +46,9 @@ pub struct WgpuFontHandle(usize);
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct WgpuGlyphHandle(usize);
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WgpuShapeHandle(usize);
+
 #[repr(C)]
 #[derive(Copy, Clone, Pod, Zeroable)]
 struct TextureVertex {
@@ -83,19 +87,46 @@ impl TextureVertex {
             tex: glam::Vec2::new(node_size.x / texture_size.x, node_size.y / texture_size.y),
         }
     }
+
+    /// Creates a vertex at `pos` inside of the unit quad, interpolating the texture coordinates
+    /// from the corners of `quad` (top left, top right, bottom left, bottom right)
+    fn interpolated(quad: &[glam::Vec2; 4], pos: glam::Vec2) -> Self {
+        let t = pos + 0.5;
+        let top = quad[0].lerp(quad[1], t.x);
+        let bottom = quad[2].lerp(quad[3], t.x);
+        Self {
+            pos: pos.extend(0.0),
+            tex: top.lerp(bottom, t.y),
+        }
+    }
 }
 
 struct ReservedTexture {
+    /// Name the texture was requested by, textures with identical images share the same `wgpu::Texture`
+    name: String,
     texture: wgpu::Texture,
     sampler: wgpu::Sampler,
     bind_group: wgpu::BindGroup,
     scaling_mode_x: wgpu::AddressMode,
     scaling_mode_y: wgpu::AddressMode,
+    quad: [glam::Vec2; 4],
+    fill: TextureFillArgs,
+    vertex_count: u32,
+}
+
+/// An image that was uploaded, kept so that images with the same bytes can share its texture
+struct UploadedImage {
+    texture: wgpu::Texture,
+    cpu_image: Vec<u8>,
 }
 
 struct TextureBackend {
     cpu_image_cache: IndexMap<Cow<'static, str>, Vec<u8>>,
     image_cache: IndexMap<Cow<'static, str>, wgpu::Texture>,
+
+    /// Uploaded images by the [`envy::content_hash`] of their bytes, so that identical images are only uploaded once.
+    /// Images whose hashes collide are told apart by their pixels
+    uploaded: HashMap<u64, Vec<UploadedImage>>,
     textures: Vec<ReservedTexture>,
     texture_slots: BitVec,
     texture_bgl: wgpu::BindGroupLayout,
@@ -111,6 +142,94 @@ impl TextureBackend {
         TextureVertex::TOP_RIGHT, TextureVertex::BOTTOM_LEFT, TextureVertex::BOTTOM_RIGHT
     ];
 
+    /// Number of vertices reserved for each texture handle, radial fills can require up to
+    /// five triangles
+    const VERTICES_PER_TEXTURE: usize = 18;
+
+    const FULL_FILL: TextureFillArgs = TextureFillArgs {
+        method: ImageFillMethod::Horizontal,
+        origin: ImageFillOrigin::Left,
+        amount: 1.0,
+    };
+
+    /// Rewrites the vertices of the texture with the current quad and fill
+    fn write_vertices(&mut self, handle: WgpuTextureHandle) {
+        let texture = &mut self.textures[handle.0];
+        let quad = &texture.quad;
+        let fill = texture.fill;
+        let amount = fill.amount.clamp(0.0, 1.0);
+
+        let mut vertices = [TextureVertex::TOP_LEFT; Self::VERTICES_PER_TEXTURE];
+        let mut count = 0;
+
+        let push_rect = |min: glam::Vec2, max: glam::Vec2, vertices: &mut [TextureVertex], count: &mut usize| {
+            let top_left = TextureVertex::interpolated(quad, min);
+            let top_right = TextureVertex::interpolated(quad, glam::Vec2::new(max.x, min.y));
+            let bottom_left = TextureVertex::interpolated(quad, glam::Vec2::new(min.x, max.y));
+            let bottom_right = TextureVertex::interpolated(quad, max);
+            vertices[*count..*count + 6]
+                .copy_from_slice(&[top_left, bottom_left, top_right, top_right, bottom_left, bottom_right]);
+            *count += 6;
+        };
+
+        let min = glam::Vec2::splat(-0.5);
+        let max = glam::Vec2::splat(0.5);
+
+        match (fill.method, fill.origin) {
+            _ if amount <= 0.0 => {}
+            (ImageFillMethod::Horizontal, ImageFillOrigin::Left | ImageFillOrigin::Top) => {
+                push_rect(min, glam::Vec2::new(amount - 0.5, 0.5), &mut vertices, &mut count)
+            }
+            (ImageFillMethod::Horizontal, ImageFillOrigin::Right | ImageFillOrigin::Bottom) => {
+                push_rect(glam::Vec2::new(0.5 - amount, -0.5), max, &mut vertices, &mut count)
+            }
+            (ImageFillMethod::Vertical, ImageFillOrigin::Top | ImageFillOrigin::Left) => {
+                push_rect(min, glam::Vec2::new(0.5, amount - 0.5), &mut vertices, &mut count)
+            }
+            (ImageFillMethod::Vertical, ImageFillOrigin::Bottom | ImageFillOrigin::Right) => {
+                push_rect(glam::Vec2::new(-0.5, 0.5 - amount), max, &mut vertices, &mut count)
+            }
+            (ImageFillMethod::RadialClockwise | ImageFillMethod::RadialCounterClockwise, origin) => {
+                let start = match origin {
+                    ImageFillOrigin::Left => glam::Vec2::NEG_X,
+                    ImageFillOrigin::Top => glam::Vec2::NEG_Y,
+                    ImageFillOrigin::Right => glam::Vec2::X,
+                    ImageFillOrigin::Bottom => glam::Vec2::Y,
+                };
+
+                // Positions are y-down, so a positive angle sweeps clockwise on screen
+                let direction = if fill.method == ImageFillMethod::RadialClockwise { 1.0 } else { -1.0 };
+                let point_at = |angle: f32| {
+                    let dir = glam::Vec2::from_angle(angle * direction).rotate(start);
+                    dir / dir.abs().max_element() * 0.5
+                };
+
+                let sweep = amount * std::f32::consts::TAU;
+                let mut perimeter = vec![point_at(0.0)];
+                for corner in 0..4 {
+                    let angle = std::f32::consts::FRAC_PI_4 + corner as f32 * std::f32::consts::FRAC_PI_2;
+                    if angle >= sweep {
+                        break;
+                    }
+                    perimeter.push(point_at(angle));
+                }
+                perimeter.push(point_at(sweep));
+
+                let center = TextureVertex::interpolated(quad, glam::Vec2::ZERO);
+                for edge in perimeter.windows(2) {
+                    vertices[count] = center;
+                    vertices[count + 1] = TextureVertex::interpolated(quad, edge[0]);
+                    vertices[count + 2] = TextureVertex::interpolated(quad, edge[1]);
+                    count += 3;
+                }
+            }
+        }
+
+        texture.vertex_count = count as u32;
+        let start = handle.0 * Self::VERTICES_PER_TEXTURE;
+        self.vertex_buffer[start..start + Self::VERTICES_PER_TEXTURE].copy_from_slice(&vertices);
+    }
+
     fn new(
         device: &wgpu::Device,
         queue: &wgpu::Queue,
@@ -298,6 +417,7 @@ impl TextureBackend {
         Self {
             cpu_image_cache,
             image_cache,
+            uploaded: HashMap::new(),
             textures: vec![],
             texture_slots: BitVec::new(),
             texture_bgl,
@@ -309,8 +429,18 @@ impl TextureBackend {
 
     fn reset(&mut self) {
         self.image_cache.clear();
+        self.uploaded.clear();
         self.textures.clear();
     }
+
+    /// Forgets uploaded images that no name uses anymore, so that their textures can be freed
+    fn release_unused_uploads(&mut self) {
+        let image_cache = &self.image_cache;
+        self.uploaded.retain(|_, images| {
+            images.retain(|image| image_cache.values().any(|texture| *texture == image.texture));
+            !images.is_empty()
+        });
+    }
 }
 
 struct BufferVec<T: Pod + Zeroable> {
@@ -674,10 +804,14 @@ impl WgpuFontBackend {
             }),
         );
 
+        let line_starts = LineIter::new(args.text).map(|(range, _)| range.start).collect::<Vec<_>>();
+
         let mut glyphs = vec![];
 
         for run in buffer.layout_runs() {
             for glyph in run.glyphs.iter() {
+                let byte_index = line_starts.get(run.line_i).copied().unwrap_or_default() + glyph.start;
+                let char_index = args.text.get(..byte_index).map(|text| text.chars().count()).unwrap_or_default();
                 glyphs.push((
                     OutlineCacheKey {
                         inner: CacheKey::new(
@@ -694,12 +828,13 @@ impl WgpuFontBackend {
                     run.line_height,
                     glyph.x + glyph.x_offset * glyph.font_size,
                     glyph.y + glyph.y_offset * glyph.font_size + run.line_y,
+                    char_index,
                 ));
             }
         }
 
         let mut prepared_glyphs = vec![];
-        for (key, w, h, x, y) in glyphs {
+        for (key, w, h, x, y, char_index) in glyphs {
             let handle = self.prepare_glyph(key, w, h, args.outline_thickness);
             prepared_glyphs.push(PreparedGlyph {
                 glyph_handle: handle,
@@ -707,6 +842,7 @@ impl WgpuFontBackend {
                 outline_uniform_handle: (args.outline_thickness > 0.0).then(|| new_uniform()),
                 offset_in_buffer: glam::Vec2::new(x, y),
                 size: glam::Vec2::new(w, h),
+                char_index,
             });
         }
 
@@ -714,6 +850,165 @@ impl WgpuFontBackend {
     }
 }
 
+struct ReservedShape {
+    vertices: BufferVec<Vec3>,
+    indices: BufferVec<i32>,
+    fill: Range<u32>,
+    stroke: Range<u32>,
+}
+
+struct WgpuShapeBackend {
+    shapes: Vec<ReservedShape>,
+    shape_slots: BitVec,
+}
+
+impl WgpuShapeBackend {
+    pub fn new() -> Self {
+        Self {
+            shapes: vec![],
+            shape_slots: BitVec::new(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.shapes.clear();
+        self.shape_slots.clear();
+    }
+
+    fn build_path(kind: &ShapeKind, size: glam::Vec2) -> Option<lyon::path::Path> {
+        let half = size / 2.0;
+        let norm_point = |p: glam::Vec2| point(p.x * size.x - half.x, p.y * size.y - half.y);
+
+        let mut builder = lyon::path::Path::builder();
+        match kind {
+            ShapeKind::Rectangle { corner_radii } => {
+                let max_radius = half.x.min(half.y);
+                let [top_left, top_right, bottom_right, bottom_left] =
+                    corner_radii.map(|radius| radius.clamp(0.0, max_radius));
+                builder.add_rounded_rectangle(
+                    &Box2D::new(point(-half.x, -half.y), point(half.x, half.y)),
+                    &BorderRadii {
+                        top_left,
+                        top_right,
+                        bottom_left,
+                        bottom_right,
+                    },
+                    Winding::Positive,
+                );
+            }
+            ShapeKind::Ellipse => {
+                builder.add_ellipse(
+                    point(0.0, 0.0),
+                    vector(half.x, half.y),
+                    Angle::zero(),
+                    Winding::Positive,
+                );
+            }
+            ShapeKind::Polygon { points } | ShapeKind::Polyline { points } => {
+                if points.len() < 2 {
+                    return None;
+                }
+
+                let points = points.iter().copied().map(norm_point).collect::<Vec<_>>();
+                builder.add_polygon(Polygon {
+                    points: &points,
+                    closed: matches!(kind, ShapeKind::Polygon { .. }),
+                });
+            }
+        }
+
+        Some(builder.build())
+    }
+
+    pub fn prepare(&mut self, args: ShapeTessellationArgs<'_>) -> Option<WgpuShapeHandle> {
+        let path = Self::build_path(args.kind, args.size)?;
+
+        let index = match self.shape_slots.first_zero() {
+            Some(index) => {
+                self.shape_slots.set(index, true);
+                index
+            }
+            None => {
+                self.shape_slots.push(true);
+                self.shapes.push(ReservedShape {
+                    vertices: BufferVec::new(wgpu::BufferUsages::VERTEX),
+                    indices: BufferVec::new(wgpu::BufferUsages::INDEX),
+                    fill: 0..0,
+                    stroke: 0..0,
+                });
+                self.shapes.len() - 1
+            }
+        };
+
+        let shape = &mut self.shapes[index];
+        shape.vertices.truncate(0);
+        shape.indices.truncate(0);
+
+        if args.fill {
+            let mut builder = InPlaceBufferBuilders {
+                vertex_start: 0,
+                index_start: 0,
+                vertex_buffer: &mut shape.vertices,
+                index_buffer: &mut shape.indices,
+            };
+            let mut fill_tesselator = FillTessellator::new();
+            if fill_tesselator
+                .tessellate_path(
+                    &path,
+                    &FillOptions::tolerance(0.1).with_fill_rule(FillRule::NonZero),
+                    &mut builder,
+                )
+                .is_err()
+            {
+                self.shape_slots.set(index, false);
+                return None;
+            }
+        }
+
+        shape.fill = 0..shape.indices.len() as u32;
+
+        if args.stroke_thickness > 0.0 {
+            let start = shape.indices.len() as u32;
+            let mut builder = InPlaceStrokeBufferBuilders {
+                vertex_start: shape.vertices.len(),
+                index_start: shape.indices.len(),
+                vertex_buffer: &mut shape.vertices,
+                index_buffer: &mut shape.indices,
+                thickness: 0.0,
+            };
+            let mut stroke_tesselator = StrokeTessellator::new();
+            if stroke_tesselator
+                .tessellate_path(
+                    &path,
+                    &StrokeOptions::tolerance(0.1).with_line_width(args.stroke_thickness),
+                    &mut builder,
+                )
+                .is_err()
+            {
+                self.shape_slots.set(index, false);
+                return None;
+            }
+            shape.stroke = start..shape.indices.len() as u32;
+        } else {
+            shape.stroke = 0..0;
+        }
+
+        Some(WgpuShapeHandle(index))
+    }
+
+    pub fn flush(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        for (shape, _) in self
+            .shapes
+            .iter_mut()
+            .zip(self.shape_slots.iter())
+            .filter(|(shape, slot)| **slot && !shape.indices.is_empty())
+        {
+            shape.vertices.flush(device, queue);
+            shape.indices.flush(device, queue);
+        }
+    }
+}
+
 const fn align_up(value: usize, align: usize) -> usize {
     (value + (align - 1)) & !(align - 1)
 }
@@ -814,7 +1109,9 @@ pub struct WgpuBackend {
     uniform_slots: BitVec,
     textures: TextureBackend,
     fonts: WgpuFontBackend,
+    shapes: WgpuShapeBackend,
     uniform_bind_group: Option<wgpu::BindGroup>,
+    deferred: DeferredResources,
 }
 
 impl WgpuBackend {
@@ -860,7 +1157,7 @@ impl WgpuBackend {
             label: Some("envy_draw_layout"),
             entries: &[wgpu::BindGroupLayoutEntry {
                 binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
                 ty: wgpu::BindingType::Buffer {
                     ty: wgpu::BufferBindingType::Uniform,
                     has_dynamic_offset: true,
@@ -897,7 +1194,9 @@ impl WgpuBackend {
             uniform_slots: BitVec::new(),
             textures,
             fonts,
+            shapes: WgpuShapeBackend::new(),
             uniform_bind_group: None,
+            deferred: DeferredResources::new(),
         }
     }
 
@@ -905,44 +1204,80 @@ impl WgpuBackend {
         self.uniforms.truncate(0);
         self.textures.reset();
         self.fonts.reset();
+        self.shapes.reset();
+        self.deferred.clear();
+    }
+
+    /// Loads every image and font from an asset that has not been requested by a node yet
+    ///
+    /// Resources from [`envy::asset::deserialize`] are only uploaded once a node requests them, call this to make all
+    /// of them show up in [`WgpuBackend::iter_texture_names`] and [`WgpuBackend::iter_font_names`].
+    pub fn load_deferred_resources(&mut self) {
+        let resources = self.deferred.drain().collect::<Vec<_>>();
+        for resource in resources {
+            let Some(bytes) = resource.load() else {
+                continue;
+            };
+
+            match resource.kind() {
+                ResourceKind::Image => {
+                    let _ = self.add_texture(resource.name().to_string(), &bytes);
+                }
+                ResourceKind::Font => {
+                    let _ = self.add_font(resource.name(), bytes);
+                }
+            }
+        }
     }
 
+    /// Adds a texture, replacing the texture of any image with the same name
+    ///
+    /// Images with the same bytes as a texture that was already added share its `wgpu::Texture`, they are only uploaded
+    /// once.
     pub fn add_texture(
         &mut self,
         name: impl Into<Cow<'static, str>>,
         image_bytes: &[u8],
     ) -> wgpu::Texture {
-        let image = image::load_from_memory(image_bytes).unwrap().to_rgba8();
+        let name: Cow<'static, str> = name.into();
+        let hash = envy::content_hash(image_bytes);
 
-        let texture = self.device.create_texture_with_data(
-            &self.queue,
-            &wgpu::TextureDescriptor {
-                label: None,
-                size: wgpu::Extent3d {
-                    width: image.width(),
-                    height: image.height(),
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::Rgba8Unorm,
-                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_SRC,
-                view_formats: &[],
-            },
-            wgpu::wgt::TextureDataOrder::LayerMajor,
-            image.as_raw(),
-        );
+        let image = image::load_from_memory(image_bytes).unwrap().to_rgba8();
 
-        let name: Cow<'static, str> = name.into();
+        // Only the re-encoded copy of each upload is kept, a matching hash is confirmed by decoding it again
+        let shared = self
+            .textures
+            .uploaded
+            .get(&hash)
+            .into_iter()
+            .flatten()
+            .find(|uploaded| {
+                image::load_from_memory(&uploaded.cpu_image)
+                    .is_ok_and(|uploaded| uploaded.to_rgba8() == image)
+            })
+            .map(|image| (image.texture.clone(), image.cpu_image.clone()));
+
+        let (texture, cpu_image) = match shared {
+            Some(shared) => shared,
+            None => {
+                let (texture, cpu_image) = self.upload_texture(&image);
+                self.textures.uploaded.entry(hash).or_default().push(UploadedImage {
+                    texture: texture.clone(),
+                    cpu_image: cpu_image.clone(),
+                });
+                (texture, cpu_image)
+            }
+        };
 
         if let Some(prev) = self
             .textures
             .image_cache
             .insert(name.to_string().into(), texture.clone())
+            .filter(|prev| *prev != texture)
         {
             self.textures.textures.iter_mut().for_each(|reserved| {
-                if reserved.texture == prev {
+                // Other names might still use the previous texture, only rebind the ones requested by this name
+                if reserved.texture == prev && reserved.name == name {
                     reserved.texture = texture.clone();
                     reserved.bind_group =
                         self.device.create_bind_group(&wgpu::BindGroupDescriptor {
@@ -963,8 +1298,37 @@ impl WgpuBackend {
                         });
                 }
             });
+
+            self.textures.release_unused_uploads();
         }
 
+        let _ = self.textures.cpu_image_cache.insert(name, cpu_image);
+
+        texture
+    }
+
+    /// Uploads an image, returning the texture and the image encoded as a PNG
+    fn upload_texture(&mut self, image: &image::RgbaImage) -> (wgpu::Texture, Vec<u8>) {
+        let texture = self.device.create_texture_with_data(
+            &self.queue,
+            &wgpu::TextureDescriptor {
+                label: None,
+                size: wgpu::Extent3d {
+                    width: image.width(),
+                    height: image.height(),
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            },
+            wgpu::wgt::TextureDataOrder::LayerMajor,
+            image.as_raw(),
+        );
+
         let mut out = std::io::Cursor::new(vec![]);
         {
             let encoder = PngEncoder::new_with_quality(
@@ -982,9 +1346,7 @@ impl WgpuBackend {
                 .unwrap();
         }
 
-        let _ = self.textures.cpu_image_cache.insert(name, out.into_inner());
-
-        texture
+        (texture, out.into_inner())
     }
 
     pub fn load_textures_from_bytes<'a>(
@@ -992,35 +1354,7 @@ impl WgpuBackend {
         names_and_bytes: impl IntoIterator<Item = (&'a str, Cow<'a, [u8]>)>,
     ) {
         for (name, bytes) in names_and_bytes {
-            let image = image::load_from_memory(&bytes).unwrap().to_rgba8();
-
-            let texture = self.device.create_texture_with_data(
-                &self.queue,
-                &wgpu::TextureDescriptor {
-                    label: None,
-                    size: wgpu::Extent3d {
-                        width: image.width(),
-                        height: image.height(),
-                        depth_or_array_layers: 1,
-                    },
-                    mip_level_count: 1,
-                    sample_count: 1,
-                    dimension: wgpu::TextureDimension::D2,
-                    format: wgpu::TextureFormat::Rgba8Unorm,
-                    usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_SRC,
-                    view_formats: &[],
-                },
-                wgpu::wgt::TextureDataOrder::LayerMajor,
-                image.as_raw(),
-            );
-
-            self.textures
-                .cpu_image_cache
-                .insert(name.to_string().into(), image.to_vec());
-
-            self.textures
-                .image_cache
-                .insert(name.to_string().into(), texture);
+            let _ = self.add_texture(name.to_string(), &bytes);
         }
     }
 
@@ -1079,6 +1413,7 @@ impl WgpuBackend {
         self.textures.vertex_buffer.flush(&self.device, &self.queue);
         self.fonts.vertices.flush(&self.device, &self.queue);
         self.fonts.indices.flush(&self.device, &self.queue);
+        self.shapes.flush(&self.device, &self.queue);
     }
 
     pub fn rename_texture(&mut self, old: &str, new: impl Into<Cow<'static, str>>) {
@@ -1106,6 +1441,12 @@ impl WgpuBackend {
             .shift_remove_index(index)
             .unwrap()
             .1;
+        self.textures
+            .textures
+            .iter_mut()
+            .filter(|reserved| reserved.name == old)
+            .for_each(|reserved| reserved.name = new.to_string());
+
         self.textures
             .cpu_image_cache
             .insert_before(index, new, texture);
@@ -1125,10 +1466,13 @@ impl WgpuBackend {
 
     pub fn remove_font(&mut self, name: &str) {
         let _ = self.fonts.loaded_fonts.shift_remove(name);
+        self.deferred.remove(ResourceKind::Font, name);
     }
 
     pub fn remove_texture(&mut self, name: &str) {
         let _ = self.textures.image_cache.shift_remove(name);
+        self.textures.release_unused_uploads();
+        self.deferred.remove(ResourceKind::Image, name);
     }
 
     pub fn iter_texture_names<'a>(&'a self) -> impl Iterator<Item = &'a str> {
@@ -1258,10 +1602,15 @@ impl EnvyBackend for WgpuBackend {
     type UniformHandle = WgpuUniformHandle;
     type FontHandle = WgpuFontHandle;
     type GlyphHandle = WgpuGlyphHandle;
+    type ShapeHandle = WgpuShapeHandle;
 
     type RenderPass<'a> = wgpu::RenderPass<'a>;
 
     fn request_texture_by_name(&mut self, name: impl AsRef<str>, args: TextureRequestArgs) -> Option<Self::TextureHandle> {
+        if let Some(bytes) = self.deferred.take(ResourceKind::Image, name.as_ref()) {
+            let _ = self.add_texture(name.as_ref().to_string(), &bytes);
+        }
+
         let texture = self.textures.image_cache.get(name.as_ref())?.clone();
         let scaling_mode_x = match args.scaling_x {
             ImageScalingMode::Stretch => wgpu::AddressMode::ClampToEdge,
@@ -1295,7 +1644,17 @@ impl EnvyBackend for WgpuBackend {
             ],
         });
 
-        let texture = ReservedTexture { texture, sampler, bind_group, scaling_mode_x, scaling_mode_y };
+        let texture = ReservedTexture {
+            name: name.as_ref().to_string(),
+            texture,
+            sampler,
+            bind_group,
+            scaling_mode_x,
+            scaling_mode_y,
+            quad: [glam::Vec2::ZERO, glam::Vec2::X, glam::Vec2::Y, glam::Vec2::ONE],
+            fill: TextureBackend::FULL_FILL,
+            vertex_count: 6,
+        };
 
         let handle = if let Some(first_available) = self.textures.texture_slots.first_zero() {
             self.textures.texture_slots.set(first_available, true);
@@ -1308,17 +1667,24 @@ impl EnvyBackend for WgpuBackend {
             handle
         };
 
-        let vertex_start = handle.0 * 6;
+        let vertex_start = handle.0 * TextureBackend::VERTICES_PER_TEXTURE;
         match self.textures.vertex_buffer.len().cmp(&vertex_start) {
             Ordering::Greater => {},
-            Ordering::Equal => self.textures.vertex_buffer.extend(TextureBackend::STRETCH_VERTICES.iter().copied()),
+            Ordering::Equal => self.textures.vertex_buffer.extend(
+                std::iter::repeat_n(TextureVertex::TOP_LEFT, TextureBackend::VERTICES_PER_TEXTURE)
+            ),
             Ordering::Less => panic!("Texture handle set too high!")
         }
+        self.textures.write_vertices(handle);
 
         Some(handle)
     }
 
     fn request_font_by_name(&mut self, name: impl AsRef<str>) -> Option<Self::FontHandle> {
+        if let Some(bytes) = self.deferred.take(ResourceKind::Font, name.as_ref()) {
+            let _ = self.add_font(name.as_ref(), bytes);
+        }
+
         self.fonts
             .loaded_fonts
             .get_index_of(name.as_ref())
@@ -1377,7 +1743,13 @@ impl EnvyBackend for WgpuBackend {
             vert.tex = vert.tex * uv_scale + uv_offset / texture_size;
         });
 
-        self.textures.vertex_buffer[handle.0 * 6..(handle.0 + 1) * 6].copy_from_slice(&vertices);
+        self.textures.textures[handle.0].quad = [vertices[0].tex, vertices[2].tex, vertices[1].tex, vertices[5].tex];
+        self.textures.write_vertices(handle);
+    }
+
+    fn update_texture_fill(&mut self, handle: Self::TextureHandle, args: TextureFillArgs) {
+        self.textures.textures[handle.0].fill = args;
+        self.textures.write_vertices(handle);
     }
 
     fn layout_text(&mut self, args: TextLayoutArgs<'_, Self>) -> Vec<PreparedGlyph<Self>> {
@@ -1398,6 +1770,10 @@ impl EnvyBackend for WgpuBackend {
         )
     }
 
+    fn prepare_shape(&mut self, args: ShapeTessellationArgs<'_>) -> Option<Self::ShapeHandle> {
+        self.shapes.prepare(args)
+    }
+
     fn draw_texture(
         &self,
         uniform: Self::UniformHandle,
@@ -1435,7 +1811,8 @@ impl EnvyBackend for WgpuBackend {
             &[],
         );
         pass.set_vertex_buffer(0, self.textures.vertex_buffer.buffer().unwrap().slice(..));
-        pass.draw(args.texture.0 as u32 * 6..(args.texture.0 + 1) as u32 * 6, 0..1);
+        let start = (args.texture.0 * TextureBackend::VERTICES_PER_TEXTURE) as u32;
+        pass.draw(start..start + self.textures.textures[args.texture.0].vertex_count, 0..1);
     }
 
     fn draw_glyph(
@@ -1473,8 +1850,46 @@ impl EnvyBackend for WgpuBackend {
         }
     }
 
+    fn draw_shape(
+        &self,
+        fill_uniform: Option<Self::UniformHandle>,
+        stroke_uniform: Option<Self::UniformHandle>,
+        handle: Self::ShapeHandle,
+        pass: &mut Self::RenderPass<'_>,
+    ) {
+        let shape = &self.shapes.shapes[handle.0];
+        let (Some(vertices), Some(indices)) = (shape.vertices.buffer(), shape.indices.buffer()) else {
+            return;
+        };
+
+        pass.set_pipeline(&self.fonts.constant_pipeline);
+        pass.set_vertex_buffer(0, vertices.slice(..));
+        pass.set_index_buffer(indices.slice(..), wgpu::IndexFormat::Uint32);
+
+        for (uniform, range) in [(fill_uniform, &shape.fill), (stroke_uniform, &shape.stroke)] {
+            let Some(uniform) = uniform else {
+                continue;
+            };
+
+            if range.is_empty() {
+                continue;
+            }
+
+            pass.set_bind_group(
+                1,
+                self.uniform_bind_group.as_ref().unwrap(),
+                &[(uniform.0 * std::mem::size_of::<DrawUniform>()) as wgpu::DynamicOffset],
+            );
+            pass.draw_indexed(range.clone(), 0, 0..1);
+        }
+    }
+
     fn release_font(&mut self, _handle: Self::FontHandle) {}
 
+    fn release_shape(&mut self, handle: Self::ShapeHandle) {
+        self.shapes.shape_slots.set(handle.0, false);
+    }
+
     fn release_texture(&mut self, handle: Self::TextureHandle) {
         self.textures.texture_slots.set(handle.0, false);
     }
@@ -1513,6 +1928,10 @@ impl EnvyBackend for WgpuBackend {
 
 impl EnvyAssetProvider for WgpuBackend {
     fn fetch_font_bytes_by_name<'a>(&'a self, name: &str) -> Cow<'a, [u8]> {
+        if let Some(bytes) = self.deferred.bytes(ResourceKind::Font, name) {
+            return Cow::Owned(bytes);
+        }
+
         self.fonts
             .loaded_fonts
             .get(name)
@@ -1524,16 +1943,29 @@ impl EnvyAssetProvider for WgpuBackend {
     }
 
     fn fetch_image_bytes_by_name<'a>(&'a self, name: &str) -> Cow<'a, [u8]> {
+        if let Some(bytes) = self.deferred.bytes(ResourceKind::Image, name) {
+            return Cow::Owned(bytes);
+        }
+
         let texture = self.textures.cpu_image_cache.get(name).unwrap();
 
         Cow::Borrowed(texture.as_slice())
     }
 
     fn load_font_bytes_with_name(&mut self, name: String, bytes: Vec<u8>) {
+        // The deferred bytes are out of date and would be picked over these ones
+        self.deferred.remove(ResourceKind::Font, &name);
         let _ = self.add_font(name, bytes);
     }
 
     fn load_image_bytes_with_name(&mut self, name: String, bytes: Vec<u8>) {
+        self.deferred.remove(ResourceKind::Image, &name);
         let _ = self.add_texture(name, &bytes);
     }
+
+    /// Images and fonts are only decoded and uploaded once a node requests them, or
+    /// [`WgpuBackend::load_deferred_resources`] is called
+    fn defer_resource(&mut self, resource: LazyResource) {
+        self.deferred.insert(resource);
+    }
 }
@@ -4,10 +4,21 @@ struct ViewUniform {
     view_proj_matrix: mat4x4<f32>,
 };
 
+struct GradientUniform {
+    uv_matrix: vec4<f32>,
+    uv_translation: vec2<f32>,
+    kind: u32,
+    stop_count: u32,
+    points: vec4<f32>,
+    offsets: array<vec4<f32>, 2>,
+    colors: array<vec4<u32>, 2>,
+};
+
 struct DrawUniform {
     world_matrix: mat4x4<f32>,
     base_color: vec4<f32>,
     world_inverse_matrix: mat4x4<f32>,
+    gradient: GradientUniform,
 };
 
 struct VertexIn {
@@ -17,22 +28,54 @@ struct VertexIn {
 struct VertexOut {
     @builtin(position) position: vec4<f32>,
     @location(0) color: vec4<f32>,
+    @location(1) gradient_uv: vec2<f32>,
 };
 
 @group(0) @binding(0) var<uniform> view_uniform: ViewUniform;
 @group(1) @binding(0) var<uniform> draw_uniform: DrawUniform;
 
+fn gradient_uv(vertex: vec2<f32>) -> vec2<f32> {
+    let matrix = mat2x2(draw_uniform.gradient.uv_matrix.xy, draw_uniform.gradient.uv_matrix.zw);
+    return matrix * vertex + draw_uniform.gradient.uv_translation;
+}
+
+fn gradient_color(uv: vec2<f32>) -> vec4<f32> {
+    if draw_uniform.gradient.kind == 0u || draw_uniform.gradient.stop_count == 0u {
+        return vec4(1.0);
+    }
+
+    var t: f32;
+    if draw_uniform.gradient.kind == 1u {
+        let direction = draw_uniform.gradient.points.zw - draw_uniform.gradient.points.xy;
+        t = dot(uv - draw_uniform.gradient.points.xy, direction) / max(dot(direction, direction), 0.000001);
+    } else {
+        t = length(uv - draw_uniform.gradient.points.xy) / max(draw_uniform.gradient.points.z, 0.000001);
+    }
+    t = clamp(t, 0.0, 1.0);
+
+    var color = unpack4x8unorm(draw_uniform.gradient.colors[0][0]);
+    for (var i = 1u; i < draw_uniform.gradient.stop_count; i++) {
+        let start = draw_uniform.gradient.offsets[(i - 1u) / 4u][(i - 1u) % 4u];
+        let end = draw_uniform.gradient.offsets[i / 4u][i % 4u];
+        let progress = clamp((t - start) / max(end - start, 0.000001), 0.0, 1.0);
+        color = mix(color, unpack4x8unorm(draw_uniform.gradient.colors[i / 4u][i % 4u]), progress);
+    }
+
+    return color;
+}
+
 @vertex
 fn vertex(vertex: VertexIn) -> VertexOut {
     var out: VertexOut;
 
     out.position = view_uniform.view_proj_matrix * view_uniform.view_matrix * draw_uniform.world_matrix * vec4(vertex.vertex, 1.0);
     out.color = draw_uniform.base_color;
+    out.gradient_uv = gradient_uv(vertex.vertex.xy);
 
     return out;
 }
 
 @fragment
 fn fragment(in: VertexOut) -> @location(0) vec4<f32> {
-    return in.color;
+    return in.color * gradient_color(in.gradient_uv);
 }
@@ -0,0 +1,54 @@
+//! Embeds the images and fonts referenced by an asset so that it can be shipped on its own
+//!
+//! ```text
+//! cargo run --example pack -- <input> <resource directory> <output>
+//! ```
+//!
+//! Each referenced resource is looked up in the resource directory by its name, then by its name with the usual
+//! extension for its kind (`.png` for images, `.ttf` or `.otf` for fonts).
+
+use std::path::{Path, PathBuf};
+
+use envy::asset::{ResourceKind, ResourceReference};
+
+fn find_resource(dir: &Path, reference: &ResourceReference) -> Option<Vec<u8>> {
+    let extensions: &[&str] = match reference.kind {
+        ResourceKind::Image => &["png"],
+        ResourceKind::Font => &["ttf", "otf"],
+    };
+
+    std::iter::once(dir.join(&reference.name))
+        .chain(
+            extensions
+                .iter()
+                .map(|extension| dir.join(format!("{}.{extension}", reference.name))),
+        )
+        .find_map(|path| std::fs::read(path).ok())
+}
+
+fn main() {
+    let args = std::env::args_os().skip(1).map(PathBuf::from).collect::<Vec<_>>();
+    let [input, resource_dir, output] = args.as_slice() else {
+        eprintln!("usage: pack <input> <resource directory> <output>");
+        std::process::exit(1);
+    };
+
+    let bytes = std::fs::read(input).unwrap_or_else(|error| panic!("Failed to read {}: {error}", input.display()));
+
+    let packed = match envy::asset::pack(&bytes, |reference| find_resource(resource_dir, reference)) {
+        Ok(packed) => packed,
+        Err(error) => {
+            eprintln!("Failed to pack {}: {error}", input.display());
+            std::process::exit(1);
+        }
+    };
+
+    for reference in &packed.changed {
+        eprintln!(
+            "warning: {} '{}' has changed since the asset was written",
+            reference.kind, reference.name
+        );
+    }
+
+    std::fs::write(output, packed.bytes).unwrap_or_else(|error| panic!("Failed to write {}: {error}", output.display()));
+}
@@ -1,6 +1,12 @@
 use std::cmp::Ordering;
 
-use crate::{EnvyBackend, ImageNode, Node, NodeTransform};
+use crate::{EnvyBackend, Gradient, ImageNode, Node, NodeTransform, TextNode, template::NodeVisibility};
+
+mod baked;
+
+pub use baked::{BakedChannel, BakedNodeAnimation};
+#[cfg(feature = "asset")]
+pub(crate) use baked::BakedSamples;
 
 #[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
 #[derive(Copy, Clone, PartialEq, Eq)]
@@ -16,27 +22,213 @@ impl TransformStep {
     }
 }
 
+/// How the slopes of an [`AnimationChannel`] curve are chosen on either side of a keyframe
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum TangentMode {
+    /// Moves towards the neighbouring keyframes at a constant speed
+    #[default]
+    Linear,
+
+    /// Eases in and out of the keyframe
+    Flat,
+
+    /// Keeps the speed continuous through the keyframe, flattening out when the keyframe is a peak
+    Auto,
+
+    /// Uses `in_slope` and `out_slope`, which don't have to match
+    Broken,
+}
+
+/// Slopes of the curve entering and leaving a keyframe
+///
+/// Curves between keyframes are cubic Hermite splines. Slopes are relative to the average speed of the segment
+/// they belong to, so `1.0` is linear, `0.0` is flat and values above `1.0` overshoot.
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct KeyTangents {
+    pub mode: TangentMode,
+    pub in_slope: f32,
+    pub out_slope: f32,
+}
+
+impl KeyTangents {
+    pub const LINEAR: Self = Self::new(TangentMode::Linear);
+
+    pub const fn new(mode: TangentMode) -> Self {
+        Self {
+            mode,
+            in_slope: 1.0,
+            out_slope: 1.0,
+        }
+    }
+}
+
+impl Default for KeyTangents {
+    fn default() -> Self {
+        Self::LINEAR
+    }
+}
+
+/// Evaluates the Hermite curve from `0.0` to `1.0` with the given slopes at `progress`
+fn hermite(progress: f32, out_slope: f32, in_slope: f32) -> f32 {
+    let squared = progress * progress;
+    let cubed = squared * progress;
+    (cubed - 2.0 * squared + progress) * out_slope + (-2.0 * cubed + 3.0 * squared) + (cubed - squared) * in_slope
+}
+
+/// Derivative of [`hermite`] at `progress`
+fn hermite_slope(progress: f32, out_slope: f32, in_slope: f32) -> f32 {
+    let squared = progress * progress;
+    (3.0 * squared - 4.0 * progress + 1.0) * out_slope
+        + (-6.0 * squared + 6.0 * progress)
+        + (3.0 * squared - 2.0 * progress) * in_slope
+}
+
+/// How the keyframe values of an [`AnimationChannel`] are applied to a node
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum ChannelMode {
+    /// Keyframe values replace the node's value
+    #[default]
+    Absolute,
+
+    /// Keyframe values are added to the node's rest value
+    ///
+    /// Colors can't hold negative values, so color channels add how far their keyframes are from the channel's
+    /// start value instead, which lets them darken as well as brighten.
+    Offset,
+
+    /// Keyframe values multiply the node's rest value
+    Multiply,
+}
+
+/// A value that is smoothly animated between keyframes
+///
+/// The end frame of every transform is cached so that evaluating a channel is a binary search
+/// instead of a walk over every transform.
 #[cfg_attr(feature = "asset", derive(serde::Serialize, serde::Deserialize))]
-#[derive(Clone)]
+#[cfg_attr(
+    feature = "asset",
+    serde(
+        from = "AnimationChannelRepr<T>",
+        into = "AnimationChannelRepr<T>",
+        bound(serialize = "T: Clone + serde::Serialize", deserialize = "T: serde::Deserialize<'de>")
+    )
+)]
+#[derive(Clone, PartialEq)]
 pub struct AnimationChannel<T> {
+    pub mode: ChannelMode,
     pub start: T,
-    pub transforms: Vec<AnimationTransform<T>>,
+    pub start_tangents: KeyTangents,
+    transforms: Vec<AnimationTransform<T>>,
+    ends: Vec<usize>,
+}
+
+#[cfg(feature = "asset")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct AnimationChannelRepr<T> {
+    #[serde(default)]
+    mode: ChannelMode,
+    start: T,
+    #[serde(default)]
+    start_tangents: KeyTangents,
+    transforms: Vec<AnimationTransform<T>>,
+}
+
+#[cfg(feature = "asset")]
+impl<T> From<AnimationChannelRepr<T>> for AnimationChannel<T> {
+    fn from(value: AnimationChannelRepr<T>) -> Self {
+        let mut channel = Self::from_transforms(value.start, value.transforms).with_mode(value.mode);
+        channel.start_tangents = value.start_tangents;
+        channel
+    }
+}
+
+#[cfg(feature = "asset")]
+impl<T> From<AnimationChannel<T>> for AnimationChannelRepr<T> {
+    fn from(value: AnimationChannel<T>) -> Self {
+        Self {
+            mode: value.mode,
+            start: value.start,
+            start_tangents: value.start_tangents,
+            transforms: value.transforms,
+        }
+    }
 }
 
 pub trait Interpolatable: Copy {
     fn interpolate(start: Self, end: Self, progress: f32) -> Self;
+
+    /// Applies the change from `from` to `to`, scaled by `weight`, on top of `base`
+    ///
+    /// Used when blending additive animations.
+    fn add_difference(base: Self, from: Self, to: Self, weight: f32) -> Self;
+
+    /// Resolves a [`ChannelMode::Offset`] value against the node's rest value
+    ///
+    /// `origin` is the start value of the channel, which types that can't hold negative values measure their
+    /// offset from.
+    fn apply_offset(rest: Self, origin: Self, offset: Self) -> Self;
+
+    /// Resolves a [`ChannelMode::Multiply`] value against the node's rest value
+    fn apply_multiplier(rest: Self, multiplier: Self) -> Self;
+
+    /// The change from `from` to `to` as a vector, which [`TangentMode::Auto`] uses to find the speed through a keyframe
+    fn difference(from: Self, to: Self) -> glam::Vec4;
+
+    /// Converts a keyframe value written in `mode` into an absolute value, `origin` being the channel's start value
+    fn resolve(mode: ChannelMode, rest: Self, origin: Self, value: Self) -> Self {
+        match mode {
+            ChannelMode::Absolute => value,
+            ChannelMode::Offset => Self::apply_offset(rest, origin, value),
+            ChannelMode::Multiply => Self::apply_multiplier(rest, value),
+        }
+    }
 }
 
 impl Interpolatable for f32 {
     fn interpolate(start: Self, end: Self, progress: f32) -> Self {
         start + (end - start) * progress
     }
+
+    fn add_difference(base: Self, from: Self, to: Self, weight: f32) -> Self {
+        base + (to - from) * weight
+    }
+
+    fn apply_offset(rest: Self, _origin: Self, offset: Self) -> Self {
+        rest + offset
+    }
+
+    fn apply_multiplier(rest: Self, multiplier: Self) -> Self {
+        rest * multiplier
+    }
+
+    fn difference(from: Self, to: Self) -> glam::Vec4 {
+        glam::Vec4::new(to - from, 0.0, 0.0, 0.0)
+    }
 }
 
 impl Interpolatable for glam::Vec2 {
     fn interpolate(start: Self, end: Self, progress: f32) -> Self {
         start + (end - start) * progress
     }
+
+    fn add_difference(base: Self, from: Self, to: Self, weight: f32) -> Self {
+        base + (to - from) * weight
+    }
+
+    fn apply_offset(rest: Self, _origin: Self, offset: Self) -> Self {
+        rest + offset
+    }
+
+    fn apply_multiplier(rest: Self, multiplier: Self) -> Self {
+        rest * multiplier
+    }
+
+    fn difference(from: Self, to: Self) -> glam::Vec4 {
+        (to - from).extend(0.0).extend(0.0)
+    }
 }
 
 impl Interpolatable for [u8; 4] {
@@ -94,33 +286,146 @@ impl Interpolatable for [u8; 4] {
 
         [gamma_i(r), gamma_i(g), gamma_i(b), a].map(|c| (c * 255.0) as u8)
     }
+
+    fn add_difference(base: Self, from: Self, to: Self, weight: f32) -> Self {
+        std::array::from_fn(|idx| {
+            (base[idx] as f32 + (to[idx] as f32 - from[idx] as f32) * weight).clamp(0.0, 255.0) as u8
+        })
+    }
+
+    /// Adds the signed difference between `offset` and `origin`, clamping each component afterwards
+    fn apply_offset(rest: Self, origin: Self, offset: Self) -> Self {
+        Self::add_difference(rest, origin, offset, 1.0)
+    }
+
+    /// Multiplies as a tint, where `255` leaves the rest value unchanged
+    fn apply_multiplier(rest: Self, multiplier: Self) -> Self {
+        std::array::from_fn(|idx| ((rest[idx] as u16 * multiplier[idx] as u16) / 255) as u8)
+    }
+
+    fn difference(from: Self, to: Self) -> glam::Vec4 {
+        glam::Vec4::from_array(std::array::from_fn(|idx| to[idx] as f32 - from[idx] as f32))
+    }
+}
+
+impl Interpolatable for glam::Vec4 {
+    fn interpolate(start: Self, end: Self, progress: f32) -> Self {
+        start + (end - start) * progress
+    }
+
+    fn add_difference(base: Self, from: Self, to: Self, weight: f32) -> Self {
+        base + (to - from) * weight
+    }
+
+    fn apply_offset(rest: Self, _origin: Self, offset: Self) -> Self {
+        rest + offset
+    }
+
+    fn apply_multiplier(rest: Self, multiplier: Self) -> Self {
+        rest * multiplier
+    }
+
+    fn difference(from: Self, to: Self) -> glam::Vec4 {
+        to - from
+    }
+}
+
+impl Interpolatable for u32 {
+    /// Rounds down, so frame indices only advance once the next frame is reached
+    fn interpolate(start: Self, end: Self, progress: f32) -> Self {
+        f32::interpolate(start as f32, end as f32, progress).max(0.0) as u32
+    }
+
+    fn add_difference(base: Self, from: Self, to: Self, weight: f32) -> Self {
+        f32::add_difference(base as f32, from as f32, to as f32, weight).max(0.0) as u32
+    }
+
+    fn apply_offset(rest: Self, _origin: Self, offset: Self) -> Self {
+        rest.saturating_add(offset)
+    }
+
+    fn apply_multiplier(rest: Self, multiplier: Self) -> Self {
+        rest.saturating_mul(multiplier)
+    }
+
+    fn difference(from: Self, to: Self) -> glam::Vec4 {
+        f32::difference(from as f32, to as f32)
+    }
 }
 
 impl<T> AnimationChannel<T> {
+    pub fn new(start: T) -> Self {
+        Self::from_transforms(start, vec![])
+    }
+
+    pub fn from_transforms(start: T, transforms: Vec<AnimationTransform<T>>) -> Self {
+        let mut channel = Self {
+            mode: ChannelMode::Absolute,
+            start,
+            start_tangents: KeyTangents::LINEAR,
+            transforms,
+            ends: vec![],
+        };
+        channel.rebuild_ends();
+        channel
+    }
+
+    pub fn with_mode(mut self, mode: ChannelMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    fn rebuild_ends(&mut self) {
+        let mut total = 0usize;
+        self.ends = self
+            .transforms
+            .iter()
+            .map(|transform| {
+                total += transform.duration;
+                total
+            })
+            .collect();
+    }
+
+    pub fn transforms(&self) -> &[AnimationTransform<T>] {
+        &self.transforms
+    }
+
+    /// Total number of frames this channel animates for
+    pub fn duration(&self) -> usize {
+        self.ends.last().copied().unwrap_or_default()
+    }
+
+    /// Iterates the frame of every keyframe, starting with frame 0
+    pub fn keyframe_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        std::iter::once(0).chain(self.ends.iter().copied())
+    }
+
+    /// Index of the first transform ending at or after `keyframe`
+    fn transform_idx_for_keyframe(&self, keyframe: usize) -> usize {
+        self.ends.partition_point(|end| *end < keyframe)
+    }
+
+    fn transform_start(&self, idx: usize) -> usize {
+        if idx == 0 { 0 } else { self.ends[idx - 1] }
+    }
+
     pub fn remove_keyframe(&mut self, keyframe: usize) {
         if keyframe == 0 {
             // Don't
             return;
         }
 
-        let mut total = 0usize;
-        for idx in 0..self.transforms.len() {
-            let new_total = total + self.transforms[idx].duration;
-
-            match new_total.cmp(&keyframe) {
-                Ordering::Less => {}
-                Ordering::Equal => {
-                    let old_keyframe = self.transforms.remove(idx);
-                    if let Some(new_keyframe) = self.transforms.get_mut(idx) {
-                        new_keyframe.duration += old_keyframe.duration;
-                    }
-                    return;
-                }
-                Ordering::Greater => return,
-            }
+        let idx = self.transform_idx_for_keyframe(keyframe);
+        if self.ends.get(idx) != Some(&keyframe) {
+            return;
+        }
 
-            total = new_total;
+        let old_keyframe = self.transforms.remove(idx);
+        if let Some(new_keyframe) = self.transforms.get_mut(idx) {
+            new_keyframe.duration += old_keyframe.duration;
         }
+        self.rebuild_ends();
     }
 
     pub fn insert_keyframe(&mut self, keyframe: usize)
@@ -132,130 +437,238 @@ impl<T> AnimationChannel<T> {
             return;
         }
 
-        let mut total = 0usize;
-        for idx in 0..self.transforms.len() {
-            let new_total = total + self.transforms[idx].duration;
-
-            match new_total.cmp(&keyframe) {
-                Ordering::Less => {}
-                Ordering::Equal => return, // again don't do this
-                Ordering::Greater => {
-                    let prev = if idx == 0 {
-                        self.start
-                    } else {
-                        self.transforms[idx - 1].end
-                    };
-
-                    let progress = (keyframe - total) as f32 / (new_total - total) as f32;
-                    let new_value = T::interpolate(prev, self.transforms[idx].end, progress);
-
-                    self.transforms.insert(
-                        idx,
-                        AnimationTransform {
-                            end: new_value,
-                            duration: keyframe - total,
-                            first_step: TransformStep::Linear,
-                            additional_steps: vec![],
-                        },
-                    );
-                    self.transforms[idx + 1].duration = new_total - keyframe;
-                    return;
+        let idx = self.transform_idx_for_keyframe(keyframe);
+        match self.ends.get(idx) {
+            // again don't do this
+            Some(end) if *end == keyframe => return,
+            Some(end) => {
+                let end = *end;
+                let total = self.transform_start(idx);
+                let new_value = self.evaluate(keyframe as f32).0;
+                let split = self.split_tangents(idx, (keyframe - total) as f32 / (end - total) as f32);
+
+                self.transforms.insert(
+                    idx,
+                    AnimationTransform {
+                        end: new_value,
+                        duration: keyframe - total,
+                        first_step: TransformStep::Linear,
+                        additional_steps: vec![],
+                        end_tangents: KeyTangents::new(TangentMode::Auto),
+                    },
+                );
+                self.transforms[idx + 1].duration = end - keyframe;
+
+                for (key, tangents) in split.into_iter().flatten().enumerate() {
+                    if let Some(tangents) = tangents {
+                        *self.key_tangents_mut(idx + key) = tangents;
+                    }
                 }
             }
+            None => {
+                let duration = keyframe - self.duration();
+                let value = *self.last_value();
+                let tangents = self.key_tangents(self.transforms.len());
+                self.transforms.push(AnimationTransform {
+                    end: value,
+                    duration,
+                    first_step: TransformStep::Linear,
+                    additional_steps: vec![],
+                    end_tangents: tangents,
+                });
+            }
+        }
 
-            total = new_total;
+        self.rebuild_ends();
+    }
+
+    /// Tangents that split the curve of the `idx`th transform at `progress` into two curves with the same shape
+    ///
+    /// Returns the tangents of the keyframe before the split, of the new keyframe and of the keyframe after the
+    /// split, where `None` keeps the keyframe's tangents. Keyframes whose slopes change are switched to
+    /// [`TangentMode::Broken`], as are [`TangentMode::Auto`] keyframes since their slopes would follow the new
+    /// keyframe. Returns `None` if the curve is too steep around the split to be divided.
+    fn split_tangents(&self, idx: usize, progress: f32) -> Option<[Option<KeyTangents>; 3]>
+    where
+        T: Interpolatable,
+    {
+        let (before_in, out_slope) = self.key_slopes(idx);
+        let (in_slope, after_out) = self.key_slopes(idx + 1);
+        let value = hermite(progress, out_slope, in_slope);
+        if value.abs() < 1e-4 || (1.0 - value).abs() < 1e-4 {
+            return None;
         }
 
-        let duration = keyframe - total;
-        let value = *self.last_value();
-        self.transforms.push(AnimationTransform {
-            end: value,
-            duration,
-            first_step: TransformStep::Linear,
-            additional_steps: vec![],
-        });
+        // Both halves are the same cubic, rescaled so that they run from 0 to 1 over their own duration
+        let slope = hermite_slope(progress, out_slope, in_slope);
+        let before_out = progress * out_slope / value;
+        let (new_in, new_out) = (progress * slope / value, (1.0 - progress) * slope / (1.0 - value));
+        let after_in = (1.0 - progress) * in_slope / (1.0 - value);
+
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-4;
+        let broken = |in_slope, out_slope| KeyTangents {
+            mode: TangentMode::Broken,
+            in_slope,
+            out_slope,
+        };
+        let keeps = |key, new: f32, old: f32| self.key_tangents(key).mode != TangentMode::Auto && close(new, old);
+
+        Some([
+            (!keeps(idx, before_out, out_slope)).then(|| broken(before_in, before_out)),
+            Some(if close(new_in, 1.0) && close(new_out, 1.0) {
+                KeyTangents::LINEAR
+            } else {
+                broken(new_in, new_out)
+            }),
+            (!keeps(idx + 1, after_in, in_slope)).then(|| broken(after_in, after_out)),
+        ])
     }
 
     pub fn get_prev_keyframe_idx(&self, keyframe: usize) -> usize {
-        let mut total = 0usize;
-        for idx in 0..self.transforms.len() {
-            let new_total = total + self.transforms[idx].duration;
-
-            match new_total.cmp(&keyframe) {
-                Ordering::Less => {}
-                Ordering::Equal | Ordering::Greater => return total,
-            }
-            total = new_total;
+        let idx = self.transform_idx_for_keyframe(keyframe);
+        if idx == self.ends.len() {
+            self.duration()
+        } else {
+            self.transform_start(idx)
         }
-
-        total
     }
 
     pub fn get_next_keyframe_idx(&self, keyframe: usize) -> usize {
-        let mut total = 0usize;
-        for idx in 0..self.transforms.len() {
-            let new_total = total + self.transforms[idx].duration;
-
-            match total.cmp(&keyframe) {
-                Ordering::Less => {}
-                Ordering::Equal | Ordering::Greater => return new_total,
-            }
-            total = new_total;
-        }
+        let idx = if keyframe == 0 {
+            0
+        } else {
+            self.transform_idx_for_keyframe(keyframe) + 1
+        };
 
-        total
+        self.ends.get(idx).copied().unwrap_or(self.duration())
     }
 
     pub fn keyframe_mut(&mut self, keyframe: usize) -> Option<&mut T> {
         if keyframe == 0 {
-            Some(&mut self.start)
+            return Some(&mut self.start);
+        }
+
+        let idx = self.transform_idx_for_keyframe(keyframe);
+        if self.ends.get(idx) == Some(&keyframe) {
+            Some(&mut self.transforms[idx].end)
         } else {
-            let mut total = 0usize;
-            for transform in self.transforms.iter_mut() {
-                total += transform.duration;
+            None
+        }
+    }
 
-                match total.cmp(&keyframe) {
-                    Ordering::Less => continue,
-                    Ordering::Equal => return Some(&mut transform.end),
-                    Ordering::Greater => return None,
-                }
-            }
+    /// Tangents of the keyframe at `keyframe`, if there is one
+    pub fn tangents_mut(&mut self, keyframe: usize) -> Option<&mut KeyTangents> {
+        if keyframe == 0 {
+            return Some(&mut self.start_tangents);
+        }
 
+        let idx = self.transform_idx_for_keyframe(keyframe);
+        if self.ends.get(idx) == Some(&keyframe) {
+            Some(&mut self.transforms[idx].end_tangents)
+        } else {
             None
         }
     }
 
-    pub fn value_for_frame(&mut self, keyframe: usize) -> T
+    /// Value of the `key`th keyframe, where the start of the channel is the 0th
+    fn key_value(&self, key: usize) -> T
+    where
+        T: Copy,
+    {
+        if key == 0 { self.start } else { self.transforms[key - 1].end }
+    }
+
+    fn key_tangents(&self, key: usize) -> KeyTangents {
+        if key == 0 { self.start_tangents } else { self.transforms[key - 1].end_tangents }
+    }
+
+    fn key_tangents_mut(&mut self, key: usize) -> &mut KeyTangents {
+        if key == 0 { &mut self.start_tangents } else { &mut self.transforms[key - 1].end_tangents }
+    }
+
+    /// Slopes entering and leaving the `key`th keyframe for [`TangentMode::Auto`]
+    ///
+    /// The speed through the keyframe is the average speed of both segments, clamped so the curve can't
+    /// overshoot. The first and last keyframes, as well as peaks, are flat.
+    fn auto_slopes(&self, key: usize) -> (f32, f32)
+    where
+        T: Interpolatable,
+    {
+        if key == 0 || key >= self.transforms.len() {
+            return (0.0, 0.0);
+        }
+
+        let before = T::difference(self.key_value(key - 1), self.key_value(key));
+        let after = T::difference(self.key_value(key), self.key_value(key + 1));
+        if before.dot(after) <= 0.0 {
+            return (0.0, 0.0);
+        }
+
+        let before_duration = self.transforms[key - 1].duration as f32;
+        let after_duration = self.transforms[key].duration as f32;
+        let speed = (before.length() + after.length()) / (before_duration + after_duration);
+        (
+            (speed * before_duration / before.length()).min(3.0),
+            (speed * after_duration / after.length()).min(3.0),
+        )
+    }
+
+    /// Slope of the curve entering the `key`th keyframe and leaving it, relative to the segment on that side
+    pub fn key_slopes(&self, key: usize) -> (f32, f32)
     where
         T: Interpolatable,
     {
-        if keyframe == 0 {
+        let tangents = self.key_tangents(key);
+        match tangents.mode {
+            TangentMode::Linear => (1.0, 1.0),
+            TangentMode::Flat => (0.0, 0.0),
+            TangentMode::Auto => self.auto_slopes(key),
+            TangentMode::Broken => (tangents.in_slope, tangents.out_slope),
+        }
+    }
+
+    /// Evaluates the channel at `timer` frames, returning the value and whether the channel is still animating
+    ///
+    /// Timers before the first keyframe hold the start value instead of extrapolating the first segment.
+    pub fn evaluate(&self, timer: f32) -> (T, bool)
+    where
+        T: Interpolatable,
+    {
+        let timer = timer.max(0.0);
+        let idx = self.ends.partition_point(|end| *end as f32 <= timer);
+        let Some(transform) = self.transforms.get(idx) else {
+            return (*self.last_value(), false);
+        };
+
+        let prev = if idx == 0 {
             self.start
         } else {
-            let mut total = 0usize;
-
-            for idx in 0..self.transforms.len() {
-                let new_total = total + self.transforms[idx].duration;
-
-                match new_total.cmp(&keyframe) {
-                    Ordering::Less => {}
-                    Ordering::Equal => return self.transforms[idx].end,
-                    Ordering::Greater => {
-                        let prev = if idx == 0 {
-                            self.start
-                        } else {
-                            self.transforms[idx - 1].end
-                        };
-
-                        let progress = (keyframe - total) as f32 / (new_total - total) as f32;
-                        return T::interpolate(prev, self.transforms[idx].end, progress);
-                    }
-                }
+            self.transforms[idx - 1].end
+        };
+
+        let frame_start = self.transform_start(idx) as f32;
+        let mut progress = transform
+            .first_step
+            .transform((timer - frame_start) / transform.duration as f32);
+        transform.additional_steps.iter().for_each(|transform| {
+            progress = transform.transform(progress);
+        });
 
-                total = new_total;
-            }
+        let (_, out_slope) = self.key_slopes(idx);
+        let (in_slope, _) = self.key_slopes(idx + 1);
+        progress = hermite(progress, out_slope, in_slope);
+
+        (T::interpolate(prev, transform.end, progress), true)
+    }
 
-            *self.last_value()
+    pub fn value_for_frame(&self, keyframe: usize) -> T
+    where
+        T: Interpolatable,
+    {
+        if keyframe == 0 {
+            self.start
+        } else {
+            self.evaluate(keyframe as f32).0
         }
     }
 
@@ -268,429 +681,1632 @@ impl<T> AnimationChannel<T> {
 }
 
 #[cfg_attr(feature = "asset", derive(serde::Serialize, serde::Deserialize))]
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub struct AnimationTransform<T> {
     pub end: T,
     pub duration: usize,
     pub first_step: TransformStep,
     pub additional_steps: Vec<TransformStep>,
+    #[cfg_attr(feature = "asset", serde(default))]
+    pub end_tangents: KeyTangents,
 }
 
-#[cfg(feature = "asset")]
-const _: () = {
-    use glam::Vec2;
-
-    impl bincode::Encode for AnimationChannel<f32> {
-        fn encode<E: bincode::enc::Encoder>(
-            &self,
-            encoder: &mut E,
-        ) -> Result<(), bincode::error::EncodeError> {
-            self.start.encode(encoder)?;
-            self.transforms.encode(encoder)
-        }
-    }
+/// Animation channel whose keyframes hold their value until the next keyframe instead of interpolating
+///
+/// Used for values that can't be blended, such as visibility, texture names and text content.
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq)]
+pub struct DiscreteChannel<T> {
+    pub start: T,
+    pub keyframes: Vec<DiscreteKeyframe<T>>,
+}
 
-    impl<C> bincode::Decode<C> for AnimationChannel<f32> {
-        fn decode<D: bincode::de::Decoder<Context = C>>(
-            decoder: &mut D,
-        ) -> Result<Self, bincode::error::DecodeError> {
-            Ok(Self {
-                start: f32::decode(decoder)?,
-                transforms: <Vec<AnimationTransform<f32>>>::decode(decoder)?,
-            })
-        }
-    }
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq)]
+pub struct DiscreteKeyframe<T> {
+    pub value: T,
+    /// Number of frames after the previous keyframe that this value takes effect
+    pub duration: usize,
+}
 
-    impl<'de, C> bincode::BorrowDecode<'de, C> for AnimationChannel<f32> {
-        fn borrow_decode<D: bincode::de::BorrowDecoder<'de, Context = C>>(
-            decoder: &mut D,
-        ) -> Result<Self, bincode::error::DecodeError> {
-            bincode::Decode::decode(decoder)
+impl<T> DiscreteChannel<T> {
+    pub fn remove_keyframe(&mut self, keyframe: usize) {
+        if keyframe == 0 {
+            return;
         }
-    }
 
-    impl bincode::Encode for AnimationTransform<f32> {
-        fn encode<E: bincode::enc::Encoder>(
-            &self,
-            encoder: &mut E,
-        ) -> Result<(), bincode::error::EncodeError> {
-            self.end.encode(encoder)?;
-            self.duration.encode(encoder)?;
-            self.first_step.encode(encoder)?;
-            self.additional_steps.encode(encoder)
-        }
-    }
+        let mut total = 0usize;
+        for idx in 0..self.keyframes.len() {
+            let new_total = total + self.keyframes[idx].duration;
 
-    impl<C> bincode::Decode<C> for AnimationTransform<f32> {
-        fn decode<D: bincode::de::Decoder<Context = C>>(
-            decoder: &mut D,
-        ) -> Result<Self, bincode::error::DecodeError> {
-            Ok(Self {
-                end: f32::decode(decoder)?,
-                duration: usize::decode(decoder)?,
-                first_step: TransformStep::decode(decoder)?,
-                additional_steps: <Vec<TransformStep>>::decode(decoder)?,
-            })
+            match new_total.cmp(&keyframe) {
+                Ordering::Less => {}
+                Ordering::Equal => {
+                    let old_keyframe = self.keyframes.remove(idx);
+                    if let Some(new_keyframe) = self.keyframes.get_mut(idx) {
+                        new_keyframe.duration += old_keyframe.duration;
+                    }
+                    return;
+                }
+                Ordering::Greater => return,
+            }
+
+            total = new_total;
         }
     }
 
-    impl<'de, C> bincode::BorrowDecode<'de, C> for AnimationTransform<f32> {
-        fn borrow_decode<D: bincode::de::BorrowDecoder<'de, Context = C>>(
-            decoder: &mut D,
-        ) -> Result<Self, bincode::error::DecodeError> {
-            bincode::Decode::decode(decoder)
+    pub fn insert_keyframe(&mut self, keyframe: usize)
+    where
+        T: Clone,
+    {
+        if keyframe == 0 {
+            return;
+        }
+
+        let mut total = 0usize;
+        for idx in 0..self.keyframes.len() {
+            let new_total = total + self.keyframes[idx].duration;
+
+            match new_total.cmp(&keyframe) {
+                Ordering::Less => {}
+                Ordering::Equal => return,
+                Ordering::Greater => {
+                    let value = if idx == 0 {
+                        self.start.clone()
+                    } else {
+                        self.keyframes[idx - 1].value.clone()
+                    };
+
+                    self.keyframes.insert(
+                        idx,
+                        DiscreteKeyframe {
+                            value,
+                            duration: keyframe - total,
+                        },
+                    );
+                    self.keyframes[idx + 1].duration = new_total - keyframe;
+                    return;
+                }
+            }
+
+            total = new_total;
+        }
+
+        self.keyframes.push(DiscreteKeyframe {
+            value: self.last_value().clone(),
+            duration: keyframe - total,
+        });
+    }
+
+    /// Iterates the frame every value takes effect on, starting with `start` on frame 0
+    pub fn keyframe_starts(&self) -> impl Iterator<Item = (usize, &T)> {
+        std::iter::once((0, &self.start)).chain(self.keyframes.iter().scan(0usize, |total, keyframe| {
+            *total += keyframe.duration;
+            Some((*total, &keyframe.value))
+        }))
+    }
+
+    pub fn get_prev_keyframe_idx(&self, keyframe: usize) -> usize {
+        let mut total = 0usize;
+        for discrete in self.keyframes.iter() {
+            let new_total = total + discrete.duration;
+            if new_total >= keyframe {
+                return total;
+            }
+            total = new_total;
+        }
+
+        total
+    }
+
+    pub fn get_next_keyframe_idx(&self, keyframe: usize) -> usize {
+        let mut total = 0usize;
+        for discrete in self.keyframes.iter() {
+            let new_total = total + discrete.duration;
+            if total >= keyframe {
+                return new_total;
+            }
+            total = new_total;
+        }
+
+        total
+    }
+
+    pub fn keyframe_mut(&mut self, keyframe: usize) -> Option<&mut T> {
+        if keyframe == 0 {
+            return Some(&mut self.start);
+        }
+
+        let mut total = 0usize;
+        for discrete in self.keyframes.iter_mut() {
+            total += discrete.duration;
+
+            match total.cmp(&keyframe) {
+                Ordering::Less => continue,
+                Ordering::Equal => return Some(&mut discrete.value),
+                Ordering::Greater => return None,
+            }
+        }
+
+        None
+    }
+
+    /// Returns the value held at `timer`, along with whether there are keyframes left after it
+    pub fn value_at(&self, timer: f32) -> (&T, bool) {
+        let mut value = &self.start;
+        let mut total = 0.0;
+        for discrete in self.keyframes.iter() {
+            total += discrete.duration as f32;
+            if total > timer {
+                return (value, true);
+            }
+            value = &discrete.value;
+        }
+
+        (value, false)
+    }
+
+    pub fn value_for_frame(&self, keyframe: usize) -> &T {
+        self.value_at(keyframe as f32).0
+    }
+
+    pub fn last_value(&self) -> &T {
+        self.keyframes
+            .last()
+            .map(|discrete| &discrete.value)
+            .unwrap_or(&self.start)
+    }
+
+    /// Iterates over every value this channel can hold
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        std::iter::once(&self.start).chain(self.keyframes.iter().map(|discrete| &discrete.value))
+    }
+}
+
+/// Maps an animated value to the representation it is stored as in assets
+#[cfg(feature = "asset")]
+pub trait AnimationValue: Sized {
+    type Repr;
+
+    fn to_repr(&self) -> Self::Repr;
+    fn from_repr(repr: Self::Repr) -> Self;
+}
+
+#[cfg(feature = "asset")]
+const _: () = {
+    use bincode::{BorrowDecode, Decode, Encode};
+
+    macro_rules! identity_value {
+        ($($t:ty),*) => {
+            $(
+                impl AnimationValue for $t {
+                    type Repr = $t;
+
+                    fn to_repr(&self) -> Self::Repr {
+                        *self
+                    }
+
+                    fn from_repr(repr: Self::Repr) -> Self {
+                        repr
+                    }
+                }
+            )*
+        };
+    }
+
+    identity_value!(f32, u32, [u8; 4]);
+
+    impl AnimationValue for glam::Vec2 {
+        type Repr = [f32; 2];
+
+        fn to_repr(&self) -> Self::Repr {
+            (*self).into()
+        }
+
+        fn from_repr(repr: Self::Repr) -> Self {
+            repr.into()
+        }
+    }
+
+    impl AnimationValue for glam::Vec4 {
+        type Repr = [f32; 4];
+
+        fn to_repr(&self) -> Self::Repr {
+            (*self).into()
+        }
+
+        fn from_repr(repr: Self::Repr) -> Self {
+            repr.into()
         }
     }
 
-    impl bincode::Encode for AnimationChannel<[u8; 4]> {
-        fn encode<E: bincode::enc::Encoder>(
-            &self,
-            encoder: &mut E,
-        ) -> Result<(), bincode::error::EncodeError> {
-            self.start.encode(encoder)?;
+    impl<T: AnimationValue> Encode for AnimationChannel<T>
+    where
+        T::Repr: Encode,
+    {
+        fn encode<E: bincode::enc::Encoder>(&self, encoder: &mut E) -> Result<(), bincode::error::EncodeError> {
+            self.mode.encode(encoder)?;
+            self.start.to_repr().encode(encoder)?;
+            self.start_tangents.encode(encoder)?;
             self.transforms.encode(encoder)
         }
     }
 
-    impl<C> bincode::Decode<C> for AnimationChannel<[u8; 4]> {
-        fn decode<D: bincode::de::Decoder<Context = C>>(
-            decoder: &mut D,
-        ) -> Result<Self, bincode::error::DecodeError> {
-            Ok(Self {
-                start: <[u8; 4]>::decode(decoder)?,
-                transforms: <Vec<AnimationTransform<[u8; 4]>>>::decode(decoder)?,
-            })
+    impl<T: AnimationValue, C> Decode<C> for AnimationChannel<T>
+    where
+        T::Repr: Decode<C>,
+    {
+        fn decode<D: bincode::de::Decoder<Context = C>>(decoder: &mut D) -> Result<Self, bincode::error::DecodeError> {
+            let mode = ChannelMode::decode(decoder)?;
+            let start = T::from_repr(T::Repr::decode(decoder)?);
+            let start_tangents = KeyTangents::decode(decoder)?;
+            let transforms = <Vec<AnimationTransform<T>>>::decode(decoder)?;
+            let mut channel = Self::from_transforms(start, transforms).with_mode(mode);
+            channel.start_tangents = start_tangents;
+            Ok(channel)
         }
     }
 
-    impl<'de, C> bincode::BorrowDecode<'de, C> for AnimationChannel<[u8; 4]> {
+    impl<'de, T: AnimationValue, C> BorrowDecode<'de, C> for AnimationChannel<T>
+    where
+        T::Repr: Decode<C>,
+    {
         fn borrow_decode<D: bincode::de::BorrowDecoder<'de, Context = C>>(
             decoder: &mut D,
         ) -> Result<Self, bincode::error::DecodeError> {
-            bincode::Decode::decode(decoder)
+            Decode::decode(decoder)
         }
     }
 
-    impl bincode::Encode for AnimationTransform<[u8; 4]> {
-        fn encode<E: bincode::enc::Encoder>(
-            &self,
-            encoder: &mut E,
-        ) -> Result<(), bincode::error::EncodeError> {
-            self.end.encode(encoder)?;
+    impl<T: AnimationValue> Encode for AnimationTransform<T>
+    where
+        T::Repr: Encode,
+    {
+        fn encode<E: bincode::enc::Encoder>(&self, encoder: &mut E) -> Result<(), bincode::error::EncodeError> {
+            self.end.to_repr().encode(encoder)?;
             self.duration.encode(encoder)?;
             self.first_step.encode(encoder)?;
-            self.additional_steps.encode(encoder)
+            self.additional_steps.encode(encoder)?;
+            self.end_tangents.encode(encoder)
         }
     }
 
-    impl<C> bincode::Decode<C> for AnimationTransform<[u8; 4]> {
-        fn decode<D: bincode::de::Decoder<Context = C>>(
-            decoder: &mut D,
-        ) -> Result<Self, bincode::error::DecodeError> {
+    impl<T: AnimationValue, C> Decode<C> for AnimationTransform<T>
+    where
+        T::Repr: Decode<C>,
+    {
+        fn decode<D: bincode::de::Decoder<Context = C>>(decoder: &mut D) -> Result<Self, bincode::error::DecodeError> {
             Ok(Self {
-                end: <[u8; 4]>::decode(decoder)?,
+                end: T::from_repr(T::Repr::decode(decoder)?),
                 duration: usize::decode(decoder)?,
                 first_step: TransformStep::decode(decoder)?,
                 additional_steps: <Vec<TransformStep>>::decode(decoder)?,
+                end_tangents: KeyTangents::decode(decoder)?,
             })
         }
     }
 
-    impl<'de, C> bincode::BorrowDecode<'de, C> for AnimationTransform<[u8; 4]> {
+    impl<'de, T: AnimationValue, C> BorrowDecode<'de, C> for AnimationTransform<T>
+    where
+        T::Repr: Decode<C>,
+    {
         fn borrow_decode<D: bincode::de::BorrowDecoder<'de, Context = C>>(
             decoder: &mut D,
         ) -> Result<Self, bincode::error::DecodeError> {
-            bincode::Decode::decode(decoder)
+            Decode::decode(decoder)
         }
     }
 
-    impl bincode::Encode for AnimationChannel<Vec2> {
-        fn encode<E: bincode::enc::Encoder>(
-            &self,
-            encoder: &mut E,
-        ) -> Result<(), bincode::error::EncodeError> {
-            <[f32; 2]>::from(self.start).encode(encoder)?;
-            self.transforms.encode(encoder)
+    impl Encode for MotionPath {
+        fn encode<E: bincode::enc::Encoder>(&self, encoder: &mut E) -> Result<(), bincode::error::EncodeError> {
+            self.mode.encode(encoder)?;
+            self.points.iter().map(AnimationValue::to_repr).collect::<Vec<_>>().encode(encoder)?;
+            self.progress_channel.encode(encoder)?;
+            self.orient_to_path.encode(encoder)
         }
     }
 
-    impl<C> bincode::Decode<C> for AnimationChannel<Vec2> {
-        fn decode<D: bincode::de::Decoder<Context = C>>(
-            decoder: &mut D,
-        ) -> Result<Self, bincode::error::DecodeError> {
-            Ok(Self {
-                start: <[f32; 2]>::decode(decoder)?.into(),
-                transforms: <Vec<AnimationTransform<Vec2>>>::decode(decoder)?,
-            })
+    impl<C> Decode<C> for MotionPath {
+        fn decode<D: bincode::de::Decoder<Context = C>>(decoder: &mut D) -> Result<Self, bincode::error::DecodeError> {
+            let mode = ChannelMode::decode(decoder)?;
+            let points = <Vec<[f32; 2]>>::decode(decoder)?.into_iter().map(glam::Vec2::from_repr).collect();
+            let mut path = Self {
+                mode,
+                points,
+                progress_channel: AnimationChannel::decode(decoder)?,
+                orient_to_path: bool::decode(decoder)?,
+                lengths: vec![],
+            };
+            path.rebuild_lengths();
+            Ok(path)
         }
     }
 
-    impl<'de, C> bincode::BorrowDecode<'de, C> for AnimationChannel<Vec2> {
+    impl<'de, C> BorrowDecode<'de, C> for MotionPath {
         fn borrow_decode<D: bincode::de::BorrowDecoder<'de, Context = C>>(
             decoder: &mut D,
         ) -> Result<Self, bincode::error::DecodeError> {
-            bincode::Decode::decode(decoder)
+            Decode::decode(decoder)
         }
     }
+};
 
-    impl bincode::Encode for AnimationTransform<Vec2> {
-        fn encode<E: bincode::enc::Encoder>(
-            &self,
-            encoder: &mut E,
-        ) -> Result<(), bincode::error::EncodeError> {
-            <[f32; 2]>::from(self.end).encode(encoder)?;
-            self.duration.encode(encoder)?;
-            self.first_step.encode(encoder)?;
-            self.additional_steps.encode(encoder)
+/// Number of straight pieces each segment of a [`MotionPath`] is split into when measuring its length
+const PATH_SEGMENT_SAMPLES: usize = 16;
+
+/// A spline that a node's position follows instead of its position channel
+///
+/// The spline is a Catmull-Rom curve passing through every point. Progress is the distance travelled along the
+/// curve, where `0.0` is the first point and `1.0` is the last, so a linear progress channel moves the node at a
+/// constant speed.
+///
+/// The distance along the curve is measured once whenever the points change, so playing a path doesn't
+/// measure it again every frame.
+#[cfg_attr(feature = "asset", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "asset", serde(from = "MotionPathRepr", into = "MotionPathRepr"))]
+#[derive(Clone, PartialEq)]
+pub struct MotionPath {
+    /// How the points are applied to the node's position
+    pub mode: ChannelMode,
+    points: Vec<glam::Vec2>,
+    pub progress_channel: AnimationChannel<f32>,
+
+    /// Adds the direction the path is heading in to the node's angle
+    pub orient_to_path: bool,
+
+    /// Distance along the path at every sample, starting with `0.0`
+    lengths: Vec<f32>,
+}
+
+#[cfg(feature = "asset")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct MotionPathRepr {
+    mode: ChannelMode,
+    points: Vec<glam::Vec2>,
+    progress_channel: AnimationChannel<f32>,
+    orient_to_path: bool,
+}
+
+#[cfg(feature = "asset")]
+impl From<MotionPathRepr> for MotionPath {
+    fn from(value: MotionPathRepr) -> Self {
+        let mut path = Self {
+            mode: value.mode,
+            points: value.points,
+            progress_channel: value.progress_channel,
+            orient_to_path: value.orient_to_path,
+            lengths: vec![],
+        };
+        path.rebuild_lengths();
+        path
+    }
+}
+
+#[cfg(feature = "asset")]
+impl From<MotionPath> for MotionPathRepr {
+    fn from(value: MotionPath) -> Self {
+        Self {
+            mode: value.mode,
+            points: value.points,
+            progress_channel: value.progress_channel,
+            orient_to_path: value.orient_to_path,
         }
     }
+}
 
-    impl<'de, C> bincode::BorrowDecode<'de, C> for AnimationTransform<Vec2> {
-        fn borrow_decode<D: bincode::de::BorrowDecoder<'de, Context = C>>(
-            decoder: &mut D,
-        ) -> Result<Self, bincode::error::DecodeError> {
-            bincode::Decode::decode(decoder)
+impl MotionPath {
+    /// A straight path between `start` and `end` which is followed over `duration` frames
+    pub fn new(start: glam::Vec2, end: glam::Vec2, duration: usize) -> Self {
+        let mut progress_channel = AnimationChannel::new(0.0);
+        if duration > 0 {
+            progress_channel.insert_keyframe(duration);
+            *progress_channel.keyframe_mut(duration).unwrap() = 1.0;
         }
+
+        let mut path = Self {
+            mode: ChannelMode::Absolute,
+            points: vec![start, end],
+            progress_channel,
+            orient_to_path: false,
+            lengths: vec![],
+        };
+        path.rebuild_lengths();
+        path
     }
 
-    impl<C> bincode::Decode<C> for AnimationTransform<Vec2> {
-        fn decode<D: bincode::de::Decoder<Context = C>>(
-            decoder: &mut D,
-        ) -> Result<Self, bincode::error::DecodeError> {
-            Ok(Self {
-                end: <[f32; 2]>::decode(decoder)?.into(),
-                duration: usize::decode(decoder)?,
-                first_step: TransformStep::decode(decoder)?,
-                additional_steps: <Vec<TransformStep>>::decode(decoder)?,
-            })
+    pub fn points(&self) -> &[glam::Vec2] {
+        &self.points
+    }
+
+    pub fn set_points(&mut self, points: Vec<glam::Vec2>) {
+        self.points = points;
+        self.rebuild_lengths();
+    }
+
+    /// Moves the point at `idx`, returning `false` if there is no such point
+    pub fn set_point(&mut self, idx: usize, point: glam::Vec2) -> bool {
+        let Some(point_ref) = self.points.get_mut(idx) else {
+            return false;
+        };
+
+        *point_ref = point;
+        self.rebuild_lengths();
+        true
+    }
+
+    pub fn push_point(&mut self, point: glam::Vec2) {
+        self.points.push(point);
+        self.rebuild_lengths();
+    }
+
+    pub fn pop_point(&mut self) -> Option<glam::Vec2> {
+        let point = self.points.pop();
+        self.rebuild_lengths();
+        point
+    }
+
+    fn segment_count(&self) -> usize {
+        self.points.len().saturating_sub(1)
+    }
+
+    /// Position and derivative of the segment starting at point `idx`
+    fn evaluate_segment(&self, idx: usize, t: f32) -> (glam::Vec2, glam::Vec2) {
+        let point = |idx: isize| self.points[idx.clamp(0, self.points.len() as isize - 1) as usize];
+        let idx = idx as isize;
+        let (p0, p1, p2, p3) = (point(idx - 1), point(idx), point(idx + 1), point(idx + 2));
+
+        let a = 2.0 * p1;
+        let b = p2 - p0;
+        let c = 2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3;
+        let d = -p0 + 3.0 * p1 - 3.0 * p2 + p3;
+
+        let position = 0.5 * (a + b * t + c * t * t + d * t * t * t);
+        let derivative = 0.5 * (b + 2.0 * c * t + 3.0 * d * t * t);
+        (position, derivative)
+    }
+
+    fn rebuild_lengths(&mut self) {
+        let mut lengths = vec![0.0];
+        let mut previous = self.points.first().copied().unwrap_or_default();
+        for segment in 0..self.segment_count() {
+            for sample in 1..=PATH_SEGMENT_SAMPLES {
+                let (position, _) = self.evaluate_segment(segment, sample as f32 / PATH_SEGMENT_SAMPLES as f32);
+                lengths.push(lengths[lengths.len() - 1] + position.distance(previous));
+                previous = position;
+            }
         }
+
+        self.lengths = lengths;
     }
-};
+
+    /// Position on the path at `progress`, along with the direction the path is heading in
+    pub fn point_at(&self, progress: f32) -> Option<(glam::Vec2, glam::Vec2)> {
+        let lengths = &self.lengths;
+        let first = *self.points.first()?;
+        if self.points.len() == 1 {
+            return Some((first, glam::Vec2::X));
+        }
+
+        let progress = progress.clamp(0.0, 1.0);
+        let total = lengths.last().copied().unwrap_or_default();
+
+        // Paths where every point is in the same place are parameterized evenly instead
+        let sample = if total <= f32::EPSILON {
+            progress * (lengths.len() - 1) as f32
+        } else {
+            let distance = progress * total;
+            let idx = lengths.partition_point(|length| *length < distance).clamp(1, lengths.len() - 1);
+            let (before, after) = (lengths[idx - 1], lengths[idx]);
+            let fraction = if after > before { (distance - before) / (after - before) } else { 0.0 };
+            (idx - 1) as f32 + fraction
+        };
+
+        let t = sample / PATH_SEGMENT_SAMPLES as f32;
+        let segment = (t.floor() as usize).min(self.segment_count() - 1);
+        let (position, derivative) = self.evaluate_segment(segment, t - segment as f32);
+        Some((position, derivative.try_normalize().unwrap_or(glam::Vec2::X)))
+    }
+
+    /// Samples the path at `timer` frames, returning the position and the angle of the path in degrees
+    fn sample(&self, timer: f32, is_done: &mut bool) -> Option<(Sample<glam::Vec2>, Sample<f32>)> {
+        let (progress, is_animating) = self.progress_channel.evaluate(timer);
+        *is_done &= !is_animating;
+
+        let (position, direction) = self.point_at(progress)?;
+        let (start, start_direction) = self.point_at(self.progress_channel.start)?;
+        let angle = |direction: glam::Vec2| direction.y.atan2(direction.x).to_degrees();
+
+        Some((
+            Sample {
+                value: position,
+                start,
+                mode: self.mode,
+            },
+            Sample {
+                value: angle(direction),
+                start: angle(start_direction),
+                mode: ChannelMode::Offset,
+            },
+        ))
+    }
+}
 
 #[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub struct NodeAnimation {
     pub node_path: String,
     pub angle_channel: Option<AnimationChannel<f32>>,
     pub position_channel: Option<AnimationChannel<glam::Vec2>>,
+    /// Replaces `position_channel` when set
+    pub motion_path: Option<MotionPath>,
     pub size_channel: Option<AnimationChannel<glam::Vec2>>,
     pub scale_channel: Option<AnimationChannel<glam::Vec2>>,
     pub color_channel: Option<AnimationChannel<[u8; 4]>>,
+    pub visibility_channel: Option<DiscreteChannel<NodeVisibility>>,
 
     // Texture node specific animations
     pub uv_offset_channel: Option<AnimationChannel<glam::Vec2>>,
     pub uv_scale_channel: Option<AnimationChannel<glam::Vec2>>,
+    pub fill_amount_channel: Option<AnimationChannel<f32>>,
+    /// Texture name to display, these are requested up front when the tree is set up
+    pub texture_channel: Option<DiscreteChannel<String>>,
+
+    // Text node specific animations
+    pub font_size_channel: Option<AnimationChannel<f32>>,
+    pub line_height_channel: Option<AnimationChannel<f32>>,
+    pub outline_thickness_channel: Option<AnimationChannel<f32>>,
+    pub outline_color_channel: Option<AnimationChannel<[u8; 4]>>,
+    /// Number of characters revealed, fractional values are rounded down
+    pub visible_characters_channel: Option<AnimationChannel<f32>>,
+    pub text_channel: Option<DiscreteChannel<String>>,
+
+    // Sublayout node specific animations
+    /// Animation of the sublayout to play, which is started on the frame each keyframe begins
+    ///
+    /// Empty names don't start anything.
+    pub sublayout_animation_channel: Option<DiscreteChannel<String>>,
+
+    // Gradient specific animations, indexed by the stop they animate
+    pub gradient_stop_color_channels: Vec<Option<AnimationChannel<[u8; 4]>>>,
+
+    /// Per-frame samples of the continuous channels, which are played instead of the channels when present
+    pub baked: Option<BakedNodeAnimation>,
 }
 
-impl NodeAnimation {
-    pub fn animate<B: EnvyBackend>(&self, timer: f32, node: &mut NodeTransform, color: &mut [u8; 4], node_impl: &mut dyn Node<B>) -> bool {
-        let mut is_done = true;
+/// How an animation is combined with the layers beneath it
+#[cfg_attr(feature = "asset", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum AnimationBlendMode {
+    /// Replaces the value from lower layers, blended by the animation's weight
+    #[default]
+    Override,
+
+    /// Adds the animation's change from its first keyframe on top of lower layers, scaled by its weight
+    Additive,
+}
 
-        if let Some(angle) = self.angle_channel.as_ref() {
-            let mut transform_start = angle.start;
-            let mut frame_start = 0.0;
-            let transform = angle.transforms.iter().find(|transform| {
-                if transform.duration as f32 + frame_start <= timer {
-                    frame_start += transform.duration as f32;
-                    transform_start = transform.end;
-                    false
-                } else {
-                    true
-                }
-            });
-
-            if let Some(transform) = transform {
-                let mut progress = transform
-                    .first_step
-                    .transform((timer - frame_start) / transform.duration as f32);
-                transform.additional_steps.iter().for_each(|transform| {
-                    progress = transform.transform(progress);
-                });
+/// A channel value sampled at some point in time, along with the channel's first keyframe for additive blending
+#[derive(Clone)]
+struct Sample<T> {
+    value: T,
+    start: T,
+    mode: ChannelMode,
+}
 
-                node.angle = transform_start + progress * (transform.end - transform_start);
-                is_done = false;
-            } else {
-                node.angle = *angle.last_value();
+impl<T: Interpolatable> Sample<T> {
+    fn resolve(&mut self, rest: T) {
+        self.value = T::resolve(self.mode, rest, self.start, self.value);
+        self.start = T::resolve(self.mode, rest, self.start, self.start);
+        self.mode = ChannelMode::Absolute;
+    }
+}
+
+/// Every value written by a [`NodeAnimation`] at a single point in time, before it is blended with other animations
+#[derive(Default, Clone)]
+pub struct NodePose {
+    angle: Option<Sample<f32>>,
+    position: Option<Sample<glam::Vec2>>,
+    size: Option<Sample<glam::Vec2>>,
+    scale: Option<Sample<glam::Vec2>>,
+    color: Option<Sample<[u8; 4]>>,
+    visibility: Option<NodeVisibility>,
+    gradient_stop_colors: Vec<Option<Sample<[u8; 4]>>>,
+
+    uv_offset: Option<Sample<glam::Vec2>>,
+    uv_scale: Option<Sample<glam::Vec2>>,
+    fill_amount: Option<Sample<f32>>,
+    texture: Option<String>,
+
+    font_size: Option<Sample<f32>>,
+    line_height: Option<Sample<f32>>,
+    outline_thickness: Option<Sample<f32>>,
+    outline_color: Option<Sample<[u8; 4]>>,
+    visible_characters: Option<Sample<f32>>,
+    text: Option<String>,
+}
+
+impl NodePose {
+    /// Whether any gradient stop is written by this pose
+    pub fn has_gradient_stops(&self) -> bool {
+        self.gradient_stop_colors.iter().any(Option::is_some)
+    }
+
+    /// Converts the values of relative channels into absolute values using the node's rest values
+    pub fn resolve(&mut self, rest: &RestPose) {
+        fn resolve<T: Interpolatable>(sample: &mut Option<Sample<T>>, rest: T) {
+            if let Some(sample) = sample.as_mut() {
+                sample.resolve(rest);
             }
         }
 
-        if let Some(position) = self.position_channel.as_ref() {
-            let mut transform_start = position.start;
-            let mut frame_start = 0.0;
-            let transform = position.transforms.iter().find(|transform| {
-                if transform.duration as f32 + frame_start <= timer {
-                    frame_start += transform.duration as f32;
-                    transform_start = transform.end;
-                    false
-                } else {
-                    true
-                }
-            });
-
-            if let Some(transform) = transform {
-                let mut progress = transform
-                    .first_step
-                    .transform((timer - frame_start) / transform.duration as f32);
-                transform.additional_steps.iter().for_each(|transform| {
-                    progress = transform.transform(progress);
-                });
+        resolve(&mut self.angle, rest.transform.angle);
+        resolve(&mut self.position, rest.transform.position);
+        resolve(&mut self.size, rest.transform.size);
+        resolve(&mut self.scale, rest.transform.scale);
+        resolve(&mut self.color, rest.color);
+        for (stop_idx, sample) in self.gradient_stop_colors.iter_mut().enumerate() {
+            resolve(sample, rest.gradient_stop_colors.get(stop_idx).copied().unwrap_or([255; 4]));
+        }
 
-                node.position = transform_start + progress * (transform.end - transform_start);
-                is_done = false;
-            } else {
-                node.position = *position.last_value();
-            }
+        resolve(&mut self.uv_offset, rest.uv_offset);
+        resolve(&mut self.uv_scale, rest.uv_scale);
+        resolve(&mut self.fill_amount, rest.fill_amount);
+
+        resolve(&mut self.font_size, rest.font_size);
+        resolve(&mut self.line_height, rest.line_height);
+        resolve(&mut self.outline_thickness, rest.outline_thickness);
+        resolve(&mut self.outline_color, rest.outline_color);
+        resolve(&mut self.visible_characters, rest.visible_characters);
+    }
+}
+
+/// The values of a node before it is animated, which relative channels are applied to
+#[derive(Clone)]
+pub struct RestPose {
+    transform: NodeTransform,
+    color: [u8; 4],
+    visibility: NodeVisibility,
+    gradient_stop_colors: Vec<[u8; 4]>,
+
+    uv_offset: glam::Vec2,
+    uv_scale: glam::Vec2,
+    fill_amount: f32,
+    texture: Option<String>,
+
+    font_size: f32,
+    line_height: f32,
+    outline_thickness: f32,
+    outline_color: [u8; 4],
+    visible_characters: f32,
+    text: Option<String>,
+}
+
+impl RestPose {
+    pub fn capture<B: EnvyBackend>(
+        transform: &NodeTransform,
+        color: [u8; 4],
+        visibility: NodeVisibility,
+        gradient: Option<&Gradient>,
+        node_impl: &dyn Node<B>,
+    ) -> Self {
+        let mut rest = Self {
+            transform: *transform,
+            color,
+            visibility,
+            gradient_stop_colors: gradient
+                .map(|gradient| gradient.stops.iter().map(|stop| stop.color).collect())
+                .unwrap_or_default(),
+            uv_offset: glam::Vec2::ZERO,
+            uv_scale: glam::Vec2::ONE,
+            fill_amount: 1.0,
+            texture: None,
+            font_size: 0.0,
+            line_height: 0.0,
+            outline_thickness: 0.0,
+            outline_color: [255; 4],
+            visible_characters: 0.0,
+            text: None,
+        };
+
+        if let Some(image) = node_impl.as_any().downcast_ref::<ImageNode<B>>() {
+            rest.uv_offset = image.uv_offset();
+            rest.uv_scale = image.uv_scale();
+            rest.fill_amount = image.fill_amount();
+            rest.texture = Some(image.resource_name().to_string());
         }
 
-        if let Some(size) = self.size_channel.as_ref() {
-            let mut transform_start = size.start;
-            let mut frame_start = 0.0;
-            let transform = size.transforms.iter().find(|transform| {
-                if transform.duration as f32 + frame_start <= timer {
-                    frame_start += transform.duration as f32;
-                    transform_start = transform.end;
-                    false
-                } else {
-                    true
+        if let Some(text) = node_impl.as_any().downcast_ref::<TextNode<B>>() {
+            rest.font_size = text.font_size();
+            rest.line_height = text.line_height();
+            rest.outline_thickness = text.outline_thickness();
+            rest.outline_color = text.outline_color();
+            rest.visible_characters = text.visible_characters().unwrap_or_else(|| text.text().chars().count()) as f32;
+            rest.text = Some(text.text().to_string());
+        }
+
+        rest
+    }
+
+    pub(crate) fn transform(&self) -> &NodeTransform {
+        &self.transform
+    }
+
+    pub(crate) fn color(&self) -> [u8; 4] {
+        self.color
+    }
+
+    pub(crate) fn visibility(&self) -> NodeVisibility {
+        self.visibility
+    }
+}
+
+struct PoseContribution<'a> {
+    pose: &'a NodePose,
+    weight: f32,
+    mode: AnimationBlendMode,
+}
+
+/// Combines the [`NodePose`]s of every animation playing on a node
+///
+/// Layers are applied from lowest to highest. Within a layer, override animations are averaged by weight and
+/// then blended over the lower layers by their combined weight (capped at `1.0`), while additive animations
+/// are added on top. Values that can't be interpolated, such as visibility and text, are taken from the
+/// heaviest override animation of a layer once that layer's weight reaches `0.5`. Blending always starts from
+/// the node's [`RestPose`], so the values written on one frame never feed into the next.
+#[derive(Default)]
+pub struct PoseBlender<'a> {
+    layers: Vec<(u32, Vec<PoseContribution<'a>>)>,
+}
+
+impl<'a> PoseBlender<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, layer: u32, pose: &'a NodePose, weight: f32, mode: AnimationBlendMode) {
+        let contribution = PoseContribution { pose, weight: weight.max(0.0), mode };
+        match self.layers.binary_search_by_key(&layer, |(layer, _)| *layer) {
+            Ok(idx) => self.layers[idx].1.push(contribution),
+            Err(idx) => self.layers.insert(idx, (layer, vec![contribution])),
+        }
+    }
+
+    /// Whether any contributing pose writes to a gradient stop
+    pub fn has_gradient_stops(&self) -> bool {
+        self.contributions().any(|contribution| contribution.pose.has_gradient_stops())
+    }
+
+    fn contributions(&self) -> impl Iterator<Item = &PoseContribution<'a>> {
+        self.layers.iter().flat_map(|(_, layer)| layer.iter())
+    }
+
+    fn blend<T: Interpolatable>(&self, rest: T, get: impl Fn(&NodePose) -> Option<&Sample<T>>) -> Option<T> {
+        let mut value = rest;
+        let mut touched = false;
+
+        for (_, layer) in self.layers.iter() {
+            let mut average: Option<T> = None;
+            let mut total_weight = 0.0;
+
+            for contribution in layer.iter().filter(|contribution| contribution.mode == AnimationBlendMode::Override) {
+                let Some(sample) = get(contribution.pose) else {
+                    continue;
+                };
+
+                touched = true;
+                if contribution.weight <= 0.0 {
+                    continue;
                 }
-            });
-
-            if let Some(transform) = transform {
-                let mut progress = transform
-                    .first_step
-                    .transform((timer - frame_start) / transform.duration as f32);
-                transform.additional_steps.iter().for_each(|transform| {
-                    progress = transform.transform(progress);
+
+                total_weight += contribution.weight;
+                average = Some(match average {
+                    Some(average) => T::interpolate(average, sample.value, contribution.weight / total_weight),
+                    None => sample.value,
                 });
+            }
 
-                node.size = transform_start + progress * (transform.end - transform_start);
-                is_done = false;
-            } else {
-                node.size = *size.last_value();
+            if let Some(average) = average {
+                // Color interpolation isn't exact at the end points, so full weight takes the value as it is
+                value = if total_weight >= 1.0 {
+                    average
+                } else {
+                    T::interpolate(value, average, total_weight)
+                };
+            }
+
+            for contribution in layer.iter().filter(|contribution| contribution.mode == AnimationBlendMode::Additive) {
+                let Some(sample) = get(contribution.pose) else {
+                    continue;
+                };
+
+                touched = true;
+                value = T::add_difference(value, sample.start, sample.value, contribution.weight);
             }
         }
 
-        if let Some(scale) = self.scale_channel.as_ref() {
-            let mut transform_start = scale.start;
-            let mut frame_start = 0.0;
-            let transform = scale.transforms.iter().find(|transform| {
-                if transform.duration as f32 + frame_start <= timer {
-                    frame_start += transform.duration as f32;
-                    transform_start = transform.end;
-                    false
-                } else {
-                    true
+        touched.then_some(value)
+    }
+
+    fn blend_discrete<T: Clone>(&self, rest: Option<&T>, get: impl Fn(&NodePose) -> Option<&T>) -> Option<T> {
+        let mut value = rest.cloned();
+        let mut touched = false;
+
+        for (_, layer) in self.layers.iter() {
+            let mut heaviest: Option<(&T, f32)> = None;
+            let mut total_weight = 0.0;
+
+            for contribution in layer.iter() {
+                let Some(sample) = get(contribution.pose) else {
+                    continue;
+                };
+
+                touched = true;
+                total_weight += contribution.weight;
+                if heaviest.is_none_or(|(_, weight)| contribution.weight > weight) {
+                    heaviest = Some((sample, contribution.weight));
                 }
-            });
-
-            if let Some(transform) = transform {
-                let mut progress = transform
-                    .first_step
-                    .transform((timer - frame_start) / transform.duration as f32);
-                transform.additional_steps.iter().for_each(|transform| {
-                    progress = transform.transform(progress);
-                });
+            }
 
-                node.scale = transform_start + progress * (transform.end - transform_start);
-                is_done = false;
-            } else {
-                node.scale = *scale.last_value();
+            if let Some((sample, _)) = heaviest {
+                // Without a rest value there is nothing to fall back to below half weight
+                if (value.is_none() && total_weight > 0.0) || total_weight >= 0.5 {
+                    value = Some(sample.clone());
+                }
             }
         }
 
-        if let Some(channel) = self.color_channel.as_ref() {
-            let mut transform_start = channel.start;
-            let mut frame_start = 0.0;
-            let transform = channel.transforms.iter().find(|transform| {
-                if transform.duration as f32 + frame_start <= timer {
-                    frame_start += transform.duration as f32;
-                    transform_start = transform.end;
-                    false
-                } else {
-                    true
-                }
-            });
-
-            if let Some(transform) = transform {
-                let mut progress = transform
-                    .first_step
-                    .transform((timer - frame_start) / transform.duration as f32);
-                transform.additional_steps.iter().for_each(|transform| {
-                    progress = transform.transform(progress);
-                });
+        value.filter(|_| touched)
+    }
 
-                *color = <[u8; 4]>::interpolate(transform_start, transform.end, progress);
-                is_done = false;
-            } else {
-                *color = *channel.last_value();
+    /// Writes the blended values into the node, leaving values no animation touches as they are
+    ///
+    /// Every value an animation touches is blended starting from its value in `rest`, not from the node's
+    /// current value.
+    #[allow(clippy::too_many_arguments)]
+    pub fn apply<B: EnvyBackend>(
+        &self,
+        rest: &RestPose,
+        node: &mut NodeTransform,
+        color: &mut [u8; 4],
+        visibility: &mut NodeVisibility,
+        gradient: Option<&mut Gradient>,
+        node_impl: &mut dyn Node<B>,
+    ) {
+        if let Some(angle) = self.blend(rest.transform.angle, |pose| pose.angle.as_ref()) {
+            node.angle = angle;
+        }
+
+        if let Some(position) = self.blend(rest.transform.position, |pose| pose.position.as_ref()) {
+            node.position = position;
+        }
+
+        if let Some(size) = self.blend(rest.transform.size, |pose| pose.size.as_ref()) {
+            node.size = size;
+        }
+
+        if let Some(scale) = self.blend(rest.transform.scale, |pose| pose.scale.as_ref()) {
+            node.scale = scale;
+        }
+
+        if let Some(value) = self.blend(rest.color, |pose| pose.color.as_ref()) {
+            *color = value;
+        }
+
+        if let Some(value) = self.blend_discrete(Some(&rest.visibility), |pose| pose.visibility.as_ref()) {
+            *visibility = value;
+        }
+
+        if let Some(gradient) = gradient {
+            for (stop_idx, stop) in gradient.stops.iter_mut().enumerate() {
+                let rest_color = rest.gradient_stop_colors.get(stop_idx).copied().unwrap_or(stop.color);
+                let stop_color = self.blend(rest_color, |pose| {
+                    pose.gradient_stop_colors.get(stop_idx).and_then(Option::as_ref)
+                });
+                if let Some(value) = stop_color {
+                    stop.color = value;
+                }
             }
         }
 
         if let Some(image) = node_impl.as_any_mut().downcast_mut::<ImageNode<B>>() {
-            if let Some(channel) = self.uv_offset_channel.as_ref() {
-                let mut transform_start = channel.start;
-                let mut frame_start = 0.0;
-                let transform = channel.transforms.iter().find(|transform| {
-                    if transform.duration as f32 + frame_start <= timer {
-                        frame_start += transform.duration as f32;
-                        transform_start = transform.end;
-                        false
-                    } else {
-                        true
-                    }
-                });
+            if let Some(offset) = self.blend(rest.uv_offset, |pose| pose.uv_offset.as_ref()) {
+                image.set_uv_offset(offset);
+            }
 
-                if let Some(transform) = transform {
-                    let mut progress = transform
-                        .first_step
-                        .transform((timer - frame_start) / transform.duration as f32);
-                    transform.additional_steps.iter().for_each(|transform| {
-                        progress = transform.transform(progress);
-                    });
+            if let Some(scale) = self.blend(rest.uv_scale, |pose| pose.uv_scale.as_ref()) {
+                image.set_uv_scale(scale);
+            }
 
-                    image.set_uv_offset(glam::Vec2::interpolate(transform_start, transform.end, progress));
-                    is_done = false;
-                } else {
-                    image.set_uv_offset(*channel.last_value());
-                }
+            if let Some(amount) = self.blend(rest.fill_amount, |pose| pose.fill_amount.as_ref()) {
+                image.set_fill_amount(amount);
             }
 
-            if let Some(channel) = self.uv_scale_channel.as_ref() {
-                let mut transform_start = channel.start;
-                let mut frame_start = 0.0;
-                let transform = channel.transforms.iter().find(|transform| {
-                    if transform.duration as f32 + frame_start <= timer {
-                        frame_start += transform.duration as f32;
-                        transform_start = transform.end;
-                        false
-                    } else {
-                        true
-                    }
-                });
+            if let Some(name) = self.blend_discrete(rest.texture.as_ref(), |pose| pose.texture.as_ref()) {
+                image.set_resource_name(name);
+            }
+        }
 
-                if let Some(transform) = transform {
-                    let mut progress = transform
-                        .first_step
-                        .transform((timer - frame_start) / transform.duration as f32);
-                    transform.additional_steps.iter().for_each(|transform| {
-                        progress = transform.transform(progress);
-                    });
+        if let Some(text) = node_impl.as_any_mut().downcast_mut::<TextNode<B>>() {
+            if let Some(size) = self.blend(rest.font_size, |pose| pose.font_size.as_ref()) {
+                text.set_font_size(size);
+            }
 
-                    image.set_uv_scale(glam::Vec2::interpolate(transform_start, transform.end, progress));
-                    is_done = false;
-                } else {
-                    image.set_uv_scale(*channel.last_value());
+            if let Some(height) = self.blend(rest.line_height, |pose| pose.line_height.as_ref()) {
+                text.set_line_height(height);
+            }
+
+            if let Some(thickness) = self.blend(rest.outline_thickness, |pose| pose.outline_thickness.as_ref()) {
+                text.set_outline_thickness(thickness);
+            }
+
+            if let Some(color) = self.blend(rest.outline_color, |pose| pose.outline_color.as_ref()) {
+                text.set_outline_color(color);
+            }
+
+            if let Some(value) = self.blend_discrete(rest.text.as_ref(), |pose| pose.text.as_ref()) {
+                text.set_text(value);
+            }
+
+            if let Some(count) = self.blend(rest.visible_characters, |pose| pose.visible_characters.as_ref()) {
+                text.set_visible_characters(count.max(0.0) as usize);
+            }
+        }
+    }
+}
+
+fn sample_channel<T: Interpolatable>(
+    channel: Option<&AnimationChannel<T>>,
+    timer: f32,
+    is_done: &mut bool,
+) -> Option<Sample<T>> {
+    channel.map(|channel| {
+        let (value, is_animating) = channel.evaluate(timer);
+        *is_done &= !is_animating;
+        Sample {
+            value,
+            start: channel.start,
+            mode: channel.mode,
+        }
+    })
+}
+
+fn sample_discrete_channel<T: Clone>(channel: Option<&DiscreteChannel<T>>, timer: f32, is_done: &mut bool) -> Option<T> {
+    channel.map(|channel| {
+        let (value, has_next) = channel.value_at(timer);
+        *is_done &= !has_next;
+        value.clone()
+    })
+}
+
+impl NodeAnimation {
+    /// Samples every channel at `timer` frames, returning the pose and whether every channel has finished
+    ///
+    /// Baked animations are played from their per-frame samples instead of evaluating the curves.
+    pub fn sample(&self, timer: f32) -> (NodePose, bool) {
+        match self.baked.as_ref() {
+            Some(baked) => baked.sample(self, timer),
+            None => self.evaluate(timer),
+        }
+    }
+
+    /// Samples the authored channels at `timer` frames, ignoring any baked data
+    fn evaluate(&self, timer: f32) -> (NodePose, bool) {
+        let mut is_done = true;
+
+        let mut pose = NodePose {
+            angle: sample_channel(self.angle_channel.as_ref(), timer, &mut is_done),
+            position: sample_channel(self.position_channel.as_ref(), timer, &mut is_done),
+            size: sample_channel(self.size_channel.as_ref(), timer, &mut is_done),
+            scale: sample_channel(self.scale_channel.as_ref(), timer, &mut is_done),
+            color: sample_channel(self.color_channel.as_ref(), timer, &mut is_done),
+            gradient_stop_colors: self
+                .gradient_stop_color_channels
+                .iter()
+                .map(|channel| sample_channel(channel.as_ref(), timer, &mut is_done))
+                .collect(),
+            uv_offset: sample_channel(self.uv_offset_channel.as_ref(), timer, &mut is_done),
+            uv_scale: sample_channel(self.uv_scale_channel.as_ref(), timer, &mut is_done),
+            fill_amount: sample_channel(self.fill_amount_channel.as_ref(), timer, &mut is_done),
+            font_size: sample_channel(self.font_size_channel.as_ref(), timer, &mut is_done),
+            line_height: sample_channel(self.line_height_channel.as_ref(), timer, &mut is_done),
+            outline_thickness: sample_channel(self.outline_thickness_channel.as_ref(), timer, &mut is_done),
+            outline_color: sample_channel(self.outline_color_channel.as_ref(), timer, &mut is_done),
+            visible_characters: sample_channel(self.visible_characters_channel.as_ref(), timer, &mut is_done),
+            ..Default::default()
+        };
+
+        if let Some((position, direction)) = self.motion_path.as_ref().and_then(|path| path.sample(timer, &mut is_done)) {
+            pose.position = Some(position);
+            if self.motion_path.as_ref().is_some_and(|path| path.orient_to_path) {
+                match pose.angle.as_mut() {
+                    Some(angle) => {
+                        angle.value += direction.value;
+                        angle.start += direction.start;
+                    }
+                    None => pose.angle = Some(direction),
                 }
             }
         }
 
+        self.sample_discrete_channels(&mut pose, timer, &mut is_done);
+        (pose, is_done)
+    }
+
+    fn sample_discrete_channels(&self, pose: &mut NodePose, timer: f32, is_done: &mut bool) {
+        pose.visibility = sample_discrete_channel(self.visibility_channel.as_ref(), timer, is_done);
+        pose.texture = sample_discrete_channel(self.texture_channel.as_ref(), timer, is_done);
+        pose.text = sample_discrete_channel(self.text_channel.as_ref(), timer, is_done);
+
+        if let Some(channel) = self.sublayout_animation_channel.as_ref() {
+            *is_done &= !channel.value_at(timer).1;
+        }
+    }
+
+    /// Samples the continuous channels and motion path once per frame, so that they are played back without
+    /// evaluating their curves
+    ///
+    /// Quantized channels store each value in 16 bits. Editing the channels afterwards doesn't update the baked
+    /// samples, so the animation has to be baked again.
+    pub fn bake(&mut self, quantize: bool) {
+        self.baked = Some(BakedNodeAnimation::bake(self, quantize));
+    }
+
+    /// Removes the curves that were baked, leaving only what is needed to play the animation
+    ///
+    /// Does nothing if the animation isn't baked.
+    pub fn strip_curves(&mut self) {
+        if self.baked.is_none() {
+            return;
+        }
+
+        self.angle_channel = None;
+        self.position_channel = None;
+        self.motion_path = None;
+        self.size_channel = None;
+        self.scale_channel = None;
+        self.color_channel = None;
+        self.uv_offset_channel = None;
+        self.uv_scale_channel = None;
+        self.fill_amount_channel = None;
+        self.font_size_channel = None;
+        self.line_height_channel = None;
+        self.outline_thickness_channel = None;
+        self.outline_color_channel = None;
+        self.visible_characters_channel = None;
+
+        // Stops are kept so the tree still knows that the gradient is animated
+        self.gradient_stop_color_channels.iter_mut().for_each(|channel| *channel = None);
+    }
+
+    /// Names of the sublayout animations started after frame `after`, up to and including frame `until`
+    pub fn sublayout_animations_between(&self, after: f32, until: f32) -> impl Iterator<Item = &str> {
+        self.sublayout_animation_channel
+            .iter()
+            .flat_map(DiscreteChannel::keyframe_starts)
+            .filter(move |(frame, name)| {
+                let frame = *frame as f32;
+                frame > after && frame <= until && !name.is_empty()
+            })
+            .map(|(_, name)| name.as_str())
+    }
+
+    /// The sublayout animation that is playing at `frame`, along with how many frames ago it was started
+    pub fn sublayout_animation_at(&self, frame: usize) -> Option<(&str, usize)> {
+        self.sublayout_animation_channel
+            .iter()
+            .flat_map(DiscreteChannel::keyframe_starts)
+            .take_while(|(start, _)| *start <= frame)
+            .last()
+            .filter(|(_, name)| !name.is_empty())
+            .map(|(start, name)| (name.as_str(), frame - start))
+    }
+
+    /// Samples the animation at `timer` frames and writes it into the node at full weight
+    ///
+    /// Relative channels are applied to `rest`, or to the node's current values if it is `None`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn animate<B: EnvyBackend>(
+        &self,
+        timer: f32,
+        rest: Option<&RestPose>,
+        node: &mut NodeTransform,
+        color: &mut [u8; 4],
+        visibility: &mut NodeVisibility,
+        gradient: Option<&mut Gradient>,
+        node_impl: &mut dyn Node<B>,
+    ) -> bool {
+        let (mut pose, is_done) = self.sample(timer);
+        let captured;
+        let rest = match rest {
+            Some(rest) => rest,
+            None => {
+                captured = RestPose::capture(node, *color, *visibility, gradient.as_deref(), node_impl);
+                &captured
+            }
+        };
+        pose.resolve(rest);
+
+        let mut blender = PoseBlender::new();
+        blender.push(0, &pose, 1.0, AnimationBlendMode::Override);
+        blender.apply(rest, node, color, visibility, gradient, node_impl);
         is_done
     }
 }
 
 #[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub struct Animation {
     pub node_animations: Vec<NodeAnimation>,
     pub total_duration: usize,
 }
+
+impl Animation {
+    /// Bakes every node animation, see [`NodeAnimation::bake`]
+    pub fn bake(&mut self, quantize: bool) {
+        self.node_animations.iter_mut().for_each(|animation| animation.bake(quantize));
+    }
+
+    /// Discards the baked samples so that the authored channels are played again
+    pub fn clear_baked(&mut self) {
+        self.node_animations.iter_mut().for_each(|animation| animation.baked = None);
+    }
+
+    pub fn is_baked(&self) -> bool {
+        !self.node_animations.is_empty() && self.node_animations.iter().all(|animation| animation.baked.is_some())
+    }
+}
+
+#[cfg(any(test, feature = "asset"))]
+impl NodeAnimation {
+    /// A node animation without any channels, to be filled in by tests and when reading assets
+    pub(crate) fn empty(node_path: impl Into<String>) -> Self {
+        Self {
+            node_path: node_path.into(),
+            angle_channel: None,
+            position_channel: None,
+            motion_path: None,
+            size_channel: None,
+            scale_channel: None,
+            color_channel: None,
+            visibility_channel: None,
+            uv_offset_channel: None,
+            uv_scale_channel: None,
+            fill_amount_channel: None,
+            texture_channel: None,
+            font_size_channel: None,
+            line_height_channel: None,
+            outline_thickness_channel: None,
+            outline_color_channel: None,
+            visible_characters_channel: None,
+            text_channel: None,
+            sublayout_animation_channel: None,
+            gradient_stop_color_channels: vec![],
+            baked: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EmptyNode, LayoutTree, NodeItem, tree::AnimationPlayback, testing::RecordingBackend};
+
+    fn linear<T>(start: T, end: T, duration: usize) -> AnimationChannel<T> {
+        AnimationChannel::from_transforms(start, vec![AnimationTransform {
+            end,
+            duration,
+            first_step: TransformStep::Linear,
+            additional_steps: vec![],
+            end_tangents: KeyTangents::LINEAR,
+        }])
+    }
+
+    /// A tree with a single node at `position`, and an animation moving it along x from 0 to 10 over 10 frames
+    fn tree_with_move(position: glam::Vec2) -> LayoutTree<RecordingBackend> {
+        let mut transform = NodeTransform::from_size(glam::Vec2::splat(10.0));
+        transform.position = position;
+
+        let mut node_anim = NodeAnimation::empty("node");
+        node_anim.position_channel = Some(linear(glam::Vec2::ZERO, glam::Vec2::new(10.0, 0.0), 10));
+
+        let mut tree = LayoutTree::new().with_child(NodeItem::new("node", transform, [255; 4], EmptyNode));
+        tree.add_animation("move", Animation {
+            node_animations: vec![node_anim],
+            total_duration: 10,
+        });
+        tree
+    }
+
+    fn run(tree: &mut LayoutTree<RecordingBackend>, frames: usize) -> f32 {
+        for _ in 0..frames {
+            tree.update_animations();
+        }
+        tree.get_node_by_path("node").unwrap().transform().position.x
+    }
+
+    #[test]
+    fn additive_animation_is_added_to_rest_value() {
+        let mut tree = tree_with_move(glam::Vec2::new(5.0, 0.0));
+        tree.play_animation_with("move", AnimationPlayback {
+            blend_mode: AnimationBlendMode::Additive,
+            ..Default::default()
+        });
+
+        assert_eq!(run(&mut tree, 5), 10.0);
+        assert_eq!(run(&mut tree, 5), 15.0);
+    }
+
+    #[test]
+    fn override_animation_is_blended_with_rest_value_by_weight() {
+        let mut tree = tree_with_move(glam::Vec2::ZERO);
+        tree.play_animation_with("move", AnimationPlayback {
+            weight: 0.5,
+            ..Default::default()
+        });
+
+        assert_eq!(run(&mut tree, 5), 2.5);
+        assert_eq!(run(&mut tree, 5), 5.0);
+    }
+
+    #[test]
+    fn discrete_values_return_to_rest_value_below_half_weight() {
+        let mut node_anim = NodeAnimation::empty("node");
+        node_anim.visibility_channel = Some(DiscreteChannel {
+            start: NodeVisibility::Hidden,
+            keyframes: vec![DiscreteKeyframe {
+                value: NodeVisibility::Hidden,
+                duration: 10,
+            }],
+        });
+
+        let transform = NodeTransform::from_size(glam::Vec2::splat(10.0));
+        let mut tree = LayoutTree::<RecordingBackend>::new()
+            .with_child(NodeItem::new("node", transform, [255; 4], EmptyNode));
+        tree.add_animation("hide", Animation {
+            node_animations: vec![node_anim],
+            total_duration: 10,
+        });
+
+        tree.play_animation("hide");
+        tree.update_animations();
+        assert_eq!(tree.get_node_by_path("node").unwrap().visibility(), NodeVisibility::Hidden);
+
+        tree.set_animation_weight("hide", 0.25);
+        tree.update_animations();
+        assert_eq!(tree.get_node_by_path("node").unwrap().visibility(), NodeVisibility::Inherited);
+    }
+
+    #[test]
+    fn color_offset_can_darken_from_rest_value() {
+        let transform = NodeTransform::from_size(glam::Vec2::splat(10.0));
+        let mut node_anim = NodeAnimation::empty("node");
+        node_anim.color_channel = Some(linear([255; 4], [55, 155, 255, 255], 10).with_mode(ChannelMode::Offset));
+
+        let mut tree = LayoutTree::<RecordingBackend>::new()
+            .with_child(NodeItem::new("node", transform, [200, 100, 50, 255], EmptyNode));
+        tree.add_animation("fade", Animation {
+            node_animations: vec![node_anim],
+            total_duration: 10,
+        });
+
+        tree.play_animation("fade");
+        tree.update_animations();
+        let [r, g, ..] = tree.get_node_by_path("node").unwrap().color();
+        assert!(r < 200 && g < 100);
+
+        run(&mut tree, 9);
+        assert_eq!(tree.get_node_by_path("node").unwrap().color(), [0, 0, 50, 255]);
+    }
+
+    /// Moves from `0.0` to `10.0` over 10 frames, then to `30.0` over the next 10 frames
+    fn two_segments() -> AnimationChannel<f32> {
+        let mut channel = linear(0.0, 10.0, 10);
+        channel.insert_keyframe(20);
+        *channel.keyframe_mut(20).unwrap() = 30.0;
+        channel
+    }
+
+    #[test]
+    fn evaluate_holds_the_start_value_before_the_first_key() {
+        let channel = two_segments();
+        assert_eq!(channel.evaluate(-5.0), (0.0, true));
+        assert_eq!(channel.evaluate(0.0), (0.0, true));
+    }
+
+    #[test]
+    fn evaluate_on_a_key_returns_its_value() {
+        let channel = two_segments();
+        assert_eq!(channel.evaluate(5.0), (5.0, true));
+        assert_eq!(channel.evaluate(10.0), (10.0, true));
+        assert_eq!(channel.evaluate(15.0), (20.0, true));
+    }
+
+    #[test]
+    fn evaluate_holds_the_last_value_past_the_end() {
+        let channel = two_segments();
+        assert_eq!(channel.evaluate(20.0), (30.0, false));
+        assert_eq!(channel.evaluate(100.0), (30.0, false));
+    }
+
+    #[test]
+    fn zero_duration_segments_jump_to_their_value() {
+        let channel = AnimationChannel::from_transforms(0.0, vec![
+            AnimationTransform {
+                end: 10.0,
+                duration: 10,
+                first_step: TransformStep::Linear,
+                additional_steps: vec![],
+                end_tangents: KeyTangents::LINEAR,
+            },
+            AnimationTransform {
+                end: 50.0,
+                duration: 0,
+                first_step: TransformStep::Linear,
+                additional_steps: vec![],
+                end_tangents: KeyTangents::LINEAR,
+            },
+            AnimationTransform {
+                end: 60.0,
+                duration: 10,
+                first_step: TransformStep::Linear,
+                additional_steps: vec![],
+                end_tangents: KeyTangents::LINEAR,
+            },
+        ]);
+
+        assert_eq!(channel.duration(), 20);
+        assert_eq!(channel.evaluate(5.0), (5.0, true));
+        assert_eq!(channel.evaluate(10.0), (50.0, true));
+        assert_eq!(channel.evaluate(15.0), (55.0, true));
+        assert_eq!(channel.evaluate(20.0), (60.0, false));
+
+        let instant = linear(0.0, 1.0, 0);
+        assert_eq!(instant.evaluate(0.0), (1.0, false));
+    }
+
+    #[test]
+    fn empty_channels_hold_their_start_value() {
+        let channel = AnimationChannel::new(7.0f32);
+        assert_eq!(channel.duration(), 0);
+        assert_eq!(channel.evaluate(0.0), (7.0, false));
+        assert_eq!(channel.evaluate(3.0), (7.0, false));
+    }
+
+    #[cfg(feature = "asset")]
+    fn assert_round_trips<T>(channel: AnimationChannel<T>)
+    where
+        T: AnimationValue + PartialEq,
+        T::Repr: bincode::Encode + bincode::Decode<()>,
+    {
+        let bytes = bincode::encode_to_vec(&channel, bincode::config::standard()).unwrap();
+        let (decoded, read): (AnimationChannel<T>, usize) =
+            bincode::decode_from_slice(&bytes, bincode::config::standard()).unwrap();
+        assert_eq!(read, bytes.len());
+        assert!(decoded == channel);
+    }
+
+    #[cfg(feature = "asset")]
+    #[test]
+    fn channels_round_trip_through_bincode() {
+        let mut eased = two_segments().with_mode(ChannelMode::Offset);
+        eased.start_tangents = KeyTangents::new(TangentMode::Flat);
+        *eased.tangents_mut(10).unwrap() = KeyTangents {
+            mode: TangentMode::Broken,
+            in_slope: 0.5,
+            out_slope: 2.0,
+        };
+
+        assert_round_trips(eased);
+        assert_round_trips(linear(glam::Vec2::ZERO, glam::Vec2::new(3.0, -4.0), 5));
+        assert_round_trips(linear([255, 0, 0, 255], [0, 0, 255, 128], 8).with_mode(ChannelMode::Multiply));
+        assert_round_trips(linear(glam::Vec4::ONE, glam::Vec4::new(0.5, 0.25, 0.0, 1.0), 12));
+        assert_round_trips(linear(0u32, 7, 14));
+    }
+
+    fn samples(channel: &AnimationChannel<f32>) -> Vec<f32> {
+        (0..=channel.duration() * 4).map(|quarter| channel.evaluate(quarter as f32 / 4.0).0).collect()
+    }
+
+    fn assert_same_curve(before: &[f32], after: &[f32]) {
+        for (before, after) in before.iter().zip(after.iter()) {
+            assert!((before - after).abs() < 1e-3, "{before} != {after}");
+        }
+    }
+
+    #[test]
+    fn inserting_keyframes_keeps_curve_shape() {
+        let mut eased = linear(0.0, 100.0, 10);
+        eased.start_tangents = KeyTangents::new(TangentMode::Flat);
+        *eased.tangents_mut(10).unwrap() = KeyTangents::new(TangentMode::Flat);
+
+        let before = samples(&eased);
+        eased.insert_keyframe(3);
+        assert_same_curve(&before, &samples(&eased));
+
+        let mut auto = AnimationChannel::from_transforms(0.0, vec![
+            AnimationTransform {
+                end: 40.0,
+                duration: 10,
+                first_step: TransformStep::Linear,
+                additional_steps: vec![],
+                end_tangents: KeyTangents::new(TangentMode::Auto),
+            },
+            AnimationTransform {
+                end: 100.0,
+                duration: 20,
+                first_step: TransformStep::Linear,
+                additional_steps: vec![],
+                end_tangents: KeyTangents::LINEAR,
+            },
+        ]);
+
+        let before = samples(&auto);
+        auto.insert_keyframe(17);
+        auto.insert_keyframe(4);
+        assert_same_curve(&before, &samples(&auto));
+    }
+
+    #[test]
+    fn inserting_keyframes_into_linear_curves_keeps_them_linear() {
+        let mut channel = linear(0.0, 100.0, 10);
+        channel.insert_keyframe(4);
+
+        assert!((*channel.keyframe_mut(4).unwrap() - 40.0).abs() < 1e-4);
+        for key in channel.keyframe_indices().collect::<Vec<_>>() {
+            assert_eq!(channel.tangents_mut(key).unwrap().mode, TangentMode::Linear);
+        }
+    }
+
+    #[test]
+    fn motion_path_lengths_follow_points() {
+        let mut path = MotionPath::new(glam::Vec2::ZERO, glam::Vec2::new(10.0, 0.0), 10);
+        assert_eq!(path.point_at(0.5).unwrap().0, glam::Vec2::new(5.0, 0.0));
+
+        path.set_point(1, glam::Vec2::new(20.0, 0.0));
+        assert_eq!(path.point_at(0.5).unwrap().0, glam::Vec2::new(10.0, 0.0));
+
+        path.push_point(glam::Vec2::new(40.0, 0.0));
+        let (point, direction) = path.point_at(0.75).unwrap();
+        assert!((point.x - 30.0).abs() < 0.1 && direction == glam::Vec2::X);
+
+        #[cfg(feature = "asset")]
+        {
+            let config = bincode::config::standard();
+            let bytes = bincode::encode_to_vec(&path, config).unwrap();
+            let (decoded, _): (MotionPath, _) = bincode::decode_from_slice(&bytes, config).unwrap();
+            assert!(decoded == path);
+        }
+    }
+
+    /// A node animation moving, turning and recoloring a node over 10 frames, and filling it over the first 5
+    fn curves() -> NodeAnimation {
+        let mut animation = NodeAnimation::empty("node");
+        animation.position_channel = Some(linear(glam::Vec2::ZERO, glam::Vec2::new(10.0, -5.0), 10));
+        animation.angle_channel = Some(linear(0.0, 90.0, 10));
+        animation.color_channel = Some(linear([0, 0, 0, 255], [255, 128, 0, 255], 10));
+        animation.fill_amount_channel = Some(linear(0.0, 1.0, 5));
+        animation
+    }
+
+    fn assert_close(baked: Option<&Sample<f32>>, curve: Option<&Sample<f32>>, tolerance: f32) {
+        let (baked, curve) = (baked.unwrap(), curve.unwrap());
+        assert!((baked.value - curve.value).abs() <= tolerance, "{} != {}", baked.value, curve.value);
+        assert!((baked.start - curve.start).abs() <= tolerance, "{} != {}", baked.start, curve.start);
+    }
+
+    /// Checks that the baked samples of `animation` play back the curves at `timers` within `tolerance`
+    fn assert_matches_curves(animation: &NodeAnimation, timers: impl Iterator<Item = f32>, tolerance: f32) {
+        assert!(animation.baked.is_some());
+
+        for timer in timers {
+            let (baked, baked_done) = animation.sample(timer);
+            let (curve, curve_done) = animation.evaluate(timer);
+            assert_eq!(baked_done, curve_done, "finished at {timer}");
+
+            assert_close(baked.angle.as_ref(), curve.angle.as_ref(), tolerance);
+            assert_close(baked.fill_amount.as_ref(), curve.fill_amount.as_ref(), tolerance);
+
+            let (baked_position, curve_position) = (baked.position.unwrap(), curve.position.unwrap());
+            assert!(baked_position.value.abs_diff_eq(curve_position.value, tolerance), "position at {timer}");
+            assert!(baked_position.start.abs_diff_eq(curve_position.start, tolerance), "position at {timer}");
+
+            if let (Some(baked), Some(curve)) = (baked.color.as_ref(), curve.color.as_ref()) {
+                for (baked, curve) in baked.value.iter().zip(curve.value.iter()) {
+                    assert!(baked.abs_diff(*curve) <= 1, "color at {timer}");
+                }
+            }
+        }
+    }
+
+    /// Frames 0 to 12 in quarter frame steps, running past the end of the animation
+    fn quarter_frames() -> impl Iterator<Item = f32> {
+        (0..=48).map(|step| step as f32 / 4.0)
+    }
+
+    #[test]
+    fn baked_playback_matches_the_curves() {
+        let mut animation = curves();
+        animation.bake(false);
+
+        assert!(!animation.baked.as_ref().unwrap().is_quantized());
+        assert_eq!(animation.baked.as_ref().unwrap().frame_count(), 11);
+        assert_matches_curves(&animation, quarter_frames(), 1e-4);
+    }
+
+    #[test]
+    fn quantized_playback_matches_the_curves_within_quantization_error() {
+        let mut animation = curves();
+        animation.bake(true);
+
+        // Half a step of the largest range, which is the 90 degrees covered by the angle
+        let tolerance = 90.0 / u16::MAX as f32;
+        assert!(animation.baked.as_ref().unwrap().is_quantized());
+        assert_matches_curves(&animation, quarter_frames(), tolerance);
+    }
+
+    #[test]
+    fn baked_motion_paths_match_the_path() {
+        let mut path = MotionPath::new(glam::Vec2::ZERO, glam::Vec2::new(10.0, 0.0), 20);
+        path.push_point(glam::Vec2::new(10.0, 10.0));
+        path.orient_to_path = true;
+
+        let mut animation = NodeAnimation::empty("node");
+        animation.angle_channel = Some(linear(0.0, 45.0, 20));
+        animation.fill_amount_channel = Some(linear(0.0, 1.0, 20));
+        animation.motion_path = Some(path);
+
+        // The path bends between frames, so the samples are only compared on them
+        let frames = || (0..=24).map(|frame| frame as f32);
+
+        let mut full = animation.clone();
+        full.bake(false);
+        assert_matches_curves(&full, frames(), 1e-4);
+
+        let mut quantized = animation;
+        quantized.bake(true);
+        assert_matches_curves(&quantized, frames(), 1e-2);
+    }
+
+    #[test]
+    fn stripped_animations_still_play() {
+        let mut animation = curves();
+        animation.motion_path = Some(MotionPath::new(glam::Vec2::ZERO, glam::Vec2::new(10.0, 0.0), 10));
+        animation.gradient_stop_color_channels = vec![Some(linear([0; 4], [255; 4], 10))];
+
+        let mut stripped = animation.clone();
+        stripped.strip_curves();
+        assert!(stripped == animation, "animations which aren't baked are left alone");
+
+        animation.bake(true);
+        stripped = animation.clone();
+        stripped.strip_curves();
+
+        assert!(stripped.position_channel.is_none() && stripped.motion_path.is_none());
+        assert!(stripped.angle_channel.is_none() && stripped.color_channel.is_none());
+        assert!(stripped.fill_amount_channel.is_none());
+        assert_eq!(stripped.gradient_stop_color_channels.len(), 1);
+
+        for timer in quarter_frames() {
+            let (pose, is_done) = stripped.sample(timer);
+            let (expected, expected_done) = animation.sample(timer);
+            let position = |pose: &NodePose| pose.position.as_ref().map(|sample| sample.value);
+            assert_eq!(position(&pose), position(&expected), "position at {timer}");
+            let angle = |pose: &NodePose| pose.angle.as_ref().map(|sample| sample.value);
+            assert_eq!(angle(&pose), angle(&expected), "angle at {timer}");
+
+            let stop = |pose: &NodePose| pose.gradient_stop_colors[0].as_ref().map(|stop| stop.value);
+            assert_eq!(stop(&pose), stop(&expected), "gradient at {timer}");
+            assert_eq!(is_done, expected_done);
+        }
+
+        let mut tree = tree_with_move(glam::Vec2::ZERO);
+        tree.add_animation("move", Animation {
+            node_animations: vec![stripped],
+            total_duration: 10,
+        });
+        tree.play_animation_with("move", AnimationPlayback::default());
+
+        assert!((run(&mut tree, 5) - 5.0).abs() < 1e-3);
+        assert!((run(&mut tree, 5) - 10.0).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,296 @@
+use super::{ChannelMode, Interpolatable, NodeAnimation, NodePose, Sample};
+
+/// A value that can be stored as a fixed number of floats per frame
+trait BakeValue: Interpolatable {
+    const COMPONENTS: usize;
+
+    fn write_components(&self, out: &mut Vec<f32>);
+    fn from_components(components: &[f32]) -> Self;
+}
+
+impl BakeValue for f32 {
+    const COMPONENTS: usize = 1;
+
+    fn write_components(&self, out: &mut Vec<f32>) {
+        out.push(*self);
+    }
+
+    fn from_components(components: &[f32]) -> Self {
+        components[0]
+    }
+}
+
+impl BakeValue for glam::Vec2 {
+    const COMPONENTS: usize = 2;
+
+    fn write_components(&self, out: &mut Vec<f32>) {
+        out.extend_from_slice(&self.to_array());
+    }
+
+    fn from_components(components: &[f32]) -> Self {
+        glam::Vec2::from_slice(components)
+    }
+}
+
+impl BakeValue for [u8; 4] {
+    const COMPONENTS: usize = 4;
+
+    fn write_components(&self, out: &mut Vec<f32>) {
+        out.extend(self.map(|component| component as f32));
+    }
+
+    fn from_components(components: &[f32]) -> Self {
+        std::array::from_fn(|idx| components[idx].round().clamp(0.0, 255.0) as u8)
+    }
+}
+
+/// The per-frame values of a [`BakedChannel`], stored component by component
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum BakedSamples {
+    Full(Vec<f32>),
+
+    /// Each component is mapped onto `0..=u16::MAX` between the smallest and largest value it takes
+    Quantized {
+        min: Vec<f32>,
+        range: Vec<f32>,
+        values: Vec<u16>,
+    },
+}
+
+/// A channel sampled once per frame, which is linearly interpolated between frames when played back
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BakedChannel {
+    pub(crate) mode: ChannelMode,
+    pub(crate) components: usize,
+    pub(crate) samples: BakedSamples,
+}
+
+impl BakedChannel {
+    fn bake<T: BakeValue>(samples: &[Sample<T>], quantize: bool) -> Self {
+        let mut values = Vec::with_capacity(samples.len() * T::COMPONENTS);
+        samples.iter().for_each(|sample| sample.value.write_components(&mut values));
+
+        let samples_out = if quantize {
+            let (min, max) = (0..T::COMPONENTS)
+                .map(|component| {
+                    values
+                        .iter()
+                        .skip(component)
+                        .step_by(T::COMPONENTS)
+                        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), value| {
+                            (min.min(*value), max.max(*value))
+                        })
+                })
+                .unzip::<_, _, Vec<_>, Vec<_>>();
+
+            let range = min.iter().zip(max.iter()).map(|(min, max)| max - min).collect::<Vec<_>>();
+            let values = values
+                .iter()
+                .enumerate()
+                .map(|(idx, value)| {
+                    let component = idx % T::COMPONENTS;
+                    if range[component] > 0.0 {
+                        ((value - min[component]) / range[component] * u16::MAX as f32).round() as u16
+                    } else {
+                        0
+                    }
+                })
+                .collect();
+
+            BakedSamples::Quantized { min, range, values }
+        } else {
+            BakedSamples::Full(values)
+        };
+
+        Self {
+            mode: samples.first().map(|sample| sample.mode).unwrap_or_default(),
+            components: T::COMPONENTS,
+            samples: samples_out,
+        }
+    }
+
+    /// Number of frames stored, including frame 0
+    pub fn frame_count(&self) -> usize {
+        let len = match &self.samples {
+            BakedSamples::Full(values) => values.len(),
+            BakedSamples::Quantized { values, .. } => values.len(),
+        };
+
+        len / self.components.max(1)
+    }
+
+    pub fn is_quantized(&self) -> bool {
+        matches!(self.samples, BakedSamples::Quantized { .. })
+    }
+
+    fn value_at_frame<T: BakeValue>(&self, frame: usize) -> T {
+        let offset = frame * T::COMPONENTS;
+        match &self.samples {
+            BakedSamples::Full(values) => T::from_components(&values[offset..offset + T::COMPONENTS]),
+            BakedSamples::Quantized { min, range, values } => {
+                let mut components = [0.0; 4];
+                for (component, out) in components.iter_mut().enumerate().take(T::COMPONENTS) {
+                    *out = min[component] + values[offset + component] as f32 / u16::MAX as f32 * range[component];
+                }
+
+                T::from_components(&components[..T::COMPONENTS])
+            }
+        }
+    }
+
+    fn sample<T: BakeValue>(&self, timer: f32, is_done: &mut bool) -> Option<Sample<T>> {
+        if T::COMPONENTS != self.components || self.frame_count() == 0 {
+            return None;
+        }
+
+        let last = self.frame_count() - 1;
+        let timer = timer.max(0.0);
+        let frame = timer.floor() as usize;
+        let value = if frame >= last {
+            self.value_at_frame(last)
+        } else {
+            *is_done = false;
+            T::interpolate(self.value_at_frame(frame), self.value_at_frame(frame + 1), timer - frame as f32)
+        };
+
+        Some(Sample {
+            value,
+            start: self.value_at_frame(0),
+            mode: self.mode,
+        })
+    }
+}
+
+/// The continuous channels of a [`NodeAnimation`] sampled once per frame
+///
+/// Motion paths are baked into the position and angle channels. Discrete channels are already cheap to play, so
+/// they are read from the node animation.
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BakedNodeAnimation {
+    pub(crate) angle: Option<BakedChannel>,
+    pub(crate) position: Option<BakedChannel>,
+    pub(crate) size: Option<BakedChannel>,
+    pub(crate) scale: Option<BakedChannel>,
+    pub(crate) color: Option<BakedChannel>,
+    pub(crate) gradient_stop_colors: Vec<Option<BakedChannel>>,
+
+    pub(crate) uv_offset: Option<BakedChannel>,
+    pub(crate) uv_scale: Option<BakedChannel>,
+    pub(crate) fill_amount: Option<BakedChannel>,
+
+    pub(crate) font_size: Option<BakedChannel>,
+    pub(crate) line_height: Option<BakedChannel>,
+    pub(crate) outline_thickness: Option<BakedChannel>,
+    pub(crate) outline_color: Option<BakedChannel>,
+    pub(crate) visible_characters: Option<BakedChannel>,
+}
+
+impl BakedNodeAnimation {
+    pub(super) fn bake(animation: &NodeAnimation, quantize: bool) -> Self {
+        let mut frame_count = 1;
+        while !animation.evaluate(frame_count as f32 - 1.0).1 {
+            frame_count += 1;
+        }
+
+        let poses = (0..frame_count)
+            .map(|frame| animation.evaluate(frame as f32).0)
+            .collect::<Vec<_>>();
+
+        fn bake<T: BakeValue>(
+            poses: &[NodePose],
+            quantize: bool,
+            sample: impl Fn(&NodePose) -> Option<&Sample<T>>,
+        ) -> Option<BakedChannel> {
+            let samples = poses.iter().map(|pose| sample(pose).cloned()).collect::<Option<Vec<_>>>()?;
+            Some(BakedChannel::bake(&samples, quantize))
+        }
+
+        let stop_count = poses.first().map(|pose| pose.gradient_stop_colors.len()).unwrap_or_default();
+
+        Self {
+            angle: bake(&poses, quantize, |pose| pose.angle.as_ref()),
+            position: bake(&poses, quantize, |pose| pose.position.as_ref()),
+            size: bake(&poses, quantize, |pose| pose.size.as_ref()),
+            scale: bake(&poses, quantize, |pose| pose.scale.as_ref()),
+            color: bake(&poses, quantize, |pose| pose.color.as_ref()),
+            gradient_stop_colors: (0..stop_count)
+                .map(|stop_idx| bake(&poses, quantize, |pose| pose.gradient_stop_colors[stop_idx].as_ref()))
+                .collect(),
+            uv_offset: bake(&poses, quantize, |pose| pose.uv_offset.as_ref()),
+            uv_scale: bake(&poses, quantize, |pose| pose.uv_scale.as_ref()),
+            fill_amount: bake(&poses, quantize, |pose| pose.fill_amount.as_ref()),
+            font_size: bake(&poses, quantize, |pose| pose.font_size.as_ref()),
+            line_height: bake(&poses, quantize, |pose| pose.line_height.as_ref()),
+            outline_thickness: bake(&poses, quantize, |pose| pose.outline_thickness.as_ref()),
+            outline_color: bake(&poses, quantize, |pose| pose.outline_color.as_ref()),
+            visible_characters: bake(&poses, quantize, |pose| pose.visible_characters.as_ref()),
+        }
+    }
+
+    /// Number of frames stored by the longest channel
+    pub fn frame_count(&self) -> usize {
+        self.channels().map(BakedChannel::frame_count).max().unwrap_or_default()
+    }
+
+    pub fn is_quantized(&self) -> bool {
+        self.channels().any(BakedChannel::is_quantized)
+    }
+
+    fn channels(&self) -> impl Iterator<Item = &BakedChannel> {
+        [
+            &self.angle,
+            &self.position,
+            &self.size,
+            &self.scale,
+            &self.color,
+            &self.uv_offset,
+            &self.uv_scale,
+            &self.fill_amount,
+            &self.font_size,
+            &self.line_height,
+            &self.outline_thickness,
+            &self.outline_color,
+            &self.visible_characters,
+        ]
+        .into_iter()
+        .chain(self.gradient_stop_colors.iter())
+        .flatten()
+    }
+
+    /// Plays the baked channels at `timer` frames, reading discrete channels from `animation`
+    pub(super) fn sample(&self, animation: &NodeAnimation, timer: f32) -> (NodePose, bool) {
+        let mut is_done = true;
+
+        fn sample<T: BakeValue>(channel: &Option<BakedChannel>, timer: f32, is_done: &mut bool) -> Option<Sample<T>> {
+            channel.as_ref().and_then(|channel| channel.sample(timer, is_done))
+        }
+
+        let mut pose = NodePose {
+            angle: sample(&self.angle, timer, &mut is_done),
+            position: sample(&self.position, timer, &mut is_done),
+            size: sample(&self.size, timer, &mut is_done),
+            scale: sample(&self.scale, timer, &mut is_done),
+            color: sample(&self.color, timer, &mut is_done),
+            gradient_stop_colors: self
+                .gradient_stop_colors
+                .iter()
+                .map(|channel| sample(channel, timer, &mut is_done))
+                .collect(),
+            uv_offset: sample(&self.uv_offset, timer, &mut is_done),
+            uv_scale: sample(&self.uv_scale, timer, &mut is_done),
+            fill_amount: sample(&self.fill_amount, timer, &mut is_done),
+            font_size: sample(&self.font_size, timer, &mut is_done),
+            line_height: sample(&self.line_height, timer, &mut is_done),
+            outline_thickness: sample(&self.outline_thickness, timer, &mut is_done),
+            outline_color: sample(&self.outline_color, timer, &mut is_done),
+            visible_characters: sample(&self.visible_characters, timer, &mut is_done),
+            ..Default::default()
+        };
+
+        animation.sample_discrete_channels(&mut pose, timer, &mut is_done);
+        (pose, is_done)
+    }
+}
@@ -1,13 +1,26 @@
 use std::{
     borrow::Cow,
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
 };
 
 use bincode::{Decode, Encode};
 
-use crate::{EnvyBackend, LayoutTemplate, NodeImplTemplate, NodeTemplate};
+use crate::{EnvyBackend, InheritanceError, LayoutTemplate, NodeImplTemplate, NodeTemplate};
 
-#[derive(Decode, Encode, Debug, Copy, Clone, PartialEq, Eq)]
+use container::ContainerError;
+
+pub mod container;
+pub mod diff;
+pub mod reload;
+mod validation;
+
+pub use validation::{
+    validate, validate_with_resources, AvailableResources, Diagnostic, DiagnosticKind, Severity,
+};
+
+pub use crate::content_hash;
+
+#[derive(Decode, Encode, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 struct Version {
     major: u8,
     minor: u8,
@@ -24,7 +37,167 @@ impl Version {
     }
 
     const fn current() -> Self {
-        Self::new(0, 3, 5)
+        Self::new(0, 3, 16)
+    }
+}
+
+/// Channel layouts of older asset versions
+mod legacy {
+    use bincode::{BorrowDecode, Decode, Encode};
+
+    use crate::{AnimationValue, ChannelMode, TransformStep};
+
+    /// Transform layout used before keyframes stored [`crate::KeyTangents`]
+    pub(super) struct AnimationTransform<T>(crate::AnimationTransform<T>);
+
+    impl<T: AnimationValue> Encode for AnimationTransform<T>
+    where
+        T::Repr: Encode,
+    {
+        fn encode<E: bincode::enc::Encoder>(&self, encoder: &mut E) -> Result<(), bincode::error::EncodeError> {
+            self.0.end.to_repr().encode(encoder)?;
+            self.0.duration.encode(encoder)?;
+            self.0.first_step.encode(encoder)?;
+            self.0.additional_steps.encode(encoder)
+        }
+    }
+
+    impl<T: AnimationValue, C> Decode<C> for AnimationTransform<T>
+    where
+        T::Repr: Decode<C>,
+    {
+        fn decode<D: bincode::de::Decoder<Context = C>>(decoder: &mut D) -> Result<Self, bincode::error::DecodeError> {
+            Ok(Self(crate::AnimationTransform {
+                end: T::from_repr(T::Repr::decode(decoder)?),
+                duration: usize::decode(decoder)?,
+                first_step: TransformStep::decode(decoder)?,
+                additional_steps: <Vec<TransformStep>>::decode(decoder)?,
+                end_tangents: crate::KeyTangents::LINEAR,
+            }))
+        }
+    }
+
+    impl<'de, T: AnimationValue, C> BorrowDecode<'de, C> for AnimationTransform<T>
+    where
+        T::Repr: Decode<C>,
+    {
+        fn borrow_decode<D: bincode::de::BorrowDecoder<'de, Context = C>>(
+            decoder: &mut D,
+        ) -> Result<Self, bincode::error::DecodeError> {
+            Decode::decode(decoder)
+        }
+    }
+
+    fn encode_transforms<T: AnimationValue + Clone, E: bincode::enc::Encoder>(
+        channel: &crate::AnimationChannel<T>,
+        encoder: &mut E,
+    ) -> Result<(), bincode::error::EncodeError>
+    where
+        T::Repr: Encode,
+    {
+        channel
+            .transforms()
+            .iter()
+            .cloned()
+            .map(AnimationTransform)
+            .collect::<Vec<_>>()
+            .encode(encoder)
+    }
+
+    fn decode_transforms<T: AnimationValue, C, D: bincode::de::Decoder<Context = C>>(
+        decoder: &mut D,
+    ) -> Result<Vec<crate::AnimationTransform<T>>, bincode::error::DecodeError>
+    where
+        T::Repr: Decode<C>,
+    {
+        Ok(<Vec<AnimationTransform<T>>>::decode(decoder)?
+            .into_iter()
+            .map(|transform| transform.0)
+            .collect())
+    }
+
+    /// Channel layout used before channels stored a [`crate::ChannelMode`]
+    pub(super) struct AnimationChannel<T>(crate::AnimationChannel<T>);
+
+    impl<T> From<AnimationChannel<T>> for crate::AnimationChannel<T> {
+        fn from(value: AnimationChannel<T>) -> Self {
+            value.0
+        }
+    }
+
+    impl<T: AnimationValue + Clone> Encode for AnimationChannel<T>
+    where
+        T::Repr: Encode,
+    {
+        fn encode<E: bincode::enc::Encoder>(&self, encoder: &mut E) -> Result<(), bincode::error::EncodeError> {
+            self.0.start.to_repr().encode(encoder)?;
+            encode_transforms(&self.0, encoder)
+        }
+    }
+
+    impl<T: AnimationValue, C> Decode<C> for AnimationChannel<T>
+    where
+        T::Repr: Decode<C>,
+    {
+        fn decode<D: bincode::de::Decoder<Context = C>>(decoder: &mut D) -> Result<Self, bincode::error::DecodeError> {
+            let start = T::from_repr(T::Repr::decode(decoder)?);
+            let transforms = decode_transforms(decoder)?;
+            Ok(Self(crate::AnimationChannel::from_transforms(start, transforms)))
+        }
+    }
+
+    impl<'de, T: AnimationValue, C> BorrowDecode<'de, C> for AnimationChannel<T>
+    where
+        T::Repr: Decode<C>,
+    {
+        fn borrow_decode<D: bincode::de::BorrowDecoder<'de, Context = C>>(
+            decoder: &mut D,
+        ) -> Result<Self, bincode::error::DecodeError> {
+            Decode::decode(decoder)
+        }
+    }
+
+    /// Channel layout used before keyframes stored [`crate::KeyTangents`]
+    pub(super) struct ModalAnimationChannel<T>(crate::AnimationChannel<T>);
+
+    impl<T> From<ModalAnimationChannel<T>> for crate::AnimationChannel<T> {
+        fn from(value: ModalAnimationChannel<T>) -> Self {
+            value.0
+        }
+    }
+
+    impl<T: AnimationValue + Clone> Encode for ModalAnimationChannel<T>
+    where
+        T::Repr: Encode,
+    {
+        fn encode<E: bincode::enc::Encoder>(&self, encoder: &mut E) -> Result<(), bincode::error::EncodeError> {
+            self.0.mode.encode(encoder)?;
+            self.0.start.to_repr().encode(encoder)?;
+            encode_transforms(&self.0, encoder)
+        }
+    }
+
+    impl<T: AnimationValue, C> Decode<C> for ModalAnimationChannel<T>
+    where
+        T::Repr: Decode<C>,
+    {
+        fn decode<D: bincode::de::Decoder<Context = C>>(decoder: &mut D) -> Result<Self, bincode::error::DecodeError> {
+            let mode = ChannelMode::decode(decoder)?;
+            let start = T::from_repr(T::Repr::decode(decoder)?);
+            let transforms = decode_transforms(decoder)?;
+            Ok(Self(crate::AnimationChannel::from_transforms(start, transforms).with_mode(mode)))
+        }
+    }
+
+    impl<'de, T: AnimationValue, C> BorrowDecode<'de, C> for ModalAnimationChannel<T>
+    where
+        T::Repr: Decode<C>,
+    {
+        fn borrow_decode<D: bincode::de::BorrowDecoder<'de, Context = C>>(
+            decoder: &mut D,
+        ) -> Result<Self, bincode::error::DecodeError> {
+            Decode::decode(decoder)
+        }
     }
 }
 
@@ -199,6 +372,7 @@ mod v010 {
                     .into_iter()
                     .map(|step| step.into())
                     .collect(),
+                end_tangents: crate::KeyTangents::LINEAR,
             }
         }
     }
@@ -214,7 +388,7 @@ mod v010 {
             Self {
                 start: f(&value.start),
                 transforms: value
-                    .transforms
+                    .transforms()
                     .iter()
                     .map(|transform| AnimationTransform::from_map_t(transform, f))
                     .collect(),
@@ -222,14 +396,13 @@ mod v010 {
         }
 
         fn into_map_t<U>(self, f: fn(T) -> U) -> crate::animations::AnimationChannel<U> {
-            crate::animations::AnimationChannel::<U> {
-                start: f(self.start),
-                transforms: self
-                    .transforms
+            crate::animations::AnimationChannel::<U>::from_transforms(
+                f(self.start),
+                self.transforms
                     .into_iter()
                     .map(|transform| transform.into_map_t(f))
                     .collect(),
-            }
+            )
         }
     }
 
@@ -257,11 +430,24 @@ mod v010 {
                 node_path: value.node,
                 angle_channel: value.angle.map(|channel| channel.into_map_t(|float| float)),
                 position_channel: None,
+                motion_path: None,
                 size_channel: None,
                 scale_channel: None,
                 color_channel: None,
                 uv_offset_channel: None,
                 uv_scale_channel: None,
+                fill_amount_channel: None,
+                font_size_channel: None,
+                line_height_channel: None,
+                outline_thickness_channel: None,
+                outline_color_channel: None,
+                visible_characters_channel: None,
+                visibility_channel: None,
+                texture_channel: None,
+                text_channel: None,
+                sublayout_animation_channel: None,
+                gradient_stop_color_channels: vec![],
+                baked: None,
             }
         }
     }
@@ -285,13 +471,23 @@ mod v010 {
 
     impl From<Animation> for crate::animations::Animation {
         fn from(value: Animation) -> Self {
+            let node_animations = value
+                .node_animations
+                .into_iter()
+                .map(crate::animations::NodeAnimation::from)
+                .collect::<Vec<_>>();
+
+            // 0.1.0 did not store the duration, animations ended with their longest channel
+            let total_duration = node_animations
+                .iter()
+                .filter_map(|animation| animation.angle_channel.as_ref())
+                .map(|channel| channel.duration())
+                .max()
+                .unwrap_or_default();
+
             Self {
-                total_duration: todo!(),
-                node_animations: value
-                    .node_animations
-                    .into_iter()
-                    .map(crate::animations::NodeAnimation::from)
-                    .collect(),
+                total_duration,
+                node_animations,
             }
         }
     }
@@ -307,9 +503,9 @@ mod v010 {
     pub(super) fn deserialize<B: EnvyBackend, A: EnvyAssetProvider>(
         asset_provider: &mut A,
         reader: &mut std::io::Cursor<&[u8]>,
-    ) -> crate::LayoutRoot<B> {
+    ) -> Result<crate::LayoutRoot<B>, super::DeserializeError> {
         let mut asset: Asset =
-            bincode::decode_from_std_read(reader, bincode::config::standard()).unwrap();
+            bincode::decode_from_std_read(reader, bincode::config::standard())?;
 
         let mut root_template = LayoutTemplate::default();
 
@@ -344,6 +540,9 @@ mod v010 {
                         image_scaling_mode_y: Default::default(),
                         uv_offset: glam::Vec2::ZERO,
                         uv_scale: glam::Vec2::ONE,
+                        fill_method: Default::default(),
+                        fill_origin: Default::default(),
+                        fill_amount: 1.0,
                     })
                 }
                 NodeImplementationV010::Text(text) => {
@@ -369,6 +568,7 @@ mod v010 {
                 color,
                 implementation,
                 visibility: NodeVisibility::Inherited,
+                gradient: None,
                 children: children
                     .into_iter()
                     .map(|child| produce_children_and_deserialize(child, images, fonts, asset_provider))
@@ -392,7 +592,102 @@ mod v010 {
             .map(|(name, animation)| (name, animation.into()))
             .collect();
 
-        crate::LayoutRoot::from_root_template(root_template, [])
+        Ok(crate::LayoutRoot::from_root_template(root_template, []))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::{testing::RecordingBackend, LayoutRoot};
+
+        use super::*;
+
+        fn node(name: &str, implementation: NodeImplementationV010) -> Node {
+            Node {
+                name: name.to_string(),
+                transform: NodeTransform {
+                    angle: 0.0,
+                    position: [0.0; 2],
+                    size: [100.0, 50.0],
+                    scale: [1.0; 2],
+                    anchor: Anchor::Center,
+                },
+                color: [255; 4],
+                implementation,
+                children: vec![],
+            }
+        }
+
+        fn spin(node: &str, durations: &[f32]) -> NodeAnimation {
+            NodeAnimation {
+                node: node.to_string(),
+                angle: Some(AnimationChannel {
+                    start: 0.0,
+                    transforms: durations
+                        .iter()
+                        .map(|duration| AnimationTransform {
+                            end: 90.0,
+                            duration: *duration,
+                            first_step: AnimationTransformStep::Linear,
+                            additional_steps: vec![],
+                        })
+                        .collect(),
+                }),
+            }
+        }
+
+        /// An asset as written by envy 0.1.0
+        fn fixture() -> Vec<u8> {
+            let asset = Asset {
+                images: vec![("texture".to_string(), vec![1, 2, 3])],
+                fonts: vec![],
+                root_nodes: vec![
+                    node("image", NodeImplementationV010::Image(ImageNode {
+                        resource_name: "texture".to_string(),
+                    })),
+                    node("empty", NodeImplementationV010::Empty),
+                ],
+                animations: [(
+                    "spin".to_string(),
+                    Animation {
+                        node_animations: vec![spin("image", &[10.0, 5.0]), spin("empty", &[12.0])],
+                    },
+                )]
+                .into_iter()
+                .collect(),
+            };
+
+            let mut bytes = bincode::encode_to_vec(Version::new(0, 1, 0), bincode::config::standard()).unwrap();
+            bytes.extend(bincode::encode_to_vec(asset, bincode::config::standard()).unwrap());
+            bytes
+        }
+
+        #[test]
+        fn upgrade() {
+            let mut resources = MemoryAssetProvider::new();
+            let root: LayoutRoot<RecordingBackend> = try_deserialize(&mut resources, &fixture()).unwrap();
+
+            let template = root.root_template();
+            assert!(matches!(
+                &template.get_node_by_path("image").unwrap().implementation,
+                NodeImplTemplate::Image(image) if image.texture_name == "texture"
+            ));
+            assert_eq!(resources.get(ResourceKind::Image, "texture"), Some([1, 2, 3].as_slice()));
+
+            // The animation lasts as long as its longest channel
+            let (_, animation) = template.animations.iter().find(|(name, _)| name == "spin").unwrap();
+            assert_eq!(animation.total_duration, 15);
+            assert_eq!(animation.node_animations.len(), 2);
+        }
+
+        #[test]
+        fn truncated_assets_fail_to_decode() {
+            let bytes = fixture();
+            let result = try_deserialize::<RecordingBackend, _>(
+                &mut MemoryAssetProvider::new(),
+                &bytes[..bytes.len() / 2],
+            );
+            assert!(matches!(result, Err(DeserializeError::Legacy(_))));
+        }
     }
 }
 
@@ -413,6 +708,8 @@ mod v020 {
                 canvas_size: [1920, 1080],
                 root_nodes: value.root_nodes,
                 animations: value.animations,
+                state_machine: None,
+                inheritance: None,
             }
         }
     }
@@ -428,9 +725,9 @@ mod v020 {
     pub(super) fn deserialize<B: EnvyBackend, A: EnvyAssetProvider>(
         asset_provider: &mut A,
         reader: &mut Cursor<&[u8]>,
-    ) -> crate::LayoutRoot<B> {
+    ) -> Result<crate::LayoutRoot<B>, super::DeserializeError> {
         let asset: Asset =
-            bincode::decode_from_std_read(reader, bincode::config::standard()).unwrap();
+            bincode::decode_from_std_read(reader, bincode::config::standard())?;
 
         let root_template = crate::LayoutTemplate::from(asset.root_template);
 
@@ -449,7 +746,7 @@ mod v020 {
             asset_provider.load_font_bytes_with_name(font, bytes);
         }
 
-        root
+        Ok(root)
     }
 }
 
@@ -470,13 +767,26 @@ mod v021 {
         fn from(value: NodeAnimation) -> Self {
             Self {
                 node_path: value.node_path,
-                angle_channel: value.angle_channel,
-                position_channel: value.position_channel,
-                size_channel: value.size_channel,
-                scale_channel: value.scale_channel,
+                angle_channel: value.angle_channel.map(Into::into),
+                position_channel: value.position_channel.map(Into::into),
+                motion_path: None,
+                size_channel: value.size_channel.map(Into::into),
+                scale_channel: value.scale_channel.map(Into::into),
                 color_channel: None,
                 uv_offset_channel: None,
                 uv_scale_channel: None,
+                fill_amount_channel: None,
+                font_size_channel: None,
+                line_height_channel: None,
+                outline_thickness_channel: None,
+                outline_color_channel: None,
+                visible_characters_channel: None,
+                visibility_channel: None,
+                texture_channel: None,
+                text_channel: None,
+                sublayout_animation_channel: None,
+                gradient_stop_color_channels: vec![],
+                baked: None,
             }
         }
     }
@@ -496,7 +806,8 @@ mod v021 {
         }
     }
 
-    use crate::{template::NodeTemplate, AnimationChannel};
+    use crate::template::NodeTemplate;
+    use super::legacy::AnimationChannel;
     #[derive(bincode::Encode, bincode::Decode)]
     struct LayoutTemplate {
         canvas_size: [u32; 2],
@@ -514,6 +825,8 @@ mod v021 {
                     .into_iter()
                     .map(|(name, anim)| (name, anim.into()))
                     .collect(),
+                state_machine: None,
+                inheritance: None,
             }
         }
     }
@@ -529,9 +842,9 @@ mod v021 {
     pub(super) fn deserialize<B: EnvyBackend, A: EnvyAssetProvider>(
         backend: &mut A,
         reader: &mut Cursor<&[u8]>,
-    ) -> crate::LayoutRoot<B> {
+    ) -> Result<crate::LayoutRoot<B>, super::DeserializeError> {
         let asset: Asset =
-            bincode::decode_from_std_read(reader, bincode::config::standard()).unwrap();
+            bincode::decode_from_std_read(reader, bincode::config::standard())?;
 
         let root_template = crate::LayoutTemplate::from(asset.root_template);
 
@@ -550,14 +863,15 @@ mod v021 {
             backend.load_font_bytes_with_name(font, bytes);
         }
 
-        root
+        Ok(root)
     }
 }
 
 mod v030 {
     use std::io::Cursor;
 
-    use crate::{AnimationChannel, NodeTransform, SublayoutNodeTemplate, TextNodeTemplate, template::NodeVisibility};
+    use super::legacy::AnimationChannel;
+    use crate::{NodeTransform, SublayoutNodeTemplate, TextNodeTemplate, template::NodeVisibility};
 
     #[derive(bincode::Encode, bincode::Decode, Clone)]
     struct ImageNodeTemplate {
@@ -572,7 +886,10 @@ mod v030 {
                 image_scaling_mode_x: Default::default(),
                 image_scaling_mode_y: Default::default(),
                 uv_offset: glam::Vec2::ZERO,
-                uv_scale: glam::Vec2::ONE
+                uv_scale: glam::Vec2::ONE,
+                fill_method: Default::default(),
+                fill_origin: Default::default(),
+                fill_amount: 1.0,
             }
         }
     }
@@ -613,6 +930,7 @@ mod v030 {
                 transform: value.transform,
                 color: value.color,
                 visibility: NodeVisibility::Inherited,
+                gradient: None,
                 children: value.children.into_iter().map(Into::into).collect(),
                 implementation: value.implementation.into(),
             }
@@ -633,13 +951,26 @@ mod v030 {
         fn from(value: NodeAnimation) -> Self {
             Self {
                 node_path: value.node_path,
-                angle_channel: value.angle_channel,
-                position_channel: value.position_channel,
-                size_channel: value.size_channel,
-                scale_channel: value.scale_channel,
-                color_channel: value.color_channel,
+                angle_channel: value.angle_channel.map(Into::into),
+                position_channel: value.position_channel.map(Into::into),
+                motion_path: None,
+                size_channel: value.size_channel.map(Into::into),
+                scale_channel: value.scale_channel.map(Into::into),
+                color_channel: value.color_channel.map(Into::into),
                 uv_offset_channel: None,
                 uv_scale_channel: None,
+                fill_amount_channel: None,
+                font_size_channel: None,
+                line_height_channel: None,
+                outline_thickness_channel: None,
+                outline_color_channel: None,
+                visible_characters_channel: None,
+                visibility_channel: None,
+                texture_channel: None,
+                text_channel: None,
+                sublayout_animation_channel: None,
+                gradient_stop_color_channels: vec![],
+                baked: None,
             }
         }
     }
@@ -676,6 +1007,8 @@ mod v030 {
                     .into_iter()
                     .map(|(name, anim)| (name, anim.into()))
                     .collect(),
+                state_machine: None,
+                inheritance: None,
             }
         }
     }
@@ -691,9 +1024,9 @@ mod v030 {
     pub(super) fn deserialize<B: crate::EnvyBackend, A: super::EnvyAssetProvider>(
         backend: &mut A,
         reader: &mut Cursor<&[u8]>,
-    ) -> crate::LayoutRoot<B> {
+    ) -> Result<crate::LayoutRoot<B>, super::DeserializeError> {
         let asset: Asset =
-            bincode::decode_from_std_read(reader, bincode::config::standard()).unwrap();
+            bincode::decode_from_std_read(reader, bincode::config::standard())?;
 
         let root_template = crate::LayoutTemplate::from(asset.root_template);
 
@@ -712,14 +1045,15 @@ mod v030 {
             backend.load_font_bytes_with_name(font, bytes);
         }
 
-        root
+        Ok(root)
     }
 }
 
 mod v031 {
     use std::io::Cursor;
 
-    use crate::{Animation, NodeTransform, SublayoutNodeTemplate, TextNodeTemplate, template::NodeVisibility};
+    use super::v036::Animation;
+    use crate::{NodeTransform, SublayoutNodeTemplate, TextNodeTemplate, template::NodeVisibility};
 
     #[derive(bincode::Encode, bincode::Decode, Clone)]
     struct ImageNodeTemplate {
@@ -736,6 +1070,9 @@ mod v031 {
                 image_scaling_mode_y: Default::default(),
                 uv_offset: glam::Vec2::ZERO,
                 uv_scale: glam::Vec2::ONE,
+                fill_method: Default::default(),
+                fill_origin: Default::default(),
+                fill_amount: 1.0,
             }
         }
     }
@@ -776,6 +1113,7 @@ mod v031 {
                 transform: value.transform,
                 color: value.color,
                 visibility: NodeVisibility::Inherited,
+                gradient: None,
                 children: value.children.into_iter().map(Into::into).collect(),
                 implementation: value.implementation.into(),
             }
@@ -799,6 +1137,8 @@ mod v031 {
                     .into_iter()
                     .map(|(name, anim)| (name, anim.into()))
                     .collect(),
+                state_machine: None,
+                inheritance: None,
             }
         }
     }
@@ -814,9 +1154,9 @@ mod v031 {
     pub(super) fn deserialize<B: crate::EnvyBackend, A: super::EnvyAssetProvider>(
         backend: &mut A,
         reader: &mut Cursor<&[u8]>,
-    ) -> crate::LayoutRoot<B> {
+    ) -> Result<crate::LayoutRoot<B>, super::DeserializeError> {
         let asset: Asset =
-            bincode::decode_from_std_read(reader, bincode::config::standard()).unwrap();
+            bincode::decode_from_std_read(reader, bincode::config::standard())?;
 
         let root_template = crate::LayoutTemplate::from(asset.root_template);
 
@@ -835,14 +1175,15 @@ mod v031 {
             backend.load_font_bytes_with_name(font, bytes);
         }
 
-        root
+        Ok(root)
     }
 }
 
 mod v032 {
     use std::io::Cursor;
 
-    use crate::{AnimationChannel, ImageScalingMode, NodeTransform, SublayoutNodeTemplate, TextNodeTemplate, template::NodeVisibility};
+    use super::legacy::AnimationChannel;
+    use crate::{ImageScalingMode, NodeTransform, SublayoutNodeTemplate, TextNodeTemplate, template::NodeVisibility};
 
     #[derive(bincode::Encode, bincode::Decode, Clone)]
     struct ImageNodeTemplate {
@@ -861,6 +1202,9 @@ mod v032 {
                 image_scaling_mode_y: value.image_scaling_mode_y,
                 uv_offset: glam::Vec2::ZERO,
                 uv_scale: glam::Vec2::ONE,
+                fill_method: Default::default(),
+                fill_origin: Default::default(),
+                fill_amount: 1.0,
             }
         }
     }
@@ -901,6 +1245,7 @@ mod v032 {
                 transform: value.transform,
                 color: value.color,
                 visibility: NodeVisibility::Inherited,
+                gradient: None,
                 children: value.children.into_iter().map(Into::into).collect(),
                 implementation: value.implementation.into(),
             }
@@ -921,13 +1266,26 @@ mod v032 {
         fn from(value: NodeAnimation) -> Self {
             Self {
                 node_path: value.node_path,
-                angle_channel: value.angle_channel,
-                position_channel: value.position_channel,
-                size_channel: value.size_channel,
-                scale_channel: value.scale_channel,
-                color_channel: value.color_channel,
+                angle_channel: value.angle_channel.map(Into::into),
+                position_channel: value.position_channel.map(Into::into),
+                motion_path: None,
+                size_channel: value.size_channel.map(Into::into),
+                scale_channel: value.scale_channel.map(Into::into),
+                color_channel: value.color_channel.map(Into::into),
                 uv_offset_channel: None,
                 uv_scale_channel: None,
+                fill_amount_channel: None,
+                font_size_channel: None,
+                line_height_channel: None,
+                outline_thickness_channel: None,
+                outline_color_channel: None,
+                visible_characters_channel: None,
+                visibility_channel: None,
+                texture_channel: None,
+                text_channel: None,
+                sublayout_animation_channel: None,
+                gradient_stop_color_channels: vec![],
+                baked: None,
             }
         }
     }
@@ -964,6 +1322,8 @@ mod v032 {
                     .into_iter()
                     .map(|(name, anim)| (name, anim.into()))
                     .collect(),
+                state_machine: None,
+                inheritance: None,
             }
         }
     }
@@ -979,9 +1339,9 @@ mod v032 {
     pub(super) fn deserialize<B: crate::EnvyBackend, A: super::EnvyAssetProvider>(
         backend: &mut A,
         reader: &mut Cursor<&[u8]>,
-    ) -> crate::LayoutRoot<B> {
+    ) -> Result<crate::LayoutRoot<B>, super::DeserializeError> {
         let asset: Asset =
-            bincode::decode_from_std_read(reader, bincode::config::standard()).unwrap();
+            bincode::decode_from_std_read(reader, bincode::config::standard())?;
 
         let root_template = crate::LayoutTemplate::from(asset.root_template);
 
@@ -1000,14 +1360,15 @@ mod v032 {
             backend.load_font_bytes_with_name(font, bytes);
         }
 
-        root
+        Ok(root)
     }
 }
 
 mod v033 {
     use std::io::Cursor;
 
-    use crate::{Animation, ImageNodeTemplate, NodeTransform, SublayoutNodeTemplate, template::{NodeVisibility, TextAlignment}};
+    use super::{v036::Animation, v037::ImageNodeTemplate};
+    use crate::{NodeTransform, SublayoutNodeTemplate, template::{NodeVisibility, TextAlignment}};
 
     #[derive(bincode::Encode, bincode::Decode, Clone)]
     struct TextNodeTemplate {
@@ -1044,7 +1405,7 @@ mod v033 {
             use NodeImplTemplate as N;
             match value {
                 N::Empty => Self::Empty,
-                N::Image(image) => Self::Image(image),
+                N::Image(image) => Self::Image(image.into()),
                 N::Text(text) => Self::Text(text.into()),
                 N::Sublayout(sublayout) => Self::Sublayout(sublayout),
             }
@@ -1068,6 +1429,7 @@ mod v033 {
                 transform: value.transform,
                 color: value.color,
                 visibility: value.visibility,
+                gradient: None,
                 children: value.children.into_iter().map(Into::into).collect(),
                 implementation: value.implementation.into(),
             }
@@ -1091,6 +1453,8 @@ mod v033 {
                     .into_iter()
                     .map(|(name, anim)| (name, anim.into()))
                     .collect(),
+                state_machine: None,
+                inheritance: None,
             }
         }
     }
@@ -1106,9 +1470,9 @@ mod v033 {
     pub(super) fn deserialize<B: crate::EnvyBackend, A: super::EnvyAssetProvider>(
         backend: &mut A,
         reader: &mut Cursor<&[u8]>,
-    ) -> crate::LayoutRoot<B> {
+    ) -> Result<crate::LayoutRoot<B>, super::DeserializeError> {
         let asset: Asset =
-            bincode::decode_from_std_read(reader, bincode::config::standard()).unwrap();
+            bincode::decode_from_std_read(reader, bincode::config::standard())?;
 
         let root_template = crate::LayoutTemplate::from(asset.root_template);
 
@@ -1127,14 +1491,15 @@ mod v033 {
             backend.load_font_bytes_with_name(font, bytes);
         }
 
-        root
+        Ok(root)
     }
 }
 
 mod v034 {
     use std::io::Cursor;
 
-    use crate::{Animation, ImageNodeTemplate, NodeTransform, SublayoutNodeTemplate, template::{NodeVisibility, TextAlignment}};
+    use super::{v036::Animation, v037::ImageNodeTemplate};
+    use crate::{NodeTransform, SublayoutNodeTemplate, template::{NodeVisibility, TextAlignment}};
 
     #[derive(bincode::Encode, bincode::Decode, Clone)]
     struct TextNodeTemplate {
@@ -1173,7 +1538,7 @@ mod v034 {
             use NodeImplTemplate as N;
             match value {
                 N::Empty => Self::Empty,
-                N::Image(image) => Self::Image(image),
+                N::Image(image) => Self::Image(image.into()),
                 N::Text(text) => Self::Text(text.into()),
                 N::Sublayout(sublayout) => Self::Sublayout(sublayout),
             }
@@ -1197,6 +1562,7 @@ mod v034 {
                 transform: value.transform,
                 color: value.color,
                 visibility: value.visibility,
+                gradient: None,
                 children: value.children.into_iter().map(Into::into).collect(),
                 implementation: value.implementation.into(),
             }
@@ -1220,6 +1586,8 @@ mod v034 {
                     .into_iter()
                     .map(|(name, anim)| (name, anim.into()))
                     .collect(),
+                state_machine: None,
+                inheritance: None,
             }
         }
     }
@@ -1235,9 +1603,9 @@ mod v034 {
     pub(super) fn deserialize<B: crate::EnvyBackend, A: super::EnvyAssetProvider>(
         backend: &mut A,
         reader: &mut Cursor<&[u8]>,
-    ) -> crate::LayoutRoot<B> {
+    ) -> Result<crate::LayoutRoot<B>, super::DeserializeError> {
         let asset: Asset =
-            bincode::decode_from_std_read(reader, bincode::config::standard()).unwrap();
+            bincode::decode_from_std_read(reader, bincode::config::standard())?;
 
         let mut root_template = crate::LayoutTemplate::from(asset.root_template);
 
@@ -1281,140 +1649,143 @@ mod v034 {
             backend.load_font_bytes_with_name(font, bytes);
         }
 
-        root
+        Ok(root)
     }
 }
 
-#[derive(Decode, Encode)]
-struct Asset {
-    images: Vec<(String, Vec<u8>)>,
-    fonts: Vec<(String, Vec<u8>)>,
-    templates: Vec<(String, LayoutTemplate)>,
-    root_template: LayoutTemplate,
-}
+mod v036 {
+    use std::io::Cursor;
 
-pub trait EnvyAssetProvider {
-    fn load_image_bytes_with_name(&mut self, name: String, bytes: Vec<u8>);
-    fn load_font_bytes_with_name(&mut self, name: String, bytes: Vec<u8>);
+    use super::v037::NodeImplTemplate;
+    use super::legacy::AnimationChannel;
+    use crate::{NodeTransform, template::NodeVisibility};
 
-    fn fetch_image_bytes_by_name<'a>(&'a self, name: &str) -> Cow<'a, [u8]>;
-    fn fetch_font_bytes_by_name<'a>(&'a self, name: &str) -> Cow<'a, [u8]>;
-}
+    #[derive(Clone, bincode::Encode, bincode::Decode)]
+    struct NodeTemplate {
+        name: String,
+        transform: NodeTransform,
+        color: [u8; 4],
+        visibility: NodeVisibility,
+        children: Vec<NodeTemplate>,
+        implementation: NodeImplTemplate,
+    }
 
-pub fn serialize<B: EnvyBackend + EnvyAssetProvider>(
-    root: &crate::LayoutRoot<B>,
-    backend: &B,
-) -> Vec<u8> {
-    let mut serialized_images = HashSet::new();
-    let mut serialized_fonts = HashSet::new();
+    impl From<NodeTemplate> for crate::NodeTemplate {
+        fn from(value: NodeTemplate) -> Self {
+            Self {
+                name: value.name,
+                transform: value.transform,
+                color: value.color,
+                visibility: value.visibility,
+                gradient: None,
+                children: value.children.into_iter().map(Into::into).collect(),
+                implementation: value.implementation.into(),
+            }
+        }
+    }
 
-    let mut asset = Asset {
-        images: vec![],
-        fonts: vec![],
-        templates: vec![],
-        root_template: LayoutTemplate::default(),
-    };
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct NodeAnimation {
+        node_path: String,
+        angle_channel: Option<AnimationChannel<f32>>,
+        position_channel: Option<AnimationChannel<glam::Vec2>>,
+        size_channel: Option<AnimationChannel<glam::Vec2>>,
+        scale_channel: Option<AnimationChannel<glam::Vec2>>,
+        color_channel: Option<AnimationChannel<[u8; 4]>>,
+        uv_offset_channel: Option<AnimationChannel<glam::Vec2>>,
+        uv_scale_channel: Option<AnimationChannel<glam::Vec2>>,
+    }
 
-    asset.templates = root
-        .iter_templates()
-        .into_iter()
-        .map(|(name, template)| (name.to_string(), template.clone()))
-        .collect::<Vec<_>>();
-    asset.root_template = root.root_template().clone();
+    impl From<NodeAnimation> for crate::animations::NodeAnimation {
+        fn from(value: NodeAnimation) -> Self {
+            Self {
+                node_path: value.node_path,
+                angle_channel: value.angle_channel.map(Into::into),
+                position_channel: value.position_channel.map(Into::into),
+                motion_path: None,
+                size_channel: value.size_channel.map(Into::into),
+                scale_channel: value.scale_channel.map(Into::into),
+                color_channel: value.color_channel.map(Into::into),
+                uv_offset_channel: value.uv_offset_channel.map(Into::into),
+                uv_scale_channel: value.uv_scale_channel.map(Into::into),
+                fill_amount_channel: None,
+                font_size_channel: None,
+                line_height_channel: None,
+                outline_thickness_channel: None,
+                outline_color_channel: None,
+                visible_characters_channel: None,
+                visibility_channel: None,
+                texture_channel: None,
+                text_channel: None,
+                sublayout_animation_channel: None,
+                gradient_stop_color_channels: vec![],
+                baked: None,
+            }
+        }
+    }
 
-    for template in [&asset.root_template]
-        .into_iter()
-        .chain(asset.templates.iter().map(|(_, template)| template))
-    {
-        template.walk_tree(|node| match &node.implementation {
-            NodeImplTemplate::Image(image) => {
-                if !serialized_images.contains(&image.texture_name) {
-                    serialized_images.insert(image.texture_name.clone());
-                    asset.images.push((
-                        image.texture_name.clone(),
-                        backend
-                            .fetch_image_bytes_by_name(&image.texture_name)
-                            .to_vec(),
-                    ))
-                }
+    #[derive(bincode::Encode, bincode::Decode)]
+    pub(super) struct Animation {
+        node_animations: Vec<NodeAnimation>,
+        total_duration: usize,
+    }
 
-                if let Some(mask_name) = image.mask_texture_name.as_ref() {
-                    if !serialized_images.contains(mask_name) {
-                        serialized_images.insert(mask_name.clone());
-                        asset.images.push((
-                            mask_name.clone(),
-                            backend.fetch_image_bytes_by_name(&mask_name).to_vec(),
-                        ))
-                    }
-                }
-            }
-            NodeImplTemplate::Text(text) if !serialized_fonts.contains(&text.font_name) => {
-                serialized_fonts.insert(text.font_name.clone());
-                asset.fonts.push((
-                    text.font_name.clone(),
-                    backend.fetch_font_bytes_by_name(&text.font_name).to_vec(),
-                ))
+    impl From<Animation> for crate::animations::Animation {
+        fn from(value: Animation) -> Self {
+            Self {
+                node_animations: value.node_animations.into_iter().map(Into::into).collect(),
+                total_duration: value.total_duration,
             }
-            _ => {}
-        })
+        }
     }
 
-    let mut output = std::io::Cursor::new(vec![]);
-    let _ = bincode::encode_into_std_write(
-        Version::current(),
-        &mut output,
-        bincode::config::standard(),
-    )
-    .unwrap();
-    let _ =
-        bincode::encode_into_std_write(asset, &mut output, bincode::config::standard()).unwrap();
-    output.into_inner()
-}
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct LayoutTemplate {
+        canvas_size: [u32; 2],
+        root_nodes: Vec<NodeTemplate>,
+        animations: Vec<(String, Animation)>,
+    }
 
-pub fn deserialize<B: EnvyBackend, A: EnvyAssetProvider>(
-    asset_provider: &mut A,
-    bytes: &[u8],
-) -> crate::LayoutRoot<B> {
-    let mut reader = std::io::Cursor::new(bytes);
-    let version: Version =
-        bincode::decode_from_std_read(&mut reader, bincode::config::standard()).unwrap();
+    impl From<LayoutTemplate> for crate::LayoutTemplate {
+        fn from(value: LayoutTemplate) -> Self {
+            Self {
+                canvas_size: value.canvas_size,
+                root_nodes: value.root_nodes.into_iter().map(Into::into).collect(),
+                animations: value
+                    .animations
+                    .into_iter()
+                    .map(|(name, anim)| (name, anim.into()))
+                    .collect(),
+                state_machine: None,
+                inheritance: None,
+            }
+        }
+    }
 
-    if version == Version::new(0, 1, 0) {
-        return v010::deserialize(asset_provider, &mut reader);
-    } else if version == Version::new(0, 2, 0) {
-        return v020::deserialize(asset_provider, &mut reader);
-    } else if version == Version::new(0, 2, 1) {
-        return v021::deserialize(asset_provider, &mut reader);
-    } else if version == Version::new(0, 3, 0) {
-        return v030::deserialize(asset_provider, &mut reader);
-    } else if version == Version::new(0, 3, 1) {
-        return v031::deserialize(asset_provider, &mut reader);
-    } else if version == Version::new(0, 3, 2) {
-        return v032::deserialize(asset_provider, &mut reader);
-    } else if version == Version::new(0, 3, 3) {
-        return v033::deserialize(asset_provider, &mut reader);
-    } else if version == Version::new(0, 3, 4) {
-        return v034::deserialize(asset_provider, &mut reader);
+    #[derive(bincode::Decode, bincode::Encode)]
+    struct Asset {
+        images: Vec<(String, Vec<u8>)>,
+        fonts: Vec<(String, Vec<u8>)>,
+        templates: Vec<(String, LayoutTemplate)>,
+        root_template: LayoutTemplate,
     }
 
-    assert_eq!(version, Version::current());
+    pub(super) fn deserialize<B: crate::EnvyBackend, A: super::EnvyAssetProvider>(
+        backend: &mut A,
+        reader: &mut Cursor<&[u8]>,
+    ) -> Result<crate::LayoutRoot<B>, super::DeserializeError> {
+        let asset: Asset =
+            bincode::decode_from_std_read(reader, bincode::config::standard())?;
 
-    let mut asset: Asset =
-        bincode::decode_from_std_read(&mut reader, bincode::config::standard()).unwrap();
+        let mut root_template = crate::LayoutTemplate::from(asset.root_template);
 
-    asset.root_template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
-        #[cfg(target_os = "windows")]
-        {
-            anim.node_path = anim.node_path.replace("/", "\\");
-        }
-        #[cfg(not(target_os = "windows"))]
-        {
-            anim.node_path = anim.node_path.replace("\\", "/");
-        }
-    }));
+        let mut templates = asset
+            .templates
+            .into_iter()
+            .map(|(name, template)| (name, crate::LayoutTemplate::from(template)))
+            .collect::<Vec<_>>();
 
-    asset.templates.iter_mut().for_each(|(_, template)| {
-        template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
+        root_template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
             #[cfg(target_os = "windows")]
             {
                 anim.node_path = anim.node_path.replace("/", "\\");
@@ -1424,17 +1795,2327 @@ pub fn deserialize<B: EnvyBackend, A: EnvyAssetProvider>(
                 anim.node_path = anim.node_path.replace("\\", "/");
             }
         }));
-    });
 
-    let root = crate::LayoutRoot::from_root_template(asset.root_template, asset.templates);
-
-    for (image, bytes) in asset.images {
-        asset_provider.load_image_bytes_with_name(image, bytes);
-    }
+        templates.iter_mut().for_each(|(_, template)| {
+            template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
+                #[cfg(target_os = "windows")]
+                {
+                    anim.node_path = anim.node_path.replace("/", "\\");
+                }
+                #[cfg(not(target_os = "windows"))]
+                {
+                    anim.node_path = anim.node_path.replace("\\", "/");
+                }
+            }));
+        });
+
+        let root = crate::LayoutRoot::from_root_template(root_template, templates);
+
+        for (image, bytes) in asset.images {
+            backend.load_image_bytes_with_name(image, bytes);
+        }
+
+        for (font, bytes) in asset.fonts {
+            backend.load_font_bytes_with_name(font, bytes);
+        }
+
+        Ok(root)
+    }
+}
+
+mod v037 {
+    use std::io::Cursor;
+
+    use super::legacy::AnimationChannel;
+    use crate::{
+        ImageScalingMode, NodeTransform, ShapeNodeTemplate, SublayoutNodeTemplate, TextNodeTemplate,
+        template::{Gradient, NodeVisibility},
+    };
+
+    #[derive(bincode::Encode, bincode::Decode, Clone)]
+    pub(super) struct ImageNodeTemplate {
+        texture_name: String,
+        mask_texture_name: Option<String>,
+        image_scaling_mode_x: ImageScalingMode,
+        image_scaling_mode_y: ImageScalingMode,
+        uv_offset: [f32; 2],
+        uv_scale: [f32; 2],
+    }
+
+    impl From<ImageNodeTemplate> for crate::ImageNodeTemplate {
+        fn from(value: ImageNodeTemplate) -> Self {
+            Self {
+                texture_name: value.texture_name,
+                mask_texture_name: value.mask_texture_name,
+                image_scaling_mode_x: value.image_scaling_mode_x,
+                image_scaling_mode_y: value.image_scaling_mode_y,
+                uv_offset: value.uv_offset.into(),
+                uv_scale: value.uv_scale.into(),
+                fill_method: Default::default(),
+                fill_origin: Default::default(),
+                fill_amount: 1.0,
+            }
+        }
+    }
+
+    #[derive(bincode::Encode, bincode::Decode, Clone)]
+    pub(super) enum NodeImplTemplate {
+        Empty,
+        Image(ImageNodeTemplate),
+        Text(TextNodeTemplate),
+        Sublayout(SublayoutNodeTemplate),
+        Shape(ShapeNodeTemplate),
+    }
+
+    impl From<NodeImplTemplate> for crate::NodeImplTemplate {
+        fn from(value: NodeImplTemplate) -> Self {
+            use NodeImplTemplate as N;
+            match value {
+                N::Empty => Self::Empty,
+                N::Image(image) => Self::Image(image.into()),
+                N::Text(text) => Self::Text(text),
+                N::Sublayout(sublayout) => Self::Sublayout(sublayout),
+                N::Shape(shape) => Self::Shape(shape),
+            }
+        }
+    }
+
+    #[derive(Clone, bincode::Encode, bincode::Decode)]
+    struct NodeTemplate {
+        name: String,
+        transform: NodeTransform,
+        color: [u8; 4],
+        visibility: NodeVisibility,
+        gradient: Option<Gradient>,
+        children: Vec<NodeTemplate>,
+        implementation: NodeImplTemplate,
+    }
+
+    impl From<NodeTemplate> for crate::NodeTemplate {
+        fn from(value: NodeTemplate) -> Self {
+            Self {
+                name: value.name,
+                transform: value.transform,
+                color: value.color,
+                visibility: value.visibility,
+                gradient: value.gradient,
+                children: value.children.into_iter().map(Into::into).collect(),
+                implementation: value.implementation.into(),
+            }
+        }
+    }
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct NodeAnimation {
+        node_path: String,
+        angle_channel: Option<AnimationChannel<f32>>,
+        position_channel: Option<AnimationChannel<glam::Vec2>>,
+        size_channel: Option<AnimationChannel<glam::Vec2>>,
+        scale_channel: Option<AnimationChannel<glam::Vec2>>,
+        color_channel: Option<AnimationChannel<[u8; 4]>>,
+        uv_offset_channel: Option<AnimationChannel<glam::Vec2>>,
+        uv_scale_channel: Option<AnimationChannel<glam::Vec2>>,
+        gradient_stop_color_channels: Vec<Option<AnimationChannel<[u8; 4]>>>,
+    }
+
+    impl From<NodeAnimation> for crate::animations::NodeAnimation {
+        fn from(value: NodeAnimation) -> Self {
+            Self {
+                node_path: value.node_path,
+                angle_channel: value.angle_channel.map(Into::into),
+                position_channel: value.position_channel.map(Into::into),
+                motion_path: None,
+                size_channel: value.size_channel.map(Into::into),
+                scale_channel: value.scale_channel.map(Into::into),
+                color_channel: value.color_channel.map(Into::into),
+                uv_offset_channel: value.uv_offset_channel.map(Into::into),
+                uv_scale_channel: value.uv_scale_channel.map(Into::into),
+                fill_amount_channel: None,
+                font_size_channel: None,
+                line_height_channel: None,
+                outline_thickness_channel: None,
+                outline_color_channel: None,
+                visible_characters_channel: None,
+                visibility_channel: None,
+                texture_channel: None,
+                text_channel: None,
+                sublayout_animation_channel: None,
+                gradient_stop_color_channels: value
+                    .gradient_stop_color_channels
+                    .into_iter()
+                    .map(|channel| channel.map(Into::into))
+                    .collect(),
+                baked: None,
+            }
+        }
+    }
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct Animation {
+        node_animations: Vec<NodeAnimation>,
+        total_duration: usize,
+    }
+
+    impl From<Animation> for crate::animations::Animation {
+        fn from(value: Animation) -> Self {
+            Self {
+                node_animations: value.node_animations.into_iter().map(Into::into).collect(),
+                total_duration: value.total_duration,
+            }
+        }
+    }
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct LayoutTemplate {
+        canvas_size: [u32; 2],
+        root_nodes: Vec<NodeTemplate>,
+        animations: Vec<(String, Animation)>,
+    }
+
+    impl From<LayoutTemplate> for crate::LayoutTemplate {
+        fn from(value: LayoutTemplate) -> Self {
+            Self {
+                canvas_size: value.canvas_size,
+                root_nodes: value.root_nodes.into_iter().map(Into::into).collect(),
+                animations: value
+                    .animations
+                    .into_iter()
+                    .map(|(name, anim)| (name, anim.into()))
+                    .collect(),
+                state_machine: None,
+                inheritance: None,
+            }
+        }
+    }
+
+    #[derive(bincode::Decode, bincode::Encode)]
+    struct Asset {
+        images: Vec<(String, Vec<u8>)>,
+        fonts: Vec<(String, Vec<u8>)>,
+        templates: Vec<(String, LayoutTemplate)>,
+        root_template: LayoutTemplate,
+    }
+
+    pub(super) fn deserialize<B: crate::EnvyBackend, A: super::EnvyAssetProvider>(
+        backend: &mut A,
+        reader: &mut Cursor<&[u8]>,
+    ) -> Result<crate::LayoutRoot<B>, super::DeserializeError> {
+        let asset: Asset =
+            bincode::decode_from_std_read(reader, bincode::config::standard())?;
+
+        let mut root_template = crate::LayoutTemplate::from(asset.root_template);
+
+        let mut templates = asset
+            .templates
+            .into_iter()
+            .map(|(name, template)| (name, crate::LayoutTemplate::from(template)))
+            .collect::<Vec<_>>();
+
+        root_template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
+            #[cfg(target_os = "windows")]
+            {
+                anim.node_path = anim.node_path.replace("/", "\\");
+            }
+            #[cfg(not(target_os = "windows"))]
+            {
+                anim.node_path = anim.node_path.replace("\\", "/");
+            }
+        }));
+
+        templates.iter_mut().for_each(|(_, template)| {
+            template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
+                #[cfg(target_os = "windows")]
+                {
+                    anim.node_path = anim.node_path.replace("/", "\\");
+                }
+                #[cfg(not(target_os = "windows"))]
+                {
+                    anim.node_path = anim.node_path.replace("\\", "/");
+                }
+            }));
+        });
+
+        let root = crate::LayoutRoot::from_root_template(root_template, templates);
+
+        for (image, bytes) in asset.images {
+            backend.load_image_bytes_with_name(image, bytes);
+        }
+
+        for (font, bytes) in asset.fonts {
+            backend.load_font_bytes_with_name(font, bytes);
+        }
+
+        Ok(root)
+    }
+}
+
+mod v038 {
+    use std::io::Cursor;
+
+    use super::legacy::AnimationChannel;
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct NodeAnimation {
+        node_path: String,
+        angle_channel: Option<AnimationChannel<f32>>,
+        position_channel: Option<AnimationChannel<glam::Vec2>>,
+        size_channel: Option<AnimationChannel<glam::Vec2>>,
+        scale_channel: Option<AnimationChannel<glam::Vec2>>,
+        color_channel: Option<AnimationChannel<[u8; 4]>>,
+        uv_offset_channel: Option<AnimationChannel<glam::Vec2>>,
+        uv_scale_channel: Option<AnimationChannel<glam::Vec2>>,
+        fill_amount_channel: Option<AnimationChannel<f32>>,
+        gradient_stop_color_channels: Vec<Option<AnimationChannel<[u8; 4]>>>,
+    }
+
+    impl From<NodeAnimation> for crate::animations::NodeAnimation {
+        fn from(value: NodeAnimation) -> Self {
+            Self {
+                node_path: value.node_path,
+                angle_channel: value.angle_channel.map(Into::into),
+                position_channel: value.position_channel.map(Into::into),
+                motion_path: None,
+                size_channel: value.size_channel.map(Into::into),
+                scale_channel: value.scale_channel.map(Into::into),
+                color_channel: value.color_channel.map(Into::into),
+                uv_offset_channel: value.uv_offset_channel.map(Into::into),
+                uv_scale_channel: value.uv_scale_channel.map(Into::into),
+                fill_amount_channel: value.fill_amount_channel.map(Into::into),
+                font_size_channel: None,
+                line_height_channel: None,
+                outline_thickness_channel: None,
+                outline_color_channel: None,
+                visible_characters_channel: None,
+                visibility_channel: None,
+                texture_channel: None,
+                text_channel: None,
+                sublayout_animation_channel: None,
+                gradient_stop_color_channels: value
+                    .gradient_stop_color_channels
+                    .into_iter()
+                    .map(|channel| channel.map(Into::into))
+                    .collect(),
+                baked: None,
+            }
+        }
+    }
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct Animation {
+        node_animations: Vec<NodeAnimation>,
+        total_duration: usize,
+    }
+
+    impl From<Animation> for crate::animations::Animation {
+        fn from(value: Animation) -> Self {
+            Self {
+                node_animations: value.node_animations.into_iter().map(Into::into).collect(),
+                total_duration: value.total_duration,
+            }
+        }
+    }
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct LayoutTemplate {
+        canvas_size: [u32; 2],
+        root_nodes: Vec<crate::NodeTemplate>,
+        animations: Vec<(String, Animation)>,
+    }
+
+    impl From<LayoutTemplate> for crate::LayoutTemplate {
+        fn from(value: LayoutTemplate) -> Self {
+            Self {
+                canvas_size: value.canvas_size,
+                root_nodes: value.root_nodes,
+                animations: value
+                    .animations
+                    .into_iter()
+                    .map(|(name, anim)| (name, anim.into()))
+                    .collect(),
+                state_machine: None,
+                inheritance: None,
+            }
+        }
+    }
+
+    #[derive(bincode::Decode, bincode::Encode)]
+    struct Asset {
+        images: Vec<(String, Vec<u8>)>,
+        fonts: Vec<(String, Vec<u8>)>,
+        templates: Vec<(String, LayoutTemplate)>,
+        root_template: LayoutTemplate,
+    }
+
+    pub(super) fn deserialize<B: crate::EnvyBackend, A: super::EnvyAssetProvider>(
+        backend: &mut A,
+        reader: &mut Cursor<&[u8]>,
+    ) -> Result<crate::LayoutRoot<B>, super::DeserializeError> {
+        let asset: Asset =
+            bincode::decode_from_std_read(reader, bincode::config::standard())?;
+
+        let mut root_template = crate::LayoutTemplate::from(asset.root_template);
+
+        let mut templates = asset
+            .templates
+            .into_iter()
+            .map(|(name, template)| (name, crate::LayoutTemplate::from(template)))
+            .collect::<Vec<_>>();
+
+        root_template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
+            #[cfg(target_os = "windows")]
+            {
+                anim.node_path = anim.node_path.replace("/", "\\");
+            }
+            #[cfg(not(target_os = "windows"))]
+            {
+                anim.node_path = anim.node_path.replace("\\", "/");
+            }
+        }));
+
+        templates.iter_mut().for_each(|(_, template)| {
+            template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
+                #[cfg(target_os = "windows")]
+                {
+                    anim.node_path = anim.node_path.replace("/", "\\");
+                }
+                #[cfg(not(target_os = "windows"))]
+                {
+                    anim.node_path = anim.node_path.replace("\\", "/");
+                }
+            }));
+        });
+
+        let root = crate::LayoutRoot::from_root_template(root_template, templates);
+
+        for (image, bytes) in asset.images {
+            backend.load_image_bytes_with_name(image, bytes);
+        }
+
+        for (font, bytes) in asset.fonts {
+            backend.load_font_bytes_with_name(font, bytes);
+        }
+
+        Ok(root)
+    }
+}
+
+mod v039 {
+    use std::io::Cursor;
+
+    use super::legacy::AnimationChannel;
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct NodeAnimation {
+        node_path: String,
+        angle_channel: Option<AnimationChannel<f32>>,
+        position_channel: Option<AnimationChannel<glam::Vec2>>,
+        size_channel: Option<AnimationChannel<glam::Vec2>>,
+        scale_channel: Option<AnimationChannel<glam::Vec2>>,
+        color_channel: Option<AnimationChannel<[u8; 4]>>,
+        uv_offset_channel: Option<AnimationChannel<glam::Vec2>>,
+        uv_scale_channel: Option<AnimationChannel<glam::Vec2>>,
+        fill_amount_channel: Option<AnimationChannel<f32>>,
+        font_size_channel: Option<AnimationChannel<f32>>,
+        line_height_channel: Option<AnimationChannel<f32>>,
+        outline_thickness_channel: Option<AnimationChannel<f32>>,
+        outline_color_channel: Option<AnimationChannel<[u8; 4]>>,
+        visible_characters_channel: Option<AnimationChannel<f32>>,
+        gradient_stop_color_channels: Vec<Option<AnimationChannel<[u8; 4]>>>,
+    }
+
+    impl From<NodeAnimation> for crate::animations::NodeAnimation {
+        fn from(value: NodeAnimation) -> Self {
+            Self {
+                node_path: value.node_path,
+                angle_channel: value.angle_channel.map(Into::into),
+                position_channel: value.position_channel.map(Into::into),
+                motion_path: None,
+                size_channel: value.size_channel.map(Into::into),
+                scale_channel: value.scale_channel.map(Into::into),
+                color_channel: value.color_channel.map(Into::into),
+                uv_offset_channel: value.uv_offset_channel.map(Into::into),
+                uv_scale_channel: value.uv_scale_channel.map(Into::into),
+                fill_amount_channel: value.fill_amount_channel.map(Into::into),
+                font_size_channel: value.font_size_channel.map(Into::into),
+                line_height_channel: value.line_height_channel.map(Into::into),
+                outline_thickness_channel: value.outline_thickness_channel.map(Into::into),
+                outline_color_channel: value.outline_color_channel.map(Into::into),
+                visible_characters_channel: value.visible_characters_channel.map(Into::into),
+                visibility_channel: None,
+                texture_channel: None,
+                text_channel: None,
+                sublayout_animation_channel: None,
+                gradient_stop_color_channels: value
+                    .gradient_stop_color_channels
+                    .into_iter()
+                    .map(|channel| channel.map(Into::into))
+                    .collect(),
+                baked: None,
+            }
+        }
+    }
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct Animation {
+        node_animations: Vec<NodeAnimation>,
+        total_duration: usize,
+    }
+
+    impl From<Animation> for crate::animations::Animation {
+        fn from(value: Animation) -> Self {
+            Self {
+                node_animations: value.node_animations.into_iter().map(Into::into).collect(),
+                total_duration: value.total_duration,
+            }
+        }
+    }
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct LayoutTemplate {
+        canvas_size: [u32; 2],
+        root_nodes: Vec<crate::NodeTemplate>,
+        animations: Vec<(String, Animation)>,
+    }
+
+    impl From<LayoutTemplate> for crate::LayoutTemplate {
+        fn from(value: LayoutTemplate) -> Self {
+            Self {
+                canvas_size: value.canvas_size,
+                root_nodes: value.root_nodes,
+                animations: value
+                    .animations
+                    .into_iter()
+                    .map(|(name, anim)| (name, anim.into()))
+                    .collect(),
+                state_machine: None,
+                inheritance: None,
+            }
+        }
+    }
+
+    #[derive(bincode::Decode, bincode::Encode)]
+    struct Asset {
+        images: Vec<(String, Vec<u8>)>,
+        fonts: Vec<(String, Vec<u8>)>,
+        templates: Vec<(String, LayoutTemplate)>,
+        root_template: LayoutTemplate,
+    }
+
+    pub(super) fn deserialize<B: crate::EnvyBackend, A: super::EnvyAssetProvider>(
+        backend: &mut A,
+        reader: &mut Cursor<&[u8]>,
+    ) -> Result<crate::LayoutRoot<B>, super::DeserializeError> {
+        let asset: Asset =
+            bincode::decode_from_std_read(reader, bincode::config::standard())?;
+
+        let mut root_template = crate::LayoutTemplate::from(asset.root_template);
+
+        let mut templates = asset
+            .templates
+            .into_iter()
+            .map(|(name, template)| (name, crate::LayoutTemplate::from(template)))
+            .collect::<Vec<_>>();
+
+        root_template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
+            #[cfg(target_os = "windows")]
+            {
+                anim.node_path = anim.node_path.replace("/", "\\");
+            }
+            #[cfg(not(target_os = "windows"))]
+            {
+                anim.node_path = anim.node_path.replace("\\", "/");
+            }
+        }));
+
+        templates.iter_mut().for_each(|(_, template)| {
+            template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
+                #[cfg(target_os = "windows")]
+                {
+                    anim.node_path = anim.node_path.replace("/", "\\");
+                }
+                #[cfg(not(target_os = "windows"))]
+                {
+                    anim.node_path = anim.node_path.replace("\\", "/");
+                }
+            }));
+        });
+
+        let root = crate::LayoutRoot::from_root_template(root_template, templates);
+
+        for (image, bytes) in asset.images {
+            backend.load_image_bytes_with_name(image, bytes);
+        }
+
+        for (font, bytes) in asset.fonts {
+            backend.load_font_bytes_with_name(font, bytes);
+        }
+
+        Ok(root)
+    }
+}
+
+mod v040 {
+    use std::io::Cursor;
+
+    use super::legacy::AnimationChannel;
+    use crate::{DiscreteChannel, NodeVisibility};
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct NodeAnimation {
+        node_path: String,
+        angle_channel: Option<AnimationChannel<f32>>,
+        position_channel: Option<AnimationChannel<glam::Vec2>>,
+        size_channel: Option<AnimationChannel<glam::Vec2>>,
+        scale_channel: Option<AnimationChannel<glam::Vec2>>,
+        color_channel: Option<AnimationChannel<[u8; 4]>>,
+        visibility_channel: Option<DiscreteChannel<NodeVisibility>>,
+        uv_offset_channel: Option<AnimationChannel<glam::Vec2>>,
+        uv_scale_channel: Option<AnimationChannel<glam::Vec2>>,
+        fill_amount_channel: Option<AnimationChannel<f32>>,
+        texture_channel: Option<DiscreteChannel<String>>,
+        font_size_channel: Option<AnimationChannel<f32>>,
+        line_height_channel: Option<AnimationChannel<f32>>,
+        outline_thickness_channel: Option<AnimationChannel<f32>>,
+        outline_color_channel: Option<AnimationChannel<[u8; 4]>>,
+        visible_characters_channel: Option<AnimationChannel<f32>>,
+        text_channel: Option<DiscreteChannel<String>>,
+        gradient_stop_color_channels: Vec<Option<AnimationChannel<[u8; 4]>>>,
+    }
+
+    impl From<NodeAnimation> for crate::animations::NodeAnimation {
+        fn from(value: NodeAnimation) -> Self {
+            Self {
+                node_path: value.node_path,
+                angle_channel: value.angle_channel.map(Into::into),
+                position_channel: value.position_channel.map(Into::into),
+                motion_path: None,
+                size_channel: value.size_channel.map(Into::into),
+                scale_channel: value.scale_channel.map(Into::into),
+                color_channel: value.color_channel.map(Into::into),
+                uv_offset_channel: value.uv_offset_channel.map(Into::into),
+                uv_scale_channel: value.uv_scale_channel.map(Into::into),
+                fill_amount_channel: value.fill_amount_channel.map(Into::into),
+                font_size_channel: value.font_size_channel.map(Into::into),
+                line_height_channel: value.line_height_channel.map(Into::into),
+                outline_thickness_channel: value.outline_thickness_channel.map(Into::into),
+                outline_color_channel: value.outline_color_channel.map(Into::into),
+                visible_characters_channel: value.visible_characters_channel.map(Into::into),
+                visibility_channel: value.visibility_channel,
+                texture_channel: value.texture_channel,
+                text_channel: value.text_channel,
+                sublayout_animation_channel: None,
+                gradient_stop_color_channels: value
+                    .gradient_stop_color_channels
+                    .into_iter()
+                    .map(|channel| channel.map(Into::into))
+                    .collect(),
+                baked: None,
+            }
+        }
+    }
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct Animation {
+        node_animations: Vec<NodeAnimation>,
+        total_duration: usize,
+    }
+
+    impl From<Animation> for crate::animations::Animation {
+        fn from(value: Animation) -> Self {
+            Self {
+                node_animations: value.node_animations.into_iter().map(Into::into).collect(),
+                total_duration: value.total_duration,
+            }
+        }
+    }
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct LayoutTemplate {
+        canvas_size: [u32; 2],
+        root_nodes: Vec<crate::NodeTemplate>,
+        animations: Vec<(String, Animation)>,
+    }
+
+    impl From<LayoutTemplate> for crate::LayoutTemplate {
+        fn from(value: LayoutTemplate) -> Self {
+            Self {
+                canvas_size: value.canvas_size,
+                root_nodes: value.root_nodes,
+                animations: value
+                    .animations
+                    .into_iter()
+                    .map(|(name, anim)| (name, anim.into()))
+                    .collect(),
+                state_machine: None,
+                inheritance: None,
+            }
+        }
+    }
+
+    #[derive(bincode::Decode, bincode::Encode)]
+    struct Asset {
+        images: Vec<(String, Vec<u8>)>,
+        fonts: Vec<(String, Vec<u8>)>,
+        templates: Vec<(String, LayoutTemplate)>,
+        root_template: LayoutTemplate,
+    }
+
+    pub(super) fn deserialize<B: crate::EnvyBackend, A: super::EnvyAssetProvider>(
+        backend: &mut A,
+        reader: &mut Cursor<&[u8]>,
+    ) -> Result<crate::LayoutRoot<B>, super::DeserializeError> {
+        let asset: Asset =
+            bincode::decode_from_std_read(reader, bincode::config::standard())?;
+
+        let mut root_template = crate::LayoutTemplate::from(asset.root_template);
+
+        let mut templates = asset
+            .templates
+            .into_iter()
+            .map(|(name, template)| (name, crate::LayoutTemplate::from(template)))
+            .collect::<Vec<_>>();
+
+        root_template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
+            #[cfg(target_os = "windows")]
+            {
+                anim.node_path = anim.node_path.replace("/", "\\");
+            }
+            #[cfg(not(target_os = "windows"))]
+            {
+                anim.node_path = anim.node_path.replace("\\", "/");
+            }
+        }));
+
+        templates.iter_mut().for_each(|(_, template)| {
+            template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
+                #[cfg(target_os = "windows")]
+                {
+                    anim.node_path = anim.node_path.replace("/", "\\");
+                }
+                #[cfg(not(target_os = "windows"))]
+                {
+                    anim.node_path = anim.node_path.replace("\\", "/");
+                }
+            }));
+        });
+
+        let root = crate::LayoutRoot::from_root_template(root_template, templates);
+
+        for (image, bytes) in asset.images {
+            backend.load_image_bytes_with_name(image, bytes);
+        }
+
+        for (font, bytes) in asset.fonts {
+            backend.load_font_bytes_with_name(font, bytes);
+        }
+
+        Ok(root)
+    }
+}
+
+mod v041 {
+    use std::io::Cursor;
+
+    use super::v042::Animation;
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct LayoutTemplate {
+        canvas_size: [u32; 2],
+        root_nodes: Vec<crate::NodeTemplate>,
+        animations: Vec<(String, Animation)>,
+    }
+
+    impl From<LayoutTemplate> for crate::LayoutTemplate {
+        fn from(value: LayoutTemplate) -> Self {
+            Self {
+                canvas_size: value.canvas_size,
+                root_nodes: value.root_nodes,
+                animations: value
+                    .animations
+                    .into_iter()
+                    .map(|(name, anim)| (name, anim.into()))
+                    .collect(),
+                state_machine: None,
+                inheritance: None,
+            }
+        }
+    }
+
+    #[derive(bincode::Decode, bincode::Encode)]
+    struct Asset {
+        images: Vec<(String, Vec<u8>)>,
+        fonts: Vec<(String, Vec<u8>)>,
+        templates: Vec<(String, LayoutTemplate)>,
+        root_template: LayoutTemplate,
+    }
+
+    pub(super) fn deserialize<B: crate::EnvyBackend, A: super::EnvyAssetProvider>(
+        backend: &mut A,
+        reader: &mut Cursor<&[u8]>,
+    ) -> Result<crate::LayoutRoot<B>, super::DeserializeError> {
+        let asset: Asset =
+            bincode::decode_from_std_read(reader, bincode::config::standard())?;
+
+        let mut root_template = crate::LayoutTemplate::from(asset.root_template);
+
+        let mut templates = asset
+            .templates
+            .into_iter()
+            .map(|(name, template)| (name, crate::LayoutTemplate::from(template)))
+            .collect::<Vec<_>>();
+
+        root_template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
+            #[cfg(target_os = "windows")]
+            {
+                anim.node_path = anim.node_path.replace("/", "\\");
+            }
+            #[cfg(not(target_os = "windows"))]
+            {
+                anim.node_path = anim.node_path.replace("\\", "/");
+            }
+        }));
+
+        templates.iter_mut().for_each(|(_, template)| {
+            template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
+                #[cfg(target_os = "windows")]
+                {
+                    anim.node_path = anim.node_path.replace("/", "\\");
+                }
+                #[cfg(not(target_os = "windows"))]
+                {
+                    anim.node_path = anim.node_path.replace("\\", "/");
+                }
+            }));
+        });
+
+        let root = crate::LayoutRoot::from_root_template(root_template, templates);
+
+        for (image, bytes) in asset.images {
+            backend.load_image_bytes_with_name(image, bytes);
+        }
+
+        for (font, bytes) in asset.fonts {
+            backend.load_font_bytes_with_name(font, bytes);
+        }
+
+        Ok(root)
+    }
+}
+
+mod v042 {
+    use std::io::Cursor;
+
+    use super::legacy::ModalAnimationChannel as AnimationChannel;
+    use crate::{DiscreteChannel, NodeVisibility};
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct NodeAnimation {
+        node_path: String,
+        angle_channel: Option<AnimationChannel<f32>>,
+        position_channel: Option<AnimationChannel<glam::Vec2>>,
+        size_channel: Option<AnimationChannel<glam::Vec2>>,
+        scale_channel: Option<AnimationChannel<glam::Vec2>>,
+        color_channel: Option<AnimationChannel<[u8; 4]>>,
+        visibility_channel: Option<DiscreteChannel<NodeVisibility>>,
+        uv_offset_channel: Option<AnimationChannel<glam::Vec2>>,
+        uv_scale_channel: Option<AnimationChannel<glam::Vec2>>,
+        fill_amount_channel: Option<AnimationChannel<f32>>,
+        texture_channel: Option<DiscreteChannel<String>>,
+        font_size_channel: Option<AnimationChannel<f32>>,
+        line_height_channel: Option<AnimationChannel<f32>>,
+        outline_thickness_channel: Option<AnimationChannel<f32>>,
+        outline_color_channel: Option<AnimationChannel<[u8; 4]>>,
+        visible_characters_channel: Option<AnimationChannel<f32>>,
+        text_channel: Option<DiscreteChannel<String>>,
+        gradient_stop_color_channels: Vec<Option<AnimationChannel<[u8; 4]>>>,
+    }
+
+    impl From<NodeAnimation> for crate::animations::NodeAnimation {
+        fn from(value: NodeAnimation) -> Self {
+            Self {
+                node_path: value.node_path,
+                angle_channel: value.angle_channel.map(Into::into),
+                position_channel: value.position_channel.map(Into::into),
+                motion_path: None,
+                size_channel: value.size_channel.map(Into::into),
+                scale_channel: value.scale_channel.map(Into::into),
+                color_channel: value.color_channel.map(Into::into),
+                uv_offset_channel: value.uv_offset_channel.map(Into::into),
+                uv_scale_channel: value.uv_scale_channel.map(Into::into),
+                fill_amount_channel: value.fill_amount_channel.map(Into::into),
+                font_size_channel: value.font_size_channel.map(Into::into),
+                line_height_channel: value.line_height_channel.map(Into::into),
+                outline_thickness_channel: value.outline_thickness_channel.map(Into::into),
+                outline_color_channel: value.outline_color_channel.map(Into::into),
+                visible_characters_channel: value.visible_characters_channel.map(Into::into),
+                visibility_channel: value.visibility_channel,
+                texture_channel: value.texture_channel,
+                text_channel: value.text_channel,
+                sublayout_animation_channel: None,
+                gradient_stop_color_channels: value
+                    .gradient_stop_color_channels
+                    .into_iter()
+                    .map(|channel| channel.map(Into::into))
+                    .collect(),
+                baked: None,
+            }
+        }
+    }
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    pub(super) struct Animation {
+        node_animations: Vec<NodeAnimation>,
+        total_duration: usize,
+    }
+
+    impl From<Animation> for crate::animations::Animation {
+        fn from(value: Animation) -> Self {
+            Self {
+                node_animations: value.node_animations.into_iter().map(Into::into).collect(),
+                total_duration: value.total_duration,
+            }
+        }
+    }
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct LayoutTemplate {
+        canvas_size: [u32; 2],
+        root_nodes: Vec<crate::NodeTemplate>,
+        animations: Vec<(String, Animation)>,
+        state_machine: Option<crate::StateMachine>,
+    }
+
+    impl From<LayoutTemplate> for crate::LayoutTemplate {
+        fn from(value: LayoutTemplate) -> Self {
+            Self {
+                canvas_size: value.canvas_size,
+                root_nodes: value.root_nodes,
+                animations: value
+                    .animations
+                    .into_iter()
+                    .map(|(name, anim)| (name, anim.into()))
+                    .collect(),
+                state_machine: value.state_machine,
+                inheritance: None,
+            }
+        }
+    }
+
+    #[derive(bincode::Decode, bincode::Encode)]
+    struct Asset {
+        images: Vec<(String, Vec<u8>)>,
+        fonts: Vec<(String, Vec<u8>)>,
+        templates: Vec<(String, LayoutTemplate)>,
+        root_template: LayoutTemplate,
+    }
+
+    pub(super) fn deserialize<B: crate::EnvyBackend, A: super::EnvyAssetProvider>(
+        backend: &mut A,
+        reader: &mut Cursor<&[u8]>,
+    ) -> Result<crate::LayoutRoot<B>, super::DeserializeError> {
+        let asset: Asset =
+            bincode::decode_from_std_read(reader, bincode::config::standard())?;
+
+        let mut root_template = crate::LayoutTemplate::from(asset.root_template);
+
+        let mut templates = asset
+            .templates
+            .into_iter()
+            .map(|(name, template)| (name, crate::LayoutTemplate::from(template)))
+            .collect::<Vec<_>>();
+
+        root_template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
+            #[cfg(target_os = "windows")]
+            {
+                anim.node_path = anim.node_path.replace("/", "\\");
+            }
+            #[cfg(not(target_os = "windows"))]
+            {
+                anim.node_path = anim.node_path.replace("\\", "/");
+            }
+        }));
+
+        templates.iter_mut().for_each(|(_, template)| {
+            template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
+                #[cfg(target_os = "windows")]
+                {
+                    anim.node_path = anim.node_path.replace("/", "\\");
+                }
+                #[cfg(not(target_os = "windows"))]
+                {
+                    anim.node_path = anim.node_path.replace("\\", "/");
+                }
+            }));
+        });
+
+        let root = crate::LayoutRoot::from_root_template(root_template, templates);
+
+        for (image, bytes) in asset.images {
+            backend.load_image_bytes_with_name(image, bytes);
+        }
+
+        for (font, bytes) in asset.fonts {
+            backend.load_font_bytes_with_name(font, bytes);
+        }
+
+        Ok(root)
+    }
+}
+
+mod v043 {
+    use std::io::Cursor;
+
+    use super::legacy::ModalAnimationChannel as AnimationChannel;
+    use crate::{DiscreteChannel, NodeVisibility};
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct NodeAnimation {
+        node_path: String,
+        angle_channel: Option<AnimationChannel<f32>>,
+        position_channel: Option<AnimationChannel<glam::Vec2>>,
+        size_channel: Option<AnimationChannel<glam::Vec2>>,
+        scale_channel: Option<AnimationChannel<glam::Vec2>>,
+        color_channel: Option<AnimationChannel<[u8; 4]>>,
+        visibility_channel: Option<DiscreteChannel<NodeVisibility>>,
+        uv_offset_channel: Option<AnimationChannel<glam::Vec2>>,
+        uv_scale_channel: Option<AnimationChannel<glam::Vec2>>,
+        fill_amount_channel: Option<AnimationChannel<f32>>,
+        texture_channel: Option<DiscreteChannel<String>>,
+        font_size_channel: Option<AnimationChannel<f32>>,
+        line_height_channel: Option<AnimationChannel<f32>>,
+        outline_thickness_channel: Option<AnimationChannel<f32>>,
+        outline_color_channel: Option<AnimationChannel<[u8; 4]>>,
+        visible_characters_channel: Option<AnimationChannel<f32>>,
+        text_channel: Option<DiscreteChannel<String>>,
+        sublayout_animation_channel: Option<DiscreteChannel<String>>,
+        gradient_stop_color_channels: Vec<Option<AnimationChannel<[u8; 4]>>>,
+    }
+
+    impl From<NodeAnimation> for crate::animations::NodeAnimation {
+        fn from(value: NodeAnimation) -> Self {
+            Self {
+                node_path: value.node_path,
+                angle_channel: value.angle_channel.map(Into::into),
+                position_channel: value.position_channel.map(Into::into),
+                motion_path: None,
+                size_channel: value.size_channel.map(Into::into),
+                scale_channel: value.scale_channel.map(Into::into),
+                color_channel: value.color_channel.map(Into::into),
+                uv_offset_channel: value.uv_offset_channel.map(Into::into),
+                uv_scale_channel: value.uv_scale_channel.map(Into::into),
+                fill_amount_channel: value.fill_amount_channel.map(Into::into),
+                font_size_channel: value.font_size_channel.map(Into::into),
+                line_height_channel: value.line_height_channel.map(Into::into),
+                outline_thickness_channel: value.outline_thickness_channel.map(Into::into),
+                outline_color_channel: value.outline_color_channel.map(Into::into),
+                visible_characters_channel: value.visible_characters_channel.map(Into::into),
+                visibility_channel: value.visibility_channel,
+                texture_channel: value.texture_channel,
+                text_channel: value.text_channel,
+                sublayout_animation_channel: value.sublayout_animation_channel,
+                gradient_stop_color_channels: value
+                    .gradient_stop_color_channels
+                    .into_iter()
+                    .map(|channel| channel.map(Into::into))
+                    .collect(),
+                baked: None,
+            }
+        }
+    }
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct Animation {
+        node_animations: Vec<NodeAnimation>,
+        total_duration: usize,
+    }
+
+    impl From<Animation> for crate::animations::Animation {
+        fn from(value: Animation) -> Self {
+            Self {
+                node_animations: value.node_animations.into_iter().map(Into::into).collect(),
+                total_duration: value.total_duration,
+            }
+        }
+    }
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct LayoutTemplate {
+        canvas_size: [u32; 2],
+        root_nodes: Vec<crate::NodeTemplate>,
+        animations: Vec<(String, Animation)>,
+        state_machine: Option<crate::StateMachine>,
+    }
+
+    impl From<LayoutTemplate> for crate::LayoutTemplate {
+        fn from(value: LayoutTemplate) -> Self {
+            Self {
+                canvas_size: value.canvas_size,
+                root_nodes: value.root_nodes,
+                animations: value
+                    .animations
+                    .into_iter()
+                    .map(|(name, anim)| (name, anim.into()))
+                    .collect(),
+                state_machine: value.state_machine,
+                inheritance: None,
+            }
+        }
+    }
+
+    #[derive(bincode::Decode, bincode::Encode)]
+    struct Asset {
+        images: Vec<(String, Vec<u8>)>,
+        fonts: Vec<(String, Vec<u8>)>,
+        templates: Vec<(String, LayoutTemplate)>,
+        root_template: LayoutTemplate,
+    }
+
+    pub(super) fn deserialize<B: crate::EnvyBackend, A: super::EnvyAssetProvider>(
+        backend: &mut A,
+        reader: &mut Cursor<&[u8]>,
+    ) -> Result<crate::LayoutRoot<B>, super::DeserializeError> {
+        let asset: Asset =
+            bincode::decode_from_std_read(reader, bincode::config::standard())?;
+
+        let mut root_template = crate::LayoutTemplate::from(asset.root_template);
+
+        let mut templates = asset
+            .templates
+            .into_iter()
+            .map(|(name, template)| (name, crate::LayoutTemplate::from(template)))
+            .collect::<Vec<_>>();
+
+        root_template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
+            #[cfg(target_os = "windows")]
+            {
+                anim.node_path = anim.node_path.replace("/", "\\");
+            }
+            #[cfg(not(target_os = "windows"))]
+            {
+                anim.node_path = anim.node_path.replace("\\", "/");
+            }
+        }));
+
+        templates.iter_mut().for_each(|(_, template)| {
+            template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
+                #[cfg(target_os = "windows")]
+                {
+                    anim.node_path = anim.node_path.replace("/", "\\");
+                }
+                #[cfg(not(target_os = "windows"))]
+                {
+                    anim.node_path = anim.node_path.replace("\\", "/");
+                }
+            }));
+        });
+
+        let root = crate::LayoutRoot::from_root_template(root_template, templates);
+
+        for (image, bytes) in asset.images {
+            backend.load_image_bytes_with_name(image, bytes);
+        }
+
+        for (font, bytes) in asset.fonts {
+            backend.load_font_bytes_with_name(font, bytes);
+        }
+
+        Ok(root)
+    }
+}
+
+mod v044 {
+    use std::io::Cursor;
+
+    use crate::{AnimationChannel, DiscreteChannel, NodeVisibility};
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct NodeAnimation {
+        node_path: String,
+        angle_channel: Option<AnimationChannel<f32>>,
+        position_channel: Option<AnimationChannel<glam::Vec2>>,
+        size_channel: Option<AnimationChannel<glam::Vec2>>,
+        scale_channel: Option<AnimationChannel<glam::Vec2>>,
+        color_channel: Option<AnimationChannel<[u8; 4]>>,
+        visibility_channel: Option<DiscreteChannel<NodeVisibility>>,
+        uv_offset_channel: Option<AnimationChannel<glam::Vec2>>,
+        uv_scale_channel: Option<AnimationChannel<glam::Vec2>>,
+        fill_amount_channel: Option<AnimationChannel<f32>>,
+        texture_channel: Option<DiscreteChannel<String>>,
+        font_size_channel: Option<AnimationChannel<f32>>,
+        line_height_channel: Option<AnimationChannel<f32>>,
+        outline_thickness_channel: Option<AnimationChannel<f32>>,
+        outline_color_channel: Option<AnimationChannel<[u8; 4]>>,
+        visible_characters_channel: Option<AnimationChannel<f32>>,
+        text_channel: Option<DiscreteChannel<String>>,
+        sublayout_animation_channel: Option<DiscreteChannel<String>>,
+        gradient_stop_color_channels: Vec<Option<AnimationChannel<[u8; 4]>>>,
+    }
+
+    impl From<NodeAnimation> for crate::animations::NodeAnimation {
+        fn from(value: NodeAnimation) -> Self {
+            Self {
+                node_path: value.node_path,
+                angle_channel: value.angle_channel,
+                position_channel: value.position_channel,
+                motion_path: None,
+                size_channel: value.size_channel,
+                scale_channel: value.scale_channel,
+                color_channel: value.color_channel,
+                uv_offset_channel: value.uv_offset_channel,
+                uv_scale_channel: value.uv_scale_channel,
+                fill_amount_channel: value.fill_amount_channel,
+                font_size_channel: value.font_size_channel,
+                line_height_channel: value.line_height_channel,
+                outline_thickness_channel: value.outline_thickness_channel,
+                outline_color_channel: value.outline_color_channel,
+                visible_characters_channel: value.visible_characters_channel,
+                visibility_channel: value.visibility_channel,
+                texture_channel: value.texture_channel,
+                text_channel: value.text_channel,
+                sublayout_animation_channel: value.sublayout_animation_channel,
+                gradient_stop_color_channels: value.gradient_stop_color_channels,
+                baked: None,
+            }
+        }
+    }
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct Animation {
+        node_animations: Vec<NodeAnimation>,
+        total_duration: usize,
+    }
+
+    impl From<Animation> for crate::animations::Animation {
+        fn from(value: Animation) -> Self {
+            Self {
+                node_animations: value.node_animations.into_iter().map(Into::into).collect(),
+                total_duration: value.total_duration,
+            }
+        }
+    }
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct LayoutTemplate {
+        canvas_size: [u32; 2],
+        root_nodes: Vec<crate::NodeTemplate>,
+        animations: Vec<(String, Animation)>,
+        state_machine: Option<crate::StateMachine>,
+    }
+
+    impl From<LayoutTemplate> for crate::LayoutTemplate {
+        fn from(value: LayoutTemplate) -> Self {
+            Self {
+                canvas_size: value.canvas_size,
+                root_nodes: value.root_nodes,
+                animations: value
+                    .animations
+                    .into_iter()
+                    .map(|(name, anim)| (name, anim.into()))
+                    .collect(),
+                state_machine: value.state_machine,
+                inheritance: None,
+            }
+        }
+    }
+
+    #[derive(bincode::Decode, bincode::Encode)]
+    struct Asset {
+        images: Vec<(String, Vec<u8>)>,
+        fonts: Vec<(String, Vec<u8>)>,
+        templates: Vec<(String, LayoutTemplate)>,
+        root_template: LayoutTemplate,
+    }
+
+    pub(super) fn deserialize<B: crate::EnvyBackend, A: super::EnvyAssetProvider>(
+        backend: &mut A,
+        reader: &mut Cursor<&[u8]>,
+    ) -> Result<crate::LayoutRoot<B>, super::DeserializeError> {
+        let asset: Asset =
+            bincode::decode_from_std_read(reader, bincode::config::standard())?;
+
+        let mut root_template = crate::LayoutTemplate::from(asset.root_template);
+
+        let mut templates = asset
+            .templates
+            .into_iter()
+            .map(|(name, template)| (name, crate::LayoutTemplate::from(template)))
+            .collect::<Vec<_>>();
+
+        root_template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
+            #[cfg(target_os = "windows")]
+            {
+                anim.node_path = anim.node_path.replace("/", "\\");
+            }
+            #[cfg(not(target_os = "windows"))]
+            {
+                anim.node_path = anim.node_path.replace("\\", "/");
+            }
+        }));
+
+        templates.iter_mut().for_each(|(_, template)| {
+            template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
+                #[cfg(target_os = "windows")]
+                {
+                    anim.node_path = anim.node_path.replace("/", "\\");
+                }
+                #[cfg(not(target_os = "windows"))]
+                {
+                    anim.node_path = anim.node_path.replace("\\", "/");
+                }
+            }));
+        });
+
+        let root = crate::LayoutRoot::from_root_template(root_template, templates);
+
+        for (image, bytes) in asset.images {
+            backend.load_image_bytes_with_name(image, bytes);
+        }
+
+        for (font, bytes) in asset.fonts {
+            backend.load_font_bytes_with_name(font, bytes);
+        }
+
+        Ok(root)
+    }
+}
+
+mod v045 {
+    use std::io::Cursor;
+
+    use crate::{AnimationChannel, DiscreteChannel, NodeVisibility};
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct NodeAnimation {
+        node_path: String,
+        angle_channel: Option<AnimationChannel<f32>>,
+        position_channel: Option<AnimationChannel<glam::Vec2>>,
+        motion_path: Option<crate::MotionPath>,
+        size_channel: Option<AnimationChannel<glam::Vec2>>,
+        scale_channel: Option<AnimationChannel<glam::Vec2>>,
+        color_channel: Option<AnimationChannel<[u8; 4]>>,
+        visibility_channel: Option<DiscreteChannel<NodeVisibility>>,
+        uv_offset_channel: Option<AnimationChannel<glam::Vec2>>,
+        uv_scale_channel: Option<AnimationChannel<glam::Vec2>>,
+        fill_amount_channel: Option<AnimationChannel<f32>>,
+        texture_channel: Option<DiscreteChannel<String>>,
+        font_size_channel: Option<AnimationChannel<f32>>,
+        line_height_channel: Option<AnimationChannel<f32>>,
+        outline_thickness_channel: Option<AnimationChannel<f32>>,
+        outline_color_channel: Option<AnimationChannel<[u8; 4]>>,
+        visible_characters_channel: Option<AnimationChannel<f32>>,
+        text_channel: Option<DiscreteChannel<String>>,
+        sublayout_animation_channel: Option<DiscreteChannel<String>>,
+        gradient_stop_color_channels: Vec<Option<AnimationChannel<[u8; 4]>>>,
+    }
+
+    impl From<NodeAnimation> for crate::animations::NodeAnimation {
+        fn from(value: NodeAnimation) -> Self {
+            Self {
+                node_path: value.node_path,
+                angle_channel: value.angle_channel,
+                position_channel: value.position_channel,
+                motion_path: value.motion_path,
+                size_channel: value.size_channel,
+                scale_channel: value.scale_channel,
+                color_channel: value.color_channel,
+                uv_offset_channel: value.uv_offset_channel,
+                uv_scale_channel: value.uv_scale_channel,
+                fill_amount_channel: value.fill_amount_channel,
+                font_size_channel: value.font_size_channel,
+                line_height_channel: value.line_height_channel,
+                outline_thickness_channel: value.outline_thickness_channel,
+                outline_color_channel: value.outline_color_channel,
+                visible_characters_channel: value.visible_characters_channel,
+                visibility_channel: value.visibility_channel,
+                texture_channel: value.texture_channel,
+                text_channel: value.text_channel,
+                sublayout_animation_channel: value.sublayout_animation_channel,
+                gradient_stop_color_channels: value.gradient_stop_color_channels,
+                baked: None,
+            }
+        }
+    }
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct Animation {
+        node_animations: Vec<NodeAnimation>,
+        total_duration: usize,
+    }
+
+    impl From<Animation> for crate::animations::Animation {
+        fn from(value: Animation) -> Self {
+            Self {
+                node_animations: value.node_animations.into_iter().map(Into::into).collect(),
+                total_duration: value.total_duration,
+            }
+        }
+    }
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct LayoutTemplate {
+        canvas_size: [u32; 2],
+        root_nodes: Vec<crate::NodeTemplate>,
+        animations: Vec<(String, Animation)>,
+        state_machine: Option<crate::StateMachine>,
+    }
+
+    impl From<LayoutTemplate> for crate::LayoutTemplate {
+        fn from(value: LayoutTemplate) -> Self {
+            Self {
+                canvas_size: value.canvas_size,
+                root_nodes: value.root_nodes,
+                animations: value
+                    .animations
+                    .into_iter()
+                    .map(|(name, anim)| (name, anim.into()))
+                    .collect(),
+                state_machine: value.state_machine,
+                inheritance: None,
+            }
+        }
+    }
+
+    #[derive(bincode::Decode, bincode::Encode)]
+    struct Asset {
+        images: Vec<(String, Vec<u8>)>,
+        fonts: Vec<(String, Vec<u8>)>,
+        templates: Vec<(String, LayoutTemplate)>,
+        root_template: LayoutTemplate,
+    }
+
+    pub(super) fn deserialize<B: crate::EnvyBackend, A: super::EnvyAssetProvider>(
+        backend: &mut A,
+        reader: &mut Cursor<&[u8]>,
+    ) -> Result<crate::LayoutRoot<B>, super::DeserializeError> {
+        let asset: Asset =
+            bincode::decode_from_std_read(reader, bincode::config::standard())?;
+
+        let mut root_template = crate::LayoutTemplate::from(asset.root_template);
+
+        let mut templates = asset
+            .templates
+            .into_iter()
+            .map(|(name, template)| (name, crate::LayoutTemplate::from(template)))
+            .collect::<Vec<_>>();
+
+        root_template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
+            #[cfg(target_os = "windows")]
+            {
+                anim.node_path = anim.node_path.replace("/", "\\");
+            }
+            #[cfg(not(target_os = "windows"))]
+            {
+                anim.node_path = anim.node_path.replace("\\", "/");
+            }
+        }));
+
+        templates.iter_mut().for_each(|(_, template)| {
+            template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
+                #[cfg(target_os = "windows")]
+                {
+                    anim.node_path = anim.node_path.replace("/", "\\");
+                }
+                #[cfg(not(target_os = "windows"))]
+                {
+                    anim.node_path = anim.node_path.replace("\\", "/");
+                }
+            }));
+        });
+
+        let root = crate::LayoutRoot::from_root_template(root_template, templates);
+
+        for (image, bytes) in asset.images {
+            backend.load_image_bytes_with_name(image, bytes);
+        }
+
+        for (font, bytes) in asset.fonts {
+            backend.load_font_bytes_with_name(font, bytes);
+        }
+
+        Ok(root)
+    }
+}
+
+mod v0316 {
+    use std::io::Cursor;
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    struct LayoutTemplate {
+        canvas_size: [u32; 2],
+        root_nodes: Vec<crate::NodeTemplate>,
+        animations: Vec<(String, crate::Animation)>,
+        state_machine: Option<crate::StateMachine>,
+    }
+
+    impl From<LayoutTemplate> for crate::LayoutTemplate {
+        fn from(value: LayoutTemplate) -> Self {
+            Self {
+                canvas_size: value.canvas_size,
+                root_nodes: value.root_nodes,
+                animations: value.animations,
+                state_machine: value.state_machine,
+                inheritance: None,
+            }
+        }
+    }
+
+    #[derive(bincode::Decode, bincode::Encode)]
+    struct Asset {
+        images: Vec<(String, Vec<u8>)>,
+        fonts: Vec<(String, Vec<u8>)>,
+        templates: Vec<(String, LayoutTemplate)>,
+        root_template: LayoutTemplate,
+    }
+
+    pub(super) fn deserialize<B: crate::EnvyBackend, A: super::EnvyAssetProvider>(
+        backend: &mut A,
+        reader: &mut Cursor<&[u8]>,
+    ) -> Result<crate::LayoutRoot<B>, super::DeserializeError> {
+        let asset: Asset =
+            bincode::decode_from_std_read(reader, bincode::config::standard())?;
+
+        super::load_asset(
+            backend,
+            super::Asset {
+                images: asset.images,
+                fonts: asset.fonts,
+                templates: asset
+                    .templates
+                    .into_iter()
+                    .map(|(name, template)| (name, template.into()))
+                    .collect(),
+                root_template: asset.root_template.into(),
+            },
+        )
+    }
+}
+
+struct Asset {
+    images: Vec<(String, Vec<u8>)>,
+    fonts: Vec<(String, Vec<u8>)>,
+    templates: Vec<(String, LayoutTemplate)>,
+    root_template: LayoutTemplate,
+}
+
+/// How [`serialize_with_options`] stores the images and fonts used by a layout
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum ResourceStorage {
+    /// The bytes of every image and font are embedded in the asset
+    #[default]
+    Embedded,
+
+    /// Only the name and [`content_hash`] of every image and font are stored, they are resolved through
+    /// [`EnvyAssetProvider::resolve_reference`] when the asset is loaded. Use [`pack`] to embed them for shipping.
+    Referenced,
+}
+
+/// How [`serialize_with_options`] writes an asset
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct SerializeOptions {
+    pub storage: ResourceStorage,
+
+    /// Compression of the templates, animations and embedded fonts
+    pub compression: container::Compression,
+
+    /// Compression of embedded images, which are usually stored in a format that is already compressed
+    pub image_compression: container::Compression,
+}
+
+#[derive(Decode, Encode, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ResourceKind {
+    Image,
+    Font,
+}
+
+impl std::fmt::Display for ResourceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            Self::Image => "image",
+            Self::Font => "font",
+        })
+    }
+}
+
+/// An image or font embedded in an asset, whose bytes are only decompressed and copied out of the asset when needed
+///
+/// Resources that share a chunk share a single copy of it, which is freed once all of them have been dropped.
+#[derive(Clone)]
+pub struct LazyResource {
+    kind: ResourceKind,
+    name: String,
+    hash: Option<u64>,
+    chunk: std::sync::Arc<container::LazyChunk>,
+    range: std::ops::Range<usize>,
+}
+
+impl LazyResource {
+    pub fn kind(&self) -> ResourceKind {
+        self.kind
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Copies the bytes of the resource out of the asset, decompressing the chunk it is in the first time this is
+    /// called for any of the resources in that chunk
+    ///
+    /// Returns `None` and logs an error if the chunk is corrupted.
+    pub fn load(&self) -> Option<Vec<u8>> {
+        let bytes = self.chunk.payload()?.get(self.range.clone()).map(<[u8]>::to_vec);
+        if bytes.is_none() {
+            log::error!("{} '{}' extends past the end of its chunk", self.kind, self.name);
+        }
+
+        bytes
+    }
+
+    /// [`content_hash`] of the resource's bytes, which is read from the asset instead of loading the bytes when the
+    /// asset stores it
+    ///
+    /// Returns `None` and logs an error if the bytes have to be loaded and the chunk is corrupted.
+    pub fn content_hash(&self) -> Option<u64> {
+        self.hash.or_else(|| self.load().map(|bytes| content_hash(&bytes)))
+    }
+}
+
+impl std::fmt::Debug for LazyResource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LazyResource")
+            .field("kind", &self.kind)
+            .field("name", &self.name)
+            .field("len", &self.range.len())
+            .finish()
+    }
+}
+
+/// Resources handed to [`EnvyAssetProvider::defer_resource`] that have not been requested yet
+///
+/// Backends can keep one of these to implement lazy loading: take the resource out when its name is first requested,
+/// and fall back to it when asked for the bytes of a resource that has not been loaded.
+#[derive(Debug, Default)]
+pub struct DeferredResources {
+    resources: HashMap<(ResourceKind, String), LazyResource>,
+}
+
+impl DeferredResources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a resource, replacing any deferred resource of the same kind with the same name
+    pub fn insert(&mut self, resource: LazyResource) {
+        self.resources
+            .insert((resource.kind, resource.name.clone()), resource);
+    }
+
+    pub fn contains(&self, kind: ResourceKind, name: &str) -> bool {
+        self.resources.contains_key(&(kind, name.to_string()))
+    }
+
+    /// Removes the resource and loads its bytes, so that they can be handed to the backend
+    pub fn take(&mut self, kind: ResourceKind, name: &str) -> Option<Vec<u8>> {
+        self.resources
+            .remove(&(kind, name.to_string()))
+            .and_then(|resource| resource.load())
+    }
+
+    /// Loads the bytes of the resource without removing it
+    pub fn bytes(&self, kind: ResourceKind, name: &str) -> Option<Vec<u8>> {
+        self.resources
+            .get(&(kind, name.to_string()))
+            .and_then(LazyResource::load)
+    }
+
+    pub fn remove(&mut self, kind: ResourceKind, name: &str) {
+        self.resources.remove(&(kind, name.to_string()));
+    }
+
+    /// Removes every deferred resource, for loading all of them at once
+    pub fn drain(&mut self) -> impl Iterator<Item = LazyResource> + '_ {
+        self.resources.drain().map(|(_, resource)| resource)
+    }
+
+    pub fn clear(&mut self) {
+        self.resources.clear();
+    }
+}
+
+/// An image or font that an asset refers to by name instead of embedding it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceReference {
+    pub kind: ResourceKind,
+    pub name: String,
+
+    /// [`content_hash`] of the resource's bytes when the asset was written
+    pub hash: u64,
+}
+
+impl ResourceReference {
+    /// Returns whether `bytes` are the same as the bytes the resource had when the asset was written
+    pub fn matches(&self, bytes: &[u8]) -> bool {
+        content_hash(bytes) == self.hash
+    }
+}
+
+/// Keeps the bytes of every image and font in memory, for tools that read and write assets without a renderer
+///
+/// References that are not resolved before the asset is loaded are recorded in
+/// [`MemoryAssetProvider::unresolved`] instead.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MemoryAssetProvider {
+    pub images: BTreeMap<String, Vec<u8>>,
+    pub fonts: BTreeMap<String, Vec<u8>>,
+    pub unresolved: Vec<ResourceReference>,
+}
+
+impl MemoryAssetProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, kind: ResourceKind, name: &str) -> Option<&[u8]> {
+        match kind {
+            ResourceKind::Image => self.images.get(name),
+            ResourceKind::Font => self.fonts.get(name),
+        }
+        .map(Vec::as_slice)
+    }
+
+    /// Adds a resource, replacing any resource of the same kind with the same name
+    pub fn insert(&mut self, kind: ResourceKind, name: impl Into<String>, bytes: Vec<u8>) {
+        let _ = match kind {
+            ResourceKind::Image => self.images.insert(name.into(), bytes),
+            ResourceKind::Font => self.fonts.insert(name.into(), bytes),
+        };
+    }
+
+    /// Every image and font, images first and each sorted by name
+    pub fn iter(&self) -> impl Iterator<Item = (ResourceKind, &str, &[u8])> + '_ {
+        let images = self
+            .images
+            .iter()
+            .map(|(name, bytes)| (ResourceKind::Image, name.as_str(), bytes.as_slice()));
+        let fonts = self
+            .fonts
+            .iter()
+            .map(|(name, bytes)| (ResourceKind::Font, name.as_str(), bytes.as_slice()));
+        images.chain(fonts)
+    }
+}
+
+impl EnvyAssetProvider for MemoryAssetProvider {
+    fn load_image_bytes_with_name(&mut self, name: String, bytes: Vec<u8>) {
+        self.images.insert(name, bytes);
+    }
+
+    fn load_font_bytes_with_name(&mut self, name: String, bytes: Vec<u8>) {
+        self.fonts.insert(name, bytes);
+    }
+
+    fn fetch_image_bytes_by_name<'a>(&'a self, name: &str) -> Cow<'a, [u8]> {
+        Cow::Borrowed(self.images.get(name).map(Vec::as_slice).unwrap_or_default())
+    }
+
+    fn fetch_font_bytes_by_name<'a>(&'a self, name: &str) -> Cow<'a, [u8]> {
+        Cow::Borrowed(self.fonts.get(name).map(Vec::as_slice).unwrap_or_default())
+    }
+
+    fn resolve_reference(&mut self, reference: &ResourceReference) {
+        if self.get(reference.kind, &reference.name).is_none() {
+            self.unresolved.push(reference.clone());
+        }
+    }
+}
+
+/// The `(major, minor, patch)` version of `envy` that wrote an asset from before the chunked [`container`]
+///
+/// Returns `None` for containers and for bytes that are not an asset at all.
+pub fn legacy_version(bytes: &[u8]) -> Option<(u8, u8, u16)> {
+    if container::is_container(bytes) {
+        return None;
+    }
+
+    let (version, _): (Version, _) = bincode::decode_from_slice(bytes, bincode::config::standard()).ok()?;
+    (version.major == 0 && version <= Version::current())
+        .then_some((version.major, version.minor, version.patch))
+}
+
+pub trait EnvyAssetProvider {
+    fn load_image_bytes_with_name(&mut self, name: String, bytes: Vec<u8>);
+    fn load_font_bytes_with_name(&mut self, name: String, bytes: Vec<u8>);
+
+    fn fetch_image_bytes_by_name<'a>(&'a self, name: &str) -> Cow<'a, [u8]>;
+    fn fetch_font_bytes_by_name<'a>(&'a self, name: &str) -> Cow<'a, [u8]>;
+
+    /// Called by [`deserialize`] for every image and font the asset references instead of embedding
+    ///
+    /// Providers that keep resources outside of assets, such as in a directory of shared textures, should load the
+    /// resource here. The default implementation assumes the resource has already been loaded under its name.
+    fn resolve_reference(&mut self, _reference: &ResourceReference) {}
+
+    /// Called by [`deserialize`] for every image and font embedded in the asset
+    ///
+    /// Providers that can wait until a resource is requested by name, such as in
+    /// [`EnvyBackend::request_texture_by_name`], should hold on to `resource` (see [`DeferredResources`]) and load it
+    /// then. The default implementation loads it right away.
+    fn defer_resource(&mut self, resource: LazyResource) {
+        let Some(bytes) = resource.load() else {
+            return;
+        };
+
+        match resource.kind {
+            ResourceKind::Image => self.load_image_bytes_with_name(resource.name, bytes),
+            ResourceKind::Font => self.load_font_bytes_with_name(resource.name, bytes),
+        }
+    }
+}
+
+/// Every image and font used by the nodes of `templates`, in the order they are first used
+fn used_resources<'a>(templates: impl IntoIterator<Item = &'a LayoutTemplate>) -> Vec<(ResourceKind, String)> {
+    let mut seen = HashSet::new();
+    let mut resources = vec![];
+    let mut add = |kind: ResourceKind, name: &String| {
+        if seen.insert((kind, name.clone())) {
+            resources.push((kind, name.clone()));
+        }
+    };
+
+    for template in templates {
+        template.walk_tree(|node| match &node.implementation {
+            NodeImplTemplate::Image(image) => {
+                add(ResourceKind::Image, &image.texture_name);
+                if let Some(mask_name) = image.mask_texture_name.as_ref() {
+                    add(ResourceKind::Image, mask_name);
+                }
+            }
+            NodeImplTemplate::Text(text) => add(ResourceKind::Font, &text.font_name),
+            _ => {}
+        })
+    }
+
+    resources
+}
+
+/// Writes the layout and every image and font it uses in the chunked [`container`] format
+///
+/// The bytes of the images and fonts are fetched from `asset_provider`, which is usually the backend of `root`.
+pub fn serialize<B: EnvyBackend, A: EnvyAssetProvider + ?Sized>(
+    root: &crate::LayoutRoot<B>,
+    asset_provider: &A,
+) -> Vec<u8> {
+    serialize_with_options(root, asset_provider, SerializeOptions::default())
+}
+
+/// Writes the layout in the chunked [`container`] format, storing and compressing the images and fonts it uses
+/// according to `options`
+pub fn serialize_with_options<B: EnvyBackend, A: EnvyAssetProvider + ?Sized>(
+    root: &crate::LayoutRoot<B>,
+    asset_provider: &A,
+    options: SerializeOptions,
+) -> Vec<u8> {
+    let mut asset = Asset {
+        images: vec![],
+        fonts: vec![],
+        templates: root
+            .iter_templates()
+            .into_iter()
+            .map(|(name, template)| (name.to_string(), template.clone()))
+            .collect::<Vec<_>>(),
+        root_template: root.root_template().clone(),
+    };
+
+    let templates = [&asset.root_template]
+        .into_iter()
+        .chain(asset.templates.iter().map(|(_, template)| template));
+    for (kind, name) in used_resources(templates) {
+        match kind {
+            ResourceKind::Image => asset
+                .images
+                .push((name.clone(), asset_provider.fetch_image_bytes_by_name(&name).to_vec())),
+            ResourceKind::Font => asset
+                .fonts
+                .push((name.clone(), asset_provider.fetch_font_bytes_by_name(&name).to_vec())),
+        }
+    }
+
+    container::write(&asset, options)
+}
+
+/// An asset with all of its referenced resources embedded, see [`pack`]
+pub struct PackedAsset {
+    pub bytes: Vec<u8>,
+
+    /// References whose resolved bytes no longer match the hash recorded when the asset was written, they are embedded
+    /// regardless
+    pub changed: Vec<ResourceReference>,
+}
+
+/// Why [`pack`] failed
+#[derive(Debug)]
+pub enum PackError {
+    Container(container::ContainerError),
+
+    /// `resolve` did not return the bytes of a referenced resource
+    Unresolved(ResourceReference),
+}
+
+impl std::fmt::Display for PackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Container(error) => error.fmt(f),
+            Self::Unresolved(reference) => write!(f, "could not resolve {} '{}'", reference.kind, reference.name),
+        }
+    }
+}
+
+impl std::error::Error for PackError {}
+
+impl From<container::ContainerError> for PackError {
+    fn from(value: container::ContainerError) -> Self {
+        Self::Container(value)
+    }
+}
+
+/// Embeds every image and font referenced by an asset written with [`ResourceStorage::Referenced`], so that it can be
+/// shipped on its own
+///
+/// `resolve` returns the current bytes of a referenced resource. Chunks that are unknown to this version of `envy`
+/// are copied over as they are. Assets written before the chunked [`container`] always embed their resources, so
+/// they are returned unchanged.
+pub fn pack(
+    bytes: &[u8],
+    resolve: impl FnMut(&ResourceReference) -> Option<Vec<u8>>,
+) -> Result<PackedAsset, PackError> {
+    if !container::is_container(bytes) {
+        return Ok(PackedAsset {
+            bytes: bytes.to_vec(),
+            changed: vec![],
+        });
+    }
+
+    container::embed_references(bytes, resolve)
+}
+
+/// Why [`try_deserialize`] could not read an asset
+#[derive(Debug)]
+pub enum DeserializeError {
+    Container(ContainerError),
+
+    /// An asset written before the chunked [`container`] failed to decode
+    Legacy(bincode::error::DecodeError),
+
+    /// An asset written before the chunked [`container`] has a version that no release of `envy` wrote
+    UnknownVersion { version: (u8, u8, u16) },
+
+    Template(TemplateError),
+}
+
+impl std::fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Container(error) => error.fmt(f),
+            Self::Legacy(error) => write!(f, "failed to decode legacy asset: {error}"),
+            Self::UnknownVersion {
+                version: (major, minor, patch),
+            } => write!(f, "asset version {major}.{minor}.{patch} is unknown"),
+            Self::Template(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for DeserializeError {}
+
+impl From<ContainerError> for DeserializeError {
+    fn from(value: ContainerError) -> Self {
+        Self::Container(value)
+    }
+}
+
+impl From<bincode::error::DecodeError> for DeserializeError {
+    fn from(value: bincode::error::DecodeError) -> Self {
+        Self::Legacy(value)
+    }
+}
+
+impl From<TemplateError> for DeserializeError {
+    fn from(value: TemplateError) -> Self {
+        Self::Template(value)
+    }
+}
+
+/// Why templates can't be built into a [`LayoutRoot`](crate::LayoutRoot), see [`check_templates`]
+#[derive(Debug)]
+pub enum TemplateError {
+    /// A sublayout node uses a template that does not exist
+    MissingTemplate { node: String, template: String },
+
+    /// Templates that include each other through sublayout nodes, the first template is repeated at the end
+    SublayoutCycle { templates: Vec<String> },
+
+    /// A derived template can't be resolved
+    Inheritance(InheritanceError),
+}
+
+impl std::fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingTemplate { node, template } => {
+                write!(f, "sublayout node '{node}' uses template '{template}', which does not exist")
+            }
+            Self::SublayoutCycle { templates } => {
+                write!(f, "templates include each other through sublayouts: {}", templates.join(" -> "))
+            }
+            Self::Inheritance(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+impl From<InheritanceError> for TemplateError {
+    fn from(value: InheritanceError) -> Self {
+        Self::Inheritance(value)
+    }
+}
+
+/// Makes sure that templates can be built into a [`LayoutRoot`](crate::LayoutRoot), which panics on missing and
+/// recursive sublayouts and on derived templates that can't be resolved
+pub fn check_templates(
+    root_template: &LayoutTemplate,
+    templates: &HashMap<String, LayoutTemplate>,
+) -> Result<(), TemplateError> {
+    let resolved_root = root_template.resolve(templates)?;
+    let resolved = templates
+        .iter()
+        .map(|(name, template)| Ok((name.clone(), template.resolve(templates)?)))
+        .collect::<Result<BTreeMap<_, _>, InheritanceError>>()?;
+    check_sublayouts(&resolved_root, &resolved)
+}
+
+/// Finds missing and recursive sublayouts in templates that have already been resolved
+pub(super) fn check_sublayouts(
+    root_template: &LayoutTemplate,
+    templates: &BTreeMap<String, Cow<'_, LayoutTemplate>>,
+) -> Result<(), TemplateError> {
+    let mut sublayouts: HashMap<Option<&str>, Vec<String>> = HashMap::new();
+    let all = [(None, root_template)]
+        .into_iter()
+        .chain(templates.iter().map(|(name, template)| (Some(name.as_str()), &**template)));
+
+    for (name, template) in all {
+        let mut error = None;
+        let names = sublayouts.entry(name).or_default();
+        template.walk_tree(|node| {
+            let NodeImplTemplate::Sublayout(sublayout) = &node.implementation else {
+                return;
+            };
+
+            let reference = &sublayout.sublayout_name;
+            if reference.is_empty() {
+                return;
+            }
+
+            if !templates.contains_key(reference) {
+                error.get_or_insert_with(|| TemplateError::MissingTemplate {
+                    node: node.name.clone(),
+                    template: reference.clone(),
+                });
+            }
+
+            names.push(reference.clone());
+        });
+
+        if let Some(error) = error {
+            return Err(error);
+        }
+    }
+
+    fn visit<'s>(
+        name: &'s str,
+        sublayouts: &'s HashMap<Option<&'s str>, Vec<String>>,
+        stack: &mut Vec<&'s str>,
+        finished: &mut HashSet<&'s str>,
+    ) -> Result<(), TemplateError> {
+        if let Some(start) = stack.iter().position(|visiting| *visiting == name) {
+            let mut templates = stack[start..].iter().map(|name| name.to_string()).collect::<Vec<_>>();
+            templates.push(name.to_string());
+            return Err(TemplateError::SublayoutCycle { templates });
+        }
+
+        if !finished.insert(name) {
+            return Ok(());
+        }
+
+        stack.push(name);
+        for sublayout in sublayouts.get(&Some(name)).into_iter().flatten() {
+            visit(sublayout, sublayouts, stack, finished)?;
+        }
+        stack.pop();
+        Ok(())
+    }
+
+    let mut finished = HashSet::new();
+    for name in templates.keys() {
+        visit(name, &sublayouts, &mut vec![], &mut finished)?;
+    }
+
+    Ok(())
+}
+
+/// Reads a layout written by [`serialize`] or any older version of `envy`, passing its images and fonts to
+/// `asset_provider`
+///
+/// Panics if the asset can't be read, use [`try_deserialize`] for assets that may be damaged or were written by a
+/// newer version of `envy`.
+pub fn deserialize<B: EnvyBackend, A: EnvyAssetProvider>(
+    asset_provider: &mut A,
+    bytes: &[u8],
+) -> crate::LayoutRoot<B> {
+    try_deserialize(asset_provider, bytes).unwrap_or_else(|error| panic!("Failed to read asset: {error}"))
+}
+
+/// Reads a layout like [`deserialize`], but returns an error instead of panicking when the asset can't be read
+///
+/// Images and fonts that were read before the error may already have been passed to `asset_provider`.
+pub fn try_deserialize<B: EnvyBackend, A: EnvyAssetProvider>(
+    asset_provider: &mut A,
+    bytes: &[u8],
+) -> Result<crate::LayoutRoot<B>, DeserializeError> {
+    if container::is_container(bytes) {
+        let asset = container::read(bytes)?;
+        asset
+            .references
+            .iter()
+            .for_each(|reference| asset_provider.resolve_reference(reference));
+        asset
+            .resources
+            .into_iter()
+            .for_each(|resource| asset_provider.defer_resource(resource));
+        return load_asset(asset_provider, asset.asset);
+    }
+
+    let mut reader = std::io::Cursor::new(bytes);
+    let version: Version = bincode::decode_from_std_read(&mut reader, bincode::config::standard())?;
+
+    if version == Version::new(0, 1, 0) {
+        return v010::deserialize(asset_provider, &mut reader);
+    } else if version == Version::new(0, 2, 0) {
+        return v020::deserialize(asset_provider, &mut reader);
+    } else if version == Version::new(0, 2, 1) {
+        return v021::deserialize(asset_provider, &mut reader);
+    } else if version == Version::new(0, 3, 0) {
+        return v030::deserialize(asset_provider, &mut reader);
+    } else if version == Version::new(0, 3, 1) {
+        return v031::deserialize(asset_provider, &mut reader);
+    } else if version == Version::new(0, 3, 2) {
+        return v032::deserialize(asset_provider, &mut reader);
+    } else if version == Version::new(0, 3, 3) {
+        return v033::deserialize(asset_provider, &mut reader);
+    } else if version == Version::new(0, 3, 4) {
+        return v034::deserialize(asset_provider, &mut reader);
+    } else if version == Version::new(0, 3, 5) || version == Version::new(0, 3, 6) {
+        // 0.3.6 only appended `NodeImplTemplate::Shape`, so both versions share a schema
+        return v036::deserialize(asset_provider, &mut reader);
+    } else if version == Version::new(0, 3, 7) {
+        return v037::deserialize(asset_provider, &mut reader);
+    } else if version == Version::new(0, 3, 8) {
+        return v038::deserialize(asset_provider, &mut reader);
+    } else if version == Version::new(0, 3, 9) {
+        return v039::deserialize(asset_provider, &mut reader);
+    } else if version == Version::new(0, 3, 10) {
+        return v040::deserialize(asset_provider, &mut reader);
+    } else if version == Version::new(0, 3, 11) {
+        return v041::deserialize(asset_provider, &mut reader);
+    } else if version == Version::new(0, 3, 12) {
+        return v042::deserialize(asset_provider, &mut reader);
+    } else if version == Version::new(0, 3, 13) {
+        return v043::deserialize(asset_provider, &mut reader);
+    } else if version == Version::new(0, 3, 14) {
+        return v044::deserialize(asset_provider, &mut reader);
+    } else if version == Version::new(0, 3, 15) {
+        return v045::deserialize(asset_provider, &mut reader);
+    }
+
+    // 0.3.16 is the last version written before assets moved to the chunked container
+    if version != Version::current() {
+        return Err(DeserializeError::UnknownVersion {
+            version: (version.major, version.minor, version.patch),
+        });
+    }
+
+    v0316::deserialize(asset_provider, &mut reader)
+}
+
+/// Animation node paths are stored with the separator of the platform that wrote the asset
+fn normalize_node_paths(asset: &mut Asset) {
+    asset.root_template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
+        #[cfg(target_os = "windows")]
+        {
+            anim.node_path = anim.node_path.replace("/", "\\");
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            anim.node_path = anim.node_path.replace("\\", "/");
+        }
+    }));
+
+    asset.templates.iter_mut().for_each(|(_, template)| {
+        template.animations.iter_mut().for_each(|(_, anim)| anim.node_animations.iter_mut().for_each(|anim| {
+            #[cfg(target_os = "windows")]
+            {
+                anim.node_path = anim.node_path.replace("/", "\\");
+            }
+            #[cfg(not(target_os = "windows"))]
+            {
+                anim.node_path = anim.node_path.replace("\\", "/");
+            }
+        }));
+    });
+}
+
+fn load_asset<B: EnvyBackend, A: EnvyAssetProvider>(
+    asset_provider: &mut A,
+    mut asset: Asset,
+) -> Result<crate::LayoutRoot<B>, DeserializeError> {
+    normalize_node_paths(&mut asset);
+
+    let templates = asset.templates.into_iter().collect::<HashMap<_, _>>();
+    check_templates(&asset.root_template, &templates)?;
+    let root = crate::LayoutRoot::from_root_template(asset.root_template, templates);
+
+    for (image, bytes) in asset.images {
+        asset_provider.load_image_bytes_with_name(image, bytes);
+    }
 
     for (font, bytes) in asset.fonts {
         asset_provider.load_font_bytes_with_name(font, bytes);
     }
 
-    root
+    Ok(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        template::{ImageNodeTemplate, NodeVisibility},
+        testing::RecordingBackend,
+        LayoutRoot, NodeTransform,
+    };
+
+    const RED: &[u8] = &[255, 0, 0, 255];
+    const BLUE: &[u8] = &[0, 0, 255, 255];
+
+    fn image(name: &str, texture: &str) -> NodeTemplate {
+        NodeTemplate {
+            name: name.to_string(),
+            transform: NodeTransform::from_size(glam::Vec2::splat(10.0)),
+            color: [255; 4],
+            visibility: NodeVisibility::Inherited,
+            gradient: None,
+            children: vec![],
+            implementation: NodeImplTemplate::Image(ImageNodeTemplate {
+                texture_name: texture.to_string(),
+                mask_texture_name: None,
+                image_scaling_mode_x: Default::default(),
+                image_scaling_mode_y: Default::default(),
+                uv_offset: glam::Vec2::ZERO,
+                uv_scale: glam::Vec2::ONE,
+                fill_method: Default::default(),
+                fill_origin: Default::default(),
+                fill_amount: 1.0,
+            }),
+        }
+    }
+
+    fn resources() -> MemoryAssetProvider {
+        let mut resources = MemoryAssetProvider::new();
+        resources.insert(ResourceKind::Image, "red", RED.to_vec());
+        resources.insert(ResourceKind::Image, "blue", BLUE.to_vec());
+        resources
+    }
+
+    /// An asset with two images, written with [`ResourceStorage::Referenced`]
+    fn referenced() -> Vec<u8> {
+        let root = LayoutRoot::<RecordingBackend>::from_root_template(
+            LayoutTemplate {
+                root_nodes: vec![image("a", "red"), image("b", "blue")],
+                ..Default::default()
+            },
+            [],
+        );
+
+        serialize_with_options(&root, &resources(), SerializeOptions {
+            storage: ResourceStorage::Referenced,
+            ..Default::default()
+        })
+    }
+
+    fn texture_name(root: &LayoutRoot<RecordingBackend>, path: &str) -> String {
+        match &root.root_template().get_node_by_path(path).unwrap().implementation {
+            NodeImplTemplate::Image(image) => image.texture_name.clone(),
+            _ => panic!("'{path}' is not an image"),
+        }
+    }
+
+    #[test]
+    fn packed_assets_embed_their_references() {
+        let bytes = referenced();
+
+        let mut unpacked = MemoryAssetProvider::new();
+        let _: LayoutRoot<RecordingBackend> = deserialize(&mut unpacked, &bytes);
+        assert!(unpacked.images.is_empty());
+        assert_eq!(
+            unpacked.unresolved.iter().map(|reference| reference.name.as_str()).collect::<Vec<_>>(),
+            ["red", "blue"]
+        );
+
+        let resources = resources();
+        let packed = pack(&bytes, |reference| resources.get(reference.kind, &reference.name).map(<[u8]>::to_vec))
+            .unwrap();
+        assert!(packed.changed.is_empty());
+
+        let mut loaded = MemoryAssetProvider::new();
+        let root: LayoutRoot<RecordingBackend> = deserialize(&mut loaded, &packed.bytes);
+        assert!(loaded.unresolved.is_empty());
+        assert_eq!(loaded.images, resources.images);
+        assert_eq!(texture_name(&root, "a"), "red");
+        assert_eq!(texture_name(&root, "b"), "blue");
+    }
+
+    #[test]
+    fn changed_references_are_reported_and_embedded() {
+        let packed = pack(&referenced(), |reference| match reference.name.as_str() {
+            "blue" => Some(RED.to_vec()),
+            name => resources().get(reference.kind, name).map(<[u8]>::to_vec),
+        })
+        .unwrap();
+
+        let [changed] = packed.changed.as_slice() else {
+            panic!("expected a single changed reference");
+        };
+        assert_eq!((changed.kind, changed.name.as_str()), (ResourceKind::Image, "blue"));
+        assert!(changed.matches(BLUE) && !changed.matches(RED));
+
+        let mut loaded = MemoryAssetProvider::new();
+        let _: LayoutRoot<RecordingBackend> = deserialize(&mut loaded, &packed.bytes);
+        assert_eq!(loaded.get(ResourceKind::Image, "blue"), Some(RED));
+    }
+
+    #[test]
+    fn unresolved_references_fail_to_pack() {
+        let result = pack(&referenced(), |reference| match reference.name.as_str() {
+            "blue" => None,
+            name => resources().get(reference.kind, name).map(<[u8]>::to_vec),
+        });
+
+        match result {
+            Err(PackError::Unresolved(reference)) => {
+                assert_eq!((reference.kind, reference.name.as_str()), (ResourceKind::Image, "blue"))
+            }
+            Err(error) => panic!("unexpected error: {error}"),
+            Ok(_) => panic!("packing should fail"),
+        }
+    }
 }
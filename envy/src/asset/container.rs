@@ -0,0 +1,2662 @@
+//! Chunked container that assets are written in
+//!
+//! A container starts with a fixed header, all integers in it are little endian:
+//! - the magic bytes [`MAGIC`]
+//! - the container's major and minor version, as two `u16`s
+//! - the number of chunks, as a `u32`
+//! - a table of contents entry for each chunk: its tag (`[u8; 4]`), flags (`u32`), and the offset from the start of
+//!   the file and length of its payload (both `u64`)
+//!
+//! Readers skip chunks with tags they do not know, unless the chunk is flagged with [`ChunkFlags::REQUIRED`]. Only
+//! changes that older readers would misread bump the major version.
+//!
+//! Since version 2.0, the payload of a chunk can be compressed with one of the methods in [`Compression`], which is
+//! stored in the chunk's flags. Readers of version 1.0 ignore those flags and would decode compressed payloads as
+//! records, so containers are only written as version 2.0 when any of their chunks is compressed, and as version 1.0
+//! otherwise. Support for each method is behind a feature of the same name, readers fail with
+//! [`ContainerError::UnsupportedCompression`] when they come across a method they were built without.
+//!
+//! Chunk payloads are a stream of records, each one a bincode length followed by that many bytes of bincode data.
+//! Records are decoded as if they were followed by an endless run of zeros, and bytes left over after decoding are
+//! skipped. This means new fields can be appended to the end of the `*Record` structs in this module without a new
+//! asset version: older runtimes ignore them, and newer runtimes read them from older files as their zero value (`0`,
+//! `false`, empty, `None` or the first variant of an enum). Fields that have no sensible zero value should be appended
+//! as an `Option`.
+//!
+//! Nothing that could grow is nested inside of a record. Lists, node implementations, gradients and animation channels
+//! are written as records of their own following the record they belong to, which holds their count or kind. Node
+//! kinds, animation channels, shapes, gradients and transition conditions are tagged with an id, and a reader that
+//! does not know an id skips the records that belong to it, so new kinds and channels don't need a new asset version
+//! either. Older runtimes build nodes of an unknown kind as empty nodes.
+//!
+//! The records of each chunk are:
+//! - `META`: one [`MetadataRecord`]
+//! - `TMPL`: a [`TemplateRecord`] for the root template and each named template, each followed by its state machine
+//!   and its root nodes. A node is a [`NodeRecord`], a [`TransformRecord`], the record of its kind (see [`NodeKind`]),
+//!   its gradient, and then each of its children. A gradient is a [`GradientRecord`] followed by a
+//!   [`GradientStopRecord`] per stop. A state machine is a [`StateMachineRecord`] followed by a [`ParameterRecord`]
+//!   per parameter, a [`StateRecord`] per state and a [`TransitionRecord`] per transition, each followed by a
+//!   [`ConditionRecord`] per condition
+//! - `ANIM`: an [`AnimationRecord`] followed by a [`NodeAnimationRecord`] for each of its node animations, each
+//!   followed by a [`ChannelRecord`] and the records of each of its channels
+//! - `IMAG` and `FONT`: one [`ResourceRecord`] per embedded image or font
+//! - `IREF` and `FREF`: one [`ReferenceRecord`] per image or font that is referenced instead of embedded, see
+//!   [`ResourceStorage::Referenced`]
+//! - `RIDX`: one [`ResourceIndexRecord`] per embedded image or font, pointing at its bytes inside of `IMAG` or `FONT`.
+//!   This lets [`deserialize`](super::deserialize) hand out [`LazyResource`]s without decompressing or decoding
+//!   either chunk. It is never compressed
+//! - `ALIA`: one [`AliasRecord`] per embedded image or font with the same bytes as another one of the same kind, which
+//!   are only stored once. Only written when there are any, and flagged as required since older readers would quietly
+//!   leave the aliased resources out
+//! - `INHR`: an [`InheritanceRecord`] for each derived template, followed by a [`PatchRecord`] for each of its patches
+//!   (each followed by the transform, implementation and gradient it replaces, written like in `TMPL`) and an
+//!   [`AddedNodeRecord`] for each node it adds, followed by the node like in `TMPL`. Only written when any template
+//!   derives from another one, and flagged as required since older readers would build derived templates without
+//!   their base
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt,
+    sync::{Arc, OnceLock},
+};
+
+use bincode::{de::read::Reader, error::DecodeError, Decode, Encode};
+
+use crate::{
+    Animation, AnimationChannel, AnimationState, AnimationTransform, AnimationValue, BakedChannel, BakedNodeAnimation,
+    ChannelMode, DiscreteChannel, DiscreteKeyframe, Gradient, GradientKind, GradientStop, ImageFillMethod,
+    ImageFillOrigin, ImageNodeTemplate, ImageScalingMode, KeyTangents, LayoutTemplate, MotionPath, NodeAnimation,
+    NodeImplTemplate, NodePatch, NodeTemplate, NodeTransform, NodeVisibility, ParamValue, ShapeKind,
+    ShapeNodeTemplate, StateMachine, StateTransition, SublayoutNodeTemplate, TangentMode, TemplateInheritance,
+    TextAlignment, TextNodeTemplate, TransformStep, TransitionCondition, animations::BakedSamples, node::Anchor,
+};
+
+use super::{
+    Asset, LazyResource, PackError, PackedAsset, ResourceKind, ResourceReference, ResourceStorage, SerializeOptions,
+};
+
+pub const MAGIC: &[u8; 4] = b"ENVY";
+
+const MAJOR_VERSION: u16 = 2;
+const MINOR_VERSION: u16 = 0;
+
+/// Major version of containers without compressed chunks, which readers from before compression can still load
+const UNCOMPRESSED_MAJOR_VERSION: u16 = 1;
+
+/// Size of the header before the table of contents
+const HEADER_SIZE: usize = 12;
+
+/// Size of a single table of contents entry
+const ENTRY_SIZE: usize = 24;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ChunkTag(pub [u8; 4]);
+
+impl ChunkTag {
+    pub const METADATA: Self = Self(*b"META");
+    pub const TEMPLATES: Self = Self(*b"TMPL");
+    pub const ANIMATIONS: Self = Self(*b"ANIM");
+    pub const IMAGES: Self = Self(*b"IMAG");
+    pub const FONTS: Self = Self(*b"FONT");
+    pub const IMAGE_REFERENCES: Self = Self(*b"IREF");
+    pub const FONT_REFERENCES: Self = Self(*b"FREF");
+    pub const RESOURCE_INDEX: Self = Self(*b"RIDX");
+    pub const ALIASES: Self = Self(*b"ALIA");
+    pub const INHERITANCE: Self = Self(*b"INHR");
+}
+
+impl fmt::Display for ChunkTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&String::from_utf8_lossy(&self.0))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ChunkFlags(pub u32);
+
+impl ChunkFlags {
+    pub const NONE: Self = Self(0);
+
+    /// Readers that do not know the chunk's tag must refuse to load the asset instead of skipping the chunk
+    pub const REQUIRED: Self = Self(1 << 0);
+
+    /// Bits of the flags that hold the [`Compression`] of the payload
+    const COMPRESSION_MASK: u32 = 0xFF << 8;
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn with_compression(self, compression: Compression) -> Self {
+        Self(self.0 & !Self::COMPRESSION_MASK | (compression.id() as u32) << 8)
+    }
+
+    /// Whether the payload of the chunk is compressed, including with a method this build of `envy` does not support
+    pub fn is_compressed(self) -> bool {
+        self.0 & Self::COMPRESSION_MASK != 0
+    }
+
+    /// How the payload of the chunk is compressed, or `None` if it uses a method this build of `envy` does not support
+    pub fn compression(self) -> Option<Compression> {
+        Compression::from_id(((self.0 & Self::COMPRESSION_MASK) >> 8) as u8)
+    }
+}
+
+/// How the payload of a chunk is compressed
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Compression {
+    #[default]
+    None,
+
+    /// Zstandard, which compresses better than [`Compression::Lz4`] but is slower to decompress
+    #[cfg(feature = "zstd")]
+    Zstd,
+
+    #[cfg(feature = "lz4")]
+    Lz4,
+}
+
+impl Compression {
+    fn id(self) -> u8 {
+        match self {
+            Self::None => 0,
+            #[cfg(feature = "zstd")]
+            Self::Zstd => 1,
+            #[cfg(feature = "lz4")]
+            Self::Lz4 => 2,
+        }
+    }
+
+    fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Self::None),
+            #[cfg(feature = "zstd")]
+            1 => Some(Self::Zstd),
+            #[cfg(feature = "lz4")]
+            2 => Some(Self::Lz4),
+            _ => None,
+        }
+    }
+
+    fn compress(self, payload: Vec<u8>) -> Vec<u8> {
+        match self {
+            Self::None => payload,
+            #[cfg(feature = "zstd")]
+            Self::Zstd => zstd::encode_all(payload.as_slice(), 0).unwrap(),
+            #[cfg(feature = "lz4")]
+            Self::Lz4 => lz4_flex::compress_prepend_size(&payload),
+        }
+    }
+}
+
+/// Decompresses the payload of a chunk, borrowing it when it is not compressed
+fn decompress(tag: ChunkTag, flags: ChunkFlags, payload: &[u8]) -> Result<Cow<'_, [u8]>, ContainerError> {
+    match flags.compression() {
+        Some(Compression::None) => Ok(Cow::Borrowed(payload)),
+        #[cfg(feature = "zstd")]
+        Some(Compression::Zstd) => zstd::decode_all(payload)
+            .map(Cow::Owned)
+            .map_err(|error| ContainerError::Decompress(tag, error.into())),
+        #[cfg(feature = "lz4")]
+        Some(Compression::Lz4) => lz4_flex::decompress_size_prepended(payload)
+            .map(Cow::Owned)
+            .map_err(|error| ContainerError::Decompress(tag, error.into())),
+        None => Err(ContainerError::UnsupportedCompression(tag)),
+    }
+}
+
+/// Why bytes could not be read as a container
+#[derive(Debug)]
+pub enum ContainerError {
+    /// The bytes do not start with [`MAGIC`]
+    NotAContainer,
+
+    /// The container was written with an incompatible major version
+    UnsupportedVersion { major: u16, minor: u16 },
+
+    /// The header or a chunk extends past the end of the bytes
+    Truncated,
+
+    /// A chunk that readers must understand has a tag that is unknown to this version of `envy`
+    UnknownRequiredChunk(ChunkTag),
+
+    /// A chunk that is needed to load the asset is not in the container
+    MissingChunk(ChunkTag),
+
+    /// A record inside of a chunk failed to decode
+    Record(ChunkTag, DecodeError),
+
+    /// A chunk is compressed with a method that this build of `envy` does not support
+    UnsupportedCompression(ChunkTag),
+
+    /// A compressed chunk failed to decompress
+    Decompress(ChunkTag, Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl fmt::Display for ContainerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAContainer => f.write_str("not an envy asset container"),
+            Self::UnsupportedVersion { major, minor } => write!(
+                f,
+                "container version {major}.{minor} is not supported, the newest supported version is {MAJOR_VERSION}.{MINOR_VERSION}"
+            ),
+            Self::Truncated => f.write_str("container is truncated"),
+            Self::UnknownRequiredChunk(tag) => write!(f, "chunk '{tag}' is required but is not supported"),
+            Self::MissingChunk(tag) => write!(f, "chunk '{tag}' is missing"),
+            Self::Record(tag, error) => write!(f, "failed to decode a record in chunk '{tag}': {error}"),
+            Self::UnsupportedCompression(tag) => write!(
+                f,
+                "chunk '{tag}' is compressed with a method that is not supported, enable the `zstd` or `lz4` feature"
+            ),
+            Self::Decompress(tag, error) => write!(f, "failed to decompress chunk '{tag}': {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ContainerError {}
+
+/// Returns whether `bytes` start with the container magic, as opposed to being a legacy versioned asset
+pub fn is_container(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+struct Chunk {
+    tag: ChunkTag,
+    flags: ChunkFlags,
+    payload: Vec<u8>,
+
+    /// Whether the payload has already been compressed according to the flags
+    compressed: bool,
+}
+
+/// Builds a container out of chunks of records
+#[derive(Default)]
+pub struct ContainerWriter {
+    chunks: Vec<Chunk>,
+}
+
+impl ContainerWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a chunk, records written to the returned writer are appended to its payload
+    ///
+    /// The payload is compressed when the container is finished if `flags` has a [`Compression`].
+    pub fn chunk(&mut self, tag: ChunkTag, flags: ChunkFlags) -> RecordWriter<'_> {
+        self.chunks.push(Chunk {
+            tag,
+            flags,
+            payload: vec![],
+            compressed: false,
+        });
+
+        RecordWriter {
+            payload: &mut self.chunks.last_mut().unwrap().payload,
+        }
+    }
+
+    /// Adds a chunk with a payload that is stored as it is, such as a chunk copied out of another container with
+    /// [`Container::chunks`]
+    pub fn raw_chunk(&mut self, tag: ChunkTag, flags: ChunkFlags, payload: &[u8]) {
+        self.chunks.push(Chunk {
+            tag,
+            flags,
+            payload: payload.to_vec(),
+            compressed: true,
+        });
+    }
+
+    /// Writes the header, table of contents and payloads of the container
+    ///
+    /// The container is written as version 2.0 if any chunk is compressed, and as version 1.0 otherwise.
+    pub fn finish(self) -> Vec<u8> {
+        let major = match self.chunks.iter().any(|chunk| chunk.flags.is_compressed()) {
+            true => MAJOR_VERSION,
+            false => UNCOMPRESSED_MAJOR_VERSION,
+        };
+
+        let chunks = self
+            .chunks
+            .into_iter()
+            .map(|chunk| match chunk.compressed {
+                true => chunk,
+                false => Chunk {
+                    payload: chunk.flags.compression().unwrap_or_default().compress(chunk.payload),
+                    compressed: true,
+                    ..chunk
+                },
+            })
+            .collect::<Vec<_>>();
+
+        let mut offset = (HEADER_SIZE + ENTRY_SIZE * chunks.len()) as u64;
+
+        let mut out = Vec::with_capacity(
+            offset as usize + chunks.iter().map(|chunk| chunk.payload.len()).sum::<usize>(),
+        );
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&major.to_le_bytes());
+        out.extend_from_slice(&MINOR_VERSION.to_le_bytes());
+        out.extend_from_slice(&(chunks.len() as u32).to_le_bytes());
+
+        for chunk in chunks.iter() {
+            out.extend_from_slice(&chunk.tag.0);
+            out.extend_from_slice(&chunk.flags.0.to_le_bytes());
+            out.extend_from_slice(&offset.to_le_bytes());
+            out.extend_from_slice(&(chunk.payload.len() as u64).to_le_bytes());
+            offset += chunk.payload.len() as u64;
+        }
+
+        for chunk in chunks {
+            out.extend(chunk.payload);
+        }
+
+        out
+    }
+}
+
+pub struct RecordWriter<'a> {
+    payload: &'a mut Vec<u8>,
+}
+
+impl RecordWriter<'_> {
+    pub fn write(&mut self, record: impl Encode) -> &mut Self {
+        let bytes = bincode::encode_to_vec(record, bincode::config::standard()).unwrap();
+        let _ = bincode::encode_into_std_write(bytes.len() as u64, self.payload, bincode::config::standard())
+            .unwrap();
+        self.payload.extend(bytes);
+        self
+    }
+}
+
+/// A parsed container, which borrows the payloads of its chunks from the bytes it was read from
+pub struct Container<'a> {
+    chunks: Vec<(ChunkTag, ChunkFlags, &'a [u8])>,
+}
+
+impl<'a> Container<'a> {
+    /// Parses the header and table of contents of a container
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, ContainerError> {
+        if !is_container(bytes) {
+            return Err(ContainerError::NotAContainer);
+        }
+
+        let header = bytes.get(..HEADER_SIZE).ok_or(ContainerError::Truncated)?;
+        let major = u16::from_le_bytes([header[4], header[5]]);
+        let minor = u16::from_le_bytes([header[6], header[7]]);
+        if major != MAJOR_VERSION && major != UNCOMPRESSED_MAJOR_VERSION {
+            return Err(ContainerError::UnsupportedVersion { major, minor });
+        }
+
+        let count = u32::from_le_bytes(header[8..12].try_into().unwrap()) as usize;
+        let entries = count
+            .checked_mul(ENTRY_SIZE)
+            .and_then(|size| bytes.get(HEADER_SIZE..HEADER_SIZE + size))
+            .ok_or(ContainerError::Truncated)?;
+
+        let mut chunks = Vec::with_capacity(count);
+        for entry in entries.chunks_exact(ENTRY_SIZE) {
+            let tag = ChunkTag(entry[0..4].try_into().unwrap());
+            let flags = ChunkFlags(u32::from_le_bytes(entry[4..8].try_into().unwrap()));
+            let offset = u64::from_le_bytes(entry[8..16].try_into().unwrap());
+            let length = u64::from_le_bytes(entry[16..24].try_into().unwrap());
+
+            let payload = usize::try_from(offset)
+                .ok()
+                .zip(usize::try_from(length).ok())
+                .and_then(|(offset, length)| bytes.get(offset..offset.checked_add(length)?))
+                .ok_or(ContainerError::Truncated)?;
+
+            chunks.push((tag, flags, payload));
+        }
+
+        Ok(Self { chunks })
+    }
+
+    /// Checks that every chunk flagged as [`ChunkFlags::REQUIRED`] is one of `known_tags`, the tags the reader is able
+    /// to make sense of
+    pub fn ensure_supported(&self, known_tags: &[ChunkTag]) -> Result<(), ContainerError> {
+        match self
+            .chunks
+            .iter()
+            .find(|(tag, flags, _)| flags.contains(ChunkFlags::REQUIRED) && !known_tags.contains(tag))
+        {
+            Some((tag, _, _)) => Err(ContainerError::UnknownRequiredChunk(*tag)),
+            None => Ok(()),
+        }
+    }
+
+    /// Every chunk in the container, in the order they are listed in the table of contents
+    ///
+    /// Payloads are returned as they are stored, without being decompressed.
+    pub fn chunks(&self) -> impl Iterator<Item = (ChunkTag, ChunkFlags, &'a [u8])> + '_ {
+        self.chunks.iter().copied()
+    }
+
+    fn find(&self, tag: ChunkTag) -> Option<(ChunkFlags, &'a [u8])> {
+        self.chunks
+            .iter()
+            .find(|(chunk_tag, _, _)| *chunk_tag == tag)
+            .map(|(_, flags, payload)| (*flags, *payload))
+    }
+
+    /// Records of the first chunk with `tag`, decompressing its payload if needed
+    pub fn chunk(&self, tag: ChunkTag) -> Result<Option<Records<'a>>, ContainerError> {
+        let Some((flags, payload)) = self.find(tag) else {
+            return Ok(None);
+        };
+
+        Ok(Some(Records {
+            tag,
+            payload: decompress(tag, flags, payload)?,
+            position: 0,
+        }))
+    }
+}
+
+/// Reads the records of a chunk one at a time
+pub struct Records<'a> {
+    tag: ChunkTag,
+    payload: Cow<'a, [u8]>,
+    position: usize,
+}
+
+impl Records<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.position >= self.payload.len()
+    }
+
+    pub fn read<T: Decode<()>>(&mut self) -> Result<T, ContainerError> {
+        let remaining = &self.payload[self.position..];
+        let (length, consumed): (u64, usize) = bincode::decode_from_slice(remaining, bincode::config::standard())
+            .map_err(|error| ContainerError::Record(self.tag, error))?;
+
+        let record = usize::try_from(length)
+            .ok()
+            .and_then(|length| remaining.get(consumed..consumed.checked_add(length)?))
+            .ok_or(ContainerError::Truncated)?;
+
+        self.position += consumed + record.len();
+
+        bincode::decode_from_reader(ZeroExtended(record), bincode::config::standard())
+            .map_err(|error| ContainerError::Record(self.tag, error))
+    }
+
+    /// Skips `count` records without decoding them, for records that belong to something the reader does not know
+    pub fn skip(&mut self, count: u32) -> Result<(), ContainerError> {
+        for _ in 0..count {
+            let remaining = &self.payload[self.position..];
+            let (length, consumed): (u64, usize) = bincode::decode_from_slice(remaining, bincode::config::standard())
+                .map_err(|error| ContainerError::Record(self.tag, error))?;
+
+            let end = usize::try_from(length)
+                .ok()
+                .and_then(|length| consumed.checked_add(length))
+                .filter(|end| *end <= remaining.len())
+                .ok_or(ContainerError::Truncated)?;
+
+            self.position += end;
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads a record, producing zeros once the end of the record has been reached
+struct ZeroExtended<'a>(&'a [u8]);
+
+impl Reader for ZeroExtended<'_> {
+    fn read(&mut self, bytes: &mut [u8]) -> Result<(), DecodeError> {
+        let available = self.0.len().min(bytes.len());
+        bytes[..available].copy_from_slice(&self.0[..available]);
+        bytes[available..].fill(0);
+        self.0 = &self.0[available..];
+        Ok(())
+    }
+}
+
+/// Information about the asset that is not needed to load it
+#[derive(Decode, Encode)]
+pub struct MetadataRecord {
+    /// Name and version of the program that wrote the asset
+    pub generator: String,
+}
+
+/// A layout template, without its state machine, nodes or animations
+#[derive(Decode, Encode)]
+pub struct TemplateRecord {
+    /// Name of the template, or `None` for the root template
+    pub name: Option<String>,
+    pub canvas_size: [u32; 2],
+    pub has_state_machine: bool,
+    pub root_node_count: u32,
+}
+
+/// A [`StateMachine`], without its parameters, states or transitions
+#[derive(Decode, Encode)]
+pub struct StateMachineRecord {
+    pub entry_state: String,
+    pub layer: u32,
+    pub parameter_count: u32,
+    pub state_count: u32,
+    pub transition_count: u32,
+}
+
+/// A parameter of a state machine and its initial value
+#[derive(Decode, Encode)]
+pub struct ParameterRecord {
+    pub name: String,
+    pub value: ParamValue,
+}
+
+/// An [`AnimationState`]
+#[derive(Decode, Encode)]
+pub struct StateRecord {
+    pub name: String,
+    pub animation: Option<String>,
+    pub looping: bool,
+}
+
+/// A [`StateTransition`], without its conditions
+#[derive(Decode, Encode)]
+pub struct TransitionRecord {
+    pub from: Option<String>,
+    pub to: String,
+    pub crossfade_frames: usize,
+    pub condition_count: u32,
+}
+
+/// A [`TransitionCondition`]
+///
+/// Readers drop transitions with a condition they do not know, since taking them without it could play the wrong
+/// animation.
+#[derive(Decode, Encode)]
+pub struct ConditionRecord {
+    /// One of the `ConditionRecord` constants
+    pub kind: u32,
+
+    /// Name of the parameter or trigger
+    pub param: String,
+
+    /// Value the parameter is compared to, always a float for [`ConditionRecord::GREATER`] and
+    /// [`ConditionRecord::LESS`]
+    pub value: ParamValue,
+}
+
+impl ConditionRecord {
+    pub const EQUALS: u32 = 0;
+    pub const GREATER: u32 = 1;
+    pub const LESS: u32 = 2;
+    pub const TRIGGER: u32 = 3;
+    pub const ANIMATION_FINISHED: u32 = 4;
+}
+
+/// Identifies the implementation of a node, which is written as the record following its [`TransformRecord`]
+///
+/// Empty nodes are not followed by a record. Every other kind is followed by exactly one, so that readers can skip
+/// kinds they do not know.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Decode, Encode)]
+pub struct NodeKind(pub u32);
+
+impl NodeKind {
+    pub const EMPTY: Self = Self(0);
+
+    /// Followed by an [`ImageRecord`]
+    pub const IMAGE: Self = Self(1);
+
+    /// Followed by a [`TextRecord`]
+    pub const TEXT: Self = Self(2);
+
+    /// Followed by a [`SublayoutRecord`]
+    pub const SUBLAYOUT: Self = Self(3);
+
+    /// Followed by a [`ShapeRecord`]
+    pub const SHAPE: Self = Self(4);
+}
+
+/// A node template, without its transform, implementation, gradient or children
+#[derive(Decode, Encode)]
+pub struct NodeRecord {
+    pub name: String,
+    pub color: [u8; 4],
+    pub visibility: NodeVisibility,
+    pub kind: NodeKind,
+    pub has_gradient: bool,
+    pub child_count: u32,
+}
+
+/// A [`NodeTransform`]
+#[derive(Decode, Encode)]
+pub struct TransformRecord {
+    pub angle: f32,
+    pub position: [f32; 2],
+    pub size: [f32; 2],
+    pub scale: [f32; 2],
+    pub anchor: Anchor,
+}
+
+/// The implementation of a [`NodeKind::IMAGE`] node
+#[derive(Decode, Encode)]
+pub struct ImageRecord {
+    pub texture_name: String,
+    pub mask_texture_name: Option<String>,
+    pub scaling_x: ImageScalingMode,
+    pub scaling_y: ImageScalingMode,
+    pub uv_offset: [f32; 2],
+    pub uv_scale: [f32; 2],
+    pub fill_method: ImageFillMethod,
+    pub fill_origin: ImageFillOrigin,
+    pub fill_amount: f32,
+}
+
+/// The implementation of a [`NodeKind::TEXT`] node
+#[derive(Decode, Encode)]
+pub struct TextRecord {
+    pub font_name: String,
+    pub text: String,
+    pub font_size: f32,
+    pub line_height: f32,
+    pub outline_thickness: f32,
+    pub outline_color: [u8; 4],
+    pub alignment: TextAlignment,
+}
+
+/// The implementation of a [`NodeKind::SUBLAYOUT`] node
+#[derive(Decode, Encode)]
+pub struct SublayoutRecord {
+    pub sublayout_name: String,
+}
+
+/// The implementation of a [`NodeKind::SHAPE`] node
+///
+/// Readers draw shapes they do not know as the default rectangle.
+#[derive(Decode, Encode)]
+pub struct ShapeRecord {
+    /// One of the `ShapeRecord` constants
+    pub shape: u32,
+
+    /// Only used by [`ShapeRecord::RECTANGLE`]
+    pub corner_radii: [f32; 4],
+
+    /// Only used by [`ShapeRecord::POLYGON`] and [`ShapeRecord::POLYLINE`]
+    pub points: Vec<[f32; 2]>,
+    pub fill_color: Option<[u8; 4]>,
+    pub stroke_color: [u8; 4],
+    pub stroke_thickness: f32,
+}
+
+impl ShapeRecord {
+    pub const RECTANGLE: u32 = 0;
+    pub const ELLIPSE: u32 = 1;
+    pub const POLYGON: u32 = 2;
+    pub const POLYLINE: u32 = 3;
+}
+
+/// A [`Gradient`], without its stops
+///
+/// Readers use the default geometry for gradients of a kind they do not know.
+#[derive(Decode, Encode)]
+pub struct GradientRecord {
+    /// One of the `GradientRecord` constants
+    pub kind: u32,
+
+    /// Only used by [`GradientRecord::LINEAR`]
+    pub start: [f32; 2],
+    pub end: [f32; 2],
+
+    /// Only used by [`GradientRecord::RADIAL`]
+    pub center: [f32; 2],
+    pub radius: f32,
+    pub stop_count: u32,
+}
+
+impl GradientRecord {
+    pub const LINEAR: u32 = 0;
+    pub const RADIAL: u32 = 1;
+}
+
+/// A [`GradientStop`]
+#[derive(Decode, Encode)]
+pub struct GradientStopRecord {
+    pub offset: f32,
+    pub color: [u8; 4],
+}
+
+/// An animation, without its node animations
+#[derive(Decode, Encode)]
+pub struct AnimationRecord {
+    /// Name of the template the animation belongs to, or `None` for the root template
+    pub template: Option<String>,
+    pub name: String,
+    pub total_duration: usize,
+    pub node_animation_count: u32,
+}
+
+/// A [`NodeAnimation`], without its channels
+#[derive(Decode, Encode)]
+pub struct NodeAnimationRecord {
+    pub node_path: String,
+    pub channel_count: u32,
+
+    /// Length of [`NodeAnimation::gradient_stop_color_channels`]
+    pub gradient_stop_count: u32,
+
+    /// Number of gradient stops that were baked, or `None` if the animation isn't baked
+    pub baked_gradient_stop_count: Option<u32>,
+}
+
+/// Identifies the channel of a [`ChannelRecord`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Decode, Encode)]
+pub struct ChannelId(pub u32);
+
+impl ChannelId {
+    pub const ANGLE: Self = Self(0);
+    pub const POSITION: Self = Self(1);
+    pub const MOTION_PATH: Self = Self(2);
+    pub const SIZE: Self = Self(3);
+    pub const SCALE: Self = Self(4);
+    pub const COLOR: Self = Self(5);
+    pub const VISIBILITY: Self = Self(6);
+    pub const UV_OFFSET: Self = Self(7);
+    pub const UV_SCALE: Self = Self(8);
+    pub const FILL_AMOUNT: Self = Self(9);
+    pub const TEXTURE: Self = Self(10);
+    pub const FONT_SIZE: Self = Self(11);
+    pub const LINE_HEIGHT: Self = Self(12);
+    pub const OUTLINE_THICKNESS: Self = Self(13);
+    pub const OUTLINE_COLOR: Self = Self(14);
+    pub const VISIBLE_CHARACTERS: Self = Self(15);
+    pub const TEXT: Self = Self(16);
+    pub const SUBLAYOUT_ANIMATION: Self = Self(17);
+
+    /// The color of the gradient stop at [`ChannelRecord::index`]
+    pub const GRADIENT_STOP_COLOR: Self = Self(18);
+}
+
+/// A channel of a node animation, followed by `record_count` records
+///
+/// Curves are followed by a [`CurveKeyframeRecord`] and discrete channels by a [`DiscreteKeyframeRecord`] for each
+/// keyframe, starting with the value at frame 0. A [`ChannelId::MOTION_PATH`] is followed by a [`MotionPathRecord`]
+/// and the keyframes of its progress curve. Baked channels are followed by a single [`BakedChannelRecord`].
+#[derive(Decode, Encode)]
+pub struct ChannelRecord {
+    pub channel: ChannelId,
+
+    /// Index of the gradient stop, for [`ChannelId::GRADIENT_STOP_COLOR`]
+    pub index: u32,
+    pub mode: ChannelMode,
+
+    /// Whether this holds the per-frame samples of the channel instead of its keyframes, see [`NodeAnimation::bake`]
+    pub baked: bool,
+    pub record_count: u32,
+}
+
+/// A keyframe of a curve, the first one holds the start of the curve and ignores its duration and steps
+#[derive(Decode, Encode)]
+pub struct CurveKeyframeRecord<T> {
+    pub value: T,
+
+    /// Number of frames after the previous keyframe
+    pub duration: usize,
+    pub first_step: TransformStep,
+    pub additional_steps: Vec<TransformStep>,
+    pub tangent_mode: TangentMode,
+    pub in_slope: f32,
+    pub out_slope: f32,
+}
+
+/// A keyframe of a discrete channel, the first one holds the start of the channel and ignores its duration
+#[derive(Decode, Encode)]
+pub struct DiscreteKeyframeRecord<T> {
+    pub value: T,
+
+    /// Number of frames after the previous keyframe
+    pub duration: usize,
+}
+
+/// A [`MotionPath`], without its progress curve
+#[derive(Decode, Encode)]
+pub struct MotionPathRecord {
+    pub points: Vec<[f32; 2]>,
+    pub orient_to_path: bool,
+}
+
+/// The per-frame samples of a baked channel
+#[derive(Decode, Encode)]
+pub struct BakedChannelRecord {
+    /// Number of floats per frame
+    pub components: u32,
+
+    /// Every component of every frame, unless the channel is quantized
+    pub values: Vec<f32>,
+
+    /// Smallest value and range of each component of a quantized channel, which `quantized_values` are spread over
+    pub min: Vec<f32>,
+    pub range: Vec<f32>,
+    pub quantized_values: Vec<u16>,
+    pub is_quantized: bool,
+}
+
+/// An embedded image or font
+#[derive(Decode, Encode)]
+pub struct ResourceRecord {
+    pub name: String,
+    pub bytes: Vec<u8>,
+}
+
+/// An image or font that is resolved by name when the asset is loaded
+#[derive(Decode, Encode)]
+pub struct ReferenceRecord {
+    pub name: String,
+    /// [`content_hash`](super::content_hash) of the resource when the asset was written
+    pub hash: u64,
+}
+
+/// Where the bytes of an embedded image or font are inside of the decompressed payload of `IMAG` or `FONT`
+#[derive(Decode, Encode)]
+pub struct ResourceIndexRecord {
+    pub kind: ResourceKind,
+    pub name: String,
+    pub offset: u64,
+    pub length: u64,
+
+    /// [`content_hash`](super::content_hash) of the resource, so that it can be compared without loading it. `None`
+    /// in assets written before it was stored
+    pub content_hash: Option<u64>,
+}
+
+/// An embedded image or font that has the same bytes as `target`
+#[derive(Decode, Encode)]
+pub struct AliasRecord {
+    pub kind: ResourceKind,
+    pub name: String,
+
+    /// Name of the resource of the same kind whose bytes are stored in `IMAG` or `FONT`
+    pub target: String,
+}
+
+/// The base template of a derived template, without its patches or added nodes
+#[derive(Decode, Encode)]
+pub struct InheritanceRecord {
+    /// Name of the derived template, or `None` for the root template
+    pub template: Option<String>,
+    pub base: String,
+    pub removed_nodes: Vec<String>,
+    pub patch_count: u32,
+    pub added_node_count: u32,
+}
+
+/// A [`NodePatch`], without the transform, implementation or gradient it replaces
+///
+/// Those follow the record in that order, as a [`TransformRecord`], the record of the implementation's kind and a
+/// gradient, when the patch replaces them.
+#[derive(Decode, Encode)]
+pub struct PatchRecord {
+    pub path: String,
+    pub color: Option<[u8; 4]>,
+    pub visibility: Option<NodeVisibility>,
+    pub has_transform: bool,
+
+    /// Kind of the node's new implementation, or `None` if the patch keeps the base's
+    pub implementation: Option<NodeKind>,
+
+    /// Whether the patch replaces the base's gradient, with the gradient that follows if `has_gradient` is set or
+    /// by removing it otherwise
+    pub replaces_gradient: bool,
+    pub has_gradient: bool,
+}
+
+/// A node that a derived template adds to a node of its base template
+#[derive(Decode, Encode)]
+pub struct AddedNodeRecord {
+    pub parent: String,
+}
+
+/// The payload of a chunk copied out of a container, which is only decompressed once it is first needed
+pub(super) struct LazyChunk {
+    tag: ChunkTag,
+    flags: ChunkFlags,
+    payload: Box<[u8]>,
+    decompressed: OnceLock<Option<Box<[u8]>>>,
+}
+
+impl LazyChunk {
+    fn new(tag: ChunkTag, flags: ChunkFlags, payload: Box<[u8]>) -> Result<Self, ContainerError> {
+        if flags.compression().is_none() {
+            return Err(ContainerError::UnsupportedCompression(tag));
+        }
+
+        Ok(Self {
+            tag,
+            flags,
+            payload,
+            decompressed: OnceLock::new(),
+        })
+    }
+
+    /// A chunk that holds the bytes of a single resource, for containers without a resource index
+    fn loaded(tag: ChunkTag, bytes: Vec<u8>) -> Self {
+        Self {
+            tag,
+            flags: ChunkFlags::NONE,
+            payload: bytes.into_boxed_slice(),
+            decompressed: OnceLock::new(),
+        }
+    }
+
+    pub(super) fn payload(&self) -> Option<&[u8]> {
+        if self.flags.compression() == Some(Compression::None) {
+            return Some(&self.payload);
+        }
+
+        self.decompressed
+            .get_or_init(|| match decompress(self.tag, self.flags, &self.payload) {
+                Ok(payload) => Some(payload.into_owned().into_boxed_slice()),
+                Err(error) => {
+                    log::error!("{error}");
+                    None
+                }
+            })
+            .as_deref()
+    }
+}
+
+const KNOWN_TAGS: &[ChunkTag] = &[
+    ChunkTag::METADATA,
+    ChunkTag::TEMPLATES,
+    ChunkTag::ANIMATIONS,
+    ChunkTag::IMAGES,
+    ChunkTag::FONTS,
+    ChunkTag::IMAGE_REFERENCES,
+    ChunkTag::FONT_REFERENCES,
+    ChunkTag::RESOURCE_INDEX,
+    ChunkTag::ALIASES,
+    ChunkTag::INHERITANCE,
+];
+
+impl ResourceKind {
+    fn embedded_tag(self) -> ChunkTag {
+        match self {
+            Self::Image => ChunkTag::IMAGES,
+            Self::Font => ChunkTag::FONTS,
+        }
+    }
+
+    fn referenced_tag(self) -> ChunkTag {
+        match self {
+            Self::Image => ChunkTag::IMAGE_REFERENCES,
+            Self::Font => ChunkTag::FONT_REFERENCES,
+        }
+    }
+}
+
+/// Writes the `IMAG` or `FONT` chunk, keeping track of where each resource ends up for the `RIDX` chunk
+///
+/// Resources with the same bytes as an earlier one are only stored once, the rest are added to `aliases`.
+fn write_resources<'r>(
+    writer: &mut ContainerWriter,
+    index: &mut Vec<ResourceIndexRecord>,
+    aliases: &mut Vec<AliasRecord>,
+    kind: ResourceKind,
+    flags: ChunkFlags,
+    resources: impl IntoIterator<Item = (&'r str, &'r [u8])>,
+) {
+    let mut written: HashMap<u64, Vec<(&'r str, &'r [u8])>> = HashMap::new();
+    let mut records = writer.chunk(kind.embedded_tag(), flags);
+    for (name, bytes) in resources {
+        // The bytes are compared as well, so that a hash collision can't swap one image for another
+        let hash = super::content_hash(bytes);
+        let same_hash = written.entry(hash).or_default();
+        if let Some((target, _)) = same_hash.iter().find(|(_, other)| *other == bytes) {
+            aliases.push(AliasRecord {
+                kind,
+                name: name.to_string(),
+                target: target.to_string(),
+            });
+            continue;
+        }
+
+        same_hash.push((name, bytes));
+        records.write((name, bytes));
+
+        // Byte vectors are encoded as their length followed by the bytes, so they are always at the end of the record
+        index.push(ResourceIndexRecord {
+            kind,
+            name: name.to_string(),
+            offset: (records.payload.len() - bytes.len()) as u64,
+            length: bytes.len() as u64,
+            content_hash: Some(hash),
+        });
+    }
+}
+
+fn write_resource_index(writer: &mut ContainerWriter, index: Vec<ResourceIndexRecord>, aliases: Vec<AliasRecord>) {
+    let mut records = writer.chunk(ChunkTag::RESOURCE_INDEX, ChunkFlags::NONE);
+    index.into_iter().for_each(|record| {
+        records.write(record);
+    });
+
+    if aliases.is_empty() {
+        return;
+    }
+
+    let mut records = writer.chunk(ChunkTag::ALIASES, ChunkFlags::REQUIRED);
+    aliases.into_iter().for_each(|record| {
+        records.write(record);
+    });
+}
+
+fn write_state_machine(records: &mut RecordWriter<'_>, state_machine: &StateMachine) {
+    records.write(StateMachineRecord {
+        entry_state: state_machine.entry_state.clone(),
+        layer: state_machine.layer,
+        parameter_count: state_machine.parameters.len() as u32,
+        state_count: state_machine.states.len() as u32,
+        transition_count: state_machine.transitions.len() as u32,
+    });
+
+    for (name, value) in state_machine.parameters.iter() {
+        records.write(ParameterRecord {
+            name: name.clone(),
+            value: *value,
+        });
+    }
+
+    for state in state_machine.states.iter() {
+        records.write(StateRecord {
+            name: state.name.clone(),
+            animation: state.animation.clone(),
+            looping: state.looping,
+        });
+    }
+
+    for transition in state_machine.transitions.iter() {
+        records.write(TransitionRecord {
+            from: transition.from.clone(),
+            to: transition.to.clone(),
+            crossfade_frames: transition.crossfade_frames,
+            condition_count: transition.conditions.len() as u32,
+        });
+
+        for condition in transition.conditions.iter() {
+            let (kind, param, value) = match condition {
+                TransitionCondition::Equals { param, value } => (ConditionRecord::EQUALS, param.as_str(), *value),
+                TransitionCondition::Greater { param, value } => {
+                    (ConditionRecord::GREATER, param.as_str(), ParamValue::Float(*value))
+                }
+                TransitionCondition::Less { param, value } => {
+                    (ConditionRecord::LESS, param.as_str(), ParamValue::Float(*value))
+                }
+                TransitionCondition::Trigger(trigger) => {
+                    (ConditionRecord::TRIGGER, trigger.as_str(), ParamValue::Bool(false))
+                }
+                TransitionCondition::AnimationFinished => {
+                    (ConditionRecord::ANIMATION_FINISHED, "", ParamValue::Bool(false))
+                }
+            };
+
+            records.write(ConditionRecord {
+                kind,
+                param: param.to_string(),
+                value,
+            });
+        }
+    }
+}
+
+fn read_state_machine(records: &mut Records<'_>) -> Result<StateMachine, ContainerError> {
+    let record: StateMachineRecord = records.read()?;
+    let mut state_machine = StateMachine::new(record.entry_state);
+    state_machine.layer = record.layer;
+
+    for _ in 0..record.parameter_count {
+        let parameter: ParameterRecord = records.read()?;
+        state_machine.parameters.push((parameter.name, parameter.value));
+    }
+
+    for _ in 0..record.state_count {
+        let state: StateRecord = records.read()?;
+        state_machine.states.push(AnimationState {
+            name: state.name,
+            animation: state.animation,
+            looping: state.looping,
+        });
+    }
+
+    for _ in 0..record.transition_count {
+        let transition: TransitionRecord = records.read()?;
+        let mut conditions = Some(vec![]);
+        for _ in 0..transition.condition_count {
+            let condition: ConditionRecord = records.read()?;
+            let condition = match condition.kind {
+                ConditionRecord::EQUALS => Some(TransitionCondition::Equals {
+                    param: condition.param,
+                    value: condition.value,
+                }),
+                ConditionRecord::GREATER => Some(TransitionCondition::Greater {
+                    param: condition.param,
+                    value: condition.value.as_float(),
+                }),
+                ConditionRecord::LESS => Some(TransitionCondition::Less {
+                    param: condition.param,
+                    value: condition.value.as_float(),
+                }),
+                ConditionRecord::TRIGGER => Some(TransitionCondition::Trigger(condition.param)),
+                ConditionRecord::ANIMATION_FINISHED => Some(TransitionCondition::AnimationFinished),
+                _ => None,
+            };
+
+            match (conditions.as_mut(), condition) {
+                (Some(conditions), Some(condition)) => conditions.push(condition),
+                _ => conditions = None,
+            }
+        }
+
+        match conditions {
+            Some(conditions) => state_machine.transitions.push(StateTransition {
+                from: transition.from,
+                to: transition.to,
+                conditions,
+                crossfade_frames: transition.crossfade_frames,
+            }),
+            None => log::warn!(
+                "Skipping transition to state '{}', which has a condition that is not supported",
+                transition.to
+            ),
+        }
+    }
+
+    Ok(state_machine)
+}
+
+fn write_transform(records: &mut RecordWriter<'_>, transform: &NodeTransform) {
+    records.write(TransformRecord {
+        angle: transform.angle,
+        position: transform.position.to_array(),
+        size: transform.size.to_array(),
+        scale: transform.scale.to_array(),
+        anchor: transform.anchor,
+    });
+}
+
+fn read_transform(records: &mut Records<'_>) -> Result<NodeTransform, ContainerError> {
+    let record: TransformRecord = records.read()?;
+    Ok(NodeTransform {
+        angle: record.angle,
+        position: record.position.into(),
+        size: record.size.into(),
+        scale: record.scale.into(),
+        anchor: record.anchor,
+    })
+}
+
+fn node_kind(implementation: &NodeImplTemplate) -> NodeKind {
+    match implementation {
+        NodeImplTemplate::Empty => NodeKind::EMPTY,
+        NodeImplTemplate::Image(_) => NodeKind::IMAGE,
+        NodeImplTemplate::Text(_) => NodeKind::TEXT,
+        NodeImplTemplate::Sublayout(_) => NodeKind::SUBLAYOUT,
+        NodeImplTemplate::Shape(_) => NodeKind::SHAPE,
+    }
+}
+
+/// Writes the record of the implementation's [`NodeKind`], if it has one
+fn write_implementation(records: &mut RecordWriter<'_>, implementation: &NodeImplTemplate) {
+    match implementation {
+        NodeImplTemplate::Empty => {}
+        NodeImplTemplate::Image(image) => {
+            records.write(ImageRecord {
+                texture_name: image.texture_name.clone(),
+                mask_texture_name: image.mask_texture_name.clone(),
+                scaling_x: image.image_scaling_mode_x,
+                scaling_y: image.image_scaling_mode_y,
+                uv_offset: image.uv_offset.to_array(),
+                uv_scale: image.uv_scale.to_array(),
+                fill_method: image.fill_method,
+                fill_origin: image.fill_origin,
+                fill_amount: image.fill_amount,
+            });
+        }
+        NodeImplTemplate::Text(text) => {
+            records.write(TextRecord {
+                font_name: text.font_name.clone(),
+                text: text.text.clone(),
+                font_size: text.font_size,
+                line_height: text.line_height,
+                outline_thickness: text.outline_thickness,
+                outline_color: text.outline_color,
+                alignment: text.alignment,
+            });
+        }
+        NodeImplTemplate::Sublayout(sublayout) => {
+            records.write(SublayoutRecord {
+                sublayout_name: sublayout.sublayout_name.clone(),
+            });
+        }
+        NodeImplTemplate::Shape(shape) => {
+            let (kind, corner_radii, points) = match &shape.kind {
+                ShapeKind::Rectangle { corner_radii } => (ShapeRecord::RECTANGLE, *corner_radii, &[][..]),
+                ShapeKind::Ellipse => (ShapeRecord::ELLIPSE, [0.0; 4], &[][..]),
+                ShapeKind::Polygon { points } => (ShapeRecord::POLYGON, [0.0; 4], points.as_slice()),
+                ShapeKind::Polyline { points } => (ShapeRecord::POLYLINE, [0.0; 4], points.as_slice()),
+            };
+
+            records.write(ShapeRecord {
+                shape: kind,
+                corner_radii,
+                points: points.iter().map(|point| point.to_array()).collect(),
+                fill_color: shape.fill_color,
+                stroke_color: shape.stroke_color,
+                stroke_thickness: shape.stroke_thickness,
+            });
+        }
+    }
+}
+
+/// Reads the implementation of a node of `kind`, or `None` if the kind is unknown to this version of `envy`
+fn read_implementation(records: &mut Records<'_>, kind: NodeKind) -> Result<Option<NodeImplTemplate>, ContainerError> {
+    let implementation = match kind {
+        NodeKind::EMPTY => NodeImplTemplate::Empty,
+        NodeKind::IMAGE => {
+            let image: ImageRecord = records.read()?;
+            NodeImplTemplate::Image(ImageNodeTemplate {
+                texture_name: image.texture_name,
+                mask_texture_name: image.mask_texture_name,
+                image_scaling_mode_x: image.scaling_x,
+                image_scaling_mode_y: image.scaling_y,
+                uv_offset: image.uv_offset.into(),
+                uv_scale: image.uv_scale.into(),
+                fill_method: image.fill_method,
+                fill_origin: image.fill_origin,
+                fill_amount: image.fill_amount,
+            })
+        }
+        NodeKind::TEXT => {
+            let text: TextRecord = records.read()?;
+            NodeImplTemplate::Text(TextNodeTemplate {
+                font_name: text.font_name,
+                text: text.text,
+                font_size: text.font_size,
+                line_height: text.line_height,
+                outline_thickness: text.outline_thickness,
+                outline_color: text.outline_color,
+                alignment: text.alignment,
+            })
+        }
+        NodeKind::SUBLAYOUT => {
+            let sublayout: SublayoutRecord = records.read()?;
+            NodeImplTemplate::Sublayout(SublayoutNodeTemplate {
+                sublayout_name: sublayout.sublayout_name,
+            })
+        }
+        NodeKind::SHAPE => {
+            let shape: ShapeRecord = records.read()?;
+            let points = || shape.points.iter().copied().map(glam::Vec2::from).collect();
+            let kind = match shape.shape {
+                ShapeRecord::RECTANGLE => ShapeKind::Rectangle {
+                    corner_radii: shape.corner_radii,
+                },
+                ShapeRecord::ELLIPSE => ShapeKind::Ellipse,
+                ShapeRecord::POLYGON => ShapeKind::Polygon { points: points() },
+                ShapeRecord::POLYLINE => ShapeKind::Polyline { points: points() },
+                unknown => {
+                    log::warn!("Drawing shape of unknown kind {unknown} as a rectangle");
+                    ShapeKind::default()
+                }
+            };
+
+            NodeImplTemplate::Shape(ShapeNodeTemplate {
+                kind,
+                fill_color: shape.fill_color,
+                stroke_color: shape.stroke_color,
+                stroke_thickness: shape.stroke_thickness,
+            })
+        }
+        _ => {
+            records.skip(1)?;
+            return Ok(None);
+        }
+    };
+
+    Ok(Some(implementation))
+}
+
+fn write_gradient(records: &mut RecordWriter<'_>, gradient: &Gradient) {
+    let (kind, start, end, center, radius) = match gradient.kind {
+        GradientKind::Linear { start, end } => (GradientRecord::LINEAR, start, end, glam::Vec2::ZERO, 0.0),
+        GradientKind::Radial { center, radius } => {
+            (GradientRecord::RADIAL, glam::Vec2::ZERO, glam::Vec2::ZERO, center, radius)
+        }
+    };
+
+    records.write(GradientRecord {
+        kind,
+        start: start.to_array(),
+        end: end.to_array(),
+        center: center.to_array(),
+        radius,
+        stop_count: gradient.stops.len() as u32,
+    });
+
+    for stop in gradient.stops.iter() {
+        records.write(GradientStopRecord {
+            offset: stop.offset,
+            color: stop.color,
+        });
+    }
+}
+
+fn read_gradient(records: &mut Records<'_>) -> Result<Gradient, ContainerError> {
+    let record: GradientRecord = records.read()?;
+    let kind = match record.kind {
+        GradientRecord::LINEAR => GradientKind::Linear {
+            start: record.start.into(),
+            end: record.end.into(),
+        },
+        GradientRecord::RADIAL => GradientKind::Radial {
+            center: record.center.into(),
+            radius: record.radius,
+        },
+        unknown => {
+            log::warn!("Using the default geometry for gradient of unknown kind {unknown}");
+            GradientKind::default()
+        }
+    };
+
+    let stops = (0..record.stop_count)
+        .map(|_| {
+            let stop: GradientStopRecord = records.read()?;
+            Ok(GradientStop {
+                offset: stop.offset,
+                color: stop.color,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Gradient { kind, stops })
+}
+
+fn write_node(records: &mut RecordWriter<'_>, node: &NodeTemplate) {
+    records.write(NodeRecord {
+        name: node.name.clone(),
+        color: node.color,
+        visibility: node.visibility,
+        kind: node_kind(&node.implementation),
+        has_gradient: node.gradient.is_some(),
+        child_count: node.children.len() as u32,
+    });
+
+    write_transform(records, &node.transform);
+    write_implementation(records, &node.implementation);
+    if let Some(gradient) = node.gradient.as_ref() {
+        write_gradient(records, gradient);
+    }
+
+    node.children.iter().for_each(|child| write_node(records, child));
+}
+
+fn read_node(records: &mut Records<'_>) -> Result<NodeTemplate, ContainerError> {
+    let node: NodeRecord = records.read()?;
+    let transform = read_transform(records)?;
+    let implementation = read_implementation(records, node.kind)?.unwrap_or_else(|| {
+        log::warn!("Building node '{}' of unknown kind {} as an empty node", node.name, node.kind.0);
+        NodeImplTemplate::Empty
+    });
+
+    let gradient = match node.has_gradient {
+        true => Some(read_gradient(records)?),
+        false => None,
+    };
+
+    let children = (0..node.child_count)
+        .map(|_| read_node(records))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(NodeTemplate {
+        name: node.name,
+        transform,
+        color: node.color,
+        visibility: node.visibility,
+        gradient,
+        children,
+        implementation,
+    })
+}
+
+/// Writes the channels of a node animation, counting them for its [`NodeAnimationRecord`]
+struct ChannelWriter<'a> {
+    records: RecordWriter<'a>,
+    count: u32,
+}
+
+impl ChannelWriter<'_> {
+    fn header(&mut self, channel: ChannelId, index: usize, mode: ChannelMode, baked: bool, record_count: usize) {
+        self.count += 1;
+        self.records.write(ChannelRecord {
+            channel,
+            index: index as u32,
+            mode,
+            baked,
+            record_count: record_count as u32,
+        });
+    }
+
+    fn keyframes<T: AnimationValue>(&mut self, curve: &AnimationChannel<T>)
+    where
+        T::Repr: Encode,
+    {
+        self.records.write(CurveKeyframeRecord {
+            value: curve.start.to_repr(),
+            duration: 0,
+            first_step: TransformStep::Linear,
+            additional_steps: vec![],
+            tangent_mode: curve.start_tangents.mode,
+            in_slope: curve.start_tangents.in_slope,
+            out_slope: curve.start_tangents.out_slope,
+        });
+
+        for transform in curve.transforms() {
+            self.records.write(CurveKeyframeRecord {
+                value: transform.end.to_repr(),
+                duration: transform.duration,
+                first_step: transform.first_step,
+                additional_steps: transform.additional_steps.clone(),
+                tangent_mode: transform.end_tangents.mode,
+                in_slope: transform.end_tangents.in_slope,
+                out_slope: transform.end_tangents.out_slope,
+            });
+        }
+    }
+
+    fn curve<T: AnimationValue>(&mut self, channel: ChannelId, index: usize, curve: Option<&AnimationChannel<T>>)
+    where
+        T::Repr: Encode,
+    {
+        let Some(curve) = curve else {
+            return;
+        };
+
+        self.header(channel, index, curve.mode, false, curve.transforms().len() + 1);
+        self.keyframes(curve);
+    }
+
+    fn discrete<T: Encode>(&mut self, channel: ChannelId, discrete: Option<&DiscreteChannel<T>>) {
+        let Some(discrete) = discrete else {
+            return;
+        };
+
+        self.header(channel, 0, ChannelMode::Absolute, false, discrete.keyframes.len() + 1);
+        self.records.write(DiscreteKeyframeRecord {
+            value: &discrete.start,
+            duration: 0,
+        });
+
+        for keyframe in discrete.keyframes.iter() {
+            self.records.write(DiscreteKeyframeRecord {
+                value: &keyframe.value,
+                duration: keyframe.duration,
+            });
+        }
+    }
+
+    fn motion_path(&mut self, path: Option<&MotionPath>) {
+        let Some(path) = path else {
+            return;
+        };
+
+        let record_count = path.progress_channel.transforms().len() + 2;
+        self.header(ChannelId::MOTION_PATH, 0, path.mode, false, record_count);
+        self.records.write(MotionPathRecord {
+            points: path.points().iter().map(|point| point.to_array()).collect(),
+            orient_to_path: path.orient_to_path,
+        });
+        self.keyframes(&path.progress_channel);
+    }
+
+    fn baked(&mut self, channel: ChannelId, index: usize, baked: Option<&BakedChannel>) {
+        let Some(baked) = baked else {
+            return;
+        };
+
+        self.header(channel, index, baked.mode, true, 1);
+        let mut record = BakedChannelRecord {
+            components: baked.components as u32,
+            values: vec![],
+            min: vec![],
+            range: vec![],
+            quantized_values: vec![],
+            is_quantized: false,
+        };
+
+        match &baked.samples {
+            BakedSamples::Full(values) => record.values = values.clone(),
+            BakedSamples::Quantized { min, range, values } => {
+                record.min = min.clone();
+                record.range = range.clone();
+                record.quantized_values = values.clone();
+                record.is_quantized = true;
+            }
+        }
+
+        self.records.write(record);
+    }
+}
+
+fn write_node_animation(records: &mut RecordWriter<'_>, animation: &NodeAnimation) {
+    let mut payload = vec![];
+    let mut channels = ChannelWriter {
+        records: RecordWriter { payload: &mut payload },
+        count: 0,
+    };
+
+    channels.curve(ChannelId::ANGLE, 0, animation.angle_channel.as_ref());
+    channels.curve(ChannelId::POSITION, 0, animation.position_channel.as_ref());
+    channels.motion_path(animation.motion_path.as_ref());
+    channels.curve(ChannelId::SIZE, 0, animation.size_channel.as_ref());
+    channels.curve(ChannelId::SCALE, 0, animation.scale_channel.as_ref());
+    channels.curve(ChannelId::COLOR, 0, animation.color_channel.as_ref());
+    channels.discrete(ChannelId::VISIBILITY, animation.visibility_channel.as_ref());
+    channels.curve(ChannelId::UV_OFFSET, 0, animation.uv_offset_channel.as_ref());
+    channels.curve(ChannelId::UV_SCALE, 0, animation.uv_scale_channel.as_ref());
+    channels.curve(ChannelId::FILL_AMOUNT, 0, animation.fill_amount_channel.as_ref());
+    channels.discrete(ChannelId::TEXTURE, animation.texture_channel.as_ref());
+    channels.curve(ChannelId::FONT_SIZE, 0, animation.font_size_channel.as_ref());
+    channels.curve(ChannelId::LINE_HEIGHT, 0, animation.line_height_channel.as_ref());
+    channels.curve(ChannelId::OUTLINE_THICKNESS, 0, animation.outline_thickness_channel.as_ref());
+    channels.curve(ChannelId::OUTLINE_COLOR, 0, animation.outline_color_channel.as_ref());
+    channels.curve(ChannelId::VISIBLE_CHARACTERS, 0, animation.visible_characters_channel.as_ref());
+    channels.discrete(ChannelId::TEXT, animation.text_channel.as_ref());
+    channels.discrete(ChannelId::SUBLAYOUT_ANIMATION, animation.sublayout_animation_channel.as_ref());
+    for (index, channel) in animation.gradient_stop_color_channels.iter().enumerate() {
+        channels.curve(ChannelId::GRADIENT_STOP_COLOR, index, channel.as_ref());
+    }
+
+    if let Some(baked) = animation.baked.as_ref() {
+        channels.baked(ChannelId::ANGLE, 0, baked.angle.as_ref());
+        channels.baked(ChannelId::POSITION, 0, baked.position.as_ref());
+        channels.baked(ChannelId::SIZE, 0, baked.size.as_ref());
+        channels.baked(ChannelId::SCALE, 0, baked.scale.as_ref());
+        channels.baked(ChannelId::COLOR, 0, baked.color.as_ref());
+        channels.baked(ChannelId::UV_OFFSET, 0, baked.uv_offset.as_ref());
+        channels.baked(ChannelId::UV_SCALE, 0, baked.uv_scale.as_ref());
+        channels.baked(ChannelId::FILL_AMOUNT, 0, baked.fill_amount.as_ref());
+        channels.baked(ChannelId::FONT_SIZE, 0, baked.font_size.as_ref());
+        channels.baked(ChannelId::LINE_HEIGHT, 0, baked.line_height.as_ref());
+        channels.baked(ChannelId::OUTLINE_THICKNESS, 0, baked.outline_thickness.as_ref());
+        channels.baked(ChannelId::OUTLINE_COLOR, 0, baked.outline_color.as_ref());
+        channels.baked(ChannelId::VISIBLE_CHARACTERS, 0, baked.visible_characters.as_ref());
+        for (index, channel) in baked.gradient_stop_colors.iter().enumerate() {
+            channels.baked(ChannelId::GRADIENT_STOP_COLOR, index, channel.as_ref());
+        }
+    }
+
+    records.write(NodeAnimationRecord {
+        node_path: animation.node_path.clone(),
+        channel_count: channels.count,
+        gradient_stop_count: animation.gradient_stop_color_channels.len() as u32,
+        baked_gradient_stop_count: animation
+            .baked
+            .as_ref()
+            .map(|baked| baked.gradient_stop_colors.len() as u32),
+    });
+    records.payload.extend(payload);
+}
+
+fn read_keyframes<T: AnimationValue>(
+    records: &mut Records<'_>,
+    mode: ChannelMode,
+    count: u32,
+) -> Result<Option<AnimationChannel<T>>, ContainerError>
+where
+    T::Repr: Decode<()>,
+{
+    let mut keyframes = (0..count)
+        .map(|_| records.read::<CurveKeyframeRecord<T::Repr>>())
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter();
+
+    let Some(start) = keyframes.next() else {
+        return Ok(None);
+    };
+
+    let tangents = |keyframe: &CurveKeyframeRecord<T::Repr>| KeyTangents {
+        mode: keyframe.tangent_mode,
+        in_slope: keyframe.in_slope,
+        out_slope: keyframe.out_slope,
+    };
+
+    let start_tangents = tangents(&start);
+    let transforms = keyframes
+        .map(|keyframe| AnimationTransform {
+            end_tangents: tangents(&keyframe),
+            duration: keyframe.duration,
+            first_step: keyframe.first_step,
+            additional_steps: keyframe.additional_steps,
+            end: T::from_repr(keyframe.value),
+        })
+        .collect();
+
+    let mut curve = AnimationChannel::from_transforms(T::from_repr(start.value), transforms).with_mode(mode);
+    curve.start_tangents = start_tangents;
+    Ok(Some(curve))
+}
+
+fn read_discrete<T: Decode<()>>(
+    records: &mut Records<'_>,
+    count: u32,
+) -> Result<Option<DiscreteChannel<T>>, ContainerError> {
+    let mut keyframes = (0..count)
+        .map(|_| records.read::<DiscreteKeyframeRecord<T>>())
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter();
+
+    let Some(start) = keyframes.next() else {
+        return Ok(None);
+    };
+
+    Ok(Some(DiscreteChannel {
+        start: start.value,
+        keyframes: keyframes
+            .map(|keyframe| DiscreteKeyframe {
+                value: keyframe.value,
+                duration: keyframe.duration,
+            })
+            .collect(),
+    }))
+}
+
+fn read_motion_path(records: &mut Records<'_>, channel: &ChannelRecord) -> Result<Option<MotionPath>, ContainerError> {
+    if channel.record_count == 0 {
+        return Ok(None);
+    }
+
+    let record: MotionPathRecord = records.read()?;
+    let Some(progress_channel) = read_keyframes(records, ChannelMode::Absolute, channel.record_count - 1)? else {
+        return Ok(None);
+    };
+
+    let mut path = MotionPath::new(glam::Vec2::ZERO, glam::Vec2::ZERO, 0);
+    path.mode = channel.mode;
+    path.set_points(record.points.into_iter().map(glam::Vec2::from).collect());
+    path.progress_channel = progress_channel;
+    path.orient_to_path = record.orient_to_path;
+    Ok(Some(path))
+}
+
+fn read_baked(records: &mut Records<'_>, channel: &ChannelRecord) -> Result<BakedChannel, ContainerError> {
+    let record: BakedChannelRecord = records.read()?;
+    records.skip(channel.record_count.saturating_sub(1))?;
+    Ok(BakedChannel {
+        mode: channel.mode,
+        components: record.components as usize,
+        samples: match record.is_quantized {
+            true => BakedSamples::Quantized {
+                min: record.min,
+                range: record.range,
+                values: record.quantized_values,
+            },
+            false => BakedSamples::Full(record.values),
+        },
+    })
+}
+
+fn read_node_animation(records: &mut Records<'_>) -> Result<NodeAnimation, ContainerError> {
+    let record: NodeAnimationRecord = records.read()?;
+    let mut animation = NodeAnimation::empty(record.node_path);
+    animation.gradient_stop_color_channels = vec![None; record.gradient_stop_count as usize];
+    animation.baked = record.baked_gradient_stop_count.map(|count| BakedNodeAnimation {
+        gradient_stop_colors: vec![None; count as usize],
+        ..Default::default()
+    });
+
+    for _ in 0..record.channel_count {
+        let channel: ChannelRecord = records.read()?;
+        let index = channel.index as usize;
+        let (mode, count) = (channel.mode, channel.record_count);
+
+        if channel.baked {
+            let baked = animation.baked.as_mut();
+            let slot = baked.and_then(|baked| match channel.channel {
+                ChannelId::ANGLE => Some(&mut baked.angle),
+                ChannelId::POSITION => Some(&mut baked.position),
+                ChannelId::SIZE => Some(&mut baked.size),
+                ChannelId::SCALE => Some(&mut baked.scale),
+                ChannelId::COLOR => Some(&mut baked.color),
+                ChannelId::UV_OFFSET => Some(&mut baked.uv_offset),
+                ChannelId::UV_SCALE => Some(&mut baked.uv_scale),
+                ChannelId::FILL_AMOUNT => Some(&mut baked.fill_amount),
+                ChannelId::FONT_SIZE => Some(&mut baked.font_size),
+                ChannelId::LINE_HEIGHT => Some(&mut baked.line_height),
+                ChannelId::OUTLINE_THICKNESS => Some(&mut baked.outline_thickness),
+                ChannelId::OUTLINE_COLOR => Some(&mut baked.outline_color),
+                ChannelId::VISIBLE_CHARACTERS => Some(&mut baked.visible_characters),
+                ChannelId::GRADIENT_STOP_COLOR => baked.gradient_stop_colors.get_mut(index),
+                _ => None,
+            });
+
+            match slot {
+                Some(slot) if count > 0 => *slot = Some(read_baked(records, &channel)?),
+                _ => records.skip(count)?,
+            }
+
+            continue;
+        }
+
+        match channel.channel {
+            ChannelId::ANGLE => animation.angle_channel = read_keyframes(records, mode, count)?,
+            ChannelId::POSITION => animation.position_channel = read_keyframes(records, mode, count)?,
+            ChannelId::MOTION_PATH => animation.motion_path = read_motion_path(records, &channel)?,
+            ChannelId::SIZE => animation.size_channel = read_keyframes(records, mode, count)?,
+            ChannelId::SCALE => animation.scale_channel = read_keyframes(records, mode, count)?,
+            ChannelId::COLOR => animation.color_channel = read_keyframes(records, mode, count)?,
+            ChannelId::VISIBILITY => animation.visibility_channel = read_discrete(records, count)?,
+            ChannelId::UV_OFFSET => animation.uv_offset_channel = read_keyframes(records, mode, count)?,
+            ChannelId::UV_SCALE => animation.uv_scale_channel = read_keyframes(records, mode, count)?,
+            ChannelId::FILL_AMOUNT => animation.fill_amount_channel = read_keyframes(records, mode, count)?,
+            ChannelId::TEXTURE => animation.texture_channel = read_discrete(records, count)?,
+            ChannelId::FONT_SIZE => animation.font_size_channel = read_keyframes(records, mode, count)?,
+            ChannelId::LINE_HEIGHT => animation.line_height_channel = read_keyframes(records, mode, count)?,
+            ChannelId::OUTLINE_THICKNESS => {
+                animation.outline_thickness_channel = read_keyframes(records, mode, count)?
+            }
+            ChannelId::OUTLINE_COLOR => animation.outline_color_channel = read_keyframes(records, mode, count)?,
+            ChannelId::VISIBLE_CHARACTERS => {
+                animation.visible_characters_channel = read_keyframes(records, mode, count)?
+            }
+            ChannelId::TEXT => animation.text_channel = read_discrete(records, count)?,
+            ChannelId::SUBLAYOUT_ANIMATION => animation.sublayout_animation_channel = read_discrete(records, count)?,
+            ChannelId::GRADIENT_STOP_COLOR => match animation.gradient_stop_color_channels.get_mut(index) {
+                Some(slot) => *slot = read_keyframes(records, mode, count)?,
+                None => records.skip(count)?,
+            },
+            _ => records.skip(count)?,
+        }
+    }
+
+    Ok(animation)
+}
+
+fn write_inheritance(records: &mut RecordWriter<'_>, template: Option<&String>, inheritance: &TemplateInheritance) {
+    records.write(InheritanceRecord {
+        template: template.cloned(),
+        base: inheritance.base.clone(),
+        removed_nodes: inheritance.removed_nodes.clone(),
+        patch_count: inheritance.patches.len() as u32,
+        added_node_count: inheritance.added_nodes.len() as u32,
+    });
+
+    for patch in inheritance.patches.iter() {
+        records.write(PatchRecord {
+            path: patch.path.clone(),
+            color: patch.color,
+            visibility: patch.visibility,
+            has_transform: patch.transform.is_some(),
+            implementation: patch.implementation.as_ref().map(node_kind),
+            replaces_gradient: patch.gradient.is_some(),
+            has_gradient: matches!(patch.gradient, Some(Some(_))),
+        });
+
+        if let Some(transform) = patch.transform.as_ref() {
+            write_transform(records, transform);
+        }
+
+        if let Some(implementation) = patch.implementation.as_ref() {
+            write_implementation(records, implementation);
+        }
+
+        if let Some(Some(gradient)) = patch.gradient.as_ref() {
+            write_gradient(records, gradient);
+        }
+    }
+
+    for (parent, node) in inheritance.added_nodes.iter() {
+        records.write(AddedNodeRecord { parent: parent.clone() });
+        write_node(records, node);
+    }
+}
+
+fn read_inheritance(records: &mut Records<'_>) -> Result<(Option<String>, TemplateInheritance), ContainerError> {
+    let record: InheritanceRecord = records.read()?;
+    let mut inheritance = TemplateInheritance {
+        base: record.base,
+        removed_nodes: record.removed_nodes,
+        patches: vec![],
+        added_nodes: vec![],
+    };
+
+    for _ in 0..record.patch_count {
+        let patch: PatchRecord = records.read()?;
+        let transform = match patch.has_transform {
+            true => Some(read_transform(records)?),
+            false => None,
+        };
+
+        let implementation = match patch.implementation {
+            Some(kind) => {
+                let implementation = read_implementation(records, kind)?;
+                if implementation.is_none() {
+                    log::warn!(
+                        "Keeping the base implementation of '{}', which is patched with an unknown kind",
+                        patch.path
+                    );
+                }
+                implementation
+            }
+            None => None,
+        };
+
+        let gradient = match (patch.replaces_gradient, patch.has_gradient) {
+            (true, true) => Some(Some(read_gradient(records)?)),
+            (true, false) => Some(None),
+            (false, _) => None,
+        };
+
+        inheritance.patches.push(NodePatch {
+            path: patch.path,
+            transform,
+            color: patch.color,
+            visibility: patch.visibility,
+            gradient,
+            implementation,
+        });
+    }
+
+    for _ in 0..record.added_node_count {
+        let added: AddedNodeRecord = records.read()?;
+        inheritance.added_nodes.push((added.parent, read_node(records)?));
+    }
+
+    Ok((record.template, inheritance))
+}
+
+pub(super) fn write(asset: &Asset, options: SerializeOptions) -> Vec<u8> {
+    let mut templates = [(None, &asset.root_template)]
+        .into_iter()
+        .chain(asset.templates.iter().map(|(name, template)| (Some(name), template)))
+        .collect::<Vec<_>>();
+
+    // Templates are stored in a map at runtime, sort them so the same layout always produces the same bytes
+    templates[1..].sort_by_key(|(name, _)| *name);
+
+    let mut writer = ContainerWriter::new();
+
+    writer.chunk(ChunkTag::METADATA, ChunkFlags::NONE).write(MetadataRecord {
+        generator: concat!("envy ", env!("CARGO_PKG_VERSION")).to_string(),
+    });
+
+    let compressed = ChunkFlags::NONE.with_compression(options.compression);
+
+    let mut records = writer.chunk(ChunkTag::TEMPLATES, compressed);
+    for (name, template) in templates.iter() {
+        records.write(TemplateRecord {
+            name: name.cloned(),
+            canvas_size: template.canvas_size,
+            has_state_machine: template.state_machine.is_some(),
+            root_node_count: template.root_nodes.len() as u32,
+        });
+
+        if let Some(state_machine) = template.state_machine.as_ref() {
+            write_state_machine(&mut records, state_machine);
+        }
+
+        template.root_nodes.iter().for_each(|node| write_node(&mut records, node));
+    }
+
+    let mut records = writer.chunk(ChunkTag::ANIMATIONS, compressed);
+    for (template_name, template) in templates.iter() {
+        for (name, animation) in template.animations.iter() {
+            records.write(AnimationRecord {
+                template: template_name.cloned(),
+                name: name.clone(),
+                total_duration: animation.total_duration,
+                node_animation_count: animation.node_animations.len() as u32,
+            });
+
+            animation
+                .node_animations
+                .iter()
+                .for_each(|node_animation| write_node_animation(&mut records, node_animation));
+        }
+    }
+
+    if templates.iter().any(|(_, template)| template.inheritance.is_some()) {
+        let flags = ChunkFlags::REQUIRED.with_compression(options.compression);
+        let mut records = writer.chunk(ChunkTag::INHERITANCE, flags);
+        for (name, template) in templates.iter() {
+            if let Some(inheritance) = template.inheritance.as_ref() {
+                write_inheritance(&mut records, *name, inheritance);
+            }
+        }
+    }
+
+    let resources = [
+        (
+            ResourceKind::Image,
+            ChunkFlags::NONE.with_compression(options.image_compression),
+            &asset.images,
+        ),
+        (ResourceKind::Font, compressed, &asset.fonts),
+    ];
+
+    match options.storage {
+        ResourceStorage::Embedded => {
+            let mut index = vec![];
+            let mut aliases = vec![];
+            for (kind, flags, resources) in resources {
+                write_resources(
+                    &mut writer,
+                    &mut index,
+                    &mut aliases,
+                    kind,
+                    flags,
+                    resources.iter().map(|(name, bytes)| (name.as_str(), bytes.as_slice())),
+                );
+            }
+
+            write_resource_index(&mut writer, index, aliases);
+        }
+        ResourceStorage::Referenced => {
+            for (kind, _, resources) in resources {
+                let mut records = writer.chunk(kind.referenced_tag(), ChunkFlags::NONE);
+                for (name, bytes) in resources.iter() {
+                    records.write((name, super::content_hash(bytes)));
+                }
+            }
+        }
+    }
+
+    writer.finish()
+}
+
+/// Replaces the `IREF` and `FREF` chunks of a container with the resolved resources, see [`super::pack`]
+pub(super) fn embed_references(
+    bytes: &[u8],
+    mut resolve: impl FnMut(&ResourceReference) -> Option<Vec<u8>>,
+) -> Result<PackedAsset, PackError> {
+    let container = Container::parse(bytes)?;
+    let kinds = [ResourceKind::Image, ResourceKind::Font];
+
+    let mut writer = ContainerWriter::new();
+    for (tag, flags, payload) in container.chunks() {
+        if tag != ChunkTag::RESOURCE_INDEX
+            && tag != ChunkTag::ALIASES
+            && !kinds
+                .iter()
+                .any(|kind| tag == kind.embedded_tag() || tag == kind.referenced_tag())
+        {
+            writer.raw_chunk(tag, flags, payload);
+        }
+    }
+
+    let existing_aliases = read_aliases(&container)?;
+
+    let mut changed = vec![];
+    let mut index = vec![];
+    let mut aliases = vec![];
+    for kind in kinds {
+        let mut resources = vec![];
+        if let Some(mut records) = container.chunk(kind.embedded_tag())? {
+            while !records.is_empty() {
+                let record: ResourceRecord = records.read()?;
+                resources.push((record.name, record.bytes));
+            }
+        }
+
+        // Aliases are written again along with the resolved references, which might be duplicates as well
+        for alias in existing_aliases.iter().filter(|alias| alias.kind == kind) {
+            if let Some((_, bytes)) = resources.iter().find(|(name, _)| *name == alias.target) {
+                resources.push((alias.name.clone(), bytes.clone()));
+            }
+        }
+
+        if let Some(mut records) = container.chunk(kind.referenced_tag())? {
+            while !records.is_empty() {
+                let record: ReferenceRecord = records.read()?;
+                let reference = ResourceReference {
+                    kind,
+                    name: record.name,
+                    hash: record.hash,
+                };
+
+                let Some(bytes) = resolve(&reference) else {
+                    return Err(PackError::Unresolved(reference));
+                };
+
+                if !reference.matches(&bytes) {
+                    changed.push(reference.clone());
+                }
+
+                resources.push((reference.name, bytes));
+            }
+        }
+
+        // Keep the compression of the chunk the resources were already embedded in
+        let flags = container
+            .find(kind.embedded_tag())
+            .map(|(flags, _)| flags)
+            .unwrap_or(ChunkFlags::NONE);
+
+        write_resources(
+            &mut writer,
+            &mut index,
+            &mut aliases,
+            kind,
+            flags,
+            resources.iter().map(|(name, bytes)| (name.as_str(), bytes.as_slice())),
+        );
+    }
+
+    write_resource_index(&mut writer, index, aliases);
+
+    Ok(PackedAsset {
+        bytes: writer.finish(),
+        changed,
+    })
+}
+
+/// An asset read from a container, without the bytes of its images and fonts
+pub(super) struct ContainerAsset {
+    pub asset: Asset,
+
+    /// Embedded images and fonts
+    pub resources: Vec<LazyResource>,
+
+    /// Images and fonts that are referenced instead of embedded
+    pub references: Vec<ResourceReference>,
+}
+
+/// Reads the layout in a container, leaving the bytes of embedded resources to be loaded later
+pub(super) fn read(bytes: &[u8]) -> Result<ContainerAsset, ContainerError> {
+    let container = Container::parse(bytes)?;
+    container.ensure_supported(KNOWN_TAGS)?;
+
+    let mut asset = Asset {
+        images: vec![],
+        fonts: vec![],
+        templates: vec![],
+        root_template: LayoutTemplate::default(),
+    };
+
+    let mut records = container
+        .chunk(ChunkTag::TEMPLATES)?
+        .ok_or(ContainerError::MissingChunk(ChunkTag::TEMPLATES))?;
+    while !records.is_empty() {
+        let record: TemplateRecord = records.read()?;
+        let state_machine = match record.has_state_machine {
+            true => Some(read_state_machine(&mut records)?),
+            false => None,
+        };
+
+        let template = LayoutTemplate {
+            canvas_size: record.canvas_size,
+            root_nodes: (0..record.root_node_count)
+                .map(|_| read_node(&mut records))
+                .collect::<Result<Vec<_>, _>>()?,
+            animations: vec![],
+            state_machine,
+            inheritance: None,
+        };
+
+        match record.name {
+            Some(name) => asset.templates.push((name, template)),
+            None => asset.root_template = template,
+        }
+    }
+
+    if let Some(mut records) = container.chunk(ChunkTag::ANIMATIONS)? {
+        while !records.is_empty() {
+            let record: AnimationRecord = records.read()?;
+            let animation = Animation {
+                node_animations: (0..record.node_animation_count)
+                    .map(|_| read_node_animation(&mut records))
+                    .collect::<Result<Vec<_>, _>>()?,
+                total_duration: record.total_duration,
+            };
+
+            let template = match record.template.as_ref() {
+                Some(name) => asset
+                    .templates
+                    .iter_mut()
+                    .find(|(template_name, _)| template_name == name)
+                    .map(|(_, template)| template),
+                None => Some(&mut asset.root_template),
+            };
+
+            match template {
+                Some(template) => template.animations.push((record.name, animation)),
+                None => log::warn!(
+                    "Skipping animation '{}' of missing template '{}'",
+                    record.name,
+                    record.template.unwrap_or_default()
+                ),
+            }
+        }
+    }
+
+    if let Some(mut records) = container.chunk(ChunkTag::INHERITANCE)? {
+        while !records.is_empty() {
+            let (name, inheritance) = read_inheritance(&mut records)?;
+            let template = match name.as_ref() {
+                Some(name) => asset
+                    .templates
+                    .iter_mut()
+                    .find(|(template_name, _)| template_name == name)
+                    .map(|(_, template)| template),
+                None => Some(&mut asset.root_template),
+            };
+
+            match template {
+                Some(template) => template.inheritance = Some(inheritance),
+                None => log::warn!("Skipping base template of missing template '{}'", name.unwrap_or_default()),
+            }
+        }
+    }
+
+    let mut resources = match container.chunk(ChunkTag::RESOURCE_INDEX)? {
+        Some(index) => read_indexed_resources(&container, index)?,
+        None => read_resources(&container)?,
+    };
+
+    // Aliases share the bytes of their target, which are only copied out of the asset once
+    let targets = resources
+        .iter()
+        .enumerate()
+        .map(|(index, resource)| ((resource.kind, resource.name.clone()), index))
+        .collect::<HashMap<_, _>>();
+    for alias in read_aliases(&container)? {
+        match targets.get(&(alias.kind, alias.target)) {
+            Some(index) => resources.push(LazyResource {
+                name: alias.name,
+                ..resources[*index].clone()
+            }),
+            None => log::warn!("Skipping {} '{}', which is an alias of a missing {0}", alias.kind, alias.name),
+        }
+    }
+
+    let mut references = vec![];
+    for kind in [ResourceKind::Image, ResourceKind::Font] {
+        let Some(mut records) = container.chunk(kind.referenced_tag())? else {
+            continue;
+        };
+
+        while !records.is_empty() {
+            let record: ReferenceRecord = records.read()?;
+            references.push(ResourceReference {
+                kind,
+                name: record.name,
+                hash: record.hash,
+            });
+        }
+    }
+
+    Ok(ContainerAsset {
+        asset,
+        resources,
+        references,
+    })
+}
+
+/// Hands out resources that point into copies of the `IMAG` and `FONT` chunks, without decompressing either of them
+fn read_indexed_resources(
+    container: &Container<'_>,
+    mut index: Records<'_>,
+) -> Result<Vec<LazyResource>, ContainerError> {
+    let mut chunks: [Option<Arc<LazyChunk>>; 2] = [None, None];
+    let mut resources = vec![];
+
+    while !index.is_empty() {
+        let record: ResourceIndexRecord = index.read()?;
+        let tag = record.kind.embedded_tag();
+
+        let chunk = match &mut chunks[record.kind as usize] {
+            Some(chunk) => chunk.clone(),
+            slot => {
+                let (flags, payload) = container.find(tag).ok_or(ContainerError::MissingChunk(tag))?;
+                slot.insert(Arc::new(LazyChunk::new(tag, flags, payload.into())?))
+                    .clone()
+            }
+        };
+
+        let range = usize::try_from(record.offset)
+            .ok()
+            .zip(usize::try_from(record.length).ok())
+            .and_then(|(offset, length)| Some(offset..offset.checked_add(length)?))
+            .ok_or(ContainerError::Truncated)?;
+
+        resources.push(LazyResource {
+            kind: record.kind,
+            name: record.name,
+            hash: record.content_hash,
+            chunk,
+            range,
+        });
+    }
+
+    Ok(resources)
+}
+
+fn read_aliases(container: &Container<'_>) -> Result<Vec<AliasRecord>, ContainerError> {
+    let mut aliases = vec![];
+    if let Some(mut records) = container.chunk(ChunkTag::ALIASES)? {
+        while !records.is_empty() {
+            aliases.push(records.read()?);
+        }
+    }
+
+    Ok(aliases)
+}
+
+/// Reads every resource up front, for containers written before `RIDX` existed
+fn read_resources(container: &Container<'_>) -> Result<Vec<LazyResource>, ContainerError> {
+    let mut resources = vec![];
+    for kind in [ResourceKind::Image, ResourceKind::Font] {
+        let Some(mut records) = container.chunk(kind.embedded_tag())? else {
+            continue;
+        };
+
+        while !records.is_empty() {
+            let record: ResourceRecord = records.read()?;
+            resources.push(LazyResource {
+                kind,
+                name: record.name,
+                hash: Some(super::content_hash(&record.bytes)),
+                range: 0..record.bytes.len(),
+                chunk: Arc::new(LazyChunk::loaded(kind.embedded_tag(), record.bytes)),
+            });
+        }
+    }
+
+    Ok(resources)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(bytes: &[u8]) -> u16 {
+        u16::from_le_bytes([bytes[4], bytes[5]])
+    }
+
+    #[test]
+    fn only_compressed_containers_bump_the_major_version() {
+        let mut writer = ContainerWriter::new();
+        writer.chunk(ChunkTag::METADATA, ChunkFlags::NONE).write(1u32);
+        let plain = writer.finish();
+        assert_eq!(version(&plain), UNCOMPRESSED_MAJOR_VERSION);
+        assert!(Container::parse(&plain).is_ok());
+
+        let mut writer = ContainerWriter::new();
+        writer.chunk(ChunkTag::METADATA, ChunkFlags::NONE).write(1u32);
+        writer.raw_chunk(ChunkTag::IMAGES, ChunkFlags(0xFF << 8), &[1, 2, 3]);
+        let compressed = writer.finish();
+        assert_eq!(version(&compressed), MAJOR_VERSION);
+        assert!(Container::parse(&compressed).is_ok());
+
+        let mut newer = compressed;
+        newer[4..6].copy_from_slice(&(MAJOR_VERSION + 1).to_le_bytes());
+        assert!(matches!(
+            Container::parse(&newer),
+            Err(ContainerError::UnsupportedVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn resource_hashes_are_stored_in_the_index() {
+        let bytes = vec![1, 2, 3, 4];
+        let asset = Asset {
+            images: vec![("image".to_string(), bytes.clone())],
+            fonts: vec![],
+            templates: vec![],
+            root_template: LayoutTemplate::default(),
+        };
+
+        let read = read(&write(&asset, SerializeOptions::default())).unwrap();
+        let [resource] = read.resources.as_slice() else {
+            panic!("expected a single resource");
+        };
+
+        assert_eq!(resource.hash, Some(super::super::content_hash(&bytes)));
+        assert_eq!(resource.content_hash(), resource.hash);
+    }
+
+    fn round_trip<T>(
+        write: impl FnOnce(&mut RecordWriter<'_>),
+        read: impl FnOnce(&mut Records<'_>) -> Result<T, ContainerError>,
+    ) -> T {
+        let mut payload = vec![];
+        write(&mut RecordWriter { payload: &mut payload });
+
+        let mut records = Records {
+            tag: ChunkTag::TEMPLATES,
+            payload: Cow::Owned(payload),
+            position: 0,
+        };
+        let value = read(&mut records).unwrap();
+        assert!(records.is_empty());
+        value
+    }
+
+    fn node(name: &str, implementation: NodeImplTemplate) -> NodeTemplate {
+        NodeTemplate {
+            name: name.to_string(),
+            transform: NodeTransform::from_size(glam::Vec2::new(32.0, 16.0)),
+            color: [255, 128, 64, 255],
+            visibility: NodeVisibility::Visible,
+            gradient: None,
+            children: vec![],
+            implementation,
+        }
+    }
+
+    fn curve<T: Copy>(start: T, end: T) -> AnimationChannel<T> {
+        let transform = AnimationTransform {
+            end,
+            duration: 10,
+            first_step: TransformStep::Linear,
+            additional_steps: vec![TransformStep::Linear],
+            end_tangents: KeyTangents::default(),
+        };
+        AnimationChannel::from_transforms(start, vec![transform])
+    }
+
+    fn discrete<T: Clone>(start: T, end: T) -> DiscreteChannel<T> {
+        DiscreteChannel {
+            start,
+            keyframes: vec![DiscreteKeyframe {
+                value: end,
+                duration: 5,
+            }],
+        }
+    }
+
+    #[test]
+    fn every_node_kind_round_trips() {
+        let mut root = node("root", NodeImplTemplate::Empty);
+        root.gradient = Some(Gradient {
+            kind: GradientKind::Radial {
+                center: glam::Vec2::new(0.25, 0.75),
+                radius: 0.5,
+            },
+            ..Default::default()
+        });
+
+        root.children = vec![
+            node(
+                "image",
+                NodeImplTemplate::Image(ImageNodeTemplate {
+                    texture_name: "icon".to_string(),
+                    mask_texture_name: Some("mask".to_string()),
+                    image_scaling_mode_x: ImageScalingMode::Tiling,
+                    image_scaling_mode_y: ImageScalingMode::Stretch,
+                    uv_offset: glam::Vec2::new(0.5, 0.0),
+                    uv_scale: glam::Vec2::new(2.0, 1.0),
+                    fill_method: ImageFillMethod::RadialClockwise,
+                    fill_origin: ImageFillOrigin::Bottom,
+                    fill_amount: 0.5,
+                }),
+            ),
+            node(
+                "text",
+                NodeImplTemplate::Text(TextNodeTemplate {
+                    font_name: "font".to_string(),
+                    text: "Hello".to_string(),
+                    font_size: 24.0,
+                    line_height: 30.0,
+                    outline_thickness: 2.0,
+                    outline_color: [0, 0, 0, 255],
+                    alignment: TextAlignment::Justify,
+                }),
+            ),
+            node(
+                "hud",
+                NodeImplTemplate::Sublayout(SublayoutNodeTemplate {
+                    sublayout_name: "hud".to_string(),
+                }),
+            ),
+            node(
+                "shape",
+                NodeImplTemplate::Shape(ShapeNodeTemplate {
+                    kind: ShapeKind::Polygon {
+                        points: vec![glam::Vec2::ZERO, glam::Vec2::X, glam::Vec2::ONE],
+                    },
+                    fill_color: None,
+                    stroke_color: [255, 0, 0, 255],
+                    stroke_thickness: 3.0,
+                }),
+            ),
+        ];
+
+        let read = round_trip(|records| write_node(records, &root), read_node);
+        assert!(read == root);
+    }
+
+    #[test]
+    fn state_machines_round_trip() {
+        let mut state_machine = StateMachine::new("idle");
+        state_machine.layer = 2;
+        state_machine.parameters = vec![
+            ("speed".to_string(), ParamValue::Float(0.5)),
+            ("selected".to_string(), ParamValue::Bool(true)),
+        ];
+        state_machine.states = vec![
+            AnimationState {
+                name: "idle".to_string(),
+                animation: None,
+                looping: true,
+            },
+            AnimationState {
+                name: "run".to_string(),
+                animation: Some("run".to_string()),
+                looping: false,
+            },
+        ];
+        state_machine.transitions = vec![StateTransition {
+            from: Some("idle".to_string()),
+            to: "run".to_string(),
+            conditions: vec![
+                TransitionCondition::Equals {
+                    param: "selected".to_string(),
+                    value: ParamValue::Bool(true),
+                },
+                TransitionCondition::Greater {
+                    param: "speed".to_string(),
+                    value: 0.25,
+                },
+                TransitionCondition::Less {
+                    param: "speed".to_string(),
+                    value: 0.75,
+                },
+                TransitionCondition::Trigger("go".to_string()),
+                TransitionCondition::AnimationFinished,
+            ],
+            crossfade_frames: 4,
+        }];
+
+        let read = round_trip(|records| write_state_machine(records, &state_machine), read_state_machine);
+        assert_eq!(read, state_machine);
+    }
+
+    fn every_channel() -> NodeAnimation {
+        let mut animation = NodeAnimation::empty("root/text");
+        animation.angle_channel = Some(curve(0.0, 90.0).with_mode(ChannelMode::Offset));
+        animation.position_channel = Some(curve(glam::Vec2::ZERO, glam::Vec2::new(10.0, 5.0)));
+        animation.size_channel = Some(curve(glam::Vec2::ONE, glam::Vec2::splat(2.0)));
+        animation.scale_channel = Some(curve(glam::Vec2::ONE, glam::Vec2::splat(0.5)));
+        animation.color_channel = Some(curve([255; 4], [0, 0, 0, 255]));
+        animation.visibility_channel = Some(discrete(NodeVisibility::Visible, NodeVisibility::Hidden));
+        animation.uv_offset_channel = Some(curve(glam::Vec2::ZERO, glam::Vec2::X));
+        animation.uv_scale_channel = Some(curve(glam::Vec2::ONE, glam::Vec2::splat(3.0)));
+        animation.fill_amount_channel = Some(curve(0.0, 1.0));
+        animation.texture_channel = Some(discrete("a".to_string(), "b".to_string()));
+        animation.font_size_channel = Some(curve(12.0, 24.0));
+        animation.line_height_channel = Some(curve(14.0, 28.0));
+        animation.outline_thickness_channel = Some(curve(0.0, 2.0));
+        animation.outline_color_channel = Some(curve([0; 4], [255; 4]));
+        animation.visible_characters_channel = Some(curve(0.0, 5.0));
+        animation.text_channel = Some(discrete("Hello".to_string(), "World".to_string()));
+        animation.sublayout_animation_channel = Some(discrete(String::new(), "spin".to_string()));
+        animation.gradient_stop_color_channels = vec![None, Some(curve([255, 0, 0, 255], [0, 0, 255, 255]))];
+        animation
+    }
+
+    #[test]
+    fn every_animation_channel_round_trips() {
+        let animation = every_channel();
+        let read = round_trip(|records| write_node_animation(records, &animation), read_node_animation);
+        assert!(read == animation);
+
+        let mut animation = NodeAnimation::empty("root/image");
+        let mut path = MotionPath::new(glam::Vec2::ZERO, glam::Vec2::new(10.0, 0.0), 10);
+        path.set_points(vec![glam::Vec2::ZERO, glam::Vec2::new(5.0, 5.0), glam::Vec2::new(10.0, 0.0)]);
+        path.mode = ChannelMode::Offset;
+        path.orient_to_path = true;
+        animation.motion_path = Some(path);
+        let read = round_trip(|records| write_node_animation(records, &animation), read_node_animation);
+        assert!(read == animation);
+    }
+
+    #[test]
+    fn baked_animations_round_trip() {
+        for quantize in [false, true] {
+            let mut animation = every_channel();
+            animation.bake(quantize);
+            let read = round_trip(|records| write_node_animation(records, &animation), read_node_animation);
+            assert!(read == animation);
+            assert_eq!(read.baked, animation.baked);
+        }
+    }
+
+    /// A [`SublayoutRecord`] as a later version of `envy` might write it
+    #[derive(Encode)]
+    struct ExtendedSublayoutRecord {
+        sublayout_name: String,
+        preload: bool,
+    }
+
+    #[test]
+    fn appended_fields_are_skipped() {
+        let mut payload = vec![];
+        RecordWriter { payload: &mut payload }
+            .write(ExtendedSublayoutRecord {
+                sublayout_name: "hud".to_string(),
+                preload: true,
+            })
+            .write(SublayoutRecord {
+                sublayout_name: "next".to_string(),
+            });
+
+        let mut records = Records {
+            tag: ChunkTag::TEMPLATES,
+            payload: Cow::Owned(payload),
+            position: 0,
+        };
+
+        let record: SublayoutRecord = records.read().unwrap();
+        assert_eq!(record.sublayout_name, "hud");
+        let record: SublayoutRecord = records.read().unwrap();
+        assert_eq!(record.sublayout_name, "next");
+        assert!(records.is_empty());
+    }
+
+    #[test]
+    fn unknown_channels_are_skipped() {
+        let read = round_trip(
+            |records| {
+                records.write(NodeAnimationRecord {
+                    node_path: "root".to_string(),
+                    channel_count: 2,
+                    gradient_stop_count: 0,
+                    baked_gradient_stop_count: None,
+                });
+                records
+                    .write(ChannelRecord {
+                        channel: ChannelId(1000),
+                        index: 0,
+                        mode: ChannelMode::Absolute,
+                        baked: false,
+                        record_count: 2,
+                    })
+                    .write(1u32)
+                    .write("unknown");
+
+                records.write(ChannelRecord {
+                    channel: ChannelId::FILL_AMOUNT,
+                    index: 0,
+                    mode: ChannelMode::Absolute,
+                    baked: false,
+                    record_count: 2,
+                });
+
+                let mut channels = ChannelWriter {
+                    records: RecordWriter {
+                        payload: records.payload,
+                    },
+                    count: 0,
+                };
+                channels.keyframes(&curve(0.0f32, 1.0));
+            },
+            read_node_animation,
+        );
+
+        let mut expected = NodeAnimation::empty("root");
+        expected.fill_amount_channel = Some(curve(0.0, 1.0));
+        assert!(read == expected);
+    }
+
+    #[test]
+    fn inheritance_round_trips() {
+        let mut patch = NodePatch::new("root/image");
+        patch.transform = Some(NodeTransform::from_size(glam::Vec2::new(8.0, 8.0)));
+        patch.color = Some([0, 255, 0, 255]);
+        patch.gradient = Some(Some(Gradient::default()));
+        patch.implementation = Some(NodeImplTemplate::Sublayout(SublayoutNodeTemplate {
+            sublayout_name: "hud".to_string(),
+        }));
+
+        let mut removes_gradient = NodePatch::new("root");
+        removes_gradient.visibility = Some(NodeVisibility::Hidden);
+        removes_gradient.gradient = Some(None);
+
+        let inheritance = TemplateInheritance {
+            base: "base".to_string(),
+            removed_nodes: vec!["root/text".to_string()],
+            patches: vec![patch, removes_gradient, NodePatch::new("root/shape")],
+            added_nodes: vec![("root".to_string(), node("added", NodeImplTemplate::Empty))],
+        };
+
+        let name = "derived".to_string();
+        let (template, read) = round_trip(
+            |records| write_inheritance(records, Some(&name), &inheritance),
+            read_inheritance,
+        );
+        assert_eq!(template.as_ref(), Some(&name));
+        assert!(read == inheritance);
+    }
+
+    #[test]
+    fn unknown_node_kinds_are_read_as_empty_nodes() {
+        let mut root = node("root", NodeImplTemplate::Empty);
+        root.children = vec![node("child", NodeImplTemplate::Empty)];
+
+        let read = round_trip(
+            |records| {
+                records.write(NodeRecord {
+                    name: "root".to_string(),
+                    color: root.color,
+                    visibility: root.visibility,
+                    kind: NodeKind(1000),
+                    has_gradient: false,
+                    child_count: 1,
+                });
+                write_transform(records, &root.transform);
+                records.write("some future node");
+                write_node(records, &root.children[0]);
+            },
+            read_node,
+        );
+
+        assert!(read == root);
+    }
+}
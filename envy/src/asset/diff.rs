@@ -0,0 +1,1325 @@
+//! Structural differences between two versions of a layout, and three-way merges of them
+//!
+//! Assets can't be compared or merged line by line, so [`diff`] and [`merge`] work on the layout itself instead:
+//! templates and animations are matched by name, nodes and node animations by their path, and images and fonts by
+//! their name and kind. [`merge`] combines changes that touch different nodes, properties, channels or resources.
+//! When both sides change the same thing differently it keeps the version from `ours` (or the version that was changed
+//! when the other side removed it) and reports a [`Conflict`].
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+};
+
+use crate::{
+    testing::RecordingBackend, Animation, AnimationChannel, DiscreteChannel, EnvyBackend, KeyTangents, LayoutRoot,
+    LayoutTemplate, MotionPath, NodeAnimation, NodeTemplate, TransformStep,
+};
+
+use super::{DeserializeError, EnvyAssetProvider, MemoryAssetProvider, ResourceKind, SerializeOptions, TemplateError};
+
+/// A layout and the images and fonts it uses, without a backend
+#[derive(Default, Clone, PartialEq)]
+pub struct AssetContents {
+    pub root_template: LayoutTemplate,
+
+    /// Every named template, without the empty template that a [`LayoutRoot`] always holds
+    pub templates: BTreeMap<String, LayoutTemplate>,
+    pub resources: MemoryAssetProvider,
+}
+
+impl AssetContents {
+    /// Copies the templates of `root`, along with the bytes of every image and font its nodes use
+    pub fn from_root<B: EnvyBackend, A: EnvyAssetProvider + ?Sized>(root: &LayoutRoot<B>, asset_provider: &A) -> Self {
+        let templates = named_templates(root);
+
+        let mut resources = MemoryAssetProvider::new();
+        for (kind, name) in super::used_resources([root.root_template()].into_iter().chain(templates.values())) {
+            let bytes = match kind {
+                ResourceKind::Image => asset_provider.fetch_image_bytes_by_name(&name),
+                ResourceKind::Font => asset_provider.fetch_font_bytes_by_name(&name),
+            };
+            resources.insert(kind, name, bytes.into_owned());
+        }
+
+        Self {
+            root_template: root.root_template().clone(),
+            templates,
+            resources,
+        }
+    }
+
+    /// Reads an asset written by [`serialize`](super::serialize) or any older version of `envy`
+    ///
+    /// Unlike [`AssetContents::from_root`], every image and font in the asset is kept, even if no node uses it. Panics
+    /// if the asset fails to load, like [`deserialize`](super::deserialize).
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self::try_from_bytes(bytes).unwrap_or_else(|error| panic!("Failed to read asset: {error}"))
+    }
+
+    /// Reads an asset like [`AssetContents::from_bytes`], but returns an error instead of panicking when it can't be
+    /// read
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, DeserializeError> {
+        let mut resources = MemoryAssetProvider::new();
+        let root: LayoutRoot<RecordingBackend> = super::try_deserialize(&mut resources, bytes)?;
+
+        Ok(Self {
+            root_template: root.root_template().clone(),
+            templates: named_templates(&root),
+            resources,
+        })
+    }
+
+    /// Builds a layout out of the templates
+    ///
+    /// Panics if a sublayout node uses a template that doesn't exist, like [`LayoutRoot::from_root_template`].
+    pub fn to_root<B: EnvyBackend>(&self) -> LayoutRoot<B> {
+        LayoutRoot::from_root_template(
+            self.root_template.clone(),
+            self.templates
+                .iter()
+                .map(|(name, template)| (name.clone(), template.clone())),
+        )
+    }
+
+    /// Builds a layout out of the templates like [`AssetContents::to_root`], but returns an error instead of panicking
+    /// when they can't be built
+    pub fn try_to_root<B: EnvyBackend>(&self) -> Result<LayoutRoot<B>, TemplateError> {
+        let templates = self
+            .templates
+            .iter()
+            .map(|(name, template)| (name.clone(), template.clone()))
+            .collect::<HashMap<_, _>>();
+        super::check_templates(&self.root_template, &templates)?;
+        Ok(LayoutRoot::from_root_template(self.root_template.clone(), templates))
+    }
+
+    /// Writes the layout and the images and fonts its nodes use
+    ///
+    /// See [`serialize_with_options`](super::serialize_with_options).
+    pub fn serialize(&self, options: SerializeOptions) -> Vec<u8> {
+        super::serialize_with_options(&self.to_root::<RecordingBackend>(), &self.resources, options)
+    }
+
+    /// The template named `name`, or the root template for `None`
+    pub fn template(&self, name: Option<&str>) -> Option<&LayoutTemplate> {
+        match name {
+            Some(name) => self.templates.get(name),
+            None => Some(&self.root_template),
+        }
+    }
+}
+
+fn named_templates<B: EnvyBackend>(root: &LayoutRoot<B>) -> BTreeMap<String, LayoutTemplate> {
+    root.iter_templates()
+        .into_iter()
+        .filter(|(name, _)| !name.is_empty())
+        .map(|(name, template)| (name.to_string(), template.clone()))
+        .collect()
+}
+
+/// Path of the child `name` of the node at `parent`, which is empty for root nodes
+fn node_path(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{parent}/{name}")
+    }
+}
+
+/// A property of a node that is compared and merged on its own
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum NodeProperty {
+    Angle,
+    Position,
+    Size,
+    Scale,
+    Anchor,
+    Color,
+    Visibility,
+    Gradient,
+
+    /// The type of the node and everything specific to it, such as the texture of an image or the text of a text node
+    Implementation,
+}
+
+impl NodeProperty {
+    pub const ALL: [Self; 9] = [
+        Self::Angle,
+        Self::Position,
+        Self::Size,
+        Self::Scale,
+        Self::Anchor,
+        Self::Color,
+        Self::Visibility,
+        Self::Gradient,
+        Self::Implementation,
+    ];
+
+    /// Whether `a` and `b` have the same value for this property
+    pub fn same(self, a: &NodeTemplate, b: &NodeTemplate) -> bool {
+        match self {
+            Self::Angle => a.transform.angle == b.transform.angle,
+            Self::Position => a.transform.position == b.transform.position,
+            Self::Size => a.transform.size == b.transform.size,
+            Self::Scale => a.transform.scale == b.transform.scale,
+            Self::Anchor => a.transform.anchor == b.transform.anchor,
+            Self::Color => a.color == b.color,
+            Self::Visibility => a.visibility == b.visibility,
+            Self::Gradient => a.gradient == b.gradient,
+            Self::Implementation => a.implementation == b.implementation,
+        }
+    }
+
+    /// Copies this property from `from` to `to`
+    pub fn copy(self, from: &NodeTemplate, to: &mut NodeTemplate) {
+        match self {
+            Self::Angle => to.transform.angle = from.transform.angle,
+            Self::Position => to.transform.position = from.transform.position,
+            Self::Size => to.transform.size = from.transform.size,
+            Self::Scale => to.transform.scale = from.transform.scale,
+            Self::Anchor => to.transform.anchor = from.transform.anchor,
+            Self::Color => to.color = from.color,
+            Self::Visibility => to.visibility = from.visibility,
+            Self::Gradient => to.gradient = from.gradient.clone(),
+            Self::Implementation => to.implementation = from.implementation.clone(),
+        }
+    }
+}
+
+impl fmt::Display for NodeProperty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Angle => "angle",
+            Self::Position => "position",
+            Self::Size => "size",
+            Self::Scale => "scale",
+            Self::Anchor => "anchor",
+            Self::Color => "color",
+            Self::Visibility => "visibility",
+            Self::Gradient => "gradient",
+            Self::Implementation => "implementation",
+        })
+    }
+}
+
+/// Calls `$callback!(field, "name")` for every optional channel of a [`NodeAnimation`]
+macro_rules! for_each_channel {
+    ($callback:ident) => {
+        $callback!(angle_channel, "angle");
+        $callback!(position_channel, "position");
+        $callback!(motion_path, "motion path");
+        $callback!(size_channel, "size");
+        $callback!(scale_channel, "scale");
+        $callback!(color_channel, "color");
+        $callback!(visibility_channel, "visibility");
+        $callback!(uv_offset_channel, "UV offset");
+        $callback!(uv_scale_channel, "UV scale");
+        $callback!(fill_amount_channel, "fill amount");
+        $callback!(texture_channel, "texture");
+        $callback!(font_size_channel, "font size");
+        $callback!(line_height_channel, "line height");
+        $callback!(outline_thickness_channel, "outline thickness");
+        $callback!(outline_color_channel, "outline color");
+        $callback!(visible_characters_channel, "visible characters");
+        $callback!(text_channel, "text");
+        $callback!(sublayout_animation_channel, "sublayout animation");
+    };
+}
+
+/// A channel whose keyframes can be compared one by one
+trait Keyframes {
+    /// Frames of the keyframes that were added, removed or changed between `old` and `new`
+    fn changed_frames(old: Option<&Self>, new: Option<&Self>) -> Vec<usize>;
+}
+
+/// Frames of the keys that are only in one of `old` and `new`, sorted and without duplicates
+fn changed_keys<K: PartialEq>(old: &[(usize, K)], new: &[(usize, K)]) -> Vec<usize> {
+    let mut frames = old
+        .iter()
+        .filter(|key| !new.contains(key))
+        .chain(new.iter().filter(|key| !old.contains(key)))
+        .map(|(frame, _)| *frame)
+        .collect::<Vec<_>>();
+    frames.sort_unstable();
+    frames.dedup();
+    frames
+}
+
+/// Value, steps towards it and tangents of a keyframe of an [`AnimationChannel`]
+type ChannelKey<'a, T> = (&'a T, Option<(&'a TransformStep, &'a [TransformStep])>, &'a KeyTangents);
+
+impl<T: PartialEq> Keyframes for AnimationChannel<T> {
+    fn changed_frames(old: Option<&Self>, new: Option<&Self>) -> Vec<usize> {
+        // The duration of a transform is left out, so that inserting a keyframe only changes the keyframe after it
+        // when its value, steps or tangents changed
+        fn keys<T>(channel: Option<&AnimationChannel<T>>) -> Vec<(usize, ChannelKey<'_, T>)> {
+            let Some(channel) = channel else {
+                return vec![];
+            };
+
+            std::iter::once((0, (&channel.start, None, &channel.start_tangents)))
+                .chain(channel.keyframe_indices().skip(1).zip(channel.transforms()).map(|(frame, transform)| {
+                    (
+                        frame,
+                        (
+                            &transform.end,
+                            Some((&transform.first_step, transform.additional_steps.as_slice())),
+                            &transform.end_tangents,
+                        ),
+                    )
+                }))
+                .collect()
+        }
+
+        changed_keys(&keys(old), &keys(new))
+    }
+}
+
+impl<T: PartialEq> Keyframes for DiscreteChannel<T> {
+    fn changed_frames(old: Option<&Self>, new: Option<&Self>) -> Vec<usize> {
+        fn keys<T>(channel: Option<&DiscreteChannel<T>>) -> Vec<(usize, &T)> {
+            channel
+                .map(|channel| channel.keyframe_starts().collect())
+                .unwrap_or_default()
+        }
+
+        changed_keys(&keys(old), &keys(new))
+    }
+}
+
+impl Keyframes for MotionPath {
+    fn changed_frames(old: Option<&Self>, new: Option<&Self>) -> Vec<usize> {
+        AnimationChannel::changed_frames(
+            old.map(|path| &path.progress_channel),
+            new.map(|path| &path.progress_channel),
+        )
+    }
+}
+
+/// A difference found by [`diff`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// Name of the template that changed, or `None` for the root template and changes that are not in any template
+    pub template: Option<String>,
+    pub kind: ChangeKind,
+}
+
+/// What changed, node paths are relative to the template of the [`Change`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeKind {
+    TemplateAdded,
+    TemplateRemoved,
+    CanvasSizeChanged { old: [u32; 2], new: [u32; 2] },
+    StateMachineChanged,
+
+    /// The base template of a derived template or any of the changes it makes to it, see
+    /// [`TemplateInheritance`](crate::TemplateInheritance)
+    InheritanceChanged,
+    NodeAdded { node: String },
+    NodeRemoved { node: String },
+
+    /// A node was moved to another parent, changes to the node itself are reported with its new path
+    NodeMoved { from: String, to: String },
+
+    /// The order of the children of a node changed, `parent` is empty for root nodes
+    ChildrenReordered { parent: String },
+    NodeChanged { node: String, property: NodeProperty },
+    AnimationAdded { animation: String },
+    AnimationRemoved { animation: String },
+    AnimationDurationChanged { animation: String, old: usize, new: usize },
+    NodeAnimationAdded { animation: String, node: String },
+    NodeAnimationRemoved { animation: String, node: String },
+
+    /// A channel was added, removed or changed, `frames` are the frames of every keyframe that was added, removed or
+    /// changed. It is empty when only settings of the whole channel changed
+    KeyframesChanged {
+        animation: String,
+        node: String,
+        channel: String,
+        frames: Vec<usize>,
+    },
+    ResourceAdded { kind: ResourceKind, name: String },
+    ResourceRemoved { kind: ResourceKind, name: String },
+    ResourceChanged { kind: ResourceKind, name: String },
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TemplateAdded => f.write_str("added template"),
+            Self::TemplateRemoved => f.write_str("removed template"),
+            Self::CanvasSizeChanged { old, new } => write!(
+                f,
+                "changed canvas size from {}x{} to {}x{}",
+                old[0], old[1], new[0], new[1]
+            ),
+            Self::StateMachineChanged => f.write_str("changed state machine"),
+            Self::InheritanceChanged => f.write_str("changed base template or overrides"),
+            Self::NodeAdded { node } => write!(f, "added node '{node}'"),
+            Self::NodeRemoved { node } => write!(f, "removed node '{node}'"),
+            Self::NodeMoved { from, to } => write!(f, "moved node '{from}' to '{to}'"),
+            Self::ChildrenReordered { parent } if parent.is_empty() => f.write_str("reordered root nodes"),
+            Self::ChildrenReordered { parent } => write!(f, "reordered children of node '{parent}'"),
+            Self::NodeChanged { node, property } => write!(f, "changed {property} of node '{node}'"),
+            Self::AnimationAdded { animation } => write!(f, "added animation '{animation}'"),
+            Self::AnimationRemoved { animation } => write!(f, "removed animation '{animation}'"),
+            Self::AnimationDurationChanged { animation, old, new } => {
+                write!(f, "changed duration of animation '{animation}' from {old} to {new} frames")
+            }
+            Self::NodeAnimationAdded { animation, node } => {
+                write!(f, "animation '{animation}' now animates node '{node}'")
+            }
+            Self::NodeAnimationRemoved { animation, node } => {
+                write!(f, "animation '{animation}' no longer animates node '{node}'")
+            }
+            Self::KeyframesChanged {
+                animation,
+                node,
+                channel,
+                frames,
+            } => {
+                write!(f, "changed {channel} of node '{node}' in animation '{animation}'")?;
+                if !frames.is_empty() {
+                    let frames = frames.iter().map(usize::to_string).collect::<Vec<_>>();
+                    write!(f, " on frames {}", frames.join(", "))?;
+                }
+                Ok(())
+            }
+            Self::ResourceAdded { kind, name } => write!(f, "added {kind} '{name}'"),
+            Self::ResourceRemoved { kind, name } => write!(f, "removed {kind} '{name}'"),
+            Self::ResourceChanged { kind, name } => write!(f, "changed bytes of {kind} '{name}'"),
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.template.as_ref(), &self.kind) {
+            (Some(template), ChangeKind::TemplateAdded | ChangeKind::TemplateRemoved) => {
+                write!(f, "{} '{template}'", self.kind)
+            }
+            (Some(template), kind) => write!(f, "template '{template}': {kind}"),
+            (None, kind) => kind.fmt(f),
+        }
+    }
+}
+
+/// Collects the changes between two node trees
+///
+/// Nodes that are only in one of the trees are collected first and matched up once the whole tree has been walked,
+/// since a node that was moved to another parent is found as removed in one place and added in another.
+#[derive(Default)]
+struct TreeDiff<'a> {
+    changes: Vec<ChangeKind>,
+    removed: Vec<(String, &'a NodeTemplate)>,
+    added: Vec<(String, &'a NodeTemplate)>,
+}
+
+impl<'a> TreeDiff<'a> {
+    fn children(&mut self, parent: &str, old: &'a [NodeTemplate], new: &'a [NodeTemplate]) {
+        for old_child in old {
+            let path = node_path(parent, &old_child.name);
+            match new.iter().find(|new_child| new_child.name == old_child.name) {
+                Some(new_child) => self.node(path, old_child, new_child),
+                None => self.removed.push((path, old_child)),
+            }
+        }
+
+        for new_child in new {
+            if !old.iter().any(|old_child| old_child.name == new_child.name) {
+                self.added.push((node_path(parent, &new_child.name), new_child));
+            }
+        }
+
+        if reordered(&names(old), &names(new)) {
+            self.changes.push(ChangeKind::ChildrenReordered {
+                parent: parent.to_string(),
+            });
+        }
+    }
+
+    fn node(&mut self, path: String, old: &'a NodeTemplate, new: &'a NodeTemplate) {
+        for property in NodeProperty::ALL {
+            if !property.same(old, new) {
+                self.changes.push(ChangeKind::NodeChanged {
+                    node: path.clone(),
+                    property,
+                });
+            }
+        }
+
+        self.children(&path, &old.children, &new.children);
+    }
+
+    /// Matches up removed and added nodes with the same name and implementation as moves
+    fn finish(mut self) -> Vec<ChangeKind> {
+        loop {
+            let removed = std::mem::take(&mut self.removed);
+            if removed.is_empty() {
+                break;
+            }
+
+            for (from, old) in removed {
+                let moved_to = self
+                    .added
+                    .iter()
+                    .position(|(_, new)| new.name == old.name && new.implementation == old.implementation);
+
+                match moved_to {
+                    Some(index) => {
+                        let (to, new) = self.added.remove(index);
+                        self.changes.push(ChangeKind::NodeMoved {
+                            from,
+                            to: to.clone(),
+                        });
+                        self.node(to, old, new);
+                    }
+                    None => self.changes.push(ChangeKind::NodeRemoved { node: from }),
+                }
+            }
+        }
+
+        let added = std::mem::take(&mut self.added);
+        self.changes
+            .extend(added.into_iter().map(|(node, _)| ChangeKind::NodeAdded { node }));
+        self.changes
+    }
+}
+
+fn names(nodes: &[NodeTemplate]) -> Vec<&str> {
+    nodes.iter().map(|node| node.name.as_str()).collect()
+}
+
+/// Whether the names that are in both `old` and `new` are in a different order
+fn reordered(old: &[&str], new: &[&str]) -> bool {
+    let old_common = old.iter().filter(|name| new.contains(name));
+    let new_common = new.iter().filter(|name| old.contains(name));
+    !old_common.eq(new_common)
+}
+
+fn diff_node_animation(animation: &str, old: &NodeAnimation, new: &NodeAnimation, changes: &mut Vec<ChangeKind>) {
+    let mut changed = |channel: String, frames: Vec<usize>| {
+        changes.push(ChangeKind::KeyframesChanged {
+            animation: animation.to_string(),
+            node: new.node_path.clone(),
+            channel,
+            frames,
+        });
+    };
+
+    macro_rules! diff_channel {
+        ($field:ident, $name:literal) => {
+            if old.$field != new.$field {
+                changed(
+                    $name.to_string(),
+                    Keyframes::changed_frames(old.$field.as_ref(), new.$field.as_ref()),
+                );
+            }
+        };
+    }
+
+    for_each_channel!(diff_channel);
+
+    let stop_count = old
+        .gradient_stop_color_channels
+        .len()
+        .max(new.gradient_stop_color_channels.len());
+    for stop in 0..stop_count {
+        let old_channel = old.gradient_stop_color_channels.get(stop).and_then(Option::as_ref);
+        let new_channel = new.gradient_stop_color_channels.get(stop).and_then(Option::as_ref);
+        if old_channel != new_channel {
+            changed(
+                format!("gradient stop {stop} color"),
+                AnimationChannel::changed_frames(old_channel, new_channel),
+            );
+        }
+    }
+
+    if old.baked != new.baked {
+        changed("baked samples".to_string(), vec![]);
+    }
+}
+
+fn diff_animations(old: &[(String, Animation)], new: &[(String, Animation)], changes: &mut Vec<ChangeKind>) {
+    for (name, old_animation) in old {
+        let Some((_, new_animation)) = new.iter().find(|(new_name, _)| new_name == name) else {
+            changes.push(ChangeKind::AnimationRemoved { animation: name.clone() });
+            continue;
+        };
+
+        if old_animation.total_duration != new_animation.total_duration {
+            changes.push(ChangeKind::AnimationDurationChanged {
+                animation: name.clone(),
+                old: old_animation.total_duration,
+                new: new_animation.total_duration,
+            });
+        }
+
+        for old_node in old_animation.node_animations.iter() {
+            match new_animation
+                .node_animations
+                .iter()
+                .find(|new_node| new_node.node_path == old_node.node_path)
+            {
+                Some(new_node) => diff_node_animation(name, old_node, new_node, changes),
+                None => changes.push(ChangeKind::NodeAnimationRemoved {
+                    animation: name.clone(),
+                    node: old_node.node_path.clone(),
+                }),
+            }
+        }
+
+        for new_node in new_animation.node_animations.iter() {
+            if !old_animation
+                .node_animations
+                .iter()
+                .any(|old_node| old_node.node_path == new_node.node_path)
+            {
+                changes.push(ChangeKind::NodeAnimationAdded {
+                    animation: name.clone(),
+                    node: new_node.node_path.clone(),
+                });
+            }
+        }
+    }
+
+    for (name, _) in new {
+        if !old.iter().any(|(old_name, _)| old_name == name) {
+            changes.push(ChangeKind::AnimationAdded { animation: name.clone() });
+        }
+    }
+}
+
+/// Every difference between two versions of a single template
+pub fn diff_templates(old: &LayoutTemplate, new: &LayoutTemplate) -> Vec<ChangeKind> {
+    let mut changes = vec![];
+
+    if old.canvas_size != new.canvas_size {
+        changes.push(ChangeKind::CanvasSizeChanged {
+            old: old.canvas_size,
+            new: new.canvas_size,
+        });
+    }
+
+    if old.state_machine != new.state_machine {
+        changes.push(ChangeKind::StateMachineChanged);
+    }
+
+    if old.inheritance != new.inheritance {
+        changes.push(ChangeKind::InheritanceChanged);
+    }
+
+    let mut tree = TreeDiff::default();
+    tree.children("", &old.root_nodes, &new.root_nodes);
+    changes.extend(tree.finish());
+
+    diff_animations(&old.animations, &new.animations, &mut changes);
+    changes
+}
+
+/// Every difference between two versions of a layout, starting with the root template, then every named template in
+/// order of their names, and finally the images and fonts
+pub fn diff(old: &AssetContents, new: &AssetContents) -> Vec<Change> {
+    let mut changes = diff_templates(&old.root_template, &new.root_template)
+        .into_iter()
+        .map(|kind| Change { template: None, kind })
+        .collect::<Vec<_>>();
+
+    let names = old.templates.keys().chain(new.templates.keys()).collect::<BTreeSet<_>>();
+    for name in names {
+        let kinds = match (old.templates.get(name), new.templates.get(name)) {
+            (Some(old), Some(new)) => diff_templates(old, new),
+            (Some(_), None) => vec![ChangeKind::TemplateRemoved],
+            (None, _) => vec![ChangeKind::TemplateAdded],
+        };
+
+        changes.extend(kinds.into_iter().map(|kind| Change {
+            template: Some(name.clone()),
+            kind,
+        }));
+    }
+
+    for kind in [ResourceKind::Image, ResourceKind::Font] {
+        let old_resources = old.resources.iter().filter(|(resource_kind, ..)| *resource_kind == kind);
+        for (_, name, old_bytes) in old_resources {
+            let kind = match new.resources.get(kind, name) {
+                Some(new_bytes) if new_bytes == old_bytes => continue,
+                Some(_) => ChangeKind::ResourceChanged {
+                    kind,
+                    name: name.to_string(),
+                },
+                None => ChangeKind::ResourceRemoved {
+                    kind,
+                    name: name.to_string(),
+                },
+            };
+            changes.push(Change { template: None, kind });
+        }
+
+        let new_resources = new.resources.iter().filter(|(resource_kind, ..)| *resource_kind == kind);
+        for (_, name, _) in new_resources {
+            if old.resources.get(kind, name).is_none() {
+                changes.push(Change {
+                    template: None,
+                    kind: ChangeKind::ResourceAdded {
+                        kind,
+                        name: name.to_string(),
+                    },
+                });
+            }
+        }
+    }
+
+    changes
+}
+
+/// Why both sides of a [`merge`] could not be combined
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ConflictReason {
+    /// Both sides added something with the same name, the version from `ours` is kept
+    BothAdded,
+
+    /// One side removed something that the other side changed, the changed version is kept
+    RemovedAndChanged,
+
+    /// Both sides changed the same thing differently, the version from `ours` is kept
+    BothChanged,
+}
+
+/// What both sides of a [`merge`] changed, node paths are relative to the template of the [`Conflict`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConflictKind {
+    Template,
+    CanvasSize,
+    StateMachine,
+
+    /// The base template of a derived template and the changes it makes to it, which are merged as a whole
+    Inheritance,
+    Node { node: String },
+    NodeProperty { node: String, property: NodeProperty },
+
+    /// The order of the children of a node, `parent` is empty for root nodes
+    ChildOrder { parent: String },
+    Animation { animation: String },
+    AnimationDuration { animation: String },
+    NodeAnimation { animation: String, node: String },
+    Channel {
+        animation: String,
+        node: String,
+        channel: String,
+    },
+    Resource { kind: ResourceKind, name: String },
+}
+
+/// Something both sides of a [`merge`] changed, which was resolved as described by its [`ConflictReason`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    /// Name of the template the conflict is in, or `None` for the root template and conflicts that are not in any
+    /// template
+    pub template: Option<String>,
+    pub kind: ConflictKind,
+    pub reason: ConflictReason,
+}
+
+impl fmt::Display for ConflictKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Template => f.write_str("template"),
+            Self::CanvasSize => f.write_str("canvas size"),
+            Self::StateMachine => f.write_str("state machine"),
+            Self::Inheritance => f.write_str("base template and overrides"),
+            Self::Node { node } => write!(f, "node '{node}'"),
+            Self::NodeProperty { node, property } => write!(f, "{property} of node '{node}'"),
+            Self::ChildOrder { parent } if parent.is_empty() => f.write_str("order of the root nodes"),
+            Self::ChildOrder { parent } => write!(f, "order of the children of node '{parent}'"),
+            Self::Animation { animation } => write!(f, "animation '{animation}'"),
+            Self::AnimationDuration { animation } => write!(f, "duration of animation '{animation}'"),
+            Self::NodeAnimation { animation, node } => write!(f, "node '{node}' in animation '{animation}'"),
+            Self::Channel {
+                animation,
+                node,
+                channel,
+            } => write!(f, "{channel} of node '{node}' in animation '{animation}'"),
+            Self::Resource { kind, name } => write!(f, "{kind} '{name}'"),
+        }
+    }
+}
+
+impl fmt::Display for ConflictReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::BothAdded => "added by both sides, kept ours",
+            Self::RemovedAndChanged => "removed by one side and changed by the other, kept the changed version",
+            Self::BothChanged => "changed by both sides, kept ours",
+        })
+    }
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.template.as_ref(), &self.kind) {
+            (Some(template), ConflictKind::Template) => write!(f, "template '{template}' {}", self.reason),
+            (Some(template), kind) => write!(f, "template '{template}': {kind} {}", self.reason),
+            (None, kind) => write!(f, "{kind} {}", self.reason),
+        }
+    }
+}
+
+/// The outcome of [`merge`]
+pub struct MergeResult {
+    pub contents: AssetContents,
+
+    /// Every conflict, the merge is clean when this is empty
+    pub conflicts: Vec<Conflict>,
+}
+
+/// Merges a value that can be missing from any side, or returns why it couldn't be
+fn merge_value<T: PartialEq + Clone>(
+    base: Option<&T>,
+    ours: Option<&T>,
+    theirs: Option<&T>,
+) -> Result<Option<T>, ConflictReason> {
+    if ours == theirs || theirs == base {
+        Ok(ours.cloned())
+    } else if ours == base {
+        Ok(theirs.cloned())
+    } else {
+        Err(match (base, ours, theirs) {
+            (None, _, _) => ConflictReason::BothAdded,
+            (Some(_), None, _) | (Some(_), _, None) => ConflictReason::RemovedAndChanged,
+            _ => ConflictReason::BothChanged,
+        })
+    }
+}
+
+/// Merged order of items that are identified by name, and whether both sides reordered them differently
+///
+/// The side that reordered the items is followed, items that only the other side has are placed after the item they
+/// follow on that side.
+fn merge_order<'a>(base: &[&'a str], ours: &[&'a str], theirs: &[&'a str]) -> (Vec<&'a str>, bool) {
+    let ours_reordered = reordered(base, ours);
+    let theirs_reordered = reordered(base, theirs);
+    let conflict = ours_reordered && theirs_reordered && reordered(ours, theirs);
+
+    let (primary, secondary) = if theirs_reordered && !ours_reordered {
+        (theirs, ours)
+    } else {
+        (ours, theirs)
+    };
+
+    let mut order = primary.to_vec();
+    let mut position = 0;
+    for name in secondary {
+        match order.iter().position(|ordered| ordered == name) {
+            Some(index) => position = index + 1,
+            None => {
+                order.insert(position, name);
+                position += 1;
+            }
+        }
+    }
+
+    (order, conflict)
+}
+
+struct Merger {
+    template: Option<String>,
+    conflicts: Vec<Conflict>,
+}
+
+impl Merger {
+    fn conflict(&mut self, kind: ConflictKind, reason: ConflictReason) {
+        self.conflicts.push(Conflict {
+            template: self.template.clone(),
+            kind,
+            reason,
+        });
+    }
+
+    /// Merges a value that is on every side
+    fn field<T: PartialEq + Clone>(
+        &mut self,
+        kind: impl FnOnce() -> ConflictKind,
+        base: &T,
+        ours: &T,
+        theirs: &T,
+    ) -> T {
+        merge_value(Some(base), Some(ours), Some(theirs))
+            .unwrap_or_else(|reason| {
+                self.conflict(kind(), reason);
+                Some(ours.clone())
+            })
+            .unwrap()
+    }
+
+    /// Merges items that are identified by name, merging items that changed on both sides with `merge_item`
+    fn named<T: PartialEq + Clone>(
+        &mut self,
+        [base, ours, theirs]: [&[T]; 3],
+        name: impl Fn(&T) -> &str,
+        kind: impl Fn(&str) -> ConflictKind,
+        mut merge_item: impl FnMut(&mut Self, &str, &T, &T, &T) -> T,
+    ) -> (Vec<T>, bool) {
+        let [base_names, ours_names, theirs_names] =
+            [base, ours, theirs].map(|items| items.iter().map(&name).collect::<Vec<_>>());
+        let (order, order_conflict) = merge_order(&base_names, &ours_names, &theirs_names);
+
+        let mut merged = vec![];
+        for item_name in order {
+            let [base, ours, theirs] =
+                [base, ours, theirs].map(|items| items.iter().find(|item| name(item) == item_name));
+
+            let item = match merge_value(base, ours, theirs) {
+                Ok(item) => item,
+                Err(_) if base.is_some() && ours.is_some() && theirs.is_some() => {
+                    Some(merge_item(self, item_name, base.unwrap(), ours.unwrap(), theirs.unwrap()))
+                }
+                Err(reason) => {
+                    self.conflict(kind(item_name), reason);
+                    ours.or(theirs).cloned()
+                }
+            };
+
+            merged.extend(item);
+        }
+
+        (merged, order_conflict)
+    }
+
+    fn children(&mut self, parent: &str, [base, ours, theirs]: [&[NodeTemplate]; 3]) -> Vec<NodeTemplate> {
+        let (children, order_conflict) = self.named(
+            [base, ours, theirs],
+            |node| node.name.as_str(),
+            |name| ConflictKind::Node {
+                node: node_path(parent, name),
+            },
+            |merger, name, base, ours, theirs| merger.node(&node_path(parent, name), base, ours, theirs),
+        );
+
+        if order_conflict {
+            self.conflict(
+                ConflictKind::ChildOrder {
+                    parent: parent.to_string(),
+                },
+                ConflictReason::BothChanged,
+            );
+        }
+
+        children
+    }
+
+    fn node(&mut self, path: &str, base: &NodeTemplate, ours: &NodeTemplate, theirs: &NodeTemplate) -> NodeTemplate {
+        let mut merged = ours.clone();
+
+        for property in NodeProperty::ALL {
+            if property.same(ours, theirs) || property.same(theirs, base) {
+                continue;
+            }
+
+            if property.same(ours, base) {
+                property.copy(theirs, &mut merged);
+            } else {
+                self.conflict(
+                    ConflictKind::NodeProperty {
+                        node: path.to_string(),
+                        property,
+                    },
+                    ConflictReason::BothChanged,
+                );
+            }
+        }
+
+        merged.children = self.children(path, [&base.children, &ours.children, &theirs.children]);
+        merged
+    }
+
+    fn node_animation(
+        &mut self,
+        animation: &str,
+        base: &NodeAnimation,
+        ours: &NodeAnimation,
+        theirs: &NodeAnimation,
+    ) -> NodeAnimation {
+        let mut merged = ours.clone();
+        let channel_conflict = |channel: &str| ConflictKind::Channel {
+            animation: animation.to_string(),
+            node: ours.node_path.clone(),
+            channel: channel.to_string(),
+        };
+
+        macro_rules! merge_channel {
+            ($field:ident, $name:literal) => {
+                merged.$field = self.field(|| channel_conflict($name), &base.$field, &ours.$field, &theirs.$field);
+            };
+        }
+
+        for_each_channel!(merge_channel);
+        merge_channel!(gradient_stop_color_channels, "gradient stop colors");
+        merge_channel!(baked, "baked samples");
+
+        merged
+    }
+
+    fn animation(&mut self, name: &str, base: &Animation, ours: &Animation, theirs: &Animation) -> Animation {
+        let total_duration = self.field(
+            || ConflictKind::AnimationDuration {
+                animation: name.to_string(),
+            },
+            &base.total_duration,
+            &ours.total_duration,
+            &theirs.total_duration,
+        );
+
+        let (node_animations, _) = self.named(
+            [&base.node_animations, &ours.node_animations, &theirs.node_animations],
+            |animation| animation.node_path.as_str(),
+            |node| ConflictKind::NodeAnimation {
+                animation: name.to_string(),
+                node: node.to_string(),
+            },
+            |merger, _, base, ours, theirs| merger.node_animation(name, base, ours, theirs),
+        );
+
+        Animation {
+            node_animations,
+            total_duration,
+        }
+    }
+
+    fn template(&mut self, base: &LayoutTemplate, ours: &LayoutTemplate, theirs: &LayoutTemplate) -> LayoutTemplate {
+        let canvas_size = self.field(
+            || ConflictKind::CanvasSize,
+            &base.canvas_size,
+            &ours.canvas_size,
+            &theirs.canvas_size,
+        );
+        let state_machine = self.field(
+            || ConflictKind::StateMachine,
+            &base.state_machine,
+            &ours.state_machine,
+            &theirs.state_machine,
+        );
+        let inheritance = self.field(
+            || ConflictKind::Inheritance,
+            &base.inheritance,
+            &ours.inheritance,
+            &theirs.inheritance,
+        );
+        let root_nodes = self.children("", [&base.root_nodes, &ours.root_nodes, &theirs.root_nodes]);
+
+        let (animations, _) = self.named(
+            [&base.animations, &ours.animations, &theirs.animations],
+            |(name, _)| name.as_str(),
+            |name| ConflictKind::Animation {
+                animation: name.to_string(),
+            },
+            |merger, name, (_, base), (_, ours), (_, theirs)| {
+                (name.to_string(), merger.animation(name, base, ours, theirs))
+            },
+        );
+
+        LayoutTemplate {
+            canvas_size,
+            root_nodes,
+            animations,
+            state_machine,
+            inheritance,
+        }
+    }
+}
+
+/// Combines the changes that `ours` and `theirs` made to `base`
+///
+/// Templates, nodes and animations that both sides changed are merged property by property and channel by channel.
+/// Conflicts are resolved as described by their [`ConflictReason`], the merge is clean when
+/// [`MergeResult::conflicts`] is empty.
+pub fn merge(base: &AssetContents, ours: &AssetContents, theirs: &AssetContents) -> MergeResult {
+    let mut merger = Merger {
+        template: None,
+        conflicts: vec![],
+    };
+
+    let root_template = merger.template(&base.root_template, &ours.root_template, &theirs.root_template);
+
+    let mut templates = BTreeMap::new();
+    let names = [base, ours, theirs]
+        .iter()
+        .flat_map(|contents| contents.templates.keys())
+        .collect::<BTreeSet<_>>();
+    for name in names {
+        merger.template = Some(name.clone());
+
+        let (base, ours, theirs) = (
+            base.templates.get(name),
+            ours.templates.get(name),
+            theirs.templates.get(name),
+        );
+        let template = match (merge_value(base, ours, theirs), base, ours, theirs) {
+            (Ok(template), ..) => template,
+            (Err(_), Some(base), Some(ours), Some(theirs)) => Some(merger.template(base, ours, theirs)),
+            (Err(reason), ..) => {
+                merger.conflict(ConflictKind::Template, reason);
+                ours.or(theirs).cloned()
+            }
+        };
+
+        if let Some(template) = template {
+            templates.insert(name.clone(), template);
+        }
+    }
+
+    merger.template = None;
+    let mut resources = MemoryAssetProvider::new();
+    for kind in [ResourceKind::Image, ResourceKind::Font] {
+        let names = [base, ours, theirs]
+            .iter()
+            .flat_map(|contents| contents.resources.iter())
+            .filter(|(resource_kind, ..)| *resource_kind == kind)
+            .map(|(_, name, _)| name)
+            .collect::<BTreeSet<_>>();
+
+        for name in names {
+            let [base, ours, theirs] =
+                [base, ours, theirs].map(|contents| contents.resources.get(kind, name).map(<[u8]>::to_vec));
+            let bytes = merge_value(base.as_ref(), ours.as_ref(), theirs.as_ref()).unwrap_or_else(|reason| {
+                merger.conflict(
+                    ConflictKind::Resource {
+                        kind,
+                        name: name.to_string(),
+                    },
+                    reason,
+                );
+                ours.clone().or(theirs.clone())
+            });
+
+            if let Some(bytes) = bytes {
+                resources.insert(kind, name, bytes);
+            }
+        }
+    }
+    resources.unresolved = ours.resources.unresolved.clone();
+
+    MergeResult {
+        contents: AssetContents {
+            root_template,
+            templates,
+            resources,
+        },
+        conflicts: merger.conflicts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use crate::{NodeImplTemplate, NodeTransform, NodeVisibility};
+
+    use super::*;
+
+    fn node(name: &str) -> NodeTemplate {
+        NodeTemplate {
+            name: name.to_string(),
+            transform: NodeTransform::from_size(Vec2::new(100.0, 50.0)),
+            color: [255; 4],
+            visibility: NodeVisibility::Inherited,
+            gradient: None,
+            children: vec![],
+            implementation: NodeImplTemplate::Empty,
+        }
+    }
+
+    fn contents(names: &[&str]) -> AssetContents {
+        let mut contents = AssetContents::default();
+        contents.root_template.root_nodes = names.iter().map(|name| node(name)).collect();
+        contents
+    }
+
+    fn root_node<'a>(contents: &'a AssetContents, name: &str) -> &'a NodeTemplate {
+        contents.root_template.get_node_by_path(name).unwrap()
+    }
+
+    fn root_node_mut<'a>(contents: &'a mut AssetContents, name: &str) -> &'a mut NodeTemplate {
+        contents.root_template.get_node_by_path_mut(name).unwrap()
+    }
+
+    fn names(contents: &AssetContents) -> Vec<&str> {
+        contents.root_template.root_nodes.iter().map(|node| node.name.as_str()).collect()
+    }
+
+    fn conflict(kind: ConflictKind, reason: ConflictReason) -> Conflict {
+        Conflict {
+            template: None,
+            kind,
+            reason,
+        }
+    }
+
+    fn spin(angle: f32) -> Animation {
+        let mut animation = NodeAnimation::empty("a");
+        animation.angle_channel = Some(AnimationChannel::new(angle));
+        Animation {
+            node_animations: vec![animation],
+            total_duration: 10,
+        }
+    }
+
+    #[test]
+    fn changes_to_different_things_merge_cleanly() {
+        let base = contents(&["a", "b"]);
+
+        let mut ours = base.clone();
+        root_node_mut(&mut ours, "a").transform.position = Vec2::new(10.0, 0.0);
+        ours.resources.insert(ResourceKind::Image, "ours", vec![1]);
+
+        let mut theirs = base.clone();
+        root_node_mut(&mut theirs, "a").color = [0; 4];
+        theirs.root_template.root_nodes.push(node("c"));
+
+        let result = merge(&base, &ours, &theirs);
+        assert_eq!(result.conflicts, []);
+        assert_eq!(names(&result.contents), ["a", "b", "c"]);
+        assert_eq!(root_node(&result.contents, "a").transform.position, Vec2::new(10.0, 0.0));
+        assert_eq!(root_node(&result.contents, "a").color, [0; 4]);
+        assert_eq!(result.contents.resources.get(ResourceKind::Image, "ours"), Some([1].as_slice()));
+    }
+
+    #[test]
+    fn property_changed_by_both_sides_keeps_ours() {
+        let base = contents(&["a"]);
+        let mut ours = base.clone();
+        let mut theirs = base.clone();
+        root_node_mut(&mut ours, "a").transform.position = Vec2::new(10.0, 0.0);
+        root_node_mut(&mut theirs, "a").transform.position = Vec2::new(20.0, 0.0);
+
+        // The same change on both sides is not a conflict
+        root_node_mut(&mut ours, "a").color = [0; 4];
+        root_node_mut(&mut theirs, "a").color = [0; 4];
+
+        let result = merge(&base, &ours, &theirs);
+        assert_eq!(
+            result.conflicts,
+            [conflict(
+                ConflictKind::NodeProperty {
+                    node: "a".to_string(),
+                    property: NodeProperty::Position,
+                },
+                ConflictReason::BothChanged,
+            )]
+        );
+        assert_eq!(root_node(&result.contents, "a").transform.position, Vec2::new(10.0, 0.0));
+        assert_eq!(root_node(&result.contents, "a").color, [0; 4]);
+    }
+
+    #[test]
+    fn node_removed_and_changed_keeps_the_changed_node() {
+        let base = contents(&["a", "b"]);
+        let ours = contents(&["a"]);
+        let mut theirs = base.clone();
+        root_node_mut(&mut theirs, "b").color = [0; 4];
+
+        let result = merge(&base, &ours, &theirs);
+        assert_eq!(
+            result.conflicts,
+            [conflict(
+                ConflictKind::Node { node: "b".to_string() },
+                ConflictReason::RemovedAndChanged,
+            )]
+        );
+        assert_eq!(root_node(&result.contents, "b").color, [0; 4]);
+    }
+
+    #[test]
+    fn node_added_by_both_sides_keeps_ours() {
+        let base = contents(&["a"]);
+        let mut ours = contents(&["a", "c"]);
+        let theirs = contents(&["a", "c"]);
+        root_node_mut(&mut ours, "c").color = [0; 4];
+
+        let result = merge(&base, &ours, &theirs);
+        assert_eq!(
+            result.conflicts,
+            [conflict(
+                ConflictKind::Node { node: "c".to_string() },
+                ConflictReason::BothAdded,
+            )]
+        );
+        assert_eq!(root_node(&result.contents, "c").color, [0; 4]);
+    }
+
+    #[test]
+    fn reordered_by_both_sides_follows_ours() {
+        let base = contents(&["a", "b", "c"]);
+        let ours = contents(&["c", "a", "b"]);
+        let theirs = contents(&["b", "a", "c"]);
+
+        let result = merge(&base, &ours, &theirs);
+        assert_eq!(
+            result.conflicts,
+            [conflict(
+                ConflictKind::ChildOrder { parent: String::new() },
+                ConflictReason::BothChanged,
+            )]
+        );
+        assert_eq!(names(&result.contents), ["c", "a", "b"]);
+
+        // Only one side reordering is followed without a conflict
+        let result = merge(&base, &base, &theirs);
+        assert_eq!(result.conflicts, []);
+        assert_eq!(names(&result.contents), ["b", "a", "c"]);
+    }
+
+    #[test]
+    fn channel_changed_by_both_sides_keeps_ours() {
+        let mut base = contents(&["a"]);
+        base.root_template.add_animation("spin", spin(0.0));
+
+        let mut ours = base.clone();
+        ours.root_template.animations[0].1 = spin(90.0);
+
+        let mut theirs = base.clone();
+        theirs.root_template.animations[0].1 = spin(180.0);
+        theirs.root_template.animations[0].1.node_animations[0].scale_channel = Some(AnimationChannel::new(Vec2::ONE));
+
+        let result = merge(&base, &ours, &theirs);
+        assert_eq!(
+            result.conflicts,
+            [conflict(
+                ConflictKind::Channel {
+                    animation: "spin".to_string(),
+                    node: "a".to_string(),
+                    channel: "angle".to_string(),
+                },
+                ConflictReason::BothChanged,
+            )]
+        );
+
+        let merged = &result.contents.root_template.animations[0].1.node_animations[0];
+        assert_eq!(merged.angle_channel.as_ref().unwrap().start, 90.0);
+        assert!(merged.scale_channel.is_some());
+    }
+
+    #[test]
+    fn resource_changed_by_both_sides_keeps_ours() {
+        let mut base = contents(&[]);
+        base.resources.insert(ResourceKind::Font, "font", vec![0]);
+        let mut ours = base.clone();
+        ours.resources.insert(ResourceKind::Font, "font", vec![1]);
+        let mut theirs = base.clone();
+        theirs.resources.insert(ResourceKind::Font, "font", vec![2]);
+
+        let result = merge(&base, &ours, &theirs);
+        assert_eq!(
+            result.conflicts,
+            [conflict(
+                ConflictKind::Resource {
+                    kind: ResourceKind::Font,
+                    name: "font".to_string(),
+                },
+                ConflictReason::BothChanged,
+            )]
+        );
+        assert_eq!(result.contents.resources.get(ResourceKind::Font, "font"), Some([1].as_slice()));
+    }
+}
@@ -0,0 +1,707 @@
+//! Reloading an asset into a running layout whenever it is saved
+//!
+//! [`AssetWatcher`] checks the modification time of an asset, and of any image or font files registered with
+//! [`AssetWatcher::watch_resource`], every time it is polled. When the asset changed it is read again and compared
+//! against the templates of the live [`LayoutRoot`] with [`diff_templates`]: only nodes that were added, removed or
+//! changed are built again, so every other node keeps its transform, text, update callbacks and the progress of the
+//! animations playing on it. Images and fonts that changed are loaded into the backend again, replacing the old ones
+//! under the same name.
+//!
+//! The watcher only uses the standard library and is meant to be polled from the game loop, a few times a second is
+//! plenty:
+//!
+//! ```
+//! use envy::asset::{reload::AssetWatcher, EnvyAssetProvider};
+//! use envy::{EnvyBackend, LayoutRoot};
+//!
+//! fn frame<B>(root: &mut LayoutRoot<B>, backend: &mut B, watcher: &mut AssetWatcher)
+//! where
+//!     B: EnvyBackend + EnvyAssetProvider,
+//! {
+//!     match watcher.poll(root, backend) {
+//!         Ok(Some(reload)) => reload.changes.iter().for_each(|change| log::info!("{change}")),
+//!         Ok(None) => {}
+//!         Err(error) => log::error!("failed to reload {}: {error}", watcher.path().display()),
+//!     }
+//!
+//!     root.update();
+//!     root.prepare(backend);
+//! }
+//! ```
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::{
+    tree::TemplateUpdate, EnvyBackend, InheritanceError, LayoutRoot, LayoutTemplate, NodeImplTemplate, NodeTemplate,
+};
+
+use super::{
+    container::{self, ContainerError},
+    content_hash,
+    diff::{diff_templates, Change, ChangeKind},
+    EnvyAssetProvider, ResourceKind, TemplateError,
+};
+
+/// Why an asset could not be reloaded, the layout is left as it was
+#[derive(Debug)]
+pub enum ReloadError {
+    Io(std::io::Error),
+    Container(ContainerError),
+
+    /// The asset was written before the chunked [`container`], upgrade it with `envy-cli upgrade` to reload it
+    LegacyVersion { version: (u8, u8, u16) },
+
+    /// A sublayout node uses a template that does not exist
+    MissingTemplate { node: String, template: String },
+
+    /// Templates that include each other through sublayout nodes, the first template is repeated at the end
+    SublayoutCycle { templates: Vec<String> },
+
+    /// A derived template can't be resolved
+    Inheritance(InheritanceError),
+}
+
+impl fmt::Display for ReloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => error.fmt(f),
+            Self::Container(error) => error.fmt(f),
+            Self::LegacyVersion {
+                version: (major, minor, patch),
+            } => write!(
+                f,
+                "assets written by envy {major}.{minor}.{patch} can't be reloaded, upgrade the asset first"
+            ),
+            Self::MissingTemplate { node, template } => {
+                write!(f, "sublayout node '{node}' uses template '{template}', which does not exist")
+            }
+            Self::SublayoutCycle { templates } => {
+                write!(f, "templates include each other through sublayouts: {}", templates.join(" -> "))
+            }
+            Self::Inheritance(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ReloadError {}
+
+impl From<std::io::Error> for ReloadError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<ContainerError> for ReloadError {
+    fn from(value: ContainerError) -> Self {
+        Self::Container(value)
+    }
+}
+
+impl From<TemplateError> for ReloadError {
+    fn from(value: TemplateError) -> Self {
+        match value {
+            TemplateError::MissingTemplate { node, template } => Self::MissingTemplate { node, template },
+            TemplateError::SublayoutCycle { templates } => Self::SublayoutCycle { templates },
+            TemplateError::Inheritance(error) => Self::Inheritance(error),
+        }
+    }
+}
+
+impl From<InheritanceError> for ReloadError {
+    fn from(value: InheritanceError) -> Self {
+        Self::Inheritance(value)
+    }
+}
+
+/// What a reload changed
+#[derive(Debug, Default)]
+pub struct Reload {
+    /// Every difference between the running layout and the one that was loaded, changes to templates come first
+    pub changes: Vec<Change>,
+}
+
+impl Reload {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+type ResourceKey = (ResourceKind, String);
+
+/// An image or font file registered with [`AssetWatcher::watch_resource`]
+#[derive(Debug)]
+struct WatchedFile {
+    kind: ResourceKind,
+    name: String,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+/// Reloads an asset and the files of the images and fonts it references when they change on disk
+#[derive(Debug)]
+pub struct AssetWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    files: Vec<WatchedFile>,
+
+    /// [`content_hash`] of every image and font that is loaded, to only replace the ones that changed
+    hashes: HashMap<ResourceKey, u64>,
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Path of the parent of the node at `path`, which is empty for root nodes
+fn parent_path(path: &str) -> &str {
+    path.rsplit_once('/').map(|(parent, _)| parent).unwrap_or_default()
+}
+
+/// Adds the path of every node that shows one of `resources` to `nodes`
+fn resource_users(template: &LayoutTemplate, resources: &HashSet<ResourceKey>, nodes: &mut BTreeSet<String>) {
+    fn visit(node: &NodeTemplate, path: String, resources: &HashSet<ResourceKey>, nodes: &mut BTreeSet<String>) {
+        let uses = |kind: ResourceKind, name: &String| resources.contains(&(kind, name.clone()));
+        let used = match &node.implementation {
+            NodeImplTemplate::Image(image) => {
+                uses(ResourceKind::Image, &image.texture_name)
+                    || image
+                        .mask_texture_name
+                        .as_ref()
+                        .is_some_and(|name| uses(ResourceKind::Image, name))
+            }
+            NodeImplTemplate::Text(text) => uses(ResourceKind::Font, &text.font_name),
+            _ => false,
+        };
+
+        for child in node.children.iter() {
+            visit(child, format!("{path}/{}", child.name), resources, nodes);
+        }
+
+        if used {
+            nodes.insert(path);
+        }
+    }
+
+    if resources.is_empty() {
+        return;
+    }
+
+    for node in template.root_nodes.iter() {
+        visit(node, node.name.clone(), resources, nodes);
+    }
+}
+
+/// What has to be built again in the trees of a template, given what changed in it
+fn template_update(
+    kinds: &[ChangeKind],
+    template: &LayoutTemplate,
+    resources: &HashSet<ResourceKey>,
+) -> TemplateUpdate {
+    let mut update = TemplateUpdate::default();
+    for kind in kinds {
+        match kind {
+            ChangeKind::CanvasSizeChanged { .. } => update.canvas_size = true,
+            ChangeKind::StateMachineChanged => update.state_machine = true,
+            ChangeKind::NodeAdded { node } | ChangeKind::NodeRemoved { node } => {
+                update.parents.insert(parent_path(node).to_string());
+            }
+            ChangeKind::NodeMoved { from, to } => {
+                update.parents.insert(parent_path(from).to_string());
+                update.parents.insert(parent_path(to).to_string());
+            }
+            ChangeKind::ChildrenReordered { parent } => {
+                update.parents.insert(parent.clone());
+            }
+            ChangeKind::NodeChanged { node, .. } => {
+                update.nodes.insert(node.clone());
+            }
+            ChangeKind::AnimationAdded { .. }
+            | ChangeKind::AnimationRemoved { .. }
+            | ChangeKind::AnimationDurationChanged { .. }
+            | ChangeKind::NodeAnimationAdded { .. }
+            | ChangeKind::NodeAnimationRemoved { .. }
+            | ChangeKind::KeyframesChanged { .. } => update.animations = true,
+            ChangeKind::TemplateAdded
+            | ChangeKind::TemplateRemoved
+            | ChangeKind::InheritanceChanged
+            | ChangeKind::ResourceAdded { .. }
+            | ChangeKind::ResourceRemoved { .. }
+            | ChangeKind::ResourceChanged { .. } => {}
+        }
+    }
+
+    // Backends replace textures in place, but text has to be laid out again with a new font
+    resource_users(template, resources, &mut update.nodes);
+    update
+}
+
+impl AssetWatcher {
+    /// Watches the asset at `path`, which should be the asset the layout was loaded from
+    ///
+    /// The asset is read right away to find out which of its images and fonts are already loaded. If it can't be read
+    /// every image and font is loaded again the first time it changes.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let mut this = Self {
+            modified: modified_time(&path),
+            path,
+            files: vec![],
+            hashes: HashMap::new(),
+        };
+
+        if let Ok(asset) = std::fs::read(&this.path).map_err(ReloadError::from).and_then(|bytes| {
+            container::read(&bytes).map_err(ReloadError::from)
+        }) {
+            for resource in asset.resources {
+                if let Some(hash) = resource.content_hash() {
+                    this.hashes.insert((resource.kind(), resource.name().to_string()), hash);
+                }
+            }
+
+            for reference in asset.references {
+                this.hashes.insert((reference.kind, reference.name), reference.hash);
+            }
+        }
+
+        this
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Also reloads the image or font `name` from the file at `path` whenever that file changes
+    ///
+    /// This is meant for resources that the asset references instead of embedding, which the designer doesn't write
+    /// when they change.
+    pub fn watch_resource(&mut self, kind: ResourceKind, name: impl Into<String>, path: impl Into<PathBuf>) {
+        let path = path.into();
+        self.files.push(WatchedFile {
+            kind,
+            name: name.into(),
+            modified: modified_time(&path),
+            path,
+        });
+    }
+
+    /// Reloads the asset and the watched images and fonts that changed since the last poll, returning `None` when
+    /// nothing changed
+    ///
+    /// A file that is missing is treated as unchanged, since it is usually just in the middle of being saved.
+    pub fn poll<B: EnvyBackend + EnvyAssetProvider>(
+        &mut self,
+        root: &mut LayoutRoot<B>,
+        backend: &mut B,
+    ) -> Result<Option<Reload>, ReloadError> {
+        let mut reload = None;
+
+        let modified = modified_time(&self.path);
+        if modified.is_some() && modified != self.modified {
+            // Recorded before reading, so that an asset that fails to load is only reported once per save
+            self.modified = modified;
+            let bytes = std::fs::read(&self.path)?;
+            reload = Some(self.reload_bytes(root, backend, &bytes)?);
+        }
+
+        let mut changed = HashSet::new();
+        for file in self.files.iter_mut() {
+            let modified = modified_time(&file.path);
+            if modified.is_none() || modified == file.modified {
+                continue;
+            }
+
+            file.modified = modified;
+            let bytes = std::fs::read(&file.path)?;
+            let hash = content_hash(&bytes);
+            if self.hashes.insert((file.kind, file.name.clone()), hash) == Some(hash) {
+                continue;
+            }
+
+            match file.kind {
+                ResourceKind::Image => backend.load_image_bytes_with_name(file.name.clone(), bytes),
+                ResourceKind::Font => backend.load_font_bytes_with_name(file.name.clone(), bytes),
+            }
+            changed.insert((file.kind, file.name.clone()));
+        }
+
+        if changed.is_empty() {
+            return Ok(reload);
+        }
+
+        let mut updates = HashMap::new();
+        updates.insert(None, template_update(&[], root.root_template(), &changed));
+        for (name, template) in root.iter_templates() {
+            updates.insert(Some(name.to_string()), template_update(&[], template, &changed));
+        }
+        root.apply_template_updates(&updates, backend);
+
+        let mut changed = changed.into_iter().collect::<Vec<_>>();
+        changed.sort_by_key(|(kind, name)| (*kind == ResourceKind::Font, name.clone()));
+        let mut reload = reload.unwrap_or_default();
+        reload.changes.extend(changed.into_iter().map(|(kind, name)| Change {
+            template: None,
+            kind: ChangeKind::ResourceChanged { kind, name },
+        }));
+
+        Ok(Some(reload))
+    }
+
+    /// Reloads the asset from `bytes`, such as when the asset is not read from a file
+    ///
+    /// Nothing is changed if the asset fails to load, or if it is missing a template that a sublayout uses.
+    pub fn reload_bytes<B: EnvyBackend + EnvyAssetProvider>(
+        &mut self,
+        root: &mut LayoutRoot<B>,
+        backend: &mut B,
+        bytes: &[u8],
+    ) -> Result<Reload, ReloadError> {
+        if let Some(version) = super::legacy_version(bytes) {
+            return Err(ReloadError::LegacyVersion { version });
+        }
+
+        let container::ContainerAsset {
+            mut asset,
+            resources,
+            references,
+        } = container::read(bytes)?;
+        super::normalize_node_paths(&mut asset);
+
+        let root_template = asset.root_template;
+        let templates = asset.templates.into_iter().collect::<HashMap<_, _>>();
+
+        // Derived templates are checked and compared as the complete templates that trees are built from
+        let resolved_root = root_template.resolve(&templates)?;
+        let resolved = templates
+            .iter()
+            .map(|(name, template)| Ok((name.clone(), template.resolve(&templates)?)))
+            .collect::<Result<BTreeMap<_, _>, InheritanceError>>()?;
+        super::check_sublayouts(&resolved_root, &resolved)?;
+
+        // Images and fonts first, so that the nodes that are built again use the new ones
+        let mut resource_changes = vec![];
+        let mut changed = HashSet::new();
+        let mut seen = HashSet::new();
+        for resource in resources {
+            let key = (resource.kind(), resource.name().to_string());
+            seen.insert(key.clone());
+
+            // The hash is stored in the asset, so unchanged resources are never decompressed
+            let Some(hash) = resource.content_hash() else {
+                continue;
+            };
+
+            if self.hashes.get(&key) == Some(&hash) {
+                continue;
+            }
+
+            let Some(bytes) = resource.load() else {
+                continue;
+            };
+
+            let kind = match self.hashes.insert(key.clone(), hash) {
+                Some(_) => ChangeKind::ResourceChanged {
+                    kind: key.0,
+                    name: key.1.clone(),
+                },
+                None => ChangeKind::ResourceAdded {
+                    kind: key.0,
+                    name: key.1.clone(),
+                },
+            };
+
+            match key.0 {
+                ResourceKind::Image => backend.load_image_bytes_with_name(key.1.clone(), bytes),
+                ResourceKind::Font => backend.load_font_bytes_with_name(key.1.clone(), bytes),
+            }
+            resource_changes.push(kind);
+            changed.insert(key);
+        }
+
+        for reference in references {
+            let key = (reference.kind, reference.name.clone());
+            seen.insert(key.clone());
+
+            let kind = match self.hashes.insert(key.clone(), reference.hash) {
+                Some(old) if old == reference.hash => continue,
+                Some(_) => ChangeKind::ResourceChanged {
+                    kind: key.0,
+                    name: key.1.clone(),
+                },
+                None => ChangeKind::ResourceAdded {
+                    kind: key.0,
+                    name: key.1.clone(),
+                },
+            };
+
+            backend.resolve_reference(&reference);
+            resource_changes.push(kind);
+            changed.insert(key);
+        }
+
+        // Removed resources are left in the backend, the new templates don't use them and something else might
+        let files = &self.files;
+        let mut removed = self
+            .hashes
+            .keys()
+            .filter(|key| !seen.contains(*key))
+            .filter(|(kind, name)| !files.iter().any(|file| file.kind == *kind && file.name == *name))
+            .cloned()
+            .collect::<Vec<_>>();
+        removed.sort_by_key(|(kind, name)| (*kind == ResourceKind::Font, name.clone()));
+        for (kind, name) in removed {
+            self.hashes.remove(&(kind, name.clone()));
+            resource_changes.push(ChangeKind::ResourceRemoved { kind, name });
+        }
+
+        let mut reload = Reload::default();
+        let mut updates = HashMap::new();
+
+        let kinds = diff_templates(root.root_template(), &root_template);
+        let resolved_kinds = diff_templates(&root.resolved_root_template(), &resolved_root);
+        updates.insert(None, template_update(&resolved_kinds, &resolved_root, &changed));
+        reload
+            .changes
+            .extend(kinds.into_iter().map(|kind| Change { template: None, kind }));
+
+        let names = root
+            .iter_templates()
+            .into_iter()
+            .map(|(name, _)| name.to_string())
+            .filter(|name| !name.is_empty())
+            .chain(templates.keys().cloned())
+            .collect::<BTreeSet<_>>();
+
+        for name in names {
+            let kinds = match (root.template(&name), templates.get(&name)) {
+                (Some(old), Some(new)) => {
+                    // Changes to a base template are only reported once, but every template derived from it is
+                    // brought up to date as well
+                    let old_resolved = root.resolved_template(&name).unwrap();
+                    let new_resolved = &resolved[&name];
+                    let resolved_kinds = diff_templates(&old_resolved, new_resolved);
+                    updates.insert(Some(name.clone()), template_update(&resolved_kinds, new_resolved, &changed));
+                    diff_templates(old, new)
+                }
+                (Some(_), None) => vec![ChangeKind::TemplateRemoved],
+                (None, _) => vec![ChangeKind::TemplateAdded],
+            };
+
+            reload.changes.extend(kinds.into_iter().map(|kind| Change {
+                template: Some(name.clone()),
+                kind,
+            }));
+        }
+
+        reload.changes.extend(resource_changes.into_iter().map(|kind| Change { template: None, kind }));
+        root.replace_templates(root_template, templates, &updates, backend);
+
+        Ok(reload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        asset::{Asset, SerializeOptions},
+        template::{ImageNodeTemplate, NodeVisibility},
+        testing::RecordingBackend,
+        Animation, AnimationChannel, AnimationTransform, NodeAnimation, NodeTransform, TransformStep,
+    };
+
+    const RED: &[u8] = &[255, 0, 0, 255];
+    const BLUE: &[u8] = &[0, 0, 255, 255];
+
+    fn image(name: &str, texture: &str) -> NodeTemplate {
+        NodeTemplate {
+            name: name.to_string(),
+            transform: NodeTransform::from_size(glam::Vec2::splat(10.0)),
+            color: [255; 4],
+            visibility: NodeVisibility::Inherited,
+            gradient: None,
+            children: vec![],
+            implementation: NodeImplTemplate::Image(ImageNodeTemplate {
+                texture_name: texture.to_string(),
+                mask_texture_name: None,
+                image_scaling_mode_x: Default::default(),
+                image_scaling_mode_y: Default::default(),
+                uv_offset: glam::Vec2::ZERO,
+                uv_scale: glam::Vec2::ONE,
+                fill_method: Default::default(),
+                fill_origin: Default::default(),
+                fill_amount: 1.0,
+            }),
+        }
+    }
+
+    /// Two images, where `b` is moved 100 pixels to the right over 10 frames by the `move` animation
+    fn template() -> LayoutTemplate {
+        let mut animation = NodeAnimation::empty("b");
+        animation.position_channel = Some(AnimationChannel::from_transforms(glam::Vec2::ZERO, vec![
+            AnimationTransform {
+                end: glam::Vec2::new(100.0, 0.0),
+                duration: 10,
+                first_step: TransformStep::Linear,
+                additional_steps: vec![],
+                end_tangents: Default::default(),
+            },
+        ]));
+
+        let mut template = LayoutTemplate {
+            canvas_size: [100, 100],
+            root_nodes: vec![image("a", "red"), image("b", "blue")],
+            ..Default::default()
+        };
+        template.add_animation("move", Animation {
+            node_animations: vec![animation],
+            total_duration: 10,
+        });
+        template
+    }
+
+    fn write(template: &LayoutTemplate, images: &[(&str, &[u8])]) -> Vec<u8> {
+        let asset = Asset {
+            images: images
+                .iter()
+                .map(|(name, bytes)| (name.to_string(), bytes.to_vec()))
+                .collect(),
+            fonts: vec![],
+            templates: vec![],
+            root_template: template.clone(),
+        };
+        container::write(&asset, SerializeOptions::default())
+    }
+
+    /// A root that is set up from `template`, and a watcher that has seen the asset it was loaded from
+    fn loaded() -> (LayoutRoot<RecordingBackend>, RecordingBackend, AssetWatcher) {
+        let mut backend = RecordingBackend::new();
+        let mut root = LayoutRoot::from_root_template(template(), []);
+        root.setup(&mut backend);
+
+        let mut watcher = AssetWatcher::new("missing.envy");
+        let bytes = write(&template(), &[("red", RED), ("blue", BLUE)]);
+        watcher.reload_bytes(&mut root, &mut backend, &bytes).unwrap();
+        backend.take_resource_loads();
+        (root, backend, watcher)
+    }
+
+    fn run(root: &mut LayoutRoot<RecordingBackend>, backend: &mut RecordingBackend, frames: usize) {
+        for _ in 0..frames {
+            root.as_layout_mut().update_animations();
+            root.update();
+            root.as_layout_mut().propagate();
+            root.prepare(backend);
+        }
+    }
+
+    fn position(root: &LayoutRoot<RecordingBackend>, path: &str) -> glam::Vec2 {
+        root.as_layout().get_node_by_path(path).unwrap().transform().position
+    }
+
+    fn changes(reload: Reload) -> Vec<ChangeKind> {
+        reload.changes.into_iter().map(|change| change.kind).collect()
+    }
+
+    #[test]
+    fn only_changed_nodes_are_rebuilt() {
+        let (mut root, mut backend, mut watcher) = loaded();
+        let before = backend.texture_counts();
+
+        let mut changed = template();
+        changed.root_nodes[0].color = [0, 255, 0, 255];
+        let reload = watcher
+            .reload_bytes(&mut root, &mut backend, &write(&changed, &[("red", RED), ("blue", BLUE)]))
+            .unwrap();
+
+        assert!(matches!(changes(reload).as_slice(), [ChangeKind::NodeChanged { node, .. }] if node == "a"));
+        assert_eq!(root.as_layout().get_node_by_path("a").unwrap().color(), [0, 255, 0, 255]);
+
+        // Only `a` gave up its texture and requested it again
+        let after = backend.texture_counts();
+        assert_eq!(after.requested - before.requested, 1);
+        assert_eq!(after.released - before.released, 1);
+        assert!(backend.take_resource_loads().is_empty());
+
+        root.release(&mut backend);
+        backend.assert_no_leaks();
+    }
+
+    #[test]
+    fn playing_animations_keep_their_progress() {
+        let (mut expected, mut expected_backend, _) = loaded();
+        expected.as_layout_mut().play_animation("move");
+        run(&mut expected, &mut expected_backend, 6);
+
+        let (mut root, mut backend, mut watcher) = loaded();
+        root.as_layout_mut().play_animation("move");
+        run(&mut root, &mut backend, 3);
+
+        let mut changed = template();
+        changed.root_nodes[0].transform.position = glam::Vec2::new(5.0, 5.0);
+        watcher
+            .reload_bytes(&mut root, &mut backend, &write(&changed, &[("red", RED), ("blue", BLUE)]))
+            .unwrap();
+        run(&mut root, &mut backend, 3);
+
+        assert!(root.as_layout().is_animation_playing("move"));
+        assert_eq!(position(&root, "b"), position(&expected, "b"));
+        assert_ne!(position(&root, "b"), glam::Vec2::ZERO);
+        assert_eq!(position(&root, "a"), glam::Vec2::new(5.0, 5.0));
+
+        root.release(&mut backend);
+        backend.assert_no_leaks();
+    }
+
+    #[test]
+    fn only_changed_textures_are_loaded_again() {
+        let (mut root, mut backend, mut watcher) = loaded();
+        let before = backend.texture_counts();
+
+        let green: &[u8] = &[0, 255, 0, 255];
+        let reload = watcher
+            .reload_bytes(&mut root, &mut backend, &write(&template(), &[("red", green), ("blue", BLUE)]))
+            .unwrap();
+
+        assert_eq!(changes(reload), [ChangeKind::ResourceChanged {
+            kind: ResourceKind::Image,
+            name: "red".to_string(),
+        }]);
+        assert_eq!(backend.take_resource_loads(), [(ResourceKind::Image, "red".to_string())]);
+        assert_eq!(backend.fetch_image_bytes_by_name("red").as_ref(), green);
+
+        // The node showing the texture picks up the new one
+        assert_eq!(backend.texture_counts().requested - before.requested, 1);
+
+        root.release(&mut backend);
+        backend.assert_no_leaks();
+    }
+
+    #[test]
+    fn removed_resources_are_reported() {
+        let (mut root, mut backend, mut watcher) = loaded();
+
+        let mut changed = template();
+        changed.root_nodes.remove(0);
+        let reload = watcher
+            .reload_bytes(&mut root, &mut backend, &write(&changed, &[("blue", BLUE)]))
+            .unwrap();
+
+        assert_eq!(changes(reload), [
+            ChangeKind::NodeRemoved { node: "a".to_string() },
+            ChangeKind::ResourceRemoved {
+                kind: ResourceKind::Image,
+                name: "red".to_string(),
+            },
+        ]);
+        assert!(root.as_layout().get_node_by_path("a").is_none());
+        assert!(backend.take_resource_loads().is_empty());
+
+        root.release(&mut backend);
+        backend.assert_no_leaks();
+    }
+}
@@ -0,0 +1,1102 @@
+//! Checks for mistakes in layouts that are not caught when they are loaded
+//!
+//! Most of these problems don't stop a layout from loading, they just quietly leave something off of the screen: an
+//! image whose texture was never embedded, an animation whose node was renamed, or a text node that is too small to
+//! lay out any text. [`validate`] finds them all at once so they can be reported before the layout ships.
+//!
+//! A [`LayoutRoot`] only holds templates, the images and fonts they name live in the backend or the asset they were
+//! loaded from. So checking that those exist, and that nothing unused is shipped, needs the list of available
+//! resources passed to [`validate_with_resources`], usually read with [`AvailableResources::from_asset`].
+
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use glam::Vec2;
+
+use crate::{
+    DiscreteChannel, EnvyBackend, InheritanceError, LayoutRoot, LayoutTemplate, NodeAnimation, NodeImplTemplate,
+    NodeTemplate, SUBLAYOUT_PATH_SEPARATOR,
+};
+
+use super::{DeserializeError, EnvyAssetProvider, LazyResource, ResourceKind, ResourceReference};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The layout works, but likely not as intended
+    Warning,
+
+    /// Part of the layout will not be shown or animated
+    Error,
+}
+
+/// A problem found by [`validate`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// Name of the template the problem is in, or `None` for the root template and problems that are not in any
+    /// template
+    pub template: Option<String>,
+    pub kind: DiagnosticKind,
+}
+
+/// What is wrong, node paths are relative to the template of the [`Diagnostic`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiagnosticKind {
+    /// An image or text node has an empty texture, mask or font name, or an animation switches a node to a texture
+    /// with an empty name
+    EmptyResourceName { node: String, kind: ResourceKind },
+
+    /// A node or animation uses an image or font that is not available
+    MissingResource {
+        node: String,
+        kind: ResourceKind,
+        name: String,
+    },
+
+    /// An image or font is available but not used by any node or animation
+    UnusedResource { kind: ResourceKind, name: String },
+
+    /// Two siblings have the same name, so only the first one can be found by path. `parent` is empty for root nodes
+    DuplicateNodeName { parent: String, name: String },
+
+    /// A sublayout node uses a template that does not exist
+    MissingTemplate { node: String, template: String },
+
+    /// Templates that include each other through sublayout nodes, the first template is repeated at the end
+    SublayoutCycle { templates: Vec<String> },
+
+    /// A derived template derives from a template that does not exist
+    MissingBaseTemplate { base: String },
+
+    /// Templates that derive from each other, the first template is repeated at the end
+    InheritanceCycle { templates: Vec<String> },
+
+    /// A derived template removes, patches or adds children to a node that is not in its base template, so that
+    /// change is skipped
+    MissingOverrideNode { node: String },
+
+    /// A text node will never lay out its text, which needs a size, font size and line height above zero
+    TextNotLaidOut {
+        node: String,
+        size: Vec2,
+        font_size: f32,
+        line_height: f32,
+    },
+
+    /// An animation targets a node that does not exist
+    MissingAnimationNode { animation: String, node: String },
+
+    /// An animation has a channel that does nothing for the type of node it targets
+    InapplicableChannel {
+        animation: String,
+        node: String,
+        channel: &'static str,
+    },
+
+    /// A sublayout animation channel starts an animation that the sublayout's template does not have
+    MissingSublayoutAnimation {
+        animation: String,
+        node: String,
+        sublayout_animation: String,
+    },
+
+    /// A channel has keyframes after the end of its animation, which are never played
+    KeyframePastEnd {
+        animation: String,
+        node: String,
+        channel: &'static str,
+        frame: usize,
+        total_duration: usize,
+    },
+}
+
+impl Diagnostic {
+    pub fn severity(&self) -> Severity {
+        match &self.kind {
+            DiagnosticKind::UnusedResource { .. }
+            | DiagnosticKind::InapplicableChannel { .. }
+            | DiagnosticKind::KeyframePastEnd { .. }
+            | DiagnosticKind::MissingOverrideNode { .. } => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyResourceName { node, kind } => write!(f, "node '{node}' uses an empty {kind} name"),
+            Self::MissingResource { node, kind, name } => write!(f, "node '{node}' uses missing {kind} '{name}'"),
+            Self::UnusedResource { kind, name } => write!(f, "{kind} '{name}' is not used"),
+            Self::DuplicateNodeName { parent, name } if parent.is_empty() => {
+                write!(f, "there is more than one root node named '{name}'")
+            }
+            Self::DuplicateNodeName { parent, name } => {
+                write!(f, "node '{parent}' has more than one child named '{name}'")
+            }
+            Self::MissingTemplate { node, template } => {
+                write!(f, "sublayout node '{node}' uses missing template '{template}'")
+            }
+            Self::SublayoutCycle { templates } => {
+                write!(f, "templates include each other: {}", templates.join(" -> "))
+            }
+            Self::MissingBaseTemplate { base } => write!(f, "derives from missing template '{base}'"),
+            Self::InheritanceCycle { templates } => {
+                write!(f, "templates derive from each other: {}", templates.join(" -> "))
+            }
+            Self::MissingOverrideNode { node } => {
+                write!(f, "changes node '{node}', which is not in the base template")
+            }
+            Self::TextNotLaidOut {
+                node,
+                size,
+                font_size,
+                line_height,
+            } => write!(
+                f,
+                "text of node '{node}' is never laid out (size {size}, font size {font_size}, line height {line_height})"
+            ),
+            Self::MissingAnimationNode { animation, node } => {
+                write!(f, "animation '{animation}' targets missing node '{node}'")
+            }
+            Self::InapplicableChannel {
+                animation,
+                node,
+                channel,
+            } => write!(
+                f,
+                "animation '{animation}' animates the {channel} of node '{node}', which does nothing for its type of node"
+            ),
+            Self::MissingSublayoutAnimation {
+                animation,
+                node,
+                sublayout_animation,
+            } => write!(
+                f,
+                "animation '{animation}' starts missing animation '{sublayout_animation}' of sublayout node '{node}'"
+            ),
+            Self::KeyframePastEnd {
+                animation,
+                node,
+                channel,
+                frame,
+                total_duration,
+            } => write!(
+                f,
+                "animation '{animation}' animates the {channel} of node '{node}' until frame {frame}, after it ends on frame {total_duration}"
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity() {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+
+        match self.template.as_ref() {
+            Some(template) => write!(f, "{severity} in template '{template}': {}", self.kind),
+            None => write!(f, "{severity}: {}", self.kind),
+        }
+    }
+}
+
+/// Names of the images and fonts that are available to a layout, see [`validate_with_resources`]
+#[derive(Debug, Default, Clone)]
+pub struct AvailableResources {
+    pub images: HashSet<String>,
+    pub fonts: HashSet<String>,
+}
+
+impl AvailableResources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_images<'a>(mut self, names: impl IntoIterator<Item = &'a str>) -> Self {
+        self.images.extend(names.into_iter().map(str::to_string));
+        self
+    }
+
+    pub fn with_fonts<'a>(mut self, names: impl IntoIterator<Item = &'a str>) -> Self {
+        self.fonts.extend(names.into_iter().map(str::to_string));
+        self
+    }
+
+    /// Names of the images and fonts embedded in or referenced by an asset, without decoding any of them
+    pub fn from_asset(bytes: &[u8]) -> Result<Self, DeserializeError> {
+        let mut names = Self::new();
+        let _: LayoutRoot<crate::testing::RecordingBackend> = super::try_deserialize(&mut names, bytes)?;
+        Ok(names)
+    }
+
+    fn contains(&self, kind: ResourceKind, name: &str) -> bool {
+        match kind {
+            ResourceKind::Image => self.images.contains(name),
+            ResourceKind::Font => self.fonts.contains(name),
+        }
+    }
+
+    fn insert(&mut self, kind: ResourceKind, name: String) {
+        let _ = match kind {
+            ResourceKind::Image => self.images.insert(name),
+            ResourceKind::Font => self.fonts.insert(name),
+        };
+    }
+}
+
+/// Only records names, which is all [`AvailableResources::from_asset`] needs
+impl EnvyAssetProvider for AvailableResources {
+    fn load_image_bytes_with_name(&mut self, name: String, _bytes: Vec<u8>) {
+        self.insert(ResourceKind::Image, name);
+    }
+
+    fn load_font_bytes_with_name(&mut self, name: String, _bytes: Vec<u8>) {
+        self.insert(ResourceKind::Font, name);
+    }
+
+    fn fetch_image_bytes_by_name<'a>(&'a self, _name: &str) -> Cow<'a, [u8]> {
+        Cow::Borrowed(&[])
+    }
+
+    fn fetch_font_bytes_by_name<'a>(&'a self, _name: &str) -> Cow<'a, [u8]> {
+        Cow::Borrowed(&[])
+    }
+
+    fn resolve_reference(&mut self, reference: &ResourceReference) {
+        self.insert(reference.kind, reference.name.clone());
+    }
+
+    fn defer_resource(&mut self, resource: LazyResource) {
+        self.insert(resource.kind(), resource.name().to_string());
+    }
+}
+
+/// Checks every template of `root` for problems, without checking that the images and fonts it uses exist
+///
+/// Resources are not part of a [`LayoutRoot`], they are loaded into the backend, so there is nothing to compare the
+/// names used by the layout against. Empty names are still reported, but use [`validate_with_resources`] to also
+/// report missing and unused images and fonts.
+pub fn validate<B: EnvyBackend>(root: &LayoutRoot<B>) -> Vec<Diagnostic> {
+    Validator::new(root).finish(None)
+}
+
+/// Checks every template of `root` for problems, including images and fonts that are used by the layout but not in
+/// `resources` and the other way around
+pub fn validate_with_resources<B: EnvyBackend>(root: &LayoutRoot<B>, resources: &AvailableResources) -> Vec<Diagnostic> {
+    Validator::new(root).finish(Some(resources))
+}
+
+/// A resource name used by a node or animation
+struct ResourceUse {
+    template: Option<String>,
+    node: String,
+    kind: ResourceKind,
+    name: String,
+}
+
+struct Validator<'a, B: EnvyBackend> {
+    root: &'a LayoutRoot<B>,
+    diagnostics: Vec<Diagnostic>,
+    resources: Vec<ResourceUse>,
+}
+
+impl<'a, B: EnvyBackend> Validator<'a, B> {
+    fn new(root: &'a LayoutRoot<B>) -> Self {
+        let mut this = Self {
+            root,
+            diagnostics: vec![],
+            resources: vec![],
+        };
+
+        let mut templates = root.templates().iter().collect::<Vec<_>>();
+        templates.sort_by_key(|(name, _)| *name);
+
+        this.validate_derived_template(None, root.root_template());
+        for (name, template) in templates {
+            this.validate_derived_template(Some(name), template);
+        }
+
+        this.find_sublayout_cycles();
+        this
+    }
+
+    fn report(&mut self, template: Option<&str>, kind: DiagnosticKind) {
+        self.diagnostics.push(Diagnostic {
+            template: template.map(str::to_string),
+            kind,
+        });
+    }
+
+    fn use_resource(&mut self, template: Option<&str>, node: &str, kind: ResourceKind, name: &str) {
+        if name.is_empty() {
+            self.report(
+                template,
+                DiagnosticKind::EmptyResourceName {
+                    node: node.to_string(),
+                    kind,
+                },
+            );
+            return;
+        }
+
+        self.resources.push(ResourceUse {
+            template: template.map(str::to_string),
+            node: node.to_string(),
+            kind,
+            name: name.to_string(),
+        });
+    }
+
+    /// Validates the complete template that trees are built from, along with the changes a derived template makes
+    fn validate_derived_template(&mut self, name: Option<&str>, template: &LayoutTemplate) {
+        let templates = self.root.templates();
+        let resolved = match template.resolve(templates) {
+            Ok(resolved) => resolved,
+            Err(InheritanceError::MissingBase { base }) => {
+                self.report(name, DiagnosticKind::MissingBaseTemplate { base });
+                return;
+            }
+            Err(InheritanceError::Cycle { templates }) => {
+                self.report(name, DiagnosticKind::InheritanceCycle { templates });
+                return;
+            }
+        };
+
+        if let Some(inheritance) = template.inheritance.as_ref() {
+            // The base resolves, since the derived template did
+            let base = templates[&inheritance.base].resolve(templates).unwrap();
+            let paths = inheritance
+                .removed_nodes
+                .iter()
+                .chain(inheritance.patches.iter().map(|patch| &patch.path))
+                .chain(inheritance.added_nodes.iter().map(|(parent, _)| parent));
+
+            for path in paths {
+                if base.get_node_by_path(path).is_none() {
+                    self.report(name, DiagnosticKind::MissingOverrideNode { node: path.clone() });
+                }
+            }
+        }
+
+        self.validate_template(name, &resolved);
+    }
+
+    fn validate_template(&mut self, name: Option<&str>, template: &LayoutTemplate) {
+        self.validate_siblings(name, "", &template.root_nodes);
+        for (animation_name, animation) in template.animations.iter() {
+            for node_animation in animation.node_animations.iter() {
+                self.validate_node_animation(name, template, animation_name, animation.total_duration, node_animation);
+            }
+        }
+    }
+
+    fn validate_siblings(&mut self, template: Option<&str>, parent: &str, nodes: &[NodeTemplate]) {
+        let mut names = HashSet::new();
+        for node in nodes.iter() {
+            if !names.insert(node.name.as_str()) {
+                self.report(
+                    template,
+                    DiagnosticKind::DuplicateNodeName {
+                        parent: parent.to_string(),
+                        name: node.name.clone(),
+                    },
+                );
+            }
+
+            let path = match parent.is_empty() {
+                true => node.name.clone(),
+                false => format!("{parent}/{}", node.name),
+            };
+
+            self.validate_node(template, &path, node);
+            self.validate_siblings(template, &path, &node.children);
+        }
+    }
+
+    fn validate_node(&mut self, template: Option<&str>, path: &str, node: &NodeTemplate) {
+        match &node.implementation {
+            NodeImplTemplate::Image(image) => {
+                self.use_resource(template, path, ResourceKind::Image, &image.texture_name);
+                if let Some(mask) = image.mask_texture_name.as_ref() {
+                    self.use_resource(template, path, ResourceKind::Image, mask);
+                }
+            }
+            NodeImplTemplate::Text(text) => {
+                self.use_resource(template, path, ResourceKind::Font, &text.font_name);
+
+                if text.font_size <= 0.0 || text.line_height <= 0.0 || node.transform.size.cmple(Vec2::ZERO).any() {
+                    self.report(
+                        template,
+                        DiagnosticKind::TextNotLaidOut {
+                            node: path.to_string(),
+                            size: node.transform.size,
+                            font_size: text.font_size,
+                            line_height: text.line_height,
+                        },
+                    );
+                }
+            }
+            NodeImplTemplate::Sublayout(sublayout) => {
+                if self.root.template(&sublayout.sublayout_name).is_none() {
+                    self.report(
+                        template,
+                        DiagnosticKind::MissingTemplate {
+                            node: path.to_string(),
+                            template: sublayout.sublayout_name.clone(),
+                        },
+                    );
+                }
+            }
+            NodeImplTemplate::Empty | NodeImplTemplate::Shape(_) => {}
+        }
+    }
+
+    /// Finds the node an animation targets, following sublayout paths into the complete templates of derived templates
+    fn animated_node<'t>(&self, template: &'t LayoutTemplate, path: &str) -> Option<Cow<'t, NodeTemplate>> {
+        let Some((path, sublayout_path)) = path.split_once(SUBLAYOUT_PATH_SEPARATOR) else {
+            return template.get_node_by_path(path).map(Cow::Borrowed);
+        };
+
+        let NodeImplTemplate::Sublayout(sublayout) = &template.get_node_by_path(path)?.implementation else {
+            return None;
+        };
+
+        let sublayout_template = self
+            .root
+            .template(&sublayout.sublayout_name)?
+            .resolve(self.root.templates())
+            .ok()?;
+        let node = self.animated_node(&sublayout_template, sublayout_path)?;
+        Some(Cow::Owned(node.into_owned()))
+    }
+
+    fn validate_node_animation(
+        &mut self,
+        template_name: Option<&str>,
+        template: &LayoutTemplate,
+        animation: &str,
+        total_duration: usize,
+        node_animation: &NodeAnimation,
+    ) {
+        let path = node_animation.node_path.as_str();
+        let Some(node) = self.animated_node(template, path) else {
+            self.report(
+                template_name,
+                DiagnosticKind::MissingAnimationNode {
+                    animation: animation.to_string(),
+                    node: path.to_string(),
+                },
+            );
+            return;
+        };
+
+        for channel in channels(node_animation) {
+            let applies = match channel.target {
+                ChannelTarget::Any => true,
+                ChannelTarget::Image => matches!(node.implementation, NodeImplTemplate::Image(_)),
+                ChannelTarget::Text => matches!(node.implementation, NodeImplTemplate::Text(_)),
+                ChannelTarget::Sublayout => matches!(node.implementation, NodeImplTemplate::Sublayout(_)),
+                ChannelTarget::GradientStop(idx) => node
+                    .gradient
+                    .as_ref()
+                    .is_some_and(|gradient| idx < gradient.stops.len()),
+            };
+
+            if !applies {
+                self.report(
+                    template_name,
+                    DiagnosticKind::InapplicableChannel {
+                        animation: animation.to_string(),
+                        node: path.to_string(),
+                        channel: channel.name,
+                    },
+                );
+            }
+
+            if channel.last_keyframe > total_duration {
+                self.report(
+                    template_name,
+                    DiagnosticKind::KeyframePastEnd {
+                        animation: animation.to_string(),
+                        node: path.to_string(),
+                        channel: channel.name,
+                        frame: channel.last_keyframe,
+                        total_duration,
+                    },
+                );
+            }
+        }
+
+        if let Some(textures) = node_animation.texture_channel.as_ref() {
+            // An empty name is a mistake on any node, other names are only used when the node is an image
+            let is_image = matches!(node.implementation, NodeImplTemplate::Image(_));
+            for (_, texture) in textures.keyframe_starts() {
+                if is_image || texture.is_empty() {
+                    self.use_resource(template_name, path, ResourceKind::Image, texture);
+                }
+            }
+        }
+
+        if let (Some(animations), NodeImplTemplate::Sublayout(sublayout)) =
+            (&node_animation.sublayout_animation_channel, &node.implementation)
+        {
+            let Some(sublayout_template) = self
+                .root
+                .template(&sublayout.sublayout_name)
+                .and_then(|sublayout_template| sublayout_template.resolve(self.root.templates()).ok())
+            else {
+                return;
+            };
+
+            for (_, name) in animations.keyframe_starts() {
+                if !name.is_empty() && !sublayout_template.animations.iter().any(|(animation, _)| animation == name) {
+                    self.report(
+                        template_name,
+                        DiagnosticKind::MissingSublayoutAnimation {
+                            animation: animation.to_string(),
+                            node: path.to_string(),
+                            sublayout_animation: name.clone(),
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    /// Follows the sublayouts of every template, reporting each cycle once
+    fn find_sublayout_cycles(&mut self) {
+        let mut sublayouts: HashMap<&str, Vec<String>> = HashMap::new();
+        let templates = self.root.templates();
+        for (name, template) in templates.iter() {
+            let mut names = vec![];
+            let template = template.resolve(templates).unwrap_or(Cow::Borrowed(template));
+            template.walk_tree(|node| {
+                if let NodeImplTemplate::Sublayout(sublayout) = &node.implementation {
+                    names.push(sublayout.sublayout_name.clone());
+                }
+            });
+            names.sort_unstable();
+            names.dedup();
+            sublayouts.insert(name.as_str(), names);
+        }
+
+        fn visit<'s>(
+            name: &'s str,
+            sublayouts: &'s HashMap<&'s str, Vec<String>>,
+            stack: &mut Vec<&'s str>,
+            finished: &mut HashSet<&'s str>,
+            cycles: &mut Vec<Vec<String>>,
+        ) {
+            if let Some(start) = stack.iter().position(|visiting| *visiting == name) {
+                let mut cycle = stack[start..].iter().map(|name| name.to_string()).collect::<Vec<_>>();
+                cycle.push(name.to_string());
+                cycles.push(cycle);
+                return;
+            }
+
+            if !finished.insert(name) {
+                return;
+            }
+
+            stack.push(name);
+            for sublayout in sublayouts.get(name).into_iter().flatten() {
+                visit(sublayout, sublayouts, stack, finished, cycles);
+            }
+            stack.pop();
+        }
+
+        let mut names = sublayouts.keys().copied().collect::<Vec<_>>();
+        names.sort_unstable();
+
+        let mut finished = HashSet::new();
+        let mut cycles = vec![];
+        for name in names {
+            visit(name, &sublayouts, &mut vec![], &mut finished, &mut cycles);
+        }
+
+        for templates in cycles {
+            self.report(None, DiagnosticKind::SublayoutCycle { templates });
+        }
+    }
+
+    fn finish(mut self, resources: Option<&AvailableResources>) -> Vec<Diagnostic> {
+        let Some(resources) = resources else {
+            return self.diagnostics;
+        };
+
+        let uses = std::mem::take(&mut self.resources);
+        for resource in uses.iter() {
+            if !resources.contains(resource.kind, &resource.name) {
+                self.report(
+                    resource.template.as_deref(),
+                    DiagnosticKind::MissingResource {
+                        node: resource.node.clone(),
+                        kind: resource.kind,
+                        name: resource.name.clone(),
+                    },
+                );
+            }
+        }
+
+        let used = uses
+            .iter()
+            .map(|resource| (resource.kind, resource.name.as_str()))
+            .collect::<HashSet<_>>();
+
+        for (kind, names) in [(ResourceKind::Image, &resources.images), (ResourceKind::Font, &resources.fonts)] {
+            let mut unused = names
+                .iter()
+                .filter(|name| !used.contains(&(kind, name.as_str())))
+                .collect::<Vec<_>>();
+            unused.sort_unstable();
+
+            for name in unused {
+                self.report(
+                    None,
+                    DiagnosticKind::UnusedResource {
+                        kind,
+                        name: name.clone(),
+                    },
+                );
+            }
+        }
+
+        self.diagnostics
+    }
+}
+
+/// Type of node that a channel of a [`NodeAnimation`] does something for
+#[derive(Copy, Clone)]
+enum ChannelTarget {
+    Any,
+    Image,
+    Text,
+    Sublayout,
+
+    /// Nodes with a gradient that has a stop at this index
+    GradientStop(usize),
+}
+
+struct ChannelInfo {
+    name: &'static str,
+    target: ChannelTarget,
+    last_keyframe: usize,
+}
+
+fn last_discrete_keyframe<T>(channel: &DiscreteChannel<T>) -> usize {
+    channel.keyframe_starts().last().map(|(frame, _)| frame).unwrap_or_default()
+}
+
+/// Every channel that is set on `animation`
+fn channels(animation: &NodeAnimation) -> Vec<ChannelInfo> {
+    let mut channels = vec![];
+
+    macro_rules! channel {
+        ($field:ident, $name:literal, $target:ident) => {
+            if let Some(channel) = animation.$field.as_ref() {
+                channels.push(ChannelInfo {
+                    name: $name,
+                    target: ChannelTarget::$target,
+                    last_keyframe: channel.duration(),
+                });
+            }
+        };
+        (discrete $field:ident, $name:literal, $target:ident) => {
+            if let Some(channel) = animation.$field.as_ref() {
+                channels.push(ChannelInfo {
+                    name: $name,
+                    target: ChannelTarget::$target,
+                    last_keyframe: last_discrete_keyframe(channel),
+                });
+            }
+        };
+    }
+
+    channel!(angle_channel, "angle", Any);
+    channel!(position_channel, "position", Any);
+    channel!(size_channel, "size", Any);
+    channel!(scale_channel, "scale", Any);
+    channel!(color_channel, "color", Any);
+    channel!(discrete visibility_channel, "visibility", Any);
+    channel!(uv_offset_channel, "UV offset", Image);
+    channel!(uv_scale_channel, "UV scale", Image);
+    channel!(fill_amount_channel, "fill amount", Image);
+    channel!(discrete texture_channel, "texture", Image);
+    channel!(font_size_channel, "font size", Text);
+    channel!(line_height_channel, "line height", Text);
+    channel!(outline_thickness_channel, "outline thickness", Text);
+    channel!(outline_color_channel, "outline color", Text);
+    channel!(visible_characters_channel, "visible characters", Text);
+    channel!(discrete text_channel, "text", Text);
+    channel!(discrete sublayout_animation_channel, "sublayout animation", Sublayout);
+
+    if let Some(motion_path) = animation.motion_path.as_ref() {
+        channels.push(ChannelInfo {
+            name: "motion path",
+            target: ChannelTarget::Any,
+            last_keyframe: motion_path.progress_channel.duration(),
+        });
+    }
+
+    for (idx, channel) in animation.gradient_stop_color_channels.iter().enumerate() {
+        if let Some(channel) = channel.as_ref() {
+            channels.push(ChannelInfo {
+                name: "gradient stop color",
+                target: ChannelTarget::GradientStop(idx),
+                last_keyframe: channel.duration(),
+            });
+        }
+    }
+
+    channels
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        testing::RecordingBackend, Animation, DiscreteKeyframe, ImageNodeTemplate, NodeTransform, NodeVisibility,
+        SublayoutNodeTemplate, TemplateInheritance, TextNodeTemplate,
+    };
+
+    use super::*;
+
+    fn node(name: &str, implementation: NodeImplTemplate) -> NodeTemplate {
+        NodeTemplate {
+            name: name.to_string(),
+            transform: NodeTransform::from_size(Vec2::new(100.0, 50.0)),
+            color: [255; 4],
+            visibility: NodeVisibility::Inherited,
+            gradient: None,
+            children: vec![],
+            implementation,
+        }
+    }
+
+    fn image(name: &str, texture: &str) -> NodeTemplate {
+        node(
+            name,
+            NodeImplTemplate::Image(ImageNodeTemplate {
+                texture_name: texture.to_string(),
+                mask_texture_name: None,
+                image_scaling_mode_x: Default::default(),
+                image_scaling_mode_y: Default::default(),
+                uv_offset: Vec2::ZERO,
+                uv_scale: Vec2::ONE,
+                fill_method: Default::default(),
+                fill_origin: Default::default(),
+                fill_amount: 1.0,
+            }),
+        )
+    }
+
+    fn text(name: &str, font_size: f32) -> NodeTemplate {
+        node(
+            name,
+            NodeImplTemplate::Text(TextNodeTemplate {
+                font_name: "font".to_string(),
+                text: "text".to_string(),
+                font_size,
+                line_height: 16.0,
+                outline_thickness: 0.0,
+                outline_color: [0; 4],
+                alignment: Default::default(),
+            }),
+        )
+    }
+
+    fn sublayout(name: &str, template: &str) -> NodeTemplate {
+        node(
+            name,
+            NodeImplTemplate::Sublayout(SublayoutNodeTemplate {
+                sublayout_name: template.to_string(),
+            }),
+        )
+    }
+
+    fn template(nodes: Vec<NodeTemplate>) -> LayoutTemplate {
+        LayoutTemplate {
+            canvas_size: [100, 100],
+            root_nodes: nodes,
+            ..Default::default()
+        }
+    }
+
+    fn animated(template: &mut LayoutTemplate, node_animation: NodeAnimation) {
+        template.add_animation(
+            "anim",
+            Animation {
+                node_animations: vec![node_animation],
+                total_duration: 10,
+            },
+        );
+    }
+
+    fn layout(root: LayoutTemplate, templates: Vec<(&str, LayoutTemplate)>) -> LayoutRoot<RecordingBackend> {
+        let templates = templates.into_iter().map(|(name, template)| (name.to_string(), template));
+        LayoutRoot::from_root_template(root, templates)
+    }
+
+    fn kinds(diagnostics: Vec<Diagnostic>) -> Vec<DiagnosticKind> {
+        diagnostics.into_iter().map(|diagnostic| diagnostic.kind).collect()
+    }
+
+    fn discrete<T>(start: T, keyframes: impl IntoIterator<Item = (usize, T)>) -> DiscreteChannel<T> {
+        DiscreteChannel {
+            start,
+            keyframes: keyframes
+                .into_iter()
+                .map(|(duration, value)| DiscreteKeyframe { value, duration })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn empty_resource_names() {
+        let mut root = template(vec![image("image", ""), node("empty", NodeImplTemplate::Empty)]);
+        let mut animation = NodeAnimation::empty("empty");
+        animation.texture_channel = Some(discrete("".to_string(), []));
+        animated(&mut root, animation);
+
+        assert_eq!(
+            kinds(validate(&layout(root, vec![]))),
+            [
+                DiagnosticKind::EmptyResourceName {
+                    node: "image".to_string(),
+                    kind: ResourceKind::Image,
+                },
+                DiagnosticKind::InapplicableChannel {
+                    animation: "anim".to_string(),
+                    node: "empty".to_string(),
+                    channel: "texture",
+                },
+                DiagnosticKind::EmptyResourceName {
+                    node: "empty".to_string(),
+                    kind: ResourceKind::Image,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_and_unused_resources_need_the_available_resources() {
+        let mut root = template(vec![image("image", "used")]);
+        let mut animation = NodeAnimation::empty("image");
+        animation.texture_channel = Some(discrete("used".to_string(), [(5, "animated".to_string())]));
+        animated(&mut root, animation);
+        let root = layout(root, vec![]);
+
+        assert_eq!(kinds(validate(&root)), []);
+
+        let resources = AvailableResources::new().with_images(["used", "unused"]);
+        assert_eq!(
+            kinds(validate_with_resources(&root, &resources)),
+            [
+                DiagnosticKind::MissingResource {
+                    node: "image".to_string(),
+                    kind: ResourceKind::Image,
+                    name: "animated".to_string(),
+                },
+                DiagnosticKind::UnusedResource {
+                    kind: ResourceKind::Image,
+                    name: "unused".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicate_node_names() {
+        let mut parent = node("parent", NodeImplTemplate::Empty);
+        parent.children = vec![node("child", NodeImplTemplate::Empty), node("child", NodeImplTemplate::Empty)];
+        let root = template(vec![parent.clone(), parent]);
+
+        assert_eq!(
+            kinds(validate(&layout(root, vec![]))),
+            [
+                DiagnosticKind::DuplicateNodeName {
+                    parent: "parent".to_string(),
+                    name: "child".to_string(),
+                },
+                DiagnosticKind::DuplicateNodeName {
+                    parent: String::new(),
+                    name: "parent".to_string(),
+                },
+                DiagnosticKind::DuplicateNodeName {
+                    parent: "parent".to_string(),
+                    name: "child".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_template() {
+        let mut root = layout(template(vec![]), vec![]);
+        root.root_template_mut().root_nodes.push(sublayout("sub", "missing"));
+
+        assert_eq!(
+            kinds(validate(&root)),
+            [DiagnosticKind::MissingTemplate {
+                node: "sub".to_string(),
+                template: "missing".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn sublayout_cycle() {
+        let mut root = layout(template(vec![]), vec![("a", template(vec![])), ("b", template(vec![]))]);
+        root.template_mut("a").unwrap().root_nodes.push(sublayout("b", "b"));
+        root.template_mut("b").unwrap().root_nodes.push(sublayout("a", "a"));
+
+        assert_eq!(
+            kinds(validate(&root)),
+            [DiagnosticKind::SublayoutCycle {
+                templates: vec!["a".to_string(), "b".to_string(), "a".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn missing_base_template() {
+        let mut root = layout(template(vec![]), vec![("derived", template(vec![]))]);
+        root.template_mut("derived").unwrap().inheritance = Some(TemplateInheritance::new("missing"));
+
+        let diagnostics = validate(&root);
+        assert_eq!(diagnostics[0].template.as_deref(), Some("derived"));
+        assert_eq!(
+            kinds(diagnostics),
+            [DiagnosticKind::MissingBaseTemplate {
+                base: "missing".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn inheritance_cycle() {
+        let mut root = layout(template(vec![]), vec![("a", template(vec![])), ("b", template(vec![]))]);
+        root.template_mut("a").unwrap().inheritance = Some(TemplateInheritance::new("b"));
+        root.template_mut("b").unwrap().inheritance = Some(TemplateInheritance::new("a"));
+
+        let diagnostics = validate(&root);
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| matches!(diagnostic.kind, DiagnosticKind::InheritanceCycle { .. })));
+    }
+
+    #[test]
+    fn missing_override_node() {
+        let mut inheritance = TemplateInheritance::new("base");
+        inheritance.removed_nodes.push("missing".to_string());
+        let mut derived = template(vec![]);
+        derived.inheritance = Some(inheritance);
+
+        let root = layout(
+            template(vec![]),
+            vec![("base", template(vec![node("node", NodeImplTemplate::Empty)])), ("derived", derived)],
+        );
+        let diagnostics = validate(&root);
+        assert_eq!(diagnostics[0].severity(), Severity::Warning);
+        assert_eq!(
+            kinds(diagnostics),
+            [DiagnosticKind::MissingOverrideNode {
+                node: "missing".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn text_not_laid_out() {
+        let root = template(vec![text("laid out", 16.0), text("not laid out", 0.0)]);
+
+        assert_eq!(
+            kinds(validate(&layout(root, vec![]))),
+            [DiagnosticKind::TextNotLaidOut {
+                node: "not laid out".to_string(),
+                size: Vec2::new(100.0, 50.0),
+                font_size: 0.0,
+                line_height: 16.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn missing_animation_node() {
+        let mut root = template(vec![]);
+        animated(&mut root, NodeAnimation::empty("missing"));
+
+        assert_eq!(
+            kinds(validate(&layout(root, vec![]))),
+            [DiagnosticKind::MissingAnimationNode {
+                animation: "anim".to_string(),
+                node: "missing".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn inapplicable_channel() {
+        let mut root = template(vec![node("empty", NodeImplTemplate::Empty)]);
+        let mut animation = NodeAnimation::empty("empty");
+        animation.text_channel = Some(discrete("text".to_string(), []));
+        animation.visibility_channel = Some(discrete(NodeVisibility::Hidden, []));
+        animated(&mut root, animation);
+
+        let diagnostics = validate(&layout(root, vec![]));
+        assert_eq!(diagnostics[0].severity(), Severity::Warning);
+        assert_eq!(
+            kinds(diagnostics),
+            [DiagnosticKind::InapplicableChannel {
+                animation: "anim".to_string(),
+                node: "empty".to_string(),
+                channel: "text",
+            }]
+        );
+    }
+
+    #[test]
+    fn missing_sublayout_animation() {
+        let mut sub = template(vec![node("node", NodeImplTemplate::Empty)]);
+        animated(&mut sub, NodeAnimation::empty("node"));
+
+        let mut root = template(vec![sublayout("sub", "sub")]);
+        let mut animation = NodeAnimation::empty("sub");
+        let started = [(2, "anim".to_string()), (2, "missing".to_string())];
+        animation.sublayout_animation_channel = Some(discrete(String::new(), started));
+        animated(&mut root, animation);
+
+        assert_eq!(
+            kinds(validate(&layout(root, vec![("sub", sub)]))),
+            [DiagnosticKind::MissingSublayoutAnimation {
+                animation: "anim".to_string(),
+                node: "sub".to_string(),
+                sublayout_animation: "missing".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn keyframe_past_end() {
+        let mut root = template(vec![node("node", NodeImplTemplate::Empty)]);
+        let mut animation = NodeAnimation::empty("node");
+        animation.visibility_channel = Some(discrete(NodeVisibility::Visible, [(12, NodeVisibility::Hidden)]));
+        animated(&mut root, animation);
+
+        assert_eq!(
+            kinds(validate(&layout(root, vec![]))),
+            [DiagnosticKind::KeyframePastEnd {
+                animation: "anim".to_string(),
+                node: "node".to_string(),
+                channel: "visibility",
+                frame: 12,
+                total_duration: 10,
+            }]
+        );
+    }
+}
@@ -1,10 +1,20 @@
-use crate::{DrawUniform, EnvyMaybeSendSync, ImageScalingMode, template::TextAlignment};
+use crate::{DrawUniform, EnvyMaybeSendSync, ImageFillMethod, ImageFillOrigin, ImageScalingMode, template::{ShapeKind, TextAlignment}};
 
 pub struct TextureRequestArgs {
     pub scaling_x: ImageScalingMode,
     pub scaling_y: ImageScalingMode,
 }
 
+/// Arguments passed to an [`EnvyBackend`] implementor to clip a texture to a fraction of its area
+#[derive(Debug, Copy, Clone)]
+pub struct TextureFillArgs {
+    pub method: ImageFillMethod,
+    pub origin: ImageFillOrigin,
+
+    /// Fraction of the texture to draw, in the range `[0.0, 1.0]`
+    pub amount: f32,
+}
+
 /// Arguments passed to an [`EnvyBackend`] implementor to layout text
 pub struct TextLayoutArgs<'a, R: EnvyBackend> {
     /// Handle to the font to generate the layout for
@@ -45,6 +55,24 @@ pub struct PreparedGlyph<R: EnvyBackend> {
 
     /// Size of the glyph
     pub size: glam::Vec2,
+
+    /// Index of the first character (not byte) of the laid out text that this glyph represents
+    pub char_index: usize,
+}
+
+/// Arguments passed to an [`EnvyBackend`] implementor to tessellate a shape
+pub struct ShapeTessellationArgs<'a> {
+    /// Geometry of the shape
+    pub kind: &'a ShapeKind,
+
+    /// Size of the node the shape is being drawn in
+    pub size: glam::Vec2,
+
+    /// Whether or not the inside of the shape is going to be drawn
+    pub fill: bool,
+
+    /// Thickness of the stroke, a thickness of `0.0` means the shape does not have a stroke
+    pub stroke_thickness: f32,
 }
 
 pub struct DrawTextureArgs<B: EnvyBackend> {
@@ -65,6 +93,8 @@ pub struct DrawTextureArgs<B: EnvyBackend> {
 /// - Vertex buffers for textures are assumed to have all vertices in the range of `[-0.5, 0.5]` on both axes
 /// - Vertex buffers for font glyphs are assumed that all vertices are in the range of `[-w / 2.0, w / 2.0]` and
 ///   `[-h / 2.0, h / 2.0]`, where `w` and `h` are the width and height of the glyph
+/// - Vertex buffers for shapes follow the same convention as glyphs, where `w` and `h` are the size of the node.
+///   Strokes are centered on the outline of the shape, so they can extend past these bounds
 pub trait EnvyBackend: Sized + EnvyMaybeSendSync + 'static {
     /// Handle for accessing textures
     type TextureHandle: Copy + Clone + EnvyMaybeSendSync + 'static;
@@ -78,6 +108,9 @@ pub trait EnvyBackend: Sized + EnvyMaybeSendSync + 'static {
     /// Handle for referencing glyphs
     type GlyphHandle: Copy + Clone + EnvyMaybeSendSync + 'static;
 
+    /// Handle for referencing tessellated shapes
+    type ShapeHandle: Copy + Clone + EnvyMaybeSendSync + 'static;
+
     /// Type passed through to the backend for calls to draw functions
     type RenderPass<'a>;
 
@@ -108,6 +141,9 @@ pub trait EnvyBackend: Sized + EnvyMaybeSendSync + 'static {
     /// then the UI tree can release the same uniform as many times as it's given to the ui tree
     fn release_uniform(&mut self, handle: Self::UniformHandle);
 
+    /// Informs the backend that it can release the specified shape, as it is no longer being used.
+    fn release_shape(&mut self, handle: Self::ShapeHandle);
+
     /// Informs the backend that it should update the specified uniform.
     ///
     /// IMPLEMENTOR NOTE: This method is called in a separate stage of the UI Tree than the render step, so you can wait to
@@ -119,12 +155,24 @@ pub trait EnvyBackend: Sized + EnvyMaybeSendSync + 'static {
     /// This is only useful if the [`ImageScalingMode`] is set to something other than [`ImageScalingMode::Stretch`]
     fn update_texture_scaling(&mut self, handle: Self::TextureHandle, uv_offset: glam::Vec2, uv_scale: glam::Vec2, size: glam::Vec2);
 
+    /// Informs the backend of how much of the texture should be drawn
+    ///
+    /// IMPLEMENTOR NOTE: This is called every time the texture is prepared, after [`EnvyBackend::update_texture_scaling`].
+    /// The clipped texture coordinates should follow the scaling provided to that method.
+    fn update_texture_fill(&mut self, handle: Self::TextureHandle, args: TextureFillArgs);
+
     /// Requests the backend to render the provided text
     ///
     /// IMPLEMENTOR NOTE: The instance of [`EnvyBackend::GlyphHandle`] can be shared across many invocations of this method,
     /// but the [`EnvyBackend::UniformHandle`] should be unique for each glyph laid out this way.
     fn layout_text(&mut self, args: TextLayoutArgs<'_, Self>) -> Vec<PreparedGlyph<Self>>;
 
+    /// Requests the backend to tessellate the provided shape
+    ///
+    /// IMPLEMENTOR NOTE: Unlike glyphs, shape handles are not expected to be shared. The tree will request a new shape
+    /// every time the node's size or geometry changes, and will release the old one.
+    fn prepare_shape(&mut self, args: ShapeTessellationArgs<'_>) -> Option<Self::ShapeHandle>;
+
     /// Draws a texture with the provided handle and uniform to the screen
     fn draw_texture(
         &self,
@@ -150,6 +198,17 @@ pub trait EnvyBackend: Sized + EnvyMaybeSendSync + 'static {
         pass: &mut Self::RenderPass<'_>,
     );
 
+    /// Draws a shape with the provided handle to the screen
+    ///
+    /// The fill and stroke of the shape are drawn with their own uniforms, and only if their uniform is provided
+    fn draw_shape(
+        &self,
+        fill_uniform: Option<Self::UniformHandle>,
+        stroke_uniform: Option<Self::UniformHandle>,
+        handle: Self::ShapeHandle,
+        pass: &mut Self::RenderPass<'_>,
+    );
+
     /// Draws multiple glyphs to the screen at the same time
     ///
     /// The default implementation is ignorant of any optimizations that could be made by the backend
@@ -3,23 +3,36 @@ mod animations;
 pub mod asset;
 mod backend;
 mod node;
+mod state_machine;
 mod template;
+pub mod testing;
 mod tree;
 
 pub use animations::{
-    Animation, AnimationChannel, AnimationTransform, NodeAnimation, TransformStep,
+    Animation, AnimationBlendMode, AnimationChannel, AnimationTransform, BakedChannel, BakedNodeAnimation, ChannelMode, DiscreteChannel,
+    DiscreteKeyframe, Interpolatable, KeyTangents, MotionPath, NodeAnimation, RestPose, TangentMode, TransformStep,
+};
+#[cfg(feature = "asset")]
+pub use animations::AnimationValue;
+pub use backend::{
+    DrawTextureArgs, EnvyBackend, PreparedGlyph, ShapeTessellationArgs, TextLayoutArgs, TextureFillArgs,
+    TextureRequestArgs,
 };
-pub use backend::{DrawTextureArgs, EnvyBackend, PreparedGlyph, TextLayoutArgs, TextureRequestArgs};
 pub use node::{
-    EmptyNode, ImageNode, Node, NodeDisjointAccessor, NodeUpdateCallback, NodeItem, NodeTransform, SublayoutNode,
-    TextNode,
+    EmptyNode, ImageNode, Node, NodeDisjointAccessor, NodeUpdateCallback, NodeItem, NodeTransform, ShapeNode,
+    SublayoutNode, TextNode,
 };
+pub use state_machine::{AnimationState, ParamValue, StateMachine, StateTransition, TransitionCondition};
 pub use template::{
     ImageNodeTemplate, LayoutTemplate, MoveNodePosition, NodeImplTemplate, NodeTemplate,
-    ImageScalingMode, NodeVisibility, TextAlignment,
-    SublayoutNodeTemplate, TextNodeTemplate,
+    ImageScalingMode, ImageFillMethod, ImageFillOrigin, NodeVisibility, TextAlignment, ShapeKind, ShapeNodeTemplate,
+    SublayoutNodeTemplate, TextNodeTemplate, Gradient, GradientKind, GradientStop, MAX_GRADIENT_STOPS,
+    InheritanceError, NodePatch, TemplateInheritance,
+};
+pub use tree::{
+    AnimationPlayback, LayoutRoot, LayoutTree, LayoutTreeState, NodeState, PlayingAnimationState, StateMachineState,
+    SUBLAYOUT_PATH_SEPARATOR,
 };
-pub use tree::{LayoutRoot, LayoutTree};
 
 use bytemuck::{Pod, Zeroable};
 
@@ -35,13 +48,73 @@ impl<T> EnvyMaybeSendSync for T {}
 #[cfg(not(feature = "unsend"))]
 impl<T: Send + Sync> EnvyMaybeSendSync for T {}
 
+/// Hash of the bytes of an image or font, which is stable across platforms and versions of `envy` (64-bit FNV-1a)
+///
+/// Assets use it to find images and fonts that are stored more than once, and backends to share a single copy of
+/// identical images.
+pub fn content_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Gradient data packed into the tail of a [`DrawUniform`]
+///
+/// Stop colors are packed as little-endian RGBA8, so they can be unpacked with `unpack4x8unorm` in WGSL
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub struct GradientUniform {
+    /// Columns of the matrix that maps vertex positions into node space
+    pub uv_matrix: glam::Vec4,
+    /// Translation that maps vertex positions into node space
+    pub uv_translation: glam::Vec2,
+    /// `0` for no gradient, `1` for linear and `2` for radial
+    pub kind: u32,
+    pub stop_count: u32,
+    /// `[start, end]` for linear gradients, `[center, radius, _]` for radial gradients
+    pub points: glam::Vec4,
+    pub offsets: [f32; MAX_GRADIENT_STOPS],
+    pub colors: [u32; MAX_GRADIENT_STOPS],
+}
+
+impl GradientUniform {
+    pub const KIND_NONE: u32 = 0;
+    pub const KIND_LINEAR: u32 = 1;
+    pub const KIND_RADIAL: u32 = 2;
+
+    /// Packs the gradient, where `uv_transform` maps the vertices of the geometry being drawn into node space
+    pub fn new(gradient: &Gradient, uv_transform: glam::Affine2) -> Self {
+        let (kind, points) = match gradient.kind {
+            GradientKind::Linear { start, end } => (Self::KIND_LINEAR, glam::Vec4::new(start.x, start.y, end.x, end.y)),
+            GradientKind::Radial { center, radius } => (Self::KIND_RADIAL, glam::Vec4::new(center.x, center.y, radius, 0.0)),
+        };
+
+        let mut offsets = [0.0; MAX_GRADIENT_STOPS];
+        let mut colors = [0; MAX_GRADIENT_STOPS];
+        for (idx, stop) in gradient.stops.iter().take(MAX_GRADIENT_STOPS).enumerate() {
+            offsets[idx] = stop.offset;
+            colors[idx] = u32::from_le_bytes(stop.color);
+        }
+
+        Self {
+            uv_matrix: glam::Vec4::from_array(uv_transform.matrix2.to_cols_array()),
+            uv_translation: uv_transform.translation,
+            kind,
+            stop_count: gradient.stops.len().min(MAX_GRADIENT_STOPS) as u32,
+            points,
+            offsets,
+            colors,
+        }
+    }
+}
+
 #[repr(align(256), C)]
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct DrawUniform {
     pub model_matrix: glam::Mat4,
     pub color: glam::Vec4,
     pub model_i_matrix: glam::Mat4,
-    _alignment: [u8; 0x70],
+    pub gradient: GradientUniform,
 }
 
 impl DrawUniform {
@@ -52,7 +125,14 @@ impl DrawUniform {
             model_matrix: model,
             color,
             model_i_matrix: model.inverse(),
-            _alignment: [0u8; 0x70],
+            gradient: GradientUniform::zeroed(),
+        }
+    }
+
+    pub fn with_gradient(self, gradient: &Gradient, uv_transform: glam::Affine2) -> Self {
+        Self {
+            gradient: GradientUniform::new(gradient, uv_transform),
+            ..self
         }
     }
 }
@@ -65,7 +145,7 @@ unsafe impl Zeroable for DrawUniform {
             model_matrix: glam::Mat4::zeroed(),
             color: glam::Vec4::zeroed(),
             model_i_matrix: glam::Mat4::zeroed(),
-            _alignment: [0u8; 0x70],
+            gradient: GradientUniform::zeroed(),
         }
     }
 }
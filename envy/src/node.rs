@@ -1,5 +1,5 @@
 use crate::{
-    EnvyBackend, EnvyMaybeSendSync, LayoutRoot, LayoutTemplate, LayoutTree, template::{NodeImplTemplate, NodeTemplate, NodeVisibility}
+    DrawUniform, EnvyBackend, EnvyMaybeSendSync, LayoutRoot, LayoutTemplate, LayoutTree, template::{Gradient, NodeImplTemplate, NodeTemplate, NodeVisibility}
 };
 use glam::{Affine2, Mat4, Vec2, Vec4};
 use serde::{Deserialize, Serialize};
@@ -12,10 +12,12 @@ use std::{
 };
 
 mod image;
+mod shape;
 mod sublayout;
 mod text;
 
 pub use image::ImageNode;
+pub use shape::ShapeNode;
 pub use sublayout::SublayoutNode;
 pub use text::TextNode;
 
@@ -30,6 +32,24 @@ pub struct PreparationArgs<'a> {
     pub(crate) transform: &'a NodeTransform,
     pub(crate) affine: &'a Affine2,
     pub(crate) color: Vec4,
+    pub(crate) gradient: Option<&'a Gradient>,
+}
+
+impl PreparationArgs<'_> {
+    /// Creates the uniform for geometry that is placed relative to the center of the node by `local`,
+    /// applying the node's gradient if it has one
+    pub(crate) fn draw_uniform(&self, local: Affine2, color: Vec4) -> DrawUniform {
+        let uniform = DrawUniform::new(affine2_to_mat4(*self.affine * local), color);
+
+        match self.gradient {
+            Some(gradient) if self.transform.size.cmpgt(Vec2::ZERO).all() => {
+                let size = self.transform.size;
+                let to_node_space = Affine2::from_scale(size.recip()) * Affine2::from_translation(size / 2.0);
+                uniform.with_gradient(gradient, to_node_space * local)
+            }
+            _ => uniform,
+        }
+    }
 }
 
 /// Trait that defines the base operations for nodes
@@ -130,7 +150,7 @@ impl Anchor {
     }
 }
 /// A node's positional information
-#[derive(Debug, Copy, Clone, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
 pub struct NodeTransform {
     /// Clockwise rotation of the node in degrees
     ///
@@ -333,6 +353,15 @@ impl<'a, B: EnvyBackend> ObservedMut<'a, B> {
         unsafe { (*self.node).visibility = vis };
     }
 
+    pub fn gradient(&self) -> Option<&Gradient> {
+        self.deref().gradient.as_ref()
+    }
+
+    pub fn set_gradient(&mut self, gradient: Option<Gradient>) {
+        // SAFETY: We do not access `name`, so this is safe
+        unsafe { (*self.node).set_gradient(gradient) };
+    }
+
     pub fn mark_changed(&mut self) {
         // SAFETY: We do not access `name`, so this is safe
         unsafe {
@@ -532,8 +561,10 @@ pub struct NodeItem<B: EnvyBackend> {
     transform: NodeTransform,
     color: [u8; 4],
     visibility: NodeVisibility,
+    gradient: Option<Gradient>,
     computed_vis: NodeVisibility,
     affine: Affine2,
+    position_affine: Affine2,
     was_changed: bool,
     node: Box<dyn Node<B>>,
     update: Vec<Box<dyn NodeUpdateCallback<B>>>,
@@ -558,8 +589,10 @@ impl<B: EnvyBackend> NodeItem<B> {
             transform: template.transform,
             color: template.color,
             visibility: template.visibility,
+            gradient: template.gradient.clone(),
             computed_vis: template.visibility,
             affine: Affine2::IDENTITY,
+            position_affine: Affine2::IDENTITY,
             was_changed: true,
             node: match &template.implementation {
                 NodeImplTemplate::Empty => Box::new(EmptyNode),
@@ -570,6 +603,9 @@ impl<B: EnvyBackend> NodeItem<B> {
                     node.set_scaling_y(image.image_scaling_mode_y);
                     node.set_uv_offset(image.uv_offset);
                     node.set_uv_scale(image.uv_scale);
+                    node.set_fill_method(image.fill_method);
+                    node.set_fill_origin(image.fill_origin);
+                    node.set_fill_amount(image.fill_amount);
                     Box::new(node)
                 }
                 NodeImplTemplate::Text(text) => {
@@ -592,6 +628,13 @@ impl<B: EnvyBackend> NodeItem<B> {
                         templates,
                     ),
                 )),
+                NodeImplTemplate::Shape(shape) => {
+                    let mut node = ShapeNode::new(shape.kind.clone());
+                    node.set_fill_color(shape.fill_color);
+                    node.set_stroke_color(shape.stroke_color);
+                    node.set_stroke_thickness(shape.stroke_thickness);
+                    Box::new(node)
+                }
             },
             update: vec![],
         }
@@ -613,8 +656,10 @@ impl<B: EnvyBackend> NodeItem<B> {
             transform,
             color,
             visibility: NodeVisibility::Inherited,
+            gradient: None,
             computed_vis: NodeVisibility::Inherited,
             affine: Affine2::IDENTITY,
+            position_affine: Affine2::IDENTITY,
             was_changed: true,
             node,
             update: vec![],
@@ -646,6 +691,11 @@ impl<B: EnvyBackend> NodeItem<B> {
         &self.affine
     }
 
+    /// Transforms points in the coordinate space of [`NodeTransform::position`] into layout coordinates
+    pub fn position_affine(&self) -> &Affine2 {
+        &self.position_affine
+    }
+
     pub fn transform(&self) -> &NodeTransform {
         &self.transform
     }
@@ -663,6 +713,20 @@ impl<B: EnvyBackend> NodeItem<B> {
         &mut self.color
     }
 
+    pub fn gradient(&self) -> Option<&Gradient> {
+        self.gradient.as_ref()
+    }
+
+    pub fn gradient_mut(&mut self) -> Option<&mut Gradient> {
+        self.was_changed = true;
+        self.gradient.as_mut()
+    }
+
+    pub fn set_gradient(&mut self, gradient: Option<Gradient>) {
+        self.was_changed = true;
+        self.gradient = gradient;
+    }
+
     pub fn with_on_update(mut self, callback: impl NodeUpdateCallback<B>) -> Self {
         self.update.push(Box::new(callback));
         self
@@ -711,6 +775,11 @@ impl<B: EnvyBackend> NodeItem<B> {
         self.downcast::<SublayoutNode<B>>().unwrap()
     }
 
+    #[track_caller]
+    pub fn as_shape(&self) -> &ShapeNode<B> {
+        self.downcast::<ShapeNode<B>>().unwrap()
+    }
+
     #[track_caller]
     pub fn as_image_mut(&mut self) -> &mut ImageNode<B> {
         self.downcast_mut::<ImageNode<B>>().unwrap()
@@ -726,6 +795,11 @@ impl<B: EnvyBackend> NodeItem<B> {
         self.downcast_mut::<SublayoutNode<B>>().unwrap()
     }
 
+    #[track_caller]
+    pub fn as_shape_mut(&mut self) -> &mut ShapeNode<B> {
+        self.downcast_mut::<ShapeNode<B>>().unwrap()
+    }
+
     pub fn has_child(&self, name: impl AsRef<str>) -> bool {
         let name = name.as_ref();
         self.children.iter().any(|child| child.node.name.eq(name))
@@ -783,6 +857,32 @@ impl<B: EnvyBackend> NodeItem<B> {
         true
     }
 
+    // crate private so that user must go through layout to ensure all things are properly update
+    #[cfg(feature = "asset")]
+    pub(crate) fn children_mut(&mut self) -> &mut Vec<ObservedNode<B>> {
+        &mut self.children
+    }
+
+    /// Builds this node again from `template`, keeping its children and update callbacks
+    ///
+    /// The children of `template` are ignored, only the resources of this node are released and set up again.
+    #[cfg(feature = "asset")]
+    pub(crate) fn rebuild_from_template(
+        &mut self,
+        template: &NodeTemplate,
+        templates: &HashMap<String, LayoutTemplate>,
+        backend: &mut B,
+    ) {
+        self.node.release_resources(backend);
+
+        let mut node = Self::from_template_with_root_templates(template, templates);
+        node.children = std::mem::take(&mut self.children);
+        node.update = std::mem::take(&mut self.update);
+        *self = node;
+
+        self.node.setup_resources(backend);
+    }
+
     // crate private so that user must go through layout to ensure all things are properly update
     pub(crate) fn remove_child(&mut self, name: &str) -> Option<NodeItem<B>> {
         Self::remove_child_impl(&mut self.children, name)
@@ -881,6 +981,7 @@ impl<B: EnvyBackend> NodeItem<B> {
             let self_translation = parent_anchor_to_origin + self.transform.position;
             let center = self_translation + -self.transform.anchor.as_vec() * actual_size;
 
+            self.position_affine = *parent.affine * Affine2::from_translation(parent_anchor_to_origin);
             self.affine = *parent.affine
                 * Affine2::from_scale_angle_translation(
                     self.transform.scale,
@@ -917,6 +1018,7 @@ impl<B: EnvyBackend> NodeItem<B> {
                     transform: &self.transform,
                     affine: &self.affine,
                     color: Vec4::from_array(self.color.map(|c| c as f32 / 255.0)),
+                    gradient: self.gradient.as_ref(),
                 },
                 backend,
             );
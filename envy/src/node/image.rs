@@ -1,7 +1,7 @@
 use glam::Affine2;
 
 use crate::{
-    DrawTextureArgs, DrawUniform, EnvyBackend, ImageScalingMode, Node, backend::TextureRequestArgs, node::{PreparationArgs, affine2_to_mat4}
+    DrawTextureArgs, EnvyBackend, ImageFillMethod, ImageFillOrigin, ImageScalingMode, Node, TextureFillArgs, backend::TextureRequestArgs, node::PreparationArgs
 };
 
 pub struct ImageNode<B: EnvyBackend> {
@@ -10,10 +10,14 @@ pub struct ImageNode<B: EnvyBackend> {
     scaling_y: ImageScalingMode,
     uv_offset: glam::Vec2,
     uv_scale: glam::Vec2,
+    fill_method: ImageFillMethod,
+    fill_origin: ImageFillOrigin,
+    fill_amount: f32,
     mask_texture_name: Option<String>,
     uniform: Option<B::UniformHandle>,
     texture: Option<B::TextureHandle>,
     mask_texture: Option<B::TextureHandle>,
+    preloaded_textures: Vec<(String, Option<B::TextureHandle>)>,
 }
 
 impl<B: EnvyBackend> ImageNode<B> {
@@ -24,10 +28,14 @@ impl<B: EnvyBackend> ImageNode<B> {
             scaling_y: ImageScalingMode::default(),
             uv_offset: glam::Vec2::ZERO,
             uv_scale: glam::Vec2::ONE,
+            fill_method: ImageFillMethod::default(),
+            fill_origin: ImageFillOrigin::default(),
+            fill_amount: 1.0,
             mask_texture_name: None,
             uniform: None,
             texture: None,
             mask_texture: None,
+            preloaded_textures: vec![],
         }
     }
 
@@ -35,9 +43,44 @@ impl<B: EnvyBackend> ImageNode<B> {
         self.name.as_str()
     }
 
+    /// Changes the displayed texture
+    ///
+    /// If the texture was preloaded with [`ImageNode::preload_texture`], this swaps to the preloaded handle
+    /// instead of requesting a new one from the backend.
     pub fn set_resource_name(&mut self, name: impl Into<String>) {
-        self.name = name.into();
-        self.invalidate_image_handle();
+        let name = name.into();
+        if self.name == name {
+            return;
+        }
+
+        if let Some(preloaded) = self.preloaded_textures.iter_mut().find(|(preloaded, _)| *preloaded == name) {
+            std::mem::swap(&mut self.name, &mut preloaded.0);
+            std::mem::swap(&mut self.texture, &mut preloaded.1);
+        } else {
+            self.name = name;
+            self.invalidate_image_handle();
+        }
+    }
+
+    /// Marks a texture to be requested alongside this node's resources so that it can be swapped to
+    /// with [`ImageNode::set_resource_name`] without a backend lookup
+    pub fn preload_texture(&mut self, name: impl Into<String>) {
+        let name = name.into();
+        if self.name != name && !self.preloaded_textures.iter().any(|(preloaded, _)| *preloaded == name) {
+            self.preloaded_textures.push((name, None));
+        }
+    }
+
+    /// Requests the preloaded textures that do not have a handle yet, for textures preloaded after setup
+    pub(crate) fn request_preloaded_textures(&mut self, backend: &mut B) {
+        for (name, handle) in self.preloaded_textures.iter_mut() {
+            if handle.is_none() {
+                *handle = backend.request_texture_by_name(name, TextureRequestArgs {
+                    scaling_x: self.scaling_x,
+                    scaling_y: self.scaling_y
+                });
+            }
+        }
     }
 
     pub fn mask_texture_name(&self) -> Option<&str> {
@@ -81,6 +124,31 @@ impl<B: EnvyBackend> ImageNode<B> {
         self.uv_scale = scale;
     }
 
+    pub fn fill_method(&self) -> ImageFillMethod {
+        self.fill_method
+    }
+
+    pub fn set_fill_method(&mut self, method: ImageFillMethod) {
+        self.fill_method = method;
+    }
+
+    pub fn fill_origin(&self) -> ImageFillOrigin {
+        self.fill_origin
+    }
+
+    pub fn set_fill_origin(&mut self, origin: ImageFillOrigin) {
+        self.fill_origin = origin;
+    }
+
+    pub fn fill_amount(&self) -> f32 {
+        self.fill_amount
+    }
+
+    /// Sets the fraction of the image that is drawn, this is clamped to `[0.0, 1.0]`
+    pub fn set_fill_amount(&mut self, amount: f32) {
+        self.fill_amount = amount.clamp(0.0, 1.0);
+    }
+
     pub fn invalidate_image_handle(&mut self) {
         self.texture = None;
     }
@@ -120,6 +188,8 @@ impl<B: EnvyBackend> Node<B> for ImageNode<B> {
             });
         }
 
+        self.request_preloaded_textures(backend);
+
         if self.uniform.is_none() {
             log::warn!(
                 "ImageNode::setup_resources failed to acquire uniform buffer from backend (image '{}')",
@@ -152,6 +222,10 @@ impl<B: EnvyBackend> Node<B> for ImageNode<B> {
         if let Some(texture) = self.texture.take() {
             backend.release_texture(texture);
         }
+
+        for texture in self.preloaded_textures.iter_mut().filter_map(|(_, handle)| handle.take()) {
+            backend.release_texture(texture);
+        }
     }
 
     fn prepare(&mut self, args: PreparationArgs<'_>, backend: &mut B) {
@@ -182,9 +256,13 @@ impl<B: EnvyBackend> Node<B> for ImageNode<B> {
             return;
         };
 
-        let matrix = affine2_to_mat4(*args.affine * Affine2::from_scale(args.transform.size));
-        backend.update_uniform(uniform, DrawUniform::new(matrix, args.color));
+        backend.update_uniform(uniform, args.draw_uniform(Affine2::from_scale(args.transform.size), args.color));
         backend.update_texture_scaling(texture, self.uv_offset, self.uv_scale, args.transform.size);
+        backend.update_texture_fill(texture, TextureFillArgs {
+            method: self.fill_method,
+            origin: self.fill_origin,
+            amount: self.fill_amount,
+        });
     }
 
     fn render(&self, backend: &B, pass: &mut <B as EnvyBackend>::RenderPass<'_>) {
@@ -0,0 +1,177 @@
+use std::any::Any;
+
+use glam::{Affine2, Vec2, Vec4};
+
+use crate::{
+    DrawUniform, EnvyBackend, Node, ShapeTessellationArgs, node::{PreparationArgs, affine2_to_mat4}, template::ShapeKind
+};
+
+pub struct ShapeNode<B: EnvyBackend> {
+    kind: ShapeKind,
+    fill_color: Option<[u8; 4]>,
+    stroke_color: [u8; 4],
+    stroke_thickness: f32,
+    fill_uniform: Option<B::UniformHandle>,
+    stroke_uniform: Option<B::UniformHandle>,
+    shape: Option<B::ShapeHandle>,
+    tessellated_size: Vec2,
+    needs_compute: bool,
+}
+
+impl<B: EnvyBackend> ShapeNode<B> {
+    pub fn new(kind: ShapeKind) -> Self {
+        Self {
+            kind,
+            fill_color: Some([255; 4]),
+            stroke_color: [255; 4],
+            stroke_thickness: 0.0,
+            fill_uniform: None,
+            stroke_uniform: None,
+            shape: None,
+            tessellated_size: Vec2::ZERO,
+            needs_compute: true,
+        }
+    }
+
+    pub fn kind(&self) -> &ShapeKind {
+        &self.kind
+    }
+
+    pub fn set_kind(&mut self, kind: ShapeKind) {
+        self.kind = kind;
+        self.needs_compute = true;
+    }
+
+    pub fn fill_color(&self) -> Option<[u8; 4]> {
+        self.fill_color
+    }
+
+    pub fn set_fill_color(&mut self, color: Option<[u8; 4]>) {
+        if self.fill_color.is_some() != color.is_some() {
+            self.needs_compute = true;
+        }
+
+        self.fill_color = color;
+    }
+
+    pub fn stroke_color(&self) -> [u8; 4] {
+        self.stroke_color
+    }
+
+    pub fn set_stroke_color(&mut self, color: [u8; 4]) {
+        self.stroke_color = color;
+    }
+
+    pub fn stroke_thickness(&self) -> f32 {
+        self.stroke_thickness
+    }
+
+    pub fn set_stroke_thickness(&mut self, thickness: f32) {
+        if self.stroke_thickness != thickness {
+            self.stroke_thickness = thickness;
+            self.needs_compute = true;
+        }
+    }
+
+    fn is_filled(&self) -> bool {
+        self.fill_color.is_some() && !matches!(self.kind, ShapeKind::Polyline { .. })
+    }
+
+    fn is_stroked(&self) -> bool {
+        self.stroke_thickness > 0.0
+    }
+}
+
+impl<B: EnvyBackend> super::__sealed::Sealed for ShapeNode<B> {}
+
+impl<B: EnvyBackend> Node<B> for ShapeNode<B> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn setup_resources(&mut self, backend: &mut B) {
+        if self.fill_uniform.is_none() {
+            self.fill_uniform = backend.request_new_uniform();
+        }
+
+        if self.stroke_uniform.is_none() {
+            self.stroke_uniform = backend.request_new_uniform();
+        }
+
+        if self.fill_uniform.is_none() || self.stroke_uniform.is_none() {
+            log::warn!("ShapeNode::setup_resources failed to acquire uniform buffers from backend");
+        }
+    }
+
+    fn release_resources(&mut self, backend: &mut B) {
+        if let Some(uniform) = self.fill_uniform.take() {
+            backend.release_uniform(uniform);
+        }
+
+        if let Some(uniform) = self.stroke_uniform.take() {
+            backend.release_uniform(uniform);
+        }
+
+        if let Some(shape) = self.shape.take() {
+            backend.release_shape(shape);
+        }
+
+        self.needs_compute = true;
+    }
+
+    fn prepare(&mut self, args: PreparationArgs<'_>, backend: &mut B) {
+        if self.needs_compute || self.tessellated_size != args.transform.size {
+            if let Some(shape) = self.shape.take() {
+                backend.release_shape(shape);
+            }
+
+            if args.transform.size.cmple(Vec2::ZERO).any() {
+                log::info!("ShapeNode::prepare skipping tessellation since the node size is <= 0.0");
+                return;
+            }
+
+            self.shape = backend.prepare_shape(ShapeTessellationArgs {
+                kind: &self.kind,
+                size: args.transform.size,
+                fill: self.is_filled(),
+                stroke_thickness: self.stroke_thickness,
+            });
+
+            if self.shape.is_none() {
+                log::warn!("ShapeNode::prepare failed to tessellate shape");
+            }
+
+            self.tessellated_size = args.transform.size;
+            self.needs_compute = false;
+        }
+
+        let to_vec4 = |color: [u8; 4]| Vec4::from_array(color.map(|c| c as f32 / 255.0));
+
+        if let (Some(uniform), Some(fill_color)) = (self.fill_uniform, self.fill_color) {
+            backend.update_uniform(uniform, args.draw_uniform(Affine2::IDENTITY, args.color * to_vec4(fill_color)));
+        }
+
+        // Gradients only apply to the fill, the stroke is always drawn with a solid color
+        if let Some(uniform) = self.stroke_uniform {
+            let matrix = affine2_to_mat4(*args.affine);
+            backend.update_uniform(uniform, DrawUniform::new(matrix, args.color * to_vec4(self.stroke_color)));
+        }
+    }
+
+    fn render(&self, backend: &B, pass: &mut <B as EnvyBackend>::RenderPass<'_>) {
+        let Some(shape) = self.shape else {
+            return;
+        };
+
+        backend.draw_shape(
+            self.fill_uniform.filter(|_| self.is_filled()),
+            self.stroke_uniform.filter(|_| self.is_stroked()),
+            shape,
+            pass,
+        );
+    }
+}
@@ -55,7 +55,7 @@ impl<B: EnvyBackend> Node<B> for SublayoutNode<B> {
     }
 
     fn setup_resources(&mut self, backend: &mut B) {
-        self.tree.visit_roots_mut(|node| node.setup(backend));
+        self.tree.setup(backend);
     }
 
     fn release_resources(&mut self, backend: &mut B) {
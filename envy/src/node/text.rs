@@ -17,6 +17,7 @@ pub struct TextNode<B: EnvyBackend> {
     outline_thickness: f32,
     outline_color: [u8; 4],
     alignment: TextAlignment,
+    visible_characters: Option<usize>,
 }
 
 impl<B: EnvyBackend> TextNode<B> {
@@ -37,6 +38,7 @@ impl<B: EnvyBackend> TextNode<B> {
             outline_thickness: 0.0,
             outline_color: [255; 4],
             alignment: TextAlignment::default(),
+            visible_characters: None,
         }
     }
 
@@ -76,8 +78,11 @@ impl<B: EnvyBackend> TextNode<B> {
     }
 
     pub fn set_text(&mut self, text: impl Into<String>) {
-        self.text = text.into();
-        self.needs_compute = true;
+        let text = text.into();
+        if self.text != text {
+            self.text = text;
+            self.needs_compute = true;
+        }
     }
 
     pub fn outline_thickness(&self) -> f32 {
@@ -85,8 +90,10 @@ impl<B: EnvyBackend> TextNode<B> {
     }
 
     pub fn set_outline_thickness(&mut self, thickness: f32) {
-        self.outline_thickness = thickness;
-        self.needs_compute = true;
+        if self.outline_thickness != thickness {
+            self.outline_thickness = thickness;
+            self.needs_compute = true;
+        }
     }
 
     pub fn outline_color(&self) -> [u8; 4] {
@@ -106,6 +113,17 @@ impl<B: EnvyBackend> TextNode<B> {
         self.needs_compute = true;
     }
 
+    pub fn visible_characters(&self) -> Option<usize> {
+        self.visible_characters
+    }
+
+    /// Limits rendering to the first `count` characters of the text, `None` renders all of them
+    ///
+    /// This does not require the text to be laid out again, so it is cheap to change every frame.
+    pub fn set_visible_characters(&mut self, count: impl Into<Option<usize>>) {
+        self.visible_characters = count.into();
+    }
+
     pub fn invalidate_font_handle(&mut self) {
         self.font = None;
         self.needs_compute = true;
@@ -190,19 +208,22 @@ impl<B: EnvyBackend> Node<B> for TextNode<B> {
         for glyph in self.glyphs.iter() {
             let center = (-args.transform.size / 2.0) + glyph.offset_in_buffer + glyph.size / 2.0;
 
-            let matrix = affine2_to_mat4(*args.affine * Affine2::from_translation(center));
-            backend.update_uniform(glyph.uniform_handle, DrawUniform::new(matrix, args.color));
+            let local = Affine2::from_translation(center);
+            backend.update_uniform(glyph.uniform_handle, args.draw_uniform(local, args.color));
 
             if let Some(handle) = glyph.outline_uniform_handle {
+                let matrix = affine2_to_mat4(*args.affine * local);
                 backend.update_uniform(handle, DrawUniform::new(matrix, glam::Vec4::from_array(self.outline_color.map(|c| c as f32 / 255.0))));
             }
         }
     }
 
     fn render(&self, backend: &B, pass: &mut <B as EnvyBackend>::RenderPass<'_>) {
+        let visible_characters = self.visible_characters.unwrap_or(usize::MAX);
         backend.draw_glyphs(
             self.glyphs
                 .iter()
+                .filter(|glyph| glyph.char_index < visible_characters)
                 .map(|glyph| (glyph.uniform_handle, glyph.outline_uniform_handle, glyph.glyph_handle)),
             pass,
         );
@@ -0,0 +1,377 @@
+use std::collections::{HashMap, HashSet};
+
+/// The value of a [`StateMachine`] parameter
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ParamValue {
+    Bool(bool),
+    Float(f32),
+}
+
+impl ParamValue {
+    pub fn as_bool(&self) -> bool {
+        match self {
+            Self::Bool(value) => *value,
+            Self::Float(value) => *value != 0.0,
+        }
+    }
+
+    pub fn as_float(&self) -> f32 {
+        match self {
+            Self::Bool(value) => *value as u8 as f32,
+            Self::Float(value) => *value,
+        }
+    }
+
+    /// Whether two values are equal, comparing as booleans if either of them is one and as floats otherwise
+    ///
+    /// A condition written with a different kind of value than its parameter still behaves as expected, so
+    /// `Bool(true)` equals `Float(1.0)` but also any other non-zero float.
+    pub fn matches(&self, other: &ParamValue) -> bool {
+        match (self, other) {
+            (Self::Float(a), Self::Float(b)) => a == b,
+            _ => self.as_bool() == other.as_bool(),
+        }
+    }
+}
+
+impl From<bool> for ParamValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<f32> for ParamValue {
+    fn from(value: f32) -> Self {
+        Self::Float(value)
+    }
+}
+
+/// A condition that has to pass for a [`StateTransition`] to be taken
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransitionCondition {
+    /// Passes when the parameter is equal to `value`, see [`ParamValue::matches`]
+    Equals { param: String, value: ParamValue },
+
+    /// Passes when the parameter is greater than `value`
+    Greater { param: String, value: f32 },
+
+    /// Passes when the parameter is less than `value`
+    Less { param: String, value: f32 },
+
+    /// Passes when the trigger was fired since the last update
+    Trigger(String),
+
+    /// Passes once the animation of the current state has stopped playing
+    AnimationFinished,
+}
+
+/// A state of a [`StateMachine`], which plays an animation while the machine is in it
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationState {
+    pub name: String,
+
+    /// States without an animation leave the nodes at their last animated values
+    pub animation: Option<String>,
+    pub looping: bool,
+}
+
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateTransition {
+    /// The state this transition leaves, or `None` to leave any state other than `to`
+    pub from: Option<String>,
+    pub to: String,
+
+    /// Every condition has to pass for the transition to be taken
+    pub conditions: Vec<TransitionCondition>,
+
+    /// How many frames the animation of `from` is crossfaded into the animation of `to`
+    pub crossfade_frames: usize,
+}
+
+/// Selects which animation a [`crate::LayoutTree`] plays from parameters and triggers
+///
+/// Transitions are checked in order, and at most one transition is taken per update.
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateMachine {
+    pub entry_state: String,
+
+    /// The animation layer that the animations of every state are played on
+    pub layer: u32,
+    pub parameters: Vec<(String, ParamValue)>,
+    pub states: Vec<AnimationState>,
+    pub transitions: Vec<StateTransition>,
+}
+
+impl StateMachine {
+    pub fn new(entry_state: impl Into<String>) -> Self {
+        Self {
+            entry_state: entry_state.into(),
+            layer: 0,
+            parameters: vec![],
+            states: vec![],
+            transitions: vec![],
+        }
+    }
+
+    pub fn with_parameter(mut self, name: impl Into<String>, value: impl Into<ParamValue>) -> Self {
+        self.parameters.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn with_state(mut self, name: impl Into<String>, animation: Option<String>, looping: bool) -> Self {
+        self.states.push(AnimationState {
+            name: name.into(),
+            animation,
+            looping,
+        });
+        self
+    }
+
+    pub fn with_transition(mut self, transition: StateTransition) -> Self {
+        self.transitions.push(transition);
+        self
+    }
+
+    pub fn state(&self, name: &str) -> Option<&AnimationState> {
+        self.states.iter().find(|state| state.name == name)
+    }
+}
+
+/// An animation change requested by a [`StateMachinePlayer`]
+pub(crate) struct StateChange {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub looping: bool,
+    pub crossfade_frames: usize,
+}
+
+/// The runtime state of a [`StateMachine`] in a [`crate::LayoutTree`]
+pub(crate) struct StateMachinePlayer {
+    machine: StateMachine,
+    current_state: Option<String>,
+    params: HashMap<String, ParamValue>,
+    triggers: HashSet<String>,
+}
+
+impl StateMachinePlayer {
+    pub fn new(machine: StateMachine) -> Self {
+        Self {
+            params: machine.parameters.iter().cloned().collect(),
+            machine,
+            current_state: None,
+            triggers: HashSet::new(),
+        }
+    }
+
+    pub fn layer(&self) -> u32 {
+        self.machine.layer
+    }
+
+    pub fn current_state(&self) -> Option<&str> {
+        self.current_state.as_deref()
+    }
+
+    pub fn param(&self, name: &str) -> Option<ParamValue> {
+        self.params.get(name).copied()
+    }
+
+    pub fn set_param(&mut self, name: &str, value: ParamValue) {
+        self.params.insert(name.to_string(), value);
+    }
+
+    pub fn params(&self) -> impl Iterator<Item = (&str, ParamValue)> {
+        self.params.iter().map(|(name, value)| (name.as_str(), *value))
+    }
+
+    /// Enters a state without playing its animation, returning `false` if the machine has no such state
+    ///
+    /// Restoring no state makes the next update enter the entry state again.
+    pub fn restore_state(&mut self, name: Option<&str>) -> bool {
+        if name.is_some_and(|name| self.machine.state(name).is_none()) {
+            return false;
+        }
+
+        self.current_state = name.map(str::to_string);
+        self.triggers.clear();
+        true
+    }
+
+    pub fn fire_trigger(&mut self, name: &str) {
+        self.triggers.insert(name.to_string());
+    }
+
+    fn condition_passes(&self, condition: &TransitionCondition, is_animation_finished: &impl Fn(&str) -> bool) -> bool {
+        match condition {
+            TransitionCondition::Equals { param, value } => {
+                self.param(param).is_some_and(|param| param.matches(value))
+            }
+            TransitionCondition::Greater { param, value } => {
+                self.param(param).is_some_and(|param| param.as_float() > *value)
+            }
+            TransitionCondition::Less { param, value } => {
+                self.param(param).is_some_and(|param| param.as_float() < *value)
+            }
+            TransitionCondition::Trigger(trigger) => self.triggers.contains(trigger),
+            TransitionCondition::AnimationFinished => self
+                .current_state
+                .as_deref()
+                .and_then(|state| self.machine.state(state))
+                .and_then(|state| state.animation.as_deref())
+                .is_none_or(is_animation_finished),
+        }
+    }
+
+    /// Enters the entry state or takes the first passing transition
+    ///
+    /// Triggers that were not consumed by a transition are discarded.
+    pub fn update(&mut self, is_animation_finished: impl Fn(&str) -> bool) -> Option<StateChange> {
+        let Some(current) = self.current_state.clone() else {
+            self.triggers.clear();
+            let entry = self.machine.state(&self.machine.entry_state)?;
+            self.current_state = Some(entry.name.clone());
+            return Some(StateChange {
+                from: None,
+                to: entry.animation.clone(),
+                looping: entry.looping,
+                crossfade_frames: 0,
+            });
+        };
+
+        let transition = self.machine.transitions.iter().find(|transition| {
+            let leaves_current = match transition.from.as_deref() {
+                Some(from) => from == current,
+                None => transition.to != current,
+            };
+
+            leaves_current
+                && transition
+                    .conditions
+                    .iter()
+                    .all(|condition| self.condition_passes(condition, &is_animation_finished))
+        });
+
+        let change = transition.and_then(|transition| {
+            let to = self.machine.state(&transition.to)?;
+            Some((
+                to.name.clone(),
+                StateChange {
+                    from: self.machine.state(&current).and_then(|state| state.animation.clone()),
+                    to: to.animation.clone(),
+                    looping: to.looping,
+                    crossfade_frames: transition.crossfade_frames,
+                },
+            ))
+        });
+
+        self.triggers.clear();
+        let (state, change) = change?;
+        self.current_state = Some(state);
+        Some(change)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transition(from: Option<&str>, to: &str, conditions: Vec<TransitionCondition>) -> StateTransition {
+        StateTransition {
+            from: from.map(str::to_string),
+            to: to.to_string(),
+            conditions,
+            crossfade_frames: 4,
+        }
+    }
+
+    fn player(transitions: Vec<StateTransition>) -> StateMachinePlayer {
+        let mut machine = StateMachine::new("idle")
+            .with_parameter("open", false)
+            .with_parameter("speed", 0.0f32)
+            .with_state("idle", Some("idle_anim".to_string()), true)
+            .with_state("open", Some("open_anim".to_string()), false)
+            .with_state("closed", None, false);
+        machine.transitions = transitions;
+
+        let mut player = StateMachinePlayer::new(machine);
+        let entry = player.update(|_| false).unwrap();
+        assert_eq!((entry.from, entry.to.as_deref()), (None, Some("idle_anim")));
+        assert_eq!(player.current_state(), Some("idle"));
+        player
+    }
+
+    #[test]
+    fn equals_compares_across_value_kinds() {
+        let equals = TransitionCondition::Equals {
+            param: "open".to_string(),
+            value: ParamValue::Float(1.0),
+        };
+        let mut player = player(vec![transition(Some("idle"), "open", vec![equals])]);
+
+        assert!(player.update(|_| false).is_none());
+        player.set_param("open", ParamValue::Bool(true));
+
+        let change = player.update(|_| false).unwrap();
+        assert_eq!(change.from.as_deref(), Some("idle_anim"));
+        assert_eq!(change.to.as_deref(), Some("open_anim"));
+        assert_eq!(change.crossfade_frames, 4);
+        assert_eq!(player.current_state(), Some("open"));
+
+        assert!(ParamValue::Bool(true).matches(&ParamValue::Float(2.0)));
+        assert!(!ParamValue::Float(1.0).matches(&ParamValue::Float(2.0)));
+    }
+
+    #[test]
+    fn triggers_only_last_until_the_next_update() {
+        let trigger = TransitionCondition::Trigger("press".to_string());
+        let faster = TransitionCondition::Greater {
+            param: "speed".to_string(),
+            value: 1.0,
+        };
+        let mut player = player(vec![transition(Some("idle"), "open", vec![trigger, faster])]);
+
+        player.fire_trigger("press");
+        assert!(player.update(|_| false).is_none());
+
+        player.set_param("speed", ParamValue::Float(2.0));
+        assert!(player.update(|_| false).is_none());
+
+        player.fire_trigger("press");
+        assert!(player.update(|_| false).is_some());
+        assert_eq!(player.current_state(), Some("open"));
+    }
+
+    #[test]
+    fn transitions_from_any_state_skip_their_target() {
+        let finished = vec![TransitionCondition::AnimationFinished];
+        let mut player = player(vec![
+            transition(None, "closed", finished.clone()),
+            transition(Some("closed"), "idle", vec![]),
+        ]);
+
+        assert!(player.update(|_| false).is_none());
+
+        let change = player.update(|animation| animation == "idle_anim").unwrap();
+        assert_eq!(change.to, None);
+        assert_eq!(player.current_state(), Some("closed"));
+
+        // The any-state transition can't be taken from `closed` itself, so the next one in order is
+        player.update(|_| true).unwrap();
+        assert_eq!(player.current_state(), Some("idle"));
+    }
+
+    #[test]
+    fn restoring_a_missing_state_fails() {
+        let mut player = player(vec![]);
+        assert!(!player.restore_state(Some("missing")));
+        assert_eq!(player.current_state(), Some("idle"));
+
+        assert!(player.restore_state(None));
+        assert!(player.update(|_| false).is_some());
+        assert_eq!(player.current_state(), Some("idle"));
+    }
+}
@@ -1,16 +1,47 @@
 use camino::Utf8Path;
 
-use crate::{Animation, NodeTransform};
+use crate::{Animation, NodeTransform, StateMachine};
 
-#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode))]
-#[derive(Copy, Clone, Default, Debug)]
+mod inheritance;
+
+pub use inheritance::{InheritanceError, NodePatch, TemplateInheritance};
+
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
 pub enum ImageScalingMode {
     #[default]
     Stretch,
     Tiling
 }
 
-#[derive(Clone)]
+/// How an image is revealed when its fill amount is less than `1.0`
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub enum ImageFillMethod {
+    #[default]
+    Horizontal,
+    Vertical,
+    RadialClockwise,
+    RadialCounterClockwise,
+}
+
+/// Where an image's fill starts from
+///
+/// Horizontal fills treat [`ImageFillOrigin::Top`] as [`ImageFillOrigin::Left`] and [`ImageFillOrigin::Bottom`] as
+/// [`ImageFillOrigin::Right`], vertical fills do the opposite. Radial fills sweep around the center of the image
+/// starting from the middle of the origin edge.
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub enum ImageFillOrigin {
+    #[default]
+    Left,
+    Top,
+    Right,
+    Bottom,
+}
+
+#[cfg_attr(feature = "asset", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq)]
 pub struct ImageNodeTemplate {
     pub texture_name: String,
     pub mask_texture_name: Option<String>,
@@ -18,6 +49,10 @@ pub struct ImageNodeTemplate {
     pub image_scaling_mode_y: ImageScalingMode,
     pub uv_offset: glam::Vec2,
     pub uv_scale: glam::Vec2,
+    pub fill_method: ImageFillMethod,
+    pub fill_origin: ImageFillOrigin,
+    /// Fraction of the image that is drawn, in the range `[0.0, 1.0]`
+    pub fill_amount: f32,
 }
 
 #[cfg(feature = "asset")]
@@ -31,7 +66,10 @@ const _: () = {
             self.image_scaling_mode_x.encode(encoder)?;
             self.image_scaling_mode_y.encode(encoder)?;
             self.uv_offset.to_array().encode(encoder)?;
-            self.uv_scale.to_array().encode(encoder)
+            self.uv_scale.to_array().encode(encoder)?;
+            self.fill_method.encode(encoder)?;
+            self.fill_origin.encode(encoder)?;
+            self.fill_amount.encode(encoder)
         }
     }
 
@@ -52,13 +90,16 @@ const _: () = {
                 image_scaling_mode_y: Decode::decode(decoder)?,
                 uv_offset: <[f32; 2]>::decode(decoder)?.into(),
                 uv_scale: <[f32; 2]>::decode(decoder)?.into(),
+                fill_method: Decode::decode(decoder)?,
+                fill_origin: Decode::decode(decoder)?,
+                fill_amount: Decode::decode(decoder)?,
             })
         }
     }
 };
 
-#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode))]
-#[derive(Debug, Copy, Clone, Default)]
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
 pub enum TextAlignment {
     Left,
     #[default]
@@ -69,8 +110,8 @@ pub enum TextAlignment {
 }
 
 
-#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode))]
-#[derive(Clone)]
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq)]
 pub struct TextNodeTemplate {
     pub font_name: String,
     pub text: String,
@@ -81,23 +122,234 @@ pub struct TextNodeTemplate {
     pub alignment: TextAlignment
 }
 
-#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode))]
-#[derive(Clone)]
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq, Eq)]
 pub struct SublayoutNodeTemplate {
     pub sublayout_name: String,
 }
 
-#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode))]
-#[derive(Clone)]
+/// Geometry of a [`ShapeNodeTemplate`]
+///
+/// All shapes are sized relative to the bounding box of the node. Points for [`ShapeKind::Polygon`]
+/// and [`ShapeKind::Polyline`] are in node space, where `[0.0, 0.0]` is the top left of the node and
+/// `[1.0, 1.0]` is the bottom right.
+#[cfg_attr(feature = "asset", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShapeKind {
+    /// Rectangle covering the node, with the radius (in pixels) of the top left, top right, bottom right
+    /// and bottom left corners
+    Rectangle { corner_radii: [f32; 4] },
+
+    /// Ellipse inscribed in the node
+    Ellipse,
+
+    /// Closed polygon
+    Polygon { points: Vec<glam::Vec2> },
+
+    /// Open line through the points, this shape is only ever stroked
+    Polyline { points: Vec<glam::Vec2> },
+}
+
+impl Default for ShapeKind {
+    fn default() -> Self {
+        Self::Rectangle {
+            corner_radii: [0.0; 4],
+        }
+    }
+}
+
+#[cfg(feature = "asset")]
+const _: () = {
+    use bincode::{BorrowDecode, Decode, Encode};
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    enum ShapeKindRepr {
+        Rectangle { corner_radii: [f32; 4] },
+        Ellipse,
+        Polygon { points: Vec<[f32; 2]> },
+        Polyline { points: Vec<[f32; 2]> },
+    }
+
+    impl Encode for ShapeKind {
+        fn encode<E: bincode::enc::Encoder>(&self, encoder: &mut E) -> Result<(), bincode::error::EncodeError> {
+            let repr = match self {
+                Self::Rectangle { corner_radii } => ShapeKindRepr::Rectangle {
+                    corner_radii: *corner_radii,
+                },
+                Self::Ellipse => ShapeKindRepr::Ellipse,
+                Self::Polygon { points } => ShapeKindRepr::Polygon {
+                    points: points.iter().map(|point| point.to_array()).collect(),
+                },
+                Self::Polyline { points } => ShapeKindRepr::Polyline {
+                    points: points.iter().map(|point| point.to_array()).collect(),
+                },
+            };
+
+            repr.encode(encoder)
+        }
+    }
+
+    impl<'de, C> BorrowDecode<'de, C> for ShapeKind {
+        fn borrow_decode<D: bincode::de::BorrowDecoder<'de, Context = C>>(
+            decoder: &mut D,
+        ) -> Result<Self, bincode::error::DecodeError> {
+            Decode::decode(decoder)
+        }
+    }
+
+    impl<C> Decode<C> for ShapeKind {
+        fn decode<D: bincode::de::Decoder<Context = C>>(decoder: &mut D) -> Result<Self, bincode::error::DecodeError> {
+            Ok(match ShapeKindRepr::decode(decoder)? {
+                ShapeKindRepr::Rectangle { corner_radii } => Self::Rectangle { corner_radii },
+                ShapeKindRepr::Ellipse => Self::Ellipse,
+                ShapeKindRepr::Polygon { points } => Self::Polygon {
+                    points: points.into_iter().map(glam::Vec2::from_array).collect(),
+                },
+                ShapeKindRepr::Polyline { points } => Self::Polyline {
+                    points: points.into_iter().map(glam::Vec2::from_array).collect(),
+                },
+            })
+        }
+    }
+};
+
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq)]
+pub struct ShapeNodeTemplate {
+    pub kind: ShapeKind,
+    pub fill_color: Option<[u8; 4]>,
+    pub stroke_color: [u8; 4],
+    pub stroke_thickness: f32,
+}
+
+/// Maximum number of stops in a [`Gradient`], any stops past this are ignored when rendering
+pub const MAX_GRADIENT_STOPS: usize = 8;
+
+/// Geometry of a [`Gradient`]
+///
+/// Like the points of a [`ShapeKind::Polygon`], all positions are in node space, where `[0.0, 0.0]` is
+/// the top left of the node and `[1.0, 1.0]` is the bottom right.
+#[cfg_attr(feature = "asset", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GradientKind {
+    /// Gradient along the line from `start` to `end`
+    Linear { start: glam::Vec2, end: glam::Vec2 },
+
+    /// Gradient radiating out from `center`, the gradient is stretched along with the node so it is only circular
+    /// for square nodes
+    Radial { center: glam::Vec2, radius: f32 },
+}
+
+impl Default for GradientKind {
+    fn default() -> Self {
+        Self::Linear {
+            start: glam::Vec2::new(0.5, 0.0),
+            end: glam::Vec2::new(0.5, 1.0),
+        }
+    }
+}
+
+#[cfg(feature = "asset")]
+const _: () = {
+    use bincode::{BorrowDecode, Decode, Encode};
+
+    #[derive(bincode::Encode, bincode::Decode)]
+    enum GradientKindRepr {
+        Linear { start: [f32; 2], end: [f32; 2] },
+        Radial { center: [f32; 2], radius: f32 },
+    }
+
+    impl Encode for GradientKind {
+        fn encode<E: bincode::enc::Encoder>(&self, encoder: &mut E) -> Result<(), bincode::error::EncodeError> {
+            let repr = match *self {
+                Self::Linear { start, end } => GradientKindRepr::Linear {
+                    start: start.to_array(),
+                    end: end.to_array(),
+                },
+                Self::Radial { center, radius } => GradientKindRepr::Radial {
+                    center: center.to_array(),
+                    radius,
+                },
+            };
+
+            repr.encode(encoder)
+        }
+    }
+
+    impl<'de, C> BorrowDecode<'de, C> for GradientKind {
+        fn borrow_decode<D: bincode::de::BorrowDecoder<'de, Context = C>>(
+            decoder: &mut D,
+        ) -> Result<Self, bincode::error::DecodeError> {
+            Decode::decode(decoder)
+        }
+    }
+
+    impl<C> Decode<C> for GradientKind {
+        fn decode<D: bincode::de::Decoder<Context = C>>(decoder: &mut D) -> Result<Self, bincode::error::DecodeError> {
+            Ok(match GradientKindRepr::decode(decoder)? {
+                GradientKindRepr::Linear { start, end } => Self::Linear {
+                    start: start.into(),
+                    end: end.into(),
+                },
+                GradientKindRepr::Radial { center, radius } => Self::Radial {
+                    center: center.into(),
+                    radius,
+                },
+            })
+        }
+    }
+};
+
+/// A single color stop in a [`Gradient`]
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GradientStop {
+    /// Position of the stop along the gradient, in the range `[0.0, 1.0]`
+    pub offset: f32,
+    pub color: [u8; 4],
+}
+
+/// Gradient that is multiplied over everything a node draws
+///
+/// Stops are expected to be sorted by their offset. To use a gradient as a standalone fill, put it on
+/// a white [`ShapeNodeTemplate`].
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    pub kind: GradientKind,
+    pub stops: Vec<GradientStop>,
+}
+
+impl Default for Gradient {
+    fn default() -> Self {
+        Self {
+            kind: GradientKind::default(),
+            stops: vec![
+                GradientStop {
+                    offset: 0.0,
+                    color: [255; 4],
+                },
+                GradientStop {
+                    offset: 1.0,
+                    color: [0, 0, 0, 255],
+                },
+            ],
+        }
+    }
+}
+
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq)]
 pub enum NodeImplTemplate {
     Empty,
     Image(ImageNodeTemplate),
     Text(TextNodeTemplate),
     Sublayout(SublayoutNodeTemplate),
+    Shape(ShapeNodeTemplate),
 }
 
-#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode))]
-#[derive(Debug, Copy, Clone, Default)]
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
 pub enum NodeVisibility {
     Hidden,
     #[default]
@@ -105,13 +357,14 @@ pub enum NodeVisibility {
     Visible,
 }
 
-#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode))]
-#[derive(Clone)]
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Clone, PartialEq)]
 pub struct NodeTemplate {
     pub name: String,
     pub transform: NodeTransform,
     pub color: [u8; 4],
     pub visibility: NodeVisibility,
+    pub gradient: Option<Gradient>,
     pub children: Vec<NodeTemplate>,
     pub implementation: NodeImplTemplate,
 }
@@ -408,12 +661,16 @@ pub enum MoveNodePosition<'a> {
     Last,
 }
 
-#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode))]
-#[derive(Default, Clone)]
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Default, Clone, PartialEq)]
 pub struct LayoutTemplate {
     pub canvas_size: [u32; 2],
     pub root_nodes: Vec<NodeTemplate>,
     pub animations: Vec<(String, Animation)>,
+    pub state_machine: Option<StateMachine>,
+    /// Makes this a derived template, see [`TemplateInheritance`]
+    #[cfg_attr(feature = "asset", serde(default))]
+    pub inheritance: Option<TemplateInheritance>,
 }
 
 impl LayoutTemplate {
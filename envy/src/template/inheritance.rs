@@ -0,0 +1,402 @@
+//! Templates that are stored as changes to another template
+//!
+//! A derived template sets [`LayoutTemplate::inheritance`] to the name of its base template and only stores what it
+//! does differently. It is resolved into a complete template with [`LayoutTemplate::resolve`], which starts from the
+//! first template in the chain that does not derive from another one and applies the changes of every template after
+//! it, in this order:
+//! 1. [`TemplateInheritance::removed_nodes`] are removed, along with their children and the node animations that
+//!    target them
+//! 2. [`TemplateInheritance::patches`] are applied
+//! 3. [`TemplateInheritance::added_nodes`] are added as the last children of their parents, then the derived
+//!    template's own [`LayoutTemplate::root_nodes`] are added after the base's root nodes
+//! 4. the derived template's own [`LayoutTemplate::animations`] replace the base's animations with the same name and
+//!    are added otherwise
+//! 5. the derived template's state machine replaces the base's if it has one, and its canvas size is always used
+//!
+//! [`LayoutRoot`](crate::LayoutRoot) resolves templates whenever it builds a tree from them, so changes to a base
+//! template show up in every template that derives from it the next time they are synced. Changes that refer to
+//! nodes that are no longer in the base template are skipped with a warning.
+
+use std::{borrow::Cow, collections::HashMap, fmt};
+
+use camino::Utf8Path;
+
+use crate::{
+    tree::is_same_or_inside, Gradient, LayoutTemplate, NodeImplTemplate, NodeTemplate, NodeTransform, NodeVisibility,
+};
+
+/// Lets a patch tell apart a gradient that is removed (`null`) from one that is kept (a missing field)
+#[cfg(feature = "asset")]
+fn deserialize_some<'de, T: serde::Deserialize<'de>, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error> {
+    T::deserialize(deserializer).map(Some)
+}
+
+/// Changes to the properties of a node of a base template, every field that is `None` keeps the base's value
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Default, PartialEq)]
+pub struct NodePatch {
+    /// Path of the node in the resolved base template
+    pub path: String,
+    pub transform: Option<NodeTransform>,
+    pub color: Option<[u8; 4]>,
+    pub visibility: Option<NodeVisibility>,
+    /// `Some(None)` removes the base's gradient
+    #[cfg_attr(
+        feature = "asset",
+        serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")
+    )]
+    pub gradient: Option<Option<Gradient>>,
+    /// Replaces the node's image, text, sublayout or shape, which has to match the kind of the base's node
+    pub implementation: Option<NodeImplTemplate>,
+}
+
+impl NodePatch {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            ..Default::default()
+        }
+    }
+
+    fn apply(&self, node: &mut NodeTemplate) {
+        if let Some(transform) = self.transform {
+            node.transform = transform;
+        }
+
+        if let Some(color) = self.color {
+            node.color = color;
+        }
+
+        if let Some(visibility) = self.visibility {
+            node.visibility = visibility;
+        }
+
+        if let Some(gradient) = self.gradient.as_ref() {
+            node.gradient = gradient.clone();
+        }
+
+        if let Some(implementation) = self.implementation.as_ref() {
+            if std::mem::discriminant(implementation) == std::mem::discriminant(&node.implementation) {
+                node.implementation = implementation.clone();
+            } else {
+                log::warn!(
+                    "Skipping patch of node '{}', which is a different kind of node in the base template",
+                    self.path
+                );
+            }
+        }
+    }
+}
+
+/// The base template of a derived template and the changes the derived template makes to it
+#[cfg_attr(feature = "asset", derive(bincode::Encode, bincode::Decode, serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Default, PartialEq)]
+pub struct TemplateInheritance {
+    /// Name of the base template, which can be a derived template itself
+    pub base: String,
+    /// Paths of nodes of the base template that are left out, along with their children
+    pub removed_nodes: Vec<String>,
+    pub patches: Vec<NodePatch>,
+    /// Nodes that are added to nodes of the base template, along with the path of their parent. New root nodes are
+    /// the derived template's [`LayoutTemplate::root_nodes`] instead
+    pub added_nodes: Vec<(String, NodeTemplate)>,
+}
+
+impl TemplateInheritance {
+    pub fn new(base: impl Into<String>) -> Self {
+        Self {
+            base: base.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Why a derived template could not be resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InheritanceError {
+    /// A template derives from a template that does not exist
+    MissingBase { base: String },
+
+    /// Templates that derive from each other, the first template is repeated at the end
+    Cycle { templates: Vec<String> },
+}
+
+impl fmt::Display for InheritanceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingBase { base } => write!(f, "base template '{base}' does not exist"),
+            Self::Cycle { templates } => {
+                write!(f, "templates derive from each other: {}", templates.join(" -> "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for InheritanceError {}
+
+impl LayoutTemplate {
+    /// Whether this template is stored as changes to another template
+    pub fn is_derived(&self) -> bool {
+        self.inheritance.is_some()
+    }
+
+    /// The complete template, with the changes of this template applied on top of its base templates
+    ///
+    /// Templates that don't derive from another template are returned as they are. `templates` are the named
+    /// templates that base templates are looked up in, usually [`LayoutRoot::templates`](crate::LayoutRoot::templates).
+    pub fn resolve<'a>(
+        &'a self,
+        templates: &HashMap<String, LayoutTemplate>,
+    ) -> Result<Cow<'a, LayoutTemplate>, InheritanceError> {
+        let Some(inheritance) = self.inheritance.as_ref() else {
+            return Ok(Cow::Borrowed(self));
+        };
+
+        let mut chain = vec![self];
+        let mut names: Vec<&str> = vec![];
+        let mut base_name = inheritance.base.as_str();
+        let mut resolved = loop {
+            if let Some(start) = names.iter().position(|name| *name == base_name) {
+                let mut templates = names[start..].iter().map(|name| name.to_string()).collect::<Vec<_>>();
+                templates.push(base_name.to_string());
+                return Err(InheritanceError::Cycle { templates });
+            }
+
+            names.push(base_name);
+            let base = templates.get(base_name).ok_or_else(|| InheritanceError::MissingBase {
+                base: base_name.to_string(),
+            })?;
+
+            match base.inheritance.as_ref() {
+                Some(inheritance) => {
+                    chain.push(base);
+                    base_name = inheritance.base.as_str();
+                }
+                None => break base.clone(),
+            }
+        };
+
+        for derived in chain.into_iter().rev() {
+            derived.apply_changes(&mut resolved);
+        }
+
+        Ok(Cow::Owned(resolved))
+    }
+
+    /// Applies the changes of this derived template to its resolved base template
+    fn apply_changes(&self, resolved: &mut LayoutTemplate) {
+        let Some(inheritance) = self.inheritance.as_ref() else {
+            return;
+        };
+
+        for path in inheritance.removed_nodes.iter() {
+            if resolved.remove_node(path).is_none() {
+                log::warn!("Skipping removal of node '{path}', which is not in base template '{}'", inheritance.base);
+                continue;
+            }
+
+            for (_, animation) in resolved.animations.iter_mut() {
+                animation
+                    .node_animations
+                    .retain(|animation| !is_same_or_inside(&animation.node_path, path));
+            }
+        }
+
+        for patch in inheritance.patches.iter() {
+            match resolved.get_node_by_path_mut(Utf8Path::new(&patch.path)) {
+                Some(node) => patch.apply(node),
+                None => log::warn!(
+                    "Skipping patch of node '{}', which is not in base template '{}'",
+                    patch.path,
+                    inheritance.base
+                ),
+            }
+        }
+
+        for (parent, node) in inheritance.added_nodes.iter() {
+            let added = resolved
+                .get_node_by_path_mut(Utf8Path::new(parent))
+                .map(|parent| parent.add_child(node.clone()));
+
+            if added != Some(true) {
+                log::warn!(
+                    "Skipping node '{}', its parent '{parent}' is not in base template '{}' or already has a child \
+                     with that name",
+                    node.name,
+                    inheritance.base
+                );
+            }
+        }
+
+        for node in self.root_nodes.iter() {
+            if resolved.has_root(&node.name) {
+                log::warn!("Skipping root node '{}', base template '{}' already has one", node.name, inheritance.base);
+            } else {
+                resolved.root_nodes.push(node.clone());
+            }
+        }
+
+        for (name, animation) in self.animations.iter() {
+            match resolved.animations.iter_mut().find(|(base_name, _)| base_name == name) {
+                Some((_, base_animation)) => *base_animation = animation.clone(),
+                None => resolved.animations.push((name.clone(), animation.clone())),
+            }
+        }
+
+        if self.state_machine.is_some() {
+            resolved.state_machine = self.state_machine.clone();
+        }
+
+        resolved.canvas_size = self.canvas_size;
+        resolved.inheritance = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use crate::{AnimationChannel, NodeAnimation, animations::Animation};
+
+    use super::*;
+
+    fn node(name: &str, children: Vec<NodeTemplate>) -> NodeTemplate {
+        NodeTemplate {
+            name: name.to_string(),
+            transform: NodeTransform::from_size(Vec2::new(100.0, 50.0)),
+            color: [255; 4],
+            visibility: NodeVisibility::Inherited,
+            gradient: None,
+            children,
+            implementation: NodeImplTemplate::Empty,
+        }
+    }
+
+    fn animation(paths: &[&str]) -> Animation {
+        Animation {
+            node_animations: paths
+                .iter()
+                .map(|path| {
+                    let mut animation = NodeAnimation::empty(*path);
+                    animation.angle_channel = Some(AnimationChannel::new(0.0));
+                    animation
+                })
+                .collect(),
+            total_duration: 10,
+        }
+    }
+
+    fn derived(base: &str) -> LayoutTemplate {
+        LayoutTemplate {
+            canvas_size: [200, 100],
+            inheritance: Some(TemplateInheritance::new(base)),
+            ..Default::default()
+        }
+    }
+
+    fn paths(template: &LayoutTemplate) -> Vec<String> {
+        fn visit(node: &NodeTemplate, path: String, paths: &mut Vec<String>) {
+            for child in node.children.iter() {
+                visit(child, format!("{path}/{}", child.name), paths);
+            }
+            paths.push(path);
+        }
+
+        let mut paths = vec![];
+        for node in template.root_nodes.iter() {
+            visit(node, node.name.clone(), &mut paths);
+        }
+        paths
+    }
+
+    #[test]
+    fn changes_are_applied_to_the_base() {
+        let mut base = LayoutTemplate {
+            canvas_size: [100, 100],
+            root_nodes: vec![node("bg", vec![]), node("buttons", vec![node("a", vec![]), node("b", vec![])])],
+            ..Default::default()
+        };
+        base.add_animation("intro", animation(&["buttons/a", "buttons/b", "buttons/b::icon", "buttons/bb"]));
+
+        let mut options = derived("base");
+        let inheritance = options.inheritance.as_mut().unwrap();
+        inheritance.removed_nodes.push("buttons/b".to_string());
+        inheritance.patches.push(NodePatch {
+            color: Some([0; 4]),
+            ..NodePatch::new("bg")
+        });
+        inheritance.added_nodes.push(("buttons".to_string(), node("c", vec![])));
+        options.root_nodes.push(node("extra", vec![]));
+        options.add_animation("outro", animation(&["extra"]));
+
+        let templates = [("base".to_string(), base), ("options".to_string(), options)]
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        let resolved = templates["options"].resolve(&templates).unwrap();
+
+        assert_eq!(paths(&resolved), ["bg", "buttons/a", "buttons/c", "buttons", "extra"]);
+        assert_eq!(resolved.get_node_by_path("bg").unwrap().color, [0; 4]);
+        assert_eq!(resolved.canvas_size, [200, 100]);
+        assert!(resolved.inheritance.is_none());
+
+        // Animations of removed nodes go with them, including the nodes inside of their sublayouts
+        let animated = resolved
+            .animations
+            .iter()
+            .map(|(name, animation)| {
+                let paths = animation.node_animations.iter().map(|animation| animation.node_path.as_str());
+                (name.as_str(), paths.collect::<Vec<_>>())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(animated, [("intro", vec!["buttons/a", "buttons/bb"]), ("outro", vec!["extra"])]);
+    }
+
+    #[test]
+    fn changes_to_missing_nodes_are_skipped() {
+        let base = LayoutTemplate {
+            root_nodes: vec![node("bg", vec![])],
+            ..Default::default()
+        };
+
+        let mut options = derived("base");
+        let inheritance = options.inheritance.as_mut().unwrap();
+        inheritance.removed_nodes.push("missing".to_string());
+        inheritance.patches.push(NodePatch {
+            color: Some([0; 4]),
+            ..NodePatch::new("missing")
+        });
+        inheritance.added_nodes.push(("missing".to_string(), node("c", vec![])));
+
+        let templates = [("base".to_string(), base), ("options".to_string(), options)]
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        let resolved = templates["options"].resolve(&templates).unwrap();
+        assert_eq!(paths(&resolved), ["bg"]);
+    }
+
+    #[test]
+    fn missing_base() {
+        let templates = [("options".to_string(), derived("missing"))].into_iter().collect::<HashMap<_, _>>();
+        assert_eq!(
+            templates["options"].resolve(&templates).err(),
+            Some(InheritanceError::MissingBase {
+                base: "missing".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn cycle() {
+        let templates = [("a".to_string(), derived("b")), ("b".to_string(), derived("a"))]
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        assert_eq!(
+            templates["a"].resolve(&templates).err(),
+            Some(InheritanceError::Cycle {
+                templates: vec!["b".to_string(), "a".to_string(), "b".to_string()],
+            })
+        );
+    }
+}
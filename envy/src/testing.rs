@@ -0,0 +1,430 @@
+//! A headless [`EnvyBackend`] for testing layouts without a GPU
+//!
+//! [`RecordingBackend`] hands out fake handles and records every uniform update and draw call, so tests can assert on
+//! the matrices the tree computes, the order nodes are drawn in, and whether every resource that was requested is
+//! released again.
+//!
+//! ```
+//! use envy::testing::{DrawCall, RecordingBackend};
+//! use envy::{ImageNode, LayoutTree, NodeItem, NodeTransform};
+//!
+//! let mut backend = RecordingBackend::new();
+//! let mut tree = LayoutTree::new().with_child(NodeItem::new(
+//!     "background",
+//!     NodeTransform::from_size(glam::Vec2::new(100.0, 50.0)),
+//!     [255; 4],
+//!     ImageNode::new("background"),
+//! ));
+//!
+//! tree.setup(&mut backend);
+//! tree.update();
+//! tree.propagate();
+//! tree.prepare(&mut backend);
+//! tree.render(&backend, &mut ());
+//!
+//! let draws = backend.take_draw_calls();
+//! let [DrawCall::Texture { texture, uniform, .. }] = draws.as_slice() else {
+//!     panic!("expected a single texture draw");
+//! };
+//! assert_eq!(backend.texture_name(*texture), Some("background"));
+//!
+//! // Texture vertices are in the range [-0.5, 0.5], so this is the bottom right corner of the image
+//! let corner = uniform.model_matrix.transform_point3(glam::Vec3::new(0.5, 0.5, 0.0));
+//! assert_eq!(corner.truncate(), glam::Vec2::new(100.0, 50.0));
+//!
+//! tree.release(&mut backend);
+//! backend.assert_no_leaks();
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::{
+    DrawTextureArgs, DrawUniform, EnvyBackend, PreparedGlyph, ShapeTessellationArgs, TextLayoutArgs, TextureFillArgs,
+    TextureRequestArgs, template::TextAlignment,
+};
+
+/// Width of every glyph laid out by [`RecordingBackend`], as a fraction of the font size
+pub const GLYPH_ADVANCE: f32 = 0.5;
+
+macro_rules! handles {
+    ($($(#[$meta:meta])* $name:ident),*) => {
+        $(
+            $(#[$meta])*
+            #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+            pub struct $name(pub usize);
+        )*
+    };
+}
+
+handles!(
+    /// A fake texture handle, unique to each request
+    TextureHandle,
+    /// A fake uniform handle, unique to each request
+    UniformHandle,
+    /// A fake font handle, unique to each request
+    FontHandle,
+    /// A fake glyph handle, shared by every glyph of the same character
+    GlyphHandle,
+    /// A fake shape handle, unique to each tessellation
+    ShapeHandle
+);
+
+/// How many times a kind of resource was requested and released
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct ResourceCounts {
+    pub requested: usize,
+    pub released: usize,
+}
+
+impl ResourceCounts {
+    /// Number of resources that are still held
+    pub fn live(&self) -> usize {
+        self.requested - self.released
+    }
+}
+
+/// A draw call made through [`RecordingBackend`], with the value of each uniform at the time of the call
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawCall {
+    Texture {
+        texture: TextureHandle,
+        mask_texture: Option<TextureHandle>,
+        uniform: DrawUniform,
+    },
+    Glyph {
+        glyph: GlyphHandle,
+        uniform: DrawUniform,
+        outline_uniform: Option<DrawUniform>,
+    },
+    Shape {
+        shape: ShapeHandle,
+        fill_uniform: Option<DrawUniform>,
+        stroke_uniform: Option<DrawUniform>,
+    },
+}
+
+/// Resources that are currently held, by the handle they were given out with
+struct Resources<H, T> {
+    live: HashMap<H, T>,
+    counts: ResourceCounts,
+}
+
+impl<H, T> Default for Resources<H, T> {
+    fn default() -> Self {
+        Self {
+            live: HashMap::new(),
+            counts: ResourceCounts::default(),
+        }
+    }
+}
+
+impl<H: Copy + Eq + std::hash::Hash + std::fmt::Debug, T> Resources<H, T> {
+    fn request(&mut self, handle: H, value: T) -> H {
+        self.live.insert(handle, value);
+        self.counts.requested += 1;
+        handle
+    }
+
+    fn release(&mut self, handle: H, kind: &str) {
+        if self.live.remove(&handle).is_none() {
+            panic!("{kind} {handle:?} was released without being held");
+        }
+
+        self.counts.released += 1;
+    }
+}
+
+/// An [`EnvyBackend`] that records what the tree asks of it instead of rendering
+///
+/// Every texture and font can be requested, and text is laid out with fixed metrics: each character is
+/// [`GLYPH_ADVANCE`] times the font size wide and one line tall, and lines only break on `'\n'`. With the `asset`
+/// feature it is also an [`EnvyAssetProvider`](crate::asset::EnvyAssetProvider) which keeps the bytes it is given in
+/// memory and records every image and font that is loaded.
+#[derive(Default)]
+pub struct RecordingBackend {
+    next_handle: usize,
+    textures: Resources<TextureHandle, String>,
+    fonts: Resources<FontHandle, String>,
+    uniforms: Resources<UniformHandle, DrawUniform>,
+    shapes: Resources<ShapeHandle, glam::Vec2>,
+    glyphs: HashMap<char, GlyphHandle>,
+    texture_fills: HashMap<TextureHandle, TextureFillArgs>,
+    uniform_updates: Vec<(UniformHandle, DrawUniform)>,
+    draw_calls: Mutex<Vec<DrawCall>>,
+    #[cfg(feature = "asset")]
+    resources: crate::asset::MemoryAssetProvider,
+    #[cfg(feature = "asset")]
+    resource_loads: Vec<(crate::asset::ResourceKind, String)>,
+}
+
+impl RecordingBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn next_handle(&mut self) -> usize {
+        self.next_handle += 1;
+        self.next_handle
+    }
+
+    /// Name the texture was requested with, if it is still held
+    pub fn texture_name(&self, handle: TextureHandle) -> Option<&str> {
+        self.textures.live.get(&handle).map(String::as_str)
+    }
+
+    /// Name the font was requested with, if it is still held
+    pub fn font_name(&self, handle: FontHandle) -> Option<&str> {
+        self.fonts.live.get(&handle).map(String::as_str)
+    }
+
+    /// The last value written to the uniform, if it is still held
+    pub fn uniform(&self, handle: UniformHandle) -> Option<&DrawUniform> {
+        self.uniforms.live.get(&handle)
+    }
+
+    /// The size the shape was tessellated for, if it is still held
+    pub fn shape_size(&self, handle: ShapeHandle) -> Option<glam::Vec2> {
+        self.shapes.live.get(&handle).copied()
+    }
+
+    /// The last fill the texture was prepared with
+    pub fn texture_fill(&self, handle: TextureHandle) -> Option<TextureFillArgs> {
+        self.texture_fills.get(&handle).copied()
+    }
+
+    /// Every call to [`EnvyBackend::update_uniform`] since the last call to [`RecordingBackend::take_uniform_updates`]
+    pub fn uniform_updates(&self) -> &[(UniformHandle, DrawUniform)] {
+        &self.uniform_updates
+    }
+
+    pub fn take_uniform_updates(&mut self) -> Vec<(UniformHandle, DrawUniform)> {
+        std::mem::take(&mut self.uniform_updates)
+    }
+
+    /// Every draw call since the last call to [`RecordingBackend::take_draw_calls`], in the order they were made
+    pub fn draw_calls(&self) -> Vec<DrawCall> {
+        self.draw_calls.lock().unwrap().clone()
+    }
+
+    pub fn take_draw_calls(&mut self) -> Vec<DrawCall> {
+        std::mem::take(self.draw_calls.get_mut().unwrap())
+    }
+
+    pub fn texture_counts(&self) -> ResourceCounts {
+        self.textures.counts
+    }
+
+    pub fn font_counts(&self) -> ResourceCounts {
+        self.fonts.counts
+    }
+
+    pub fn uniform_counts(&self) -> ResourceCounts {
+        self.uniforms.counts
+    }
+
+    pub fn shape_counts(&self) -> ResourceCounts {
+        self.shapes.counts
+    }
+
+    /// Every image and font loaded since the last call, in the order they were loaded
+    #[cfg(feature = "asset")]
+    pub fn take_resource_loads(&mut self) -> Vec<(crate::asset::ResourceKind, String)> {
+        std::mem::take(&mut self.resource_loads)
+    }
+
+    /// Panics if any resource that was requested has not been released
+    pub fn assert_no_leaks(&self) {
+        let mut leaks = vec![];
+        leaks.extend(self.textures.live.values().map(|name| format!("texture {name:?}")));
+        leaks.extend(self.fonts.live.values().map(|name| format!("font {name:?}")));
+        if !self.uniforms.live.is_empty() {
+            leaks.push(format!("{} uniforms", self.uniforms.live.len()));
+        }
+
+        if !self.shapes.live.is_empty() {
+            leaks.push(format!("{} shapes", self.shapes.live.len()));
+        }
+
+        assert!(leaks.is_empty(), "resources were leaked: {}", leaks.join(", "));
+    }
+
+    fn new_uniform(&mut self) -> UniformHandle {
+        let handle = UniformHandle(self.next_handle());
+        self.uniforms
+            .request(handle, DrawUniform::new(glam::Mat4::IDENTITY, glam::Vec4::ONE))
+    }
+
+    fn record(&self, call: DrawCall) {
+        self.draw_calls.lock().unwrap().push(call);
+    }
+
+    fn uniform_value(&self, handle: UniformHandle) -> DrawUniform {
+        match self.uniforms.live.get(&handle) {
+            Some(uniform) => *uniform,
+            None => panic!("uniform {handle:?} was drawn with after being released"),
+        }
+    }
+}
+
+impl EnvyBackend for RecordingBackend {
+    type TextureHandle = TextureHandle;
+    type UniformHandle = UniformHandle;
+    type FontHandle = FontHandle;
+    type GlyphHandle = GlyphHandle;
+    type ShapeHandle = ShapeHandle;
+    type RenderPass<'a> = ();
+
+    fn request_texture_by_name(&mut self, name: impl AsRef<str>, _args: TextureRequestArgs) -> Option<Self::TextureHandle> {
+        let handle = TextureHandle(self.next_handle());
+        Some(self.textures.request(handle, name.as_ref().to_string()))
+    }
+
+    fn request_font_by_name(&mut self, name: impl AsRef<str>) -> Option<Self::FontHandle> {
+        let handle = FontHandle(self.next_handle());
+        Some(self.fonts.request(handle, name.as_ref().to_string()))
+    }
+
+    fn request_new_uniform(&mut self) -> Option<Self::UniformHandle> {
+        Some(self.new_uniform())
+    }
+
+    fn release_texture(&mut self, handle: Self::TextureHandle) {
+        self.textures.release(handle, "texture");
+        self.texture_fills.remove(&handle);
+    }
+
+    fn release_font(&mut self, handle: Self::FontHandle) {
+        self.fonts.release(handle, "font");
+    }
+
+    fn release_uniform(&mut self, handle: Self::UniformHandle) {
+        self.uniforms.release(handle, "uniform");
+    }
+
+    fn release_shape(&mut self, handle: Self::ShapeHandle) {
+        self.shapes.release(handle, "shape");
+    }
+
+    fn update_uniform(&mut self, handle: Self::UniformHandle, uniform: DrawUniform) {
+        match self.uniforms.live.get_mut(&handle) {
+            Some(value) => *value = uniform,
+            None => panic!("uniform {handle:?} was updated after being released"),
+        }
+
+        self.uniform_updates.push((handle, uniform));
+    }
+
+    fn update_texture_scaling(&mut self, _handle: Self::TextureHandle, _uv_offset: glam::Vec2, _uv_scale: glam::Vec2, _size: glam::Vec2) {}
+
+    fn update_texture_fill(&mut self, handle: Self::TextureHandle, args: TextureFillArgs) {
+        self.texture_fills.insert(handle, args);
+    }
+
+    fn layout_text(&mut self, args: TextLayoutArgs<'_, Self>) -> Vec<PreparedGlyph<Self>> {
+        let advance = args.font_size * GLYPH_ADVANCE;
+        let mut glyphs = vec![];
+        let mut char_index = 0;
+        for (line_idx, line) in args.text.split('\n').enumerate() {
+            let line_width = line.chars().count() as f32 * advance;
+            let line_start = match args.alignment {
+                TextAlignment::Left | TextAlignment::Justify => 0.0,
+                TextAlignment::Center => (args.buffer_size.x - line_width) / 2.0,
+                TextAlignment::Right | TextAlignment::End => args.buffer_size.x - line_width,
+            };
+
+            for (column, character) in line.chars().enumerate() {
+                let next_handle = self.glyphs.len();
+                let glyph_handle = *self.glyphs.entry(character).or_insert(GlyphHandle(next_handle));
+                glyphs.push(PreparedGlyph {
+                    glyph_handle,
+                    uniform_handle: self.new_uniform(),
+                    outline_uniform_handle: (args.outline_thickness > 0.0).then(|| self.new_uniform()),
+                    offset_in_buffer: glam::Vec2::new(line_start + column as f32 * advance, line_idx as f32 * args.line_height),
+                    size: glam::Vec2::new(advance, args.line_height),
+                    char_index,
+                });
+                char_index += 1;
+            }
+
+            // The line break is a character too
+            char_index += 1;
+        }
+
+        glyphs
+    }
+
+    fn prepare_shape(&mut self, args: ShapeTessellationArgs<'_>) -> Option<Self::ShapeHandle> {
+        let handle = ShapeHandle(self.next_handle());
+        Some(self.shapes.request(handle, args.size))
+    }
+
+    fn draw_texture(&self, uniform: Self::UniformHandle, handle: Self::TextureHandle, pass: &mut Self::RenderPass<'_>) {
+        self.draw_texture_ext(
+            uniform,
+            DrawTextureArgs {
+                texture: handle,
+                mask_texture: None,
+            },
+            pass,
+        );
+    }
+
+    fn draw_texture_ext(&self, uniform: Self::UniformHandle, args: DrawTextureArgs<Self>, _pass: &mut Self::RenderPass<'_>) {
+        self.record(DrawCall::Texture {
+            texture: args.texture,
+            mask_texture: args.mask_texture,
+            uniform: self.uniform_value(uniform),
+        });
+    }
+
+    fn draw_glyph(
+        &self,
+        uniform: Self::UniformHandle,
+        outline_uniform: Option<Self::UniformHandle>,
+        handle: Self::GlyphHandle,
+        _pass: &mut Self::RenderPass<'_>,
+    ) {
+        self.record(DrawCall::Glyph {
+            glyph: handle,
+            uniform: self.uniform_value(uniform),
+            outline_uniform: outline_uniform.map(|uniform| self.uniform_value(uniform)),
+        });
+    }
+
+    fn draw_shape(
+        &self,
+        fill_uniform: Option<Self::UniformHandle>,
+        stroke_uniform: Option<Self::UniformHandle>,
+        handle: Self::ShapeHandle,
+        _pass: &mut Self::RenderPass<'_>,
+    ) {
+        self.record(DrawCall::Shape {
+            shape: handle,
+            fill_uniform: fill_uniform.map(|uniform| self.uniform_value(uniform)),
+            stroke_uniform: stroke_uniform.map(|uniform| self.uniform_value(uniform)),
+        });
+    }
+}
+
+#[cfg(feature = "asset")]
+impl crate::asset::EnvyAssetProvider for RecordingBackend {
+    fn load_image_bytes_with_name(&mut self, name: String, bytes: Vec<u8>) {
+        self.resource_loads.push((crate::asset::ResourceKind::Image, name.clone()));
+        self.resources.load_image_bytes_with_name(name, bytes);
+    }
+
+    fn load_font_bytes_with_name(&mut self, name: String, bytes: Vec<u8>) {
+        self.resource_loads.push((crate::asset::ResourceKind::Font, name.clone()));
+        self.resources.load_font_bytes_with_name(name, bytes);
+    }
+
+    fn fetch_image_bytes_by_name<'a>(&'a self, name: &str) -> std::borrow::Cow<'a, [u8]> {
+        self.resources.fetch_image_bytes_by_name(name)
+    }
+
+    fn fetch_font_bytes_by_name<'a>(&'a self, name: &str) -> std::borrow::Cow<'a, [u8]> {
+        self.resources.fetch_font_bytes_by_name(name)
+    }
+}
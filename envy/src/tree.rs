@@ -1,12 +1,65 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+};
 
 use camino::Utf8Path;
 use glam::{Affine2, Vec2};
 
 use crate::{
-    EnvyBackend, NodeItem, NodeTransform, SublayoutNode, animations::Animation, node::{Anchor, NodeParent, ObservedNode, PropagationArgs}, template::{LayoutTemplate, NodeImplTemplate, NodeTemplate, NodeVisibility}
+    EnvyBackend, ImageNode, Interpolatable, NodeItem, NodeTransform, SublayoutNode, animations::{Animation, AnimationBlendMode, NodePose, PoseBlender, RestPose}, node::{Anchor, NodeParent, ObservedNode, PropagationArgs}, state_machine::{ParamValue, StateMachine, StateMachinePlayer}, template::{LayoutTemplate, NodeImplTemplate, NodeTemplate, NodeVisibility}
 };
 
+mod state;
+
+pub use state::{LayoutTreeState, NodeState, PlayingAnimationState, StateMachineState};
+
+/// Separates the path of a sublayout node from the path of a node inside of that sublayout, as in `hud/button_a::icon`
+pub const SUBLAYOUT_PATH_SEPARATOR: &str = "::";
+
+/// Splits off the part of `path` after the first [`SUBLAYOUT_PATH_SEPARATOR`]
+fn split_sublayout_path(path: &Utf8Path) -> (&Utf8Path, Option<&Utf8Path>) {
+    match path.as_str().split_once(SUBLAYOUT_PATH_SEPARATOR) {
+        Some((path, sublayout_path)) => (Utf8Path::new(path), Some(Utf8Path::new(sublayout_path))),
+        None => (path, None),
+    }
+}
+
+/// The complete template that a tree is built from, see [`LayoutTemplate::resolve`]
+///
+/// Like a missing sublayout template, a derived template that cannot be resolved is a bug in the layout and panics.
+fn resolve_template<'a>(
+    template: &'a LayoutTemplate,
+    templates: &HashMap<String, LayoutTemplate>,
+) -> Cow<'a, LayoutTemplate> {
+    template
+        .resolve(templates)
+        .unwrap_or_else(|error| panic!("Template cannot be resolved: {error}"))
+}
+
+/// Whether `path` is `ancestor` or a node inside of it, including nodes inside of its sublayout
+pub(crate) fn is_same_or_inside(path: &str, ancestor: &str) -> bool {
+    ancestor.is_empty()
+        || path
+            .strip_prefix(ancestor)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/') || rest.starts_with(SUBLAYOUT_PATH_SEPARATOR))
+}
+
+/// What changed in a template, for bringing the trees built from it up to date without rebuilding all of them
+#[cfg(feature = "asset")]
+#[derive(Debug, Default)]
+pub(crate) struct TemplateUpdate {
+    pub(crate) canvas_size: bool,
+    pub(crate) state_machine: bool,
+    pub(crate) animations: bool,
+
+    /// Nodes whose children were added, removed, moved or reordered, the empty path stands for the root nodes
+    pub(crate) parents: std::collections::BTreeSet<String>,
+
+    /// Nodes to rebuild from the template
+    pub(crate) nodes: std::collections::BTreeSet<String>,
+}
+
 pub struct LayoutRoot<B: EnvyBackend> {
     root_layout: LayoutTree<B>,
     root_template: LayoutTemplate,
@@ -60,6 +113,7 @@ impl<B: EnvyBackend> LayoutRoot<B> {
             .into_iter()
             .chain(this.templates.values())
         {
+            let template = resolve_template(template, &this.templates);
             for node in template.root_nodes.iter() {
                 Self::validate_template_recursive(node, &this.templates, &mut visited);
             }
@@ -95,7 +149,7 @@ impl<B: EnvyBackend> LayoutRoot<B> {
     ) {
         tree.visit_roots_mut(|root| root.release(backend));
         *tree = LayoutTree::from_template_with_root_templates(template, templates);
-        tree.visit_roots_mut(|root| root.setup(backend));
+        tree.setup(backend);
     }
 
     fn sync_template_inner_by_path(
@@ -105,6 +159,7 @@ impl<B: EnvyBackend> LayoutRoot<B> {
         path: &Utf8Path,
         backend: &mut B,
     ) {
+        let template = resolve_template(template, templates);
         let node = tree.get_node_by_path_mut(path).unwrap();
         let template_node = template.get_node_by_path(path).unwrap();
         node.release(backend);
@@ -112,6 +167,221 @@ impl<B: EnvyBackend> LayoutRoot<B> {
         node.setup(backend);
     }
 
+    /// Replaces every template and brings the trees built from them up to date
+    ///
+    /// Only the nodes listed in `updates` (keyed by template name, `None` for the root template) are built again, so
+    /// every other node keeps its runtime state. The new templates must not be missing any sublayout.
+    #[cfg(feature = "asset")]
+    pub(crate) fn replace_templates(
+        &mut self,
+        root_template: LayoutTemplate,
+        templates: impl IntoIterator<Item = (String, LayoutTemplate)>,
+        updates: &HashMap<Option<String>, TemplateUpdate>,
+        backend: &mut B,
+    ) {
+        self.root_template = root_template;
+        self.templates = templates.into_iter().collect();
+        self.templates.insert("".to_string(), LayoutTemplate::default());
+        self.apply_template_updates(updates, backend);
+    }
+
+    /// Brings the trees built from the templates up to date after the templates were changed, see
+    /// [`LayoutRoot::replace_templates`]
+    #[cfg(feature = "asset")]
+    pub(crate) fn apply_template_updates(
+        &mut self,
+        updates: &HashMap<Option<String>, TemplateUpdate>,
+        backend: &mut B,
+    ) {
+        Self::update_tree_recursive(
+            &mut self.root_layout,
+            &resolve_template(&self.root_template, &self.templates),
+            &self.templates,
+            updates.get(&None),
+            updates,
+            backend,
+        );
+        self.root_layout.request_animation_textures(backend);
+    }
+
+    #[cfg(feature = "asset")]
+    fn update_tree_recursive(
+        tree: &mut LayoutTree<B>,
+        template: &LayoutTemplate,
+        templates: &HashMap<String, LayoutTemplate>,
+        update: Option<&TemplateUpdate>,
+        updates: &HashMap<Option<String>, TemplateUpdate>,
+        backend: &mut B,
+    ) {
+        let rebuilt = match update {
+            Some(update) => Self::update_tree(tree, template, templates, update, backend),
+            None => vec![],
+        };
+
+        // Sublayouts of nodes that were built again have just been built from the new templates
+        fn visit_recursive<B: EnvyBackend>(
+            node: &mut NodeItem<B>,
+            path: String,
+            rebuilt: &[String],
+            f: &mut dyn FnMut(&mut SublayoutNode<B>),
+        ) {
+            if rebuilt.iter().any(|rebuilt| is_same_or_inside(&path, rebuilt)) {
+                return;
+            }
+
+            if let Some(sublayout) = node.downcast_mut::<SublayoutNode<B>>() {
+                f(sublayout);
+            }
+
+            node.visit_children_mut(|child| {
+                let child_path = format!("{path}/{}", child.name());
+                visit_recursive(child, child_path, rebuilt, f);
+            });
+        }
+
+        tree.visit_roots_mut(|root| {
+            let path = root.name().to_string();
+            visit_recursive(root, path, &rebuilt, &mut |sublayout| {
+                let Some(template) = templates.get(sublayout.reference()) else {
+                    return;
+                };
+
+                let template = resolve_template(template, templates);
+                let update = updates.get(&Some(sublayout.reference().to_string()));
+                Self::update_tree_recursive(sublayout.as_layout_mut(), &template, templates, update, updates, backend);
+            });
+        });
+    }
+
+    /// Applies `update` to a single tree, returning the paths of every node that was built again along with its
+    /// children
+    #[cfg(feature = "asset")]
+    fn update_tree(
+        tree: &mut LayoutTree<B>,
+        template: &LayoutTemplate,
+        templates: &HashMap<String, LayoutTemplate>,
+        update: &TemplateUpdate,
+        backend: &mut B,
+    ) -> Vec<String> {
+        if update.canvas_size {
+            tree.canvas_size = template.canvas_size.into();
+        }
+
+        if update.state_machine {
+            tree.set_state_machine(template.state_machine.clone());
+        }
+
+        if update.animations {
+            tree.animations = template
+                .animations
+                .iter()
+                .map(|(name, animation)| (name.clone(), animation.clone()))
+                .collect();
+
+            let animations = &tree.animations;
+            tree.playing_animations
+                .retain(|name, _| animations.contains_key(name));
+        }
+
+        let child_path = |parent: &str, name: &str| {
+            if parent.is_empty() {
+                name.to_string()
+            } else {
+                format!("{parent}/{name}")
+            }
+        };
+
+        // Added and removed nodes, these are built again along with their children
+        let mut rebuilt: Vec<String> = vec![];
+
+        // Children that are still in the template are kept as they are, paths are sorted so parents come first
+        for parent in update.parents.iter() {
+            if rebuilt.iter().any(|rebuilt| is_same_or_inside(parent, rebuilt)) {
+                continue;
+            }
+
+            let (template_children, children) = if parent.is_empty() {
+                (&template.root_nodes, &mut tree.root_children)
+            } else {
+                let (Some(template_node), Some(node)) = (
+                    template.get_node_by_path(parent),
+                    Self::get_live_node(tree, parent),
+                ) else {
+                    continue;
+                };
+                (&template_node.children, node.children_mut())
+            };
+
+            let mut old_children = std::mem::take(children);
+            for child_template in template_children.iter() {
+                match old_children
+                    .iter()
+                    .position(|child| child.node.name() == child_template.name)
+                {
+                    Some(index) => children.push(old_children.remove(index)),
+                    None => {
+                        let mut node = NodeItem::from_template_with_root_templates(child_template, templates);
+                        node.setup(backend);
+                        children.push(ObservedNode::new(node));
+                        rebuilt.push(child_path(parent, &child_template.name));
+                    }
+                }
+            }
+
+            for mut removed in old_children {
+                removed.node.release(backend);
+                rebuilt.push(child_path(parent, removed.node.name()));
+            }
+        }
+
+        // Changed nodes are built again on their own, their children are only affected by their own changes
+        let mut changed: Vec<&String> = vec![];
+        for path in update.nodes.iter() {
+            if rebuilt.iter().any(|rebuilt| is_same_or_inside(path, rebuilt)) {
+                continue;
+            }
+
+            let (Some(template_node), Some(node)) =
+                (template.get_node_by_path(path), Self::get_live_node(tree, path))
+            else {
+                continue;
+            };
+
+            node.rebuild_from_template(template_node, templates, backend);
+            changed.push(path);
+        }
+
+        // Nodes that were built again are back at their template values, which become their new rest values
+        let is_changed = |path: &str| {
+            changed.iter().any(|changed| {
+                path.strip_prefix(changed.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with(SUBLAYOUT_PATH_SEPARATOR))
+            })
+        };
+        tree.rest_poses
+            .retain(|path, _| !is_changed(path) && !rebuilt.iter().any(|rebuilt| is_same_or_inside(path, rebuilt)));
+
+        rebuilt
+    }
+
+    /// Finds a node of `tree` itself, without following [`SUBLAYOUT_PATH_SEPARATOR`]
+    #[cfg(feature = "asset")]
+    fn get_live_node<'a>(tree: &'a mut LayoutTree<B>, path: &str) -> Option<&'a mut NodeItem<B>> {
+        let mut names = path.split('/');
+        let first = names.next()?;
+        let mut node = tree
+            .root_children
+            .iter_mut()
+            .find(|child| child.node.name() == first)
+            .map(|child| &mut child.node)?;
+
+        for name in names {
+            node = node.get_child_mut(name)?;
+        }
+
+        Some(node)
+    }
+
     pub fn sync_root_template(&mut self, backend: &mut B) {
         Self::sync_template_inner(
             &mut self.root_layout,
@@ -128,11 +398,65 @@ impl<B: EnvyBackend> LayoutRoot<B> {
             &self.templates,
             path.as_ref(),
             backend,
-        )
+        );
+        self.root_layout.request_animation_textures(backend);
     }
 
+    /// Names of every template that derives from `name`, directly or through other derived templates
+    fn derived_templates(&self, name: &str) -> HashSet<String> {
+        let mut derived = HashSet::new();
+        loop {
+            let count = derived.len();
+            for (template_name, template) in self.templates.iter() {
+                if template
+                    .inheritance
+                    .as_ref()
+                    .is_some_and(|inheritance| inheritance.base == name || derived.contains(&inheritance.base))
+                {
+                    derived.insert(template_name.clone());
+                }
+            }
+
+            if derived.len() == count {
+                return derived;
+            }
+        }
+    }
+
+    /// Builds the trees of derived templates again after their base template `name` was changed, returning `false`
+    /// if that includes the root template and so the whole layout
+    fn sync_derived_templates(&mut self, name: &str, backend: &mut B) -> bool {
+        let derived = self.derived_templates(name);
+        if self
+            .root_template
+            .inheritance
+            .as_ref()
+            .is_some_and(|inheritance| inheritance.base == name || derived.contains(&inheritance.base))
+        {
+            self.sync_root_template(backend);
+            return false;
+        }
+
+        self.root_layout.walk_tree_mut(|node| {
+            if let Some(sublayout) = node.downcast_mut::<SublayoutNode<B>>() {
+                if derived.contains(sublayout.reference()) {
+                    let template = self.templates.get(sublayout.reference()).unwrap();
+                    Self::sync_template_inner(sublayout.as_layout_mut(), template, &self.templates, backend);
+                }
+            }
+        });
+
+        true
+    }
+
+    /// Builds the trees of every sublayout that uses the template again, along with the ones of templates that
+    /// derive from it
     pub fn sync_template(&mut self, template_name: impl AsRef<str>, backend: &mut B) {
         let name = template_name.as_ref();
+        if !self.sync_derived_templates(name, backend) {
+            return;
+        }
+
         let template = self.templates.get(name).unwrap();
 
         self.root_layout.walk_tree_mut(|node| {
@@ -147,6 +471,7 @@ impl<B: EnvyBackend> LayoutRoot<B> {
                 }
             }
         });
+        self.root_layout.request_animation_textures(backend);
     }
 
     pub fn sync_template_by_path(
@@ -157,6 +482,12 @@ impl<B: EnvyBackend> LayoutRoot<B> {
     ) {
         let name = template_name.as_ref();
         let path = path.as_ref();
+
+        // The node might be at another path in derived templates, or not be in them at all
+        if !self.sync_derived_templates(name, backend) {
+            return;
+        }
+
         let template = self.templates.get(name).unwrap();
 
         self.root_layout.walk_tree_mut(|node| {
@@ -172,6 +503,7 @@ impl<B: EnvyBackend> LayoutRoot<B> {
                 }
             }
         });
+        self.root_layout.request_animation_textures(backend);
     }
 
     pub fn root_template(&self) -> &LayoutTemplate {
@@ -190,6 +522,37 @@ impl<B: EnvyBackend> LayoutRoot<B> {
         self.templates.get_mut(name.as_ref())
     }
 
+    /// The root template with the changes of every template it derives from applied, which is what the layout is
+    /// built from
+    pub fn resolved_root_template(&self) -> Cow<'_, LayoutTemplate> {
+        resolve_template(&self.root_template, &self.templates)
+    }
+
+    /// The template with the changes of every template it derives from applied, which is what sublayouts using it
+    /// are built from
+    pub fn resolved_template(&self, name: impl AsRef<str>) -> Option<Cow<'_, LayoutTemplate>> {
+        Some(resolve_template(self.templates.get(name.as_ref())?, &self.templates))
+    }
+
+    /// Finds a node template by its path in `template`, following [`SUBLAYOUT_PATH_SEPARATOR`] into the templates of sublayouts
+    pub fn get_template_node_by_path<'a>(
+        &'a self,
+        template: &'a LayoutTemplate,
+        path: impl AsRef<Utf8Path>,
+    ) -> Option<&'a NodeTemplate> {
+        let (path, sublayout_path) = split_sublayout_path(path.as_ref());
+        let node = template.get_node_by_path(path)?;
+        let Some(sublayout_path) = sublayout_path else {
+            return Some(node);
+        };
+
+        let NodeImplTemplate::Sublayout(sublayout) = &node.implementation else {
+            return None;
+        };
+
+        self.get_template_node_by_path(self.template(&sublayout.sublayout_name)?, sublayout_path)
+    }
+
     pub fn templates(&self) -> &HashMap<String, LayoutTemplate> {
         &self.templates
     }
@@ -260,6 +623,12 @@ impl<B: EnvyBackend> LayoutRoot<B> {
                 }
             }
 
+            for template in [&mut self.root_template].into_iter().chain(self.templates.values_mut()) {
+                if let Some(inheritance) = template.inheritance.as_mut().filter(|inheritance| inheritance.base == old) {
+                    inheritance.base = new_name.clone();
+                }
+            }
+
             self.templates.insert(new_name, template);
         }
     }
@@ -268,7 +637,7 @@ impl<B: EnvyBackend> LayoutRoot<B> {
         &self,
         reference: impl AsRef<str>,
     ) -> Option<LayoutTree<B>> {
-        let template = self.templates.get(reference.as_ref())?;
+        let template = resolve_template(self.templates.get(reference.as_ref())?, &self.templates);
 
         let mut tree = LayoutTree {
             canvas_size: template.canvas_size.into(),
@@ -278,6 +647,8 @@ impl<B: EnvyBackend> LayoutRoot<B> {
                 .map(|(name, animation)| (name.clone(), animation.clone()))
                 .collect(),
             playing_animations: HashMap::new(),
+            rest_poses: HashMap::new(),
+            state_machine: template.state_machine.clone().map(StateMachinePlayer::new),
             root_children: Vec::with_capacity(template.root_nodes.len()),
         };
 
@@ -298,10 +669,12 @@ impl<B: EnvyBackend> LayoutRoot<B> {
     }
 
     pub fn setup(&mut self, backend: &mut B) {
-        self.root_layout
-            .root_children
-            .iter_mut()
-            .for_each(|child| child.node.setup(backend));
+        self.root_layout.setup(backend);
+    }
+
+    /// Releases every resource requested by the root layout, the counterpart to [`LayoutRoot::setup`]
+    pub fn release(&mut self, backend: &mut B) {
+        self.root_layout.release(backend);
     }
 
     pub fn update(&mut self) {
@@ -329,15 +702,72 @@ impl<B: EnvyBackend> Default for LayoutRoot<B> {
     }
 }
 
+/// How a playing animation is combined with the other animations playing in a [`LayoutTree`]
+#[cfg_attr(feature = "asset", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AnimationPlayback {
+    /// Layers are blended from lowest to highest, animations on the same layer are averaged by weight
+    pub layer: u32,
+    pub weight: f32,
+    pub blend_mode: AnimationBlendMode,
+    pub looping: bool,
+}
+
+impl Default for AnimationPlayback {
+    fn default() -> Self {
+        Self {
+            layer: 0,
+            weight: 1.0,
+            blend_mode: AnimationBlendMode::Override,
+            looping: false,
+        }
+    }
+}
+
+struct WeightFade {
+    from: f32,
+    to: f32,
+    duration: f32,
+    elapsed: f32,
+    stop_when_finished: bool,
+}
+
 struct PlayingAnimation {
     progress: f32,
-    looping: bool,
+    /// Sublayout animations keyed after this frame have not been started yet
+    events_after: f32,
+    playback: AnimationPlayback,
+    fade: Option<WeightFade>,
+}
+
+impl PlayingAnimation {
+    /// Advances the weight fade by a frame, returning `false` once a fade that stops the animation has finished
+    fn update_fade(&mut self) -> bool {
+        let Some(fade) = self.fade.as_mut() else {
+            return true;
+        };
+
+        fade.elapsed += 1.0;
+        let progress = (fade.elapsed / fade.duration).min(1.0);
+        self.playback.weight = f32::interpolate(fade.from, fade.to, progress);
+
+        if progress >= 1.0 {
+            let stop_when_finished = fade.stop_when_finished;
+            self.fade = None;
+            !stop_when_finished
+        } else {
+            true
+        }
+    }
 }
 
 pub struct LayoutTree<B: EnvyBackend> {
     canvas_size: glam::UVec2,
     animations: HashMap<String, Animation>,
     playing_animations: HashMap<String, PlayingAnimation>,
+    /// Values of animated nodes before any animation touched them, used to resolve relative channels
+    rest_poses: HashMap<String, RestPose>,
+    state_machine: Option<StateMachinePlayer>,
     root_children: Vec<ObservedNode<B>>,
 }
 
@@ -346,6 +776,7 @@ impl<B: EnvyBackend> LayoutTree<B> {
         template: &LayoutTemplate,
         templates: &HashMap<String, LayoutTemplate>,
     ) -> Self {
+        let template = resolve_template(template, templates);
         Self {
             canvas_size: template.canvas_size.into(),
             animations: template
@@ -354,6 +785,8 @@ impl<B: EnvyBackend> LayoutTree<B> {
                 .map(|(name, anim)| (name.clone(), anim.clone()))
                 .collect(),
             playing_animations: HashMap::new(),
+            rest_poses: HashMap::new(),
+            state_machine: template.state_machine.clone().map(StateMachinePlayer::new),
             root_children: template
                 .root_nodes
                 .iter()
@@ -386,27 +819,174 @@ impl<B: EnvyBackend> LayoutTree<B> {
             canvas_size: glam::UVec2::new(1920, 1080),
             animations: HashMap::new(),
             playing_animations: HashMap::new(),
+            rest_poses: HashMap::new(),
+            state_machine: None,
             root_children: vec![],
         }
     }
 
     pub fn play_animation(&mut self, name: impl AsRef<str>) {
-        let name = name.as_ref();
-        if self.animations.contains_key(name) {
-            self.playing_animations.insert(name.to_string(), PlayingAnimation { progress: 0.0, looping: false });
-        }
+        self.play_animation_with(name, AnimationPlayback::default());
     }
 
     pub fn play_animation_looping(&mut self, name: impl AsRef<str>) {
+        self.play_animation_with(name, AnimationPlayback {
+            looping: true,
+            ..Default::default()
+        });
+    }
+
+    /// Plays the animation from the start, replacing the playback of the animation if it is already playing
+    pub fn play_animation_with(&mut self, name: impl AsRef<str>, playback: AnimationPlayback) {
         let name = name.as_ref();
         if self.animations.contains_key(name) {
             self.playing_animations.insert(name.to_string(), PlayingAnimation {
                 progress: 0.0,
-                looping: true
+                events_after: -1.0,
+                playback,
+                fade: None,
             });
         }
     }
 
+    pub fn stop_animation(&mut self, name: impl AsRef<str>) {
+        self.playing_animations.remove(name.as_ref());
+    }
+
+    pub fn is_animation_playing(&self, name: impl AsRef<str>) -> bool {
+        self.playing_animations.contains_key(name.as_ref())
+    }
+
+    pub fn set_animation_weight(&mut self, name: impl AsRef<str>, weight: f32) {
+        if let Some(state) = self.playing_animations.get_mut(name.as_ref()) {
+            state.playback.weight = weight;
+            state.fade = None;
+        }
+    }
+
+    /// Changes the weight of a playing animation linearly over `frames` frames
+    pub fn fade_animation_weight(&mut self, name: impl AsRef<str>, weight: f32, frames: usize) {
+        if let Some(state) = self.playing_animations.get_mut(name.as_ref()) {
+            Self::start_fade(state, weight, frames, false);
+        }
+    }
+
+    /// Fades `from` out over `frames` frames while fading `to` in, stopping `from` once the fade has finished
+    ///
+    /// `to` fades in towards the weight in `playback`. If `from` isn't playing, `to` fades in on its own.
+    pub fn crossfade_animation(
+        &mut self,
+        from: impl AsRef<str>,
+        to: impl AsRef<str>,
+        playback: AnimationPlayback,
+        frames: usize,
+    ) {
+        let to = to.as_ref();
+        if !self.animations.contains_key(to) {
+            return;
+        }
+
+        if let Some(state) = self.playing_animations.get_mut(from.as_ref()) {
+            if frames == 0 {
+                self.playing_animations.remove(from.as_ref());
+            } else {
+                Self::start_fade(state, 0.0, frames, true);
+            }
+        }
+
+        let mut state = PlayingAnimation {
+            progress: 0.0,
+            events_after: -1.0,
+            playback: AnimationPlayback {
+                weight: 0.0,
+                ..playback
+            },
+            fade: None,
+        };
+        Self::start_fade(&mut state, playback.weight, frames, false);
+        self.playing_animations.insert(to.to_string(), state);
+    }
+
+    /// Fades a playing animation out over `frames` frames and then stops it
+    pub fn fade_out_animation(&mut self, name: impl AsRef<str>, frames: usize) {
+        if frames == 0 {
+            self.stop_animation(name);
+        } else if let Some(state) = self.playing_animations.get_mut(name.as_ref()) {
+            Self::start_fade(state, 0.0, frames, true);
+        }
+    }
+
+    /// Replaces the state machine of this tree, which enters its entry state on the next update
+    pub fn set_state_machine(&mut self, state_machine: Option<StateMachine>) {
+        self.state_machine = state_machine.map(StateMachinePlayer::new);
+    }
+
+    /// The state that the state machine of this tree is in, if it has one and it has been updated
+    pub fn current_state(&self) -> Option<&str> {
+        self.state_machine.as_ref()?.current_state()
+    }
+
+    pub fn param(&self, name: impl AsRef<str>) -> Option<ParamValue> {
+        self.state_machine.as_ref()?.param(name.as_ref())
+    }
+
+    /// Sets a parameter of the state machine, which is checked against transitions on the next update
+    pub fn set_param(&mut self, name: impl AsRef<str>, value: impl Into<ParamValue>) {
+        if let Some(state_machine) = self.state_machine.as_mut() {
+            state_machine.set_param(name.as_ref(), value.into());
+        }
+    }
+
+    /// Fires a trigger of the state machine, which only lasts until the next update
+    pub fn fire_trigger(&mut self, name: impl AsRef<str>) {
+        if let Some(state_machine) = self.state_machine.as_mut() {
+            state_machine.fire_trigger(name.as_ref());
+        }
+    }
+
+    fn update_state_machine(&mut self) {
+        let Some(state_machine) = self.state_machine.as_mut() else {
+            return;
+        };
+
+        let playing_animations = &self.playing_animations;
+        let Some(change) = state_machine.update(|animation| !playing_animations.contains_key(animation)) else {
+            return;
+        };
+
+        let playback = AnimationPlayback {
+            layer: state_machine.layer(),
+            looping: change.looping,
+            ..Default::default()
+        };
+
+        match (change.from, change.to) {
+            (Some(from), Some(to)) => self.crossfade_animation(from, to, playback, change.crossfade_frames),
+            (None, Some(to)) => {
+                self.play_animation_with(&to, AnimationPlayback { weight: 0.0, ..playback });
+                self.fade_animation_weight(&to, playback.weight, change.crossfade_frames);
+            }
+            (Some(from), None) => self.fade_out_animation(from, change.crossfade_frames),
+            (None, None) => {}
+        }
+    }
+
+    fn start_fade(state: &mut PlayingAnimation, weight: f32, frames: usize, stop_when_finished: bool) {
+        if frames == 0 {
+            state.playback.weight = weight;
+            state.fade = None;
+            return;
+        }
+
+        state.fade = Some(WeightFade {
+            from: state.playback.weight,
+            to: weight,
+            duration: frames as f32,
+            elapsed: 0.0,
+            stop_when_finished,
+        });
+    }
+
     pub fn add_animation(&mut self, name: impl Into<String>, animation: Animation) {
         self.animations.insert(name.into(), animation);
     }
@@ -421,11 +1001,56 @@ impl<B: EnvyBackend> LayoutTree<B> {
     }
 
     pub fn setup(&mut self, backend: &mut B) {
+        self.preload_animation_textures();
         self.root_children
             .iter_mut()
             .for_each(|child| child.node.setup(backend));
     }
 
+    /// Releases every resource requested by the nodes of this tree, the counterpart to [`LayoutTree::setup`]
+    pub fn release(&mut self, backend: &mut B) {
+        self.visit_roots_mut(|root| root.release(backend));
+    }
+
+    /// Preloads the textures of animations for nodes that are already set up, after parts of the tree or its
+    /// animations were built again, including the trees of its sublayouts
+    pub(crate) fn request_animation_textures(&mut self, backend: &mut B) {
+        self.preload_animation_textures();
+        self.walk_tree_mut(|node| {
+            if let Some(image) = node.downcast_mut::<ImageNode<B>>() {
+                image.request_preloaded_textures(backend);
+            } else if let Some(sublayout) = node.downcast_mut::<SublayoutNode<B>>() {
+                sublayout.as_layout_mut().request_animation_textures(backend);
+            }
+        });
+    }
+
+    /// Informs image nodes of every texture their animations can swap to, so that the handles
+    /// are requested during setup instead of mid-animation
+    ///
+    /// Paths into sublayouts are followed as well, so this runs before the sublayouts are set up.
+    fn preload_animation_textures(&mut self) {
+        for animation in self.animations.values() {
+            for node_anim in animation.node_animations.iter() {
+                let Some(channel) = node_anim.texture_channel.as_ref() else {
+                    continue;
+                };
+
+                let Some(image) = Self::get_node_by_path_mut_impl(
+                    &mut self.root_children,
+                    Utf8Path::new(&node_anim.node_path),
+                )
+                .and_then(|node| node.downcast_mut::<ImageNode<B>>()) else {
+                    continue;
+                };
+
+                for name in channel.values() {
+                    image.preload_texture(name.as_str());
+                }
+            }
+        }
+    }
+
     pub fn update(&mut self) {
         NodeItem::update_batch(&mut self.root_children, NodeParent::Root);
     }
@@ -443,36 +1068,85 @@ impl<B: EnvyBackend> LayoutTree<B> {
     }
 
     pub fn update_animations(&mut self) {
+        self.update_state_machine();
+
+        let mut poses: HashMap<&str, Vec<(AnimationPlayback, NodePose)>> = HashMap::new();
+        let mut sublayout_animations: Vec<(&str, &str)> = vec![];
+
         self.playing_animations.retain(|key, state| {
             state.progress += 1.0;
-            if let Some(animation) = self.animations.get(key) {
-                let mut should_keep = false;
-                for node_anim in animation.node_animations.iter() {
-                    let Some(node) = Self::get_node_by_path_mut_impl(
-                        &mut self.root_children,
-                        Utf8Path::new(&node_anim.node_path),
-                    ) else {
-                        continue;
-                    };
-
-                    let mut color = node.color();
-                    let mut transform = *node.transform();
-                    should_keep |= !node_anim.animate(state.progress, &mut transform, &mut color, node.implementation_mut());
-                    *node.transform_mut() = transform;
-                    *node.color_mut() = color;
-                }
+            let is_fading = state.fade.is_some();
+            if !state.update_fade() {
+                return false;
+            }
 
-                if !should_keep && state.looping {
-                    state.progress = -1.0;
-                    true
-                } else {
-                    should_keep
+            let Some(animation) = self.animations.get(key) else {
+                return false;
+            };
+
+            let mut should_keep = false;
+            for node_anim in animation.node_animations.iter() {
+                let (pose, is_done) = node_anim.sample(state.progress);
+                should_keep |= !is_done;
+                poses
+                    .entry(node_anim.node_path.as_str())
+                    .or_default()
+                    .push((state.playback, pose));
+
+                for name in node_anim.sublayout_animations_between(state.events_after, state.progress) {
+                    sublayout_animations.push((node_anim.node_path.as_str(), name));
                 }
+            }
+
+            state.events_after = state.progress;
+            if !should_keep && state.playback.looping {
+                state.progress = -1.0;
+                state.events_after = -1.0;
+                true
             } else {
-                false
+                // Finished animations hold their last frame until they have faded out
+                should_keep || is_fading
             }
         });
 
+        for (path, contributions) in poses.iter_mut() {
+            let Some(node) = Self::get_node_by_path_mut_impl(&mut self.root_children, Utf8Path::new(path)) else {
+                continue;
+            };
+
+            let mut color = node.color();
+            let mut transform = *node.transform();
+            let mut visibility = node.visibility();
+            let mut gradient = node.gradient().cloned();
+
+            let rest = self.rest_poses.entry(path.to_string()).or_insert_with(|| {
+                RestPose::capture(&transform, color, visibility, gradient.as_ref(), node.implementation())
+            });
+
+            let mut blender = PoseBlender::new();
+            for (playback, pose) in contributions.iter_mut() {
+                pose.resolve(rest);
+                blender.push(playback.layer, pose, playback.weight, playback.blend_mode);
+            }
+
+            blender.apply(rest, &mut transform, &mut color, &mut visibility, gradient.as_mut(), node.implementation_mut());
+            *node.transform_mut() = transform;
+            *node.color_mut() = color;
+            node.set_visibility(visibility);
+            if blender.has_gradient_stops() {
+                node.set_gradient(gradient);
+            }
+        }
+
+        for (path, name) in sublayout_animations {
+            if let Some(sublayout) = Self::get_node_by_path_mut_impl(&mut self.root_children, Utf8Path::new(path))
+                .and_then(|node| node.downcast_mut::<SublayoutNode<B>>())
+            {
+                sublayout.as_layout_mut().play_animation(name);
+            }
+        }
+
+        // Sublayouts are updated last, so their own animations win over the parent's for the same node
         self.walk_tree_mut(|node| {
             if let Some(sublayout) = node.downcast_mut::<SublayoutNode<B>>() {
                 sublayout.as_layout_mut().update_animations();
@@ -480,6 +1154,14 @@ impl<B: EnvyBackend> LayoutTree<B> {
         });
     }
 
+    /// Forgets the rest values captured for animated nodes
+    ///
+    /// Relative channels are applied to the values a node had when it was first animated. Call this after
+    /// moving or restyling animated nodes so that the next frame captures their new values instead.
+    pub fn refresh_rest_poses(&mut self) {
+        self.rest_poses.clear();
+    }
+
     pub fn sync_to_animation_keyframe(&mut self, animation_name: &str, keyframe: usize) {
         if let Some(animation) = self.animations.get(animation_name) {
             for node_anim in animation.node_animations.iter() {
@@ -493,9 +1175,24 @@ impl<B: EnvyBackend> LayoutTree<B> {
 
                 let mut color = node.color();
                 let mut transform = *node.transform();
-                node_anim.animate(keyframe as f32, &mut transform, &mut color, node.implementation_mut());
+                let mut visibility = node.visibility();
+                let mut gradient = node.gradient().cloned();
+                let rest = self.rest_poses.entry(node_anim.node_path.clone()).or_insert_with(|| {
+                    RestPose::capture(&transform, color, visibility, gradient.as_ref(), node.implementation())
+                });
+                node_anim.animate(keyframe as f32, Some(rest), &mut transform, &mut color, &mut visibility, gradient.as_mut(), node.implementation_mut());
                 *node.transform_mut() = transform;
                 *node.color_mut() = color;
+                node.set_visibility(visibility);
+                if !node_anim.gradient_stop_color_channels.is_empty() {
+                    node.set_gradient(gradient);
+                }
+
+                if let Some((name, frame)) = node_anim.sublayout_animation_at(keyframe) {
+                    if let Some(sublayout) = node.downcast_mut::<SublayoutNode<B>>() {
+                        sublayout.as_layout_mut().sync_to_animation_keyframe(name, frame);
+                    }
+                }
             }
         }
     }
@@ -551,6 +1248,7 @@ impl<B: EnvyBackend> LayoutTree<B> {
         });
     }
 
+    /// Finds a node by its path, where [`SUBLAYOUT_PATH_SEPARATOR`] descends into the tree of a sublayout node
     pub fn get_node_by_path(&self, path: impl AsRef<Utf8Path>) -> Option<&NodeItem<B>> {
         fn get_node_by_path_recursive<'a, B: EnvyBackend>(
             current: &'a NodeItem<B>,
@@ -564,13 +1262,17 @@ impl<B: EnvyBackend> LayoutTree<B> {
             get_node_by_path_recursive(next, components)
         }
 
-        let path = path.as_ref();
+        let (path, sublayout_path) = split_sublayout_path(path.as_ref());
         let mut iter = path.components();
         let first = iter.next()?;
 
         for child in self.root_children.iter() {
             if child.node.name().eq(first.as_str()) {
-                return get_node_by_path_recursive(&child.node, &mut iter);
+                let node = get_node_by_path_recursive(&child.node, &mut iter)?;
+                return match sublayout_path {
+                    Some(sublayout_path) => node.downcast::<SublayoutNode<B>>()?.as_layout().get_node_by_path(sublayout_path),
+                    None => Some(node),
+                };
             }
         }
 
@@ -594,12 +1296,20 @@ impl<B: EnvyBackend> LayoutTree<B> {
         nodes: &'a mut [ObservedNode<B>],
         path: &Utf8Path,
     ) -> Option<&'a mut NodeItem<B>> {
+        let (path, sublayout_path) = split_sublayout_path(path);
         let mut iter = path.components();
         let first = iter.next()?;
 
         for child in nodes.iter_mut() {
             if child.node.name().eq(first.as_str()) {
-                return Self::get_node_by_path_recursive(&mut child.node, &mut iter);
+                let node = Self::get_node_by_path_recursive(&mut child.node, &mut iter)?;
+                return match sublayout_path {
+                    Some(sublayout_path) => node
+                        .downcast_mut::<SublayoutNode<B>>()?
+                        .as_layout_mut()
+                        .get_node_by_path_mut(sublayout_path),
+                    None => Some(node),
+                };
             }
         }
 
@@ -798,3 +1508,245 @@ impl<B: EnvyBackend> Default for LayoutTree<B> {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        AnimationChannel, AnimationTransform, DiscreteChannel, DiscreteKeyframe, NodeAnimation, NodeTransform,
+        TransformStep,
+        template::{ImageNodeTemplate, SublayoutNodeTemplate},
+        testing::RecordingBackend,
+    };
+
+    fn node(name: &str, implementation: NodeImplTemplate) -> NodeTemplate {
+        NodeTemplate {
+            name: name.to_string(),
+            transform: NodeTransform::from_size(Vec2::splat(10.0)),
+            color: [255; 4],
+            visibility: NodeVisibility::Inherited,
+            gradient: None,
+            children: vec![],
+            implementation,
+        }
+    }
+
+    fn image(name: &str, texture: &str) -> NodeTemplate {
+        node(
+            name,
+            NodeImplTemplate::Image(ImageNodeTemplate {
+                texture_name: texture.to_string(),
+                mask_texture_name: None,
+                image_scaling_mode_x: Default::default(),
+                image_scaling_mode_y: Default::default(),
+                uv_offset: Vec2::ZERO,
+                uv_scale: Vec2::ONE,
+                fill_method: Default::default(),
+                fill_origin: Default::default(),
+                fill_amount: 1.0,
+            }),
+        )
+    }
+
+    /// Swaps the texture of `path` between `frame_0`, `frame_1` and `frame_2` every other frame, twice
+    fn flipbook(path: &str) -> NodeAnimation {
+        let mut animation = NodeAnimation::empty(path);
+        animation.texture_channel = Some(DiscreteChannel {
+            start: "frame_0".to_string(),
+            keyframes: ["frame_1", "frame_2", "frame_0", "frame_1", "frame_2"]
+                .into_iter()
+                .map(|name| DiscreteKeyframe {
+                    value: name.to_string(),
+                    duration: 2,
+                })
+                .collect(),
+        });
+        animation
+    }
+
+    /// A root with a flipbook image of its own and one inside of a sublayout, both animated by the root
+    fn flipbook_root() -> LayoutRoot<RecordingBackend> {
+        let mut icon = LayoutTemplate {
+            canvas_size: [10, 10],
+            root_nodes: vec![image("image", "frame_0")],
+            ..Default::default()
+        };
+        icon.add_animation("spin", Animation {
+            node_animations: vec![flipbook("image")],
+            total_duration: 10,
+        });
+
+        let mut root = LayoutTemplate {
+            canvas_size: [100, 100],
+            root_nodes: vec![
+                image("image", "frame_0"),
+                node(
+                    "hud",
+                    NodeImplTemplate::Sublayout(SublayoutNodeTemplate {
+                        sublayout_name: "icon".to_string(),
+                    }),
+                ),
+            ],
+            ..Default::default()
+        };
+        root.add_animation("flip", Animation {
+            node_animations: vec![flipbook("image"), flipbook("hud::image")],
+            total_duration: 10,
+        });
+
+        LayoutRoot::from_root_template(root, [("icon".to_string(), icon)])
+    }
+
+    fn run(root: &mut LayoutRoot<RecordingBackend>, backend: &mut RecordingBackend, frames: usize) {
+        for _ in 0..frames {
+            root.as_layout_mut().update_animations();
+            root.update();
+            root.as_layout_mut().propagate();
+            root.prepare(backend);
+        }
+    }
+
+    fn texture_requests(root: &mut LayoutRoot<RecordingBackend>, backend: &mut RecordingBackend) -> usize {
+        run(root, backend, 1);
+        let requested = backend.texture_counts().requested;
+        root.as_layout_mut().play_animation("flip");
+        root.as_layout_mut()
+            .get_node_by_path_mut("hud")
+            .unwrap()
+            .downcast_mut::<SublayoutNode<RecordingBackend>>()
+            .unwrap()
+            .as_layout_mut()
+            .play_animation("spin");
+        run(root, backend, 12);
+        assert_eq!(backend.texture_counts().requested, requested);
+        requested
+    }
+
+    #[test]
+    fn flipbook_textures_are_requested_during_setup() {
+        let mut backend = RecordingBackend::new();
+        let mut root = flipbook_root();
+        root.setup(&mut backend);
+
+        // Every image holds one texture and preloads the other two frames
+        assert_eq!(texture_requests(&mut root, &mut backend), 6);
+
+        root.release(&mut backend);
+        backend.assert_no_leaks();
+    }
+
+    #[test]
+    fn flipbook_textures_are_requested_after_syncing() {
+        let mut backend = RecordingBackend::new();
+        let mut root = flipbook_root();
+        root.setup(&mut backend);
+
+        root.sync_template("icon", &mut backend);
+        texture_requests(&mut root, &mut backend);
+        root.sync_root_template_by_path("image", &mut backend);
+        texture_requests(&mut root, &mut backend);
+        root.sync_root_template(&mut backend);
+        texture_requests(&mut root, &mut backend);
+
+        root.release(&mut backend);
+        backend.assert_no_leaks();
+    }
+
+    /// Moves `path` 10 pixels to the right over 10 frames
+    fn slide(path: &str) -> NodeAnimation {
+        let mut animation = NodeAnimation::empty(path);
+        animation.position_channel = Some(AnimationChannel::from_transforms(Vec2::ZERO, vec![AnimationTransform {
+            end: Vec2::new(10.0, 0.0),
+            duration: 10,
+            first_step: TransformStep::Linear,
+            additional_steps: vec![],
+            end_tangents: Default::default(),
+        }]));
+        animation
+    }
+
+    /// A root with a `button` sublayout at `hud/button_a`, where `animation` is added to the root and the button's
+    /// `press` animation slides its `icon`
+    fn button_root(animation: NodeAnimation) -> LayoutRoot<RecordingBackend> {
+        let mut button = LayoutTemplate {
+            canvas_size: [10, 10],
+            root_nodes: vec![image("icon", "icon")],
+            ..Default::default()
+        };
+        button.add_animation("press", Animation {
+            node_animations: vec![slide("icon")],
+            total_duration: 10,
+        });
+
+        let mut hud = node("hud", NodeImplTemplate::Empty);
+        hud.children.push(node(
+            "button_a",
+            NodeImplTemplate::Sublayout(SublayoutNodeTemplate {
+                sublayout_name: "button".to_string(),
+            }),
+        ));
+
+        let mut root = LayoutTemplate {
+            canvas_size: [100, 100],
+            root_nodes: vec![hud],
+            ..Default::default()
+        };
+        root.add_animation("open", Animation {
+            node_animations: vec![animation],
+            total_duration: 10,
+        });
+
+        LayoutRoot::from_root_template(root, [("button".to_string(), button)])
+    }
+
+    fn update(root: &mut LayoutRoot<RecordingBackend>, frames: usize) -> f32 {
+        for _ in 0..frames {
+            root.as_layout_mut().update_animations();
+        }
+        root.as_layout().get_node_by_path("hud/button_a::icon").unwrap().transform().position.x
+    }
+
+    fn button(root: &mut LayoutRoot<RecordingBackend>) -> &mut LayoutTree<RecordingBackend> {
+        root.as_layout_mut()
+            .get_node_by_path_mut("hud/button_a")
+            .unwrap()
+            .downcast_mut::<SublayoutNode<RecordingBackend>>()
+            .unwrap()
+            .as_layout_mut()
+    }
+
+    #[test]
+    fn animations_reach_nodes_inside_of_sublayouts() {
+        let mut root = button_root(slide("hud/button_a::icon"));
+        root.as_layout_mut().play_animation("open");
+
+        assert_eq!(update(&mut root, 5), 5.0);
+        assert_eq!(update(&mut root, 5), 10.0);
+        assert!(!button(&mut root).is_animation_playing("press"));
+    }
+
+    #[test]
+    fn sublayout_animations_are_started_by_their_channel() {
+        let mut animation = NodeAnimation::empty("hud/button_a");
+        animation.sublayout_animation_channel = Some(DiscreteChannel {
+            start: String::new(),
+            keyframes: vec![DiscreteKeyframe {
+                value: "press".to_string(),
+                duration: 3,
+            }],
+        });
+
+        let mut root = button_root(animation);
+        root.as_layout_mut().play_animation("open");
+
+        assert_eq!(update(&mut root, 2), 0.0);
+        assert!(!button(&mut root).is_animation_playing("press"));
+
+        // The sublayout is updated after the root, so the animation plays its first frame right away
+        assert!((update(&mut root, 1) - 1.0).abs() < 1e-4);
+        assert!(button(&mut root).is_animation_playing("press"));
+
+        assert!((update(&mut root, 9) - 10.0).abs() < 1e-4);
+        assert!(!root.as_layout().is_animation_playing("open"));
+    }
+}
@@ -0,0 +1,361 @@
+//! Snapshots of the runtime state of a [`LayoutTree`]
+//!
+//! A [`LayoutTreeState`] records what a game changes about a tree while it is running, so that it can be put
+//! in a save file or carried across a scene transition and restored into a tree built from the same layout
+//! later, possibly on another machine. Everything is keyed by node path and animation name instead of by
+//! position, so a state still restores into a tree whose template has gained, lost or reordered nodes since;
+//! whatever no longer exists is skipped.
+//!
+//! Envy does not track input focus itself. Layouts that drive their selection through the state machine get
+//! it back with the state machine's current state and parameters.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    EnvyBackend, ImageNode, NodeItem, NodeTransform, RestPose, SUBLAYOUT_PATH_SEPARATOR, SublayoutNode, TextNode,
+    state_machine::ParamValue, template::NodeVisibility,
+};
+
+use super::{AnimationPlayback, LayoutTree, PlayingAnimation};
+
+/// The runtime state of a single node
+#[cfg_attr(feature = "asset", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeState {
+    /// The transform of the node, without the contribution of any animation that was playing
+    pub transform: NodeTransform,
+    /// The color of the node, without the contribution of any animation that was playing
+    pub color: [u8; 4],
+    pub visibility: NodeVisibility,
+    /// The content of a text node
+    pub text: Option<String>,
+    /// The name of the texture shown by an image node
+    pub texture_name: Option<String>,
+}
+
+/// An animation that was playing when a [`LayoutTreeState`] was captured
+#[cfg_attr(feature = "asset", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayingAnimationState {
+    pub name: String,
+    /// The frame the animation was on
+    pub progress: f32,
+    pub playback: AnimationPlayback,
+}
+
+/// The state of a tree's state machine
+#[cfg_attr(feature = "asset", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StateMachineState {
+    /// The state the machine was in, or `None` if it had not been updated yet
+    pub current_state: Option<String>,
+    pub params: BTreeMap<String, ParamValue>,
+}
+
+/// A snapshot of the runtime state of a [`LayoutTree`] and the trees of its sublayouts
+///
+/// Captured with [`LayoutTree::capture_state`] and applied with [`LayoutTree::restore_state`].
+#[cfg_attr(feature = "asset", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LayoutTreeState {
+    /// The state of every node in the tree, keyed by path
+    pub nodes: BTreeMap<String, NodeState>,
+    pub animations: Vec<PlayingAnimationState>,
+    pub state_machine: Option<StateMachineState>,
+    /// The state of the tree inside every sublayout node, keyed by the path of the sublayout node
+    pub sublayouts: BTreeMap<String, LayoutTreeState>,
+}
+
+/// Rest poses of the animated nodes of a tree, keyed by their path in that tree
+type RestPoses<'a> = HashMap<String, &'a RestPose>;
+
+fn capture_node_recursive<B: EnvyBackend>(
+    node: &NodeItem<B>,
+    path: String,
+    rest_poses: &RestPoses<'_>,
+    state: &mut LayoutTreeState,
+) {
+    // Animated nodes are stored with the values they had before they were animated, since the animations
+    // reapply their part once they are restored
+    let rest = rest_poses.get(&path);
+
+    let node_state = NodeState {
+        transform: rest.map_or(*node.transform(), |rest| *rest.transform()),
+        color: rest.map_or(node.color(), |rest| rest.color()),
+        visibility: rest.map_or(node.visibility(), |rest| rest.visibility()),
+        text: node.downcast::<TextNode<B>>().map(|text| text.text().to_string()),
+        texture_name: node.downcast::<ImageNode<B>>().map(|image| image.resource_name().to_string()),
+    };
+
+    if let Some(sublayout) = node.downcast::<SublayoutNode<B>>() {
+        // Nodes of the sublayout that this tree animates through `sublayout::node` paths have their rest pose here
+        let prefix = format!("{path}{SUBLAYOUT_PATH_SEPARATOR}");
+        let inner = rest_poses
+            .iter()
+            .filter_map(|(key, rest)| Some((key.strip_prefix(&prefix)?.to_string(), *rest)))
+            .collect();
+        state.sublayouts.insert(path.clone(), sublayout.as_layout().capture_state_with(inner));
+    }
+
+    node.visit_children(|child| {
+        capture_node_recursive(child, format!("{path}/{}", child.name()), rest_poses, state);
+    });
+
+    state.nodes.insert(path, node_state);
+}
+
+impl<B: EnvyBackend> LayoutTree<B> {
+    /// Captures the current state of this tree and of the trees of its sublayouts
+    ///
+    /// Weight fades in progress are captured as if they had finished, and animations that were fading out to stop
+    /// are left out.
+    pub fn capture_state(&self) -> LayoutTreeState {
+        self.capture_state_with(RestPoses::new())
+    }
+
+    /// Captures the state of this tree, using the rest poses of an enclosing tree for the nodes it animates
+    fn capture_state_with<'a>(&'a self, mut rest_poses: RestPoses<'a>) -> LayoutTreeState {
+        let mut state = LayoutTreeState {
+            animations: self
+                .playing_animations
+                .iter()
+                .filter(|(_, animation)| !animation.fade.as_ref().is_some_and(|fade| fade.stop_when_finished))
+                .map(|(name, animation)| {
+                    let mut playback = animation.playback;
+                    if let Some(fade) = animation.fade.as_ref() {
+                        playback.weight = fade.to;
+                    }
+
+                    PlayingAnimationState {
+                        name: name.clone(),
+                        progress: animation.progress,
+                        playback,
+                    }
+                })
+                .collect(),
+            state_machine: self.state_machine.as_ref().map(|state_machine| StateMachineState {
+                current_state: state_machine.current_state().map(str::to_string),
+                params: state_machine
+                    .params()
+                    .map(|(name, value)| (name.to_string(), value))
+                    .collect(),
+            }),
+            ..Default::default()
+        };
+
+        // Sort by name so that capturing the same tree twice gives equal states
+        state.animations.sort_by(|a, b| a.name.cmp(&b.name));
+
+        // Rest poses of an enclosing tree win, this tree captures its own after the enclosing tree has animated
+        // the node for the frame
+        let animated = self
+            .playing_animations
+            .keys()
+            .filter_map(|name| self.animations.get(name))
+            .flat_map(|animation| animation.node_animations.iter())
+            .map(|node_anim| node_anim.node_path.as_str());
+        for path in animated {
+            if let Some(rest) = self.rest_poses.get(path) {
+                rest_poses.entry(path.to_string()).or_insert(rest);
+            }
+        }
+
+        self.visit_roots(|node| {
+            capture_node_recursive(node, node.name().to_string(), &rest_poses, &mut state);
+        });
+
+        state
+    }
+
+    /// Restores a state captured with [`LayoutTree::capture_state`], possibly from a tree built from an older
+    /// version of the same template
+    ///
+    /// Nodes, animations, state machine states and parameters that no longer exist in this tree are skipped,
+    /// and whatever the state does not mention is left as it is, except that all animations that are playing
+    /// are replaced by the ones in the state. Playing animations are applied again on the next call to
+    /// [`LayoutTree::update_animations`].
+    pub fn restore_state(&mut self, state: &LayoutTreeState) {
+        for (path, node_state) in state.nodes.iter() {
+            let Some(node) = self.get_node_by_path_mut(path) else {
+                continue;
+            };
+
+            *node.transform_mut() = node_state.transform;
+            *node.color_mut() = node_state.color;
+            node.set_visibility(node_state.visibility);
+
+            if let Some(text) = node_state.text.as_ref() {
+                if let Some(node) = node.downcast_mut::<TextNode<B>>() {
+                    node.set_text(text);
+                }
+            }
+
+            if let Some(texture_name) = node_state.texture_name.as_ref() {
+                if let Some(node) = node.downcast_mut::<ImageNode<B>>() {
+                    node.set_resource_name(texture_name);
+                }
+            }
+        }
+
+        for (path, sublayout_state) in state.sublayouts.iter() {
+            if let Some(sublayout) = self
+                .get_node_by_path_mut(path)
+                .and_then(|node| node.downcast_mut::<SublayoutNode<B>>())
+            {
+                sublayout.as_layout_mut().restore_state(sublayout_state);
+            }
+        }
+
+        self.playing_animations = state
+            .animations
+            .iter()
+            .filter(|animation| self.animations.contains_key(&animation.name))
+            .map(|animation| {
+                (
+                    animation.name.clone(),
+                    PlayingAnimation {
+                        progress: animation.progress,
+                        // Sublayout animations keyed up to this frame were started before the state was captured
+                        events_after: animation.progress,
+                        playback: animation.playback,
+                        fade: None,
+                    },
+                )
+            })
+            .collect();
+
+        // The restored values are the new rest values of animated nodes
+        self.rest_poses.clear();
+
+        if let (Some(state_machine), Some(machine_state)) = (self.state_machine.as_mut(), state.state_machine.as_ref()) {
+            state_machine.restore_state(machine_state.current_state.as_deref());
+            for (name, value) in machine_state.params.iter() {
+                if state_machine.param(name).is_some() {
+                    state_machine.set_param(name, *value);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use crate::{
+        Animation, AnimationChannel, AnimationTransform, ChannelMode, DiscreteChannel, KeyTangents, LayoutRoot,
+        LayoutTemplate, NodeAnimation, NodeImplTemplate, NodeTemplate, SublayoutNodeTemplate, TransformStep,
+        testing::RecordingBackend,
+    };
+
+    use super::*;
+
+    fn node(name: &str, implementation: NodeImplTemplate) -> NodeTemplate {
+        NodeTemplate {
+            name: name.to_string(),
+            transform: NodeTransform::from_size(Vec2::new(100.0, 50.0)),
+            color: [255; 4],
+            visibility: NodeVisibility::Inherited,
+            gradient: None,
+            children: vec![],
+            implementation,
+        }
+    }
+
+    /// Moves a node by 100 pixels over 10 frames, relative to where it is
+    fn move_by(node_path: &str) -> NodeAnimation {
+        let mut animation = NodeAnimation::empty(node_path);
+        let transform = AnimationTransform {
+            end: Vec2::new(100.0, 0.0),
+            duration: 10,
+            first_step: TransformStep::Linear,
+            additional_steps: vec![],
+            end_tangents: KeyTangents::LINEAR,
+        };
+        animation.position_channel =
+            Some(AnimationChannel::from_transforms(Vec2::ZERO, vec![transform]).with_mode(ChannelMode::Offset));
+        animation
+    }
+
+    /// A root with a node and a sublayout, whose node the root animates through a sublayout path
+    fn layout() -> LayoutRoot<RecordingBackend> {
+        let mut hide_and_move = move_by("a");
+        hide_and_move.visibility_channel = Some(DiscreteChannel {
+            start: NodeVisibility::Hidden,
+            keyframes: vec![],
+        });
+
+        let mut root = LayoutTemplate {
+            canvas_size: [100, 100],
+            root_nodes: vec![
+                node("a", NodeImplTemplate::Empty),
+                node(
+                    "s",
+                    NodeImplTemplate::Sublayout(SublayoutNodeTemplate {
+                        sublayout_name: "sub".to_string(),
+                    }),
+                ),
+            ],
+            ..Default::default()
+        };
+        root.add_animation(
+            "move",
+            Animation {
+                node_animations: vec![hide_and_move, move_by("s::x")],
+                total_duration: 10,
+            },
+        );
+
+        let sub = LayoutTemplate {
+            canvas_size: [100, 100],
+            root_nodes: vec![node("x", NodeImplTemplate::Empty)],
+            ..Default::default()
+        };
+
+        LayoutRoot::from_root_template(root, [("sub".to_string(), sub)])
+    }
+
+    fn frames(tree: &mut LayoutTree<RecordingBackend>, count: usize) {
+        for _ in 0..count {
+            tree.update_animations();
+        }
+    }
+
+    #[test]
+    fn animated_nodes_are_captured_at_rest() {
+        let mut root = layout();
+        let tree = root.as_layout_mut();
+        tree.play_animation("move");
+        frames(tree, 5);
+        assert_ne!(tree.get_node_by_path("s::x").unwrap().transform().position, Vec2::ZERO);
+
+        let state = tree.capture_state();
+        let a = &state.nodes["a"];
+        assert_eq!(a.transform.position, Vec2::ZERO);
+        assert_eq!(a.visibility, NodeVisibility::Inherited);
+        assert_eq!(state.sublayouts["s"].nodes["x"].transform.position, Vec2::ZERO);
+    }
+
+    #[test]
+    fn restoring_continues_where_the_capture_left_off() {
+        let mut root = layout();
+        let tree = root.as_layout_mut();
+        tree.play_animation("move");
+        frames(tree, 5);
+        let state = tree.capture_state();
+        frames(tree, 1);
+
+        let mut restored = self::layout();
+        let restored_tree = restored.as_layout_mut();
+        restored_tree.restore_state(&state);
+        frames(restored_tree, 1);
+
+        for path in ["a", "s::x"] {
+            let [live, restored] = [&*tree, &*restored_tree].map(|tree| tree.get_node_by_path(path).unwrap());
+            assert_eq!(live.transform().position, restored.transform().position, "{path}");
+            assert_eq!(live.visibility(), restored.visibility(), "{path}");
+        }
+
+        assert_eq!(restored_tree.capture_state(), tree.capture_state());
+    }
+}